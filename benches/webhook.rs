@@ -0,0 +1,68 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use hyper::{body::HttpBody, Body};
+use std::io::Write;
+use telexide::model::Update;
+
+/// Creates an update payload of roughly 5 KB, containing a long text message
+fn update_payload() -> hyper::body::Bytes {
+    let text = "telexide ".repeat(520);
+    serde_json::to_vec(&serde_json::json!({
+        "update_id": 10,
+        "message": {
+            "message_id": 16373892,
+            "date": 1585772722,
+            "chat": {
+                "id": 538733,
+                "type": "private",
+                "first_name": "test"
+            },
+            "from": {
+                "id": 538733,
+                "is_bot": false,
+                "first_name": "test"
+            },
+            "text": text
+        }
+    }))
+    .expect("failed to serialize update")
+    .into()
+}
+
+/// The way the webhook used to collect the body: chunk by chunk into a `Vec`
+async fn collect_chunks(body: Body) -> Update {
+    let mut raw_body = body;
+    let mut bytes: Vec<u8> = Vec::new();
+    while let Some(chunk) = raw_body.data().await {
+        bytes.write_all(&chunk.unwrap()).unwrap();
+    }
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+/// The way the webhook collects the body now, without copying a single chunk
+async fn to_bytes(body: Body) -> Update {
+    let bytes = hyper::body::to_bytes(body).await.unwrap();
+    serde_json::from_slice(&bytes).unwrap()
+}
+
+fn webhook_body(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("failed to build runtime");
+    let payload = update_payload();
+
+    let mut group = c.benchmark_group("webhook_body");
+    group.bench_with_input(
+        BenchmarkId::new("collect_chunks", payload.len()),
+        &payload,
+        |b, p| b.iter(|| runtime.block_on(collect_chunks(Body::from(black_box(p.clone()))))),
+    );
+    group.bench_with_input(
+        BenchmarkId::new("to_bytes", payload.len()),
+        &payload,
+        |b, p| b.iter(|| runtime.block_on(to_bytes(Body::from(black_box(p.clone()))))),
+    );
+    group.finish();
+}
+
+criterion_group!(benches, webhook_body);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+use std::{env, sync::Arc};
+use telexide::{
+    api::types::{
+        InlineQueryResult,
+        InlineQueryResultArticle,
+        InputMessageContent,
+        InputTextMessageContent,
+    },
+    framework::{Framework, InlineAnswer},
+    model::InlineQuery,
+    prelude::*,
+};
+
+#[prepare_listener]
+async fn echo(_context: Context, query: InlineQuery) -> CommandResult<InlineAnswer> {
+    let text = if query.query.is_empty() {
+        "echo".to_owned()
+    } else {
+        query.query
+    };
+
+    let results = (1..=3)
+        .map(|times| {
+            let content = InputTextMessageContent::new(text.repeat(times));
+            InlineQueryResult::Article(InlineQueryResultArticle::new(
+                times,
+                format!("echo {times} times"),
+                InputMessageContent::Text(content),
+            ))
+        })
+        .collect::<Vec<_>>();
+    Ok(results.into())
+}
+
+#[tokio::main]
+async fn main() -> telexide::Result<()> {
+    let token = env::var("BOT_TOKEN").expect("no token environment variable set");
+    let bot_name = env::var("BOT_NAME").expect("no bot name env variable set");
+
+    let mut framework = Framework::new(&bot_name);
+    framework
+        .add_inline_answer_handler(echo)
+        .set_inline_personal(true);
+
+    ClientBuilder::new()
+        .set_token(&token)
+        .set_framework(Arc::new(framework))
+        .build()
+        .start()
+        .await
+}
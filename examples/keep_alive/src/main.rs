@@ -0,0 +1,60 @@
+//! Measures how long sending a message takes after the bot has been idle for a
+//! while, once using a plain `APIClient` and once using a running client that
+//! keeps its connection to telegram alive.
+
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+use telexide::{
+    api::{types::SendMessage, APIClient, API},
+    prelude::*,
+};
+
+// longer than the 90 seconds hyper keeps idle connections in its pool
+const IDLE: Duration = Duration::from_secs(120);
+const ROUNDS: usize = 3;
+
+async fn timed_send(
+    api: &(dyn API + Send),
+    chat_id: i64,
+    text: &str,
+) -> telexide::Result<Duration> {
+    let start = Instant::now();
+    api.send_message(SendMessage::new(chat_id.into(), text)).await?;
+    Ok(start.elapsed())
+}
+
+#[tokio::main]
+async fn main() -> telexide::Result<()> {
+    let token = env::var("BOT_TOKEN").expect("no token environment variable set");
+    let chat_id: i64 = env::var("CHAT_ID")
+        .expect("no chat id environment variable set")
+        .parse()
+        .expect("the chat id has to be a number");
+
+    let plain = APIClient::new_default(&token);
+    let mut cold = Vec::new();
+    for _ in 0..ROUNDS {
+        tokio::time::sleep(IDLE).await;
+        cold.push(timed_send(&plain, chat_id, "without keep-alive").await?);
+    }
+
+    let client = ClientBuilder::new()
+        .set_token(&token)
+        .set_keep_alive_interval(Duration::from_secs(30))
+        .build();
+    let api = client.api_client.clone();
+    let running = tokio::spawn(async move { client.start().await });
+
+    let mut warm = Vec::new();
+    for _ in 0..ROUNDS {
+        tokio::time::sleep(IDLE).await;
+        warm.push(timed_send(&**api, chat_id, "with keep-alive").await?);
+    }
+    running.abort();
+
+    println!("send after {IDLE:?} idle without keep-alive: {cold:?}");
+    println!("send after {IDLE:?} idle with keep-alive:    {warm:?}");
+    Ok(())
+}
@@ -7,9 +7,26 @@ use crate::{
     },
 };
 use async_trait::async_trait;
+use serde::Deserialize;
 use std::vec::Vec;
 
 /// This trait provides methods for interacting with the telegram API.
+///
+/// # Timeouts
+///
+/// A call that times out, see [`Context::api_with_timeout`] and
+/// [`APIClient::set_timeout`], returns a [`TelegramError::Timeout`] without
+/// waiting for the response of telegram. The request may already have been
+/// sent by then, in which case telegram still carries it out: a message whose
+/// sending timed out can still be delivered, so retrying a call that isn't
+/// idempotent, like [`API::send_message`], may send the message twice. The
+/// same goes for a call whose future is dropped before it completes, for
+/// example because it lost a `tokio::select!`.
+///
+/// [`Context::api_with_timeout`]: ../client/struct.Context.html#method.api_with_timeout
+/// [`APIClient::set_timeout`]: struct.APIClient.html#method.set_timeout
+/// [`TelegramError::Timeout`]: ../enum.TelegramError.html#variant.Timeout
+/// [`API::send_message`]: #method.send_message
 #[async_trait]
 pub trait API: Sync {
     /// executes a get request to the given telegram api endpoint
@@ -36,7 +53,9 @@ pub trait API: Sync {
     /// parameters. Returns basic information about the bot in form of a
     /// [`User`] object.
     async fn get_me(&self) -> Result<User> {
-        self.get(APIEndpoint::GetMe, None).await?.into()
+        self.get(APIEndpoint::GetMe, None)
+            .await?
+            .into_result(&APIEndpoint::GetMe)
     }
 
     /// Use this method to log out from the cloud Bot API server before
@@ -46,7 +65,9 @@ pub trait API: Sync {
     /// able to log in again using the same token for 10 minutes. Returns
     /// True on success.
     async fn log_out(&self) -> Result<bool> {
-        self.post(APIEndpoint::LogOut, None).await?.into()
+        self.post(APIEndpoint::LogOut, None)
+            .await?
+            .into_result(&APIEndpoint::LogOut)
     }
 
     /// Use this method to close the bot instance before moving it from one
@@ -55,7 +76,9 @@ pub trait API: Sync {
     /// after server restart. The method will return error 429 in the first 10
     /// minutes after the bot is launched.
     async fn close(&self) -> Result<bool> {
-        self.post(APIEndpoint::Close, None).await?.into()
+        self.post(APIEndpoint::Close, None)
+            .await?
+            .into_result(&APIEndpoint::Close)
     }
 
     /// (**WARNING:** this method should not be used by the library user
@@ -64,13 +87,35 @@ pub trait API: Sync {
     /// [`subscribe_handler`]) Use this method to receive incoming updates
     /// using long polling. A `Vec<`[`Update`]`>` is returned.
     ///
+    ///
+    /// The updates are parsed one by one, an update that can't be parsed is
+    /// logged together with its json and left out, without failing the rest.
+    ///
     /// [`Client`]: ../client/struct.Client.html
     /// [`subscribe_handler`]:
     /// ../client/struct.Client.html#method.subscribe_handler
     async fn get_updates(&self, data: GetUpdates) -> Result<Vec<Update>> {
+        let raw = self.get_raw_updates(data).await?;
+        Ok(raw
+            .into_iter()
+            .filter_map(|update| match Update::deserialize(&update) {
+                Ok(update) => Some(update),
+                Err(err) => {
+                    log::warn!(
+                        "skipping update that could not be parsed: {err}, raw update: {update}"
+                    );
+                    None
+                },
+            })
+            .collect())
+    }
+
+    /// Works like [`API::get_updates`], but returns the updates as json
+    /// without parsing them, so they can be parsed one by one.
+    async fn get_raw_updates(&self, data: GetUpdates) -> Result<Vec<serde_json::Value>> {
         self.get(APIEndpoint::GetUpdates, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::GetUpdates)
     }
 
     /// Use this method to specify a url and receive incoming updates via an
@@ -82,7 +127,7 @@ pub trait API: Sync {
     async fn set_webhook(&self, data: SetWebhook) -> Result<bool> {
         self.post(APIEndpoint::SetWebhook, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SetWebhook)
     }
 
     /// Use this method to remove webhook integration if you decide to switch
@@ -93,33 +138,43 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteWebhook)
     }
 
     /// Use this method to get current webhook status. On success, returns a
     /// [WebhookInfo] object. If the bot is using [API::get_updates], will
     /// return a [WebhookInfo] object with the url field empty.
     async fn get_webhook_info(&self) -> Result<WebhookInfo> {
-        self.get(APIEndpoint::GetWebhookInfo, None).await?.into()
+        self.get(APIEndpoint::GetWebhookInfo, None)
+            .await?
+            .into_result(&APIEndpoint::GetWebhookInfo)
     }
 
     /// Use this method to send text messages. On success, the sent [`Message`]
     /// is returned.
     async fn send_message(&self, data: SendMessage) -> Result<Message> {
-        self.post(APIEndpoint::SendMessage, Some(serde_json::to_value(data)?))
+        let mut data = serde_json::to_value(data)?;
+        strip_superseded_link_preview(&mut data);
+
+        self.post(APIEndpoint::SendMessage, Some(data))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendMessage)
     }
 
     /// Use this method to change the list of the bot's commands. Returns True
     /// on success.
+    ///
+    /// Returns an [`TelegramError::InvalidArgument`] without calling telegram
+    /// if more than [`MAX_BOT_COMMANDS`] commands are provided.
     async fn set_my_commands(&self, data: SetMyCommands) -> Result<bool> {
+        data.validate()?;
+
         self.post(
             APIEndpoint::SetMyCommands,
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetMyCommands)
     }
 
     /// Use this method to get the current list of the bot's commands. Requires
@@ -130,14 +185,14 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetMyCommands)
     }
 
     /// Use this method to change the bot's name. Returns True on success.
     async fn set_my_name(&self, data: SetMyName) -> Result<bool> {
         self.post(APIEndpoint::SetMyName, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SetMyName)
     }
 
     /// Use this method to get the current bot name for the given user language.
@@ -145,7 +200,7 @@ pub trait API: Sync {
     async fn get_my_name(&self, data: GetMyName) -> Result<BotName> {
         self.get(APIEndpoint::GetMyName, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::GetMyName)
     }
 
     /// Use this method to change the bot's description, which is shown in the
@@ -156,7 +211,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetMyDescription)
     }
 
     /// Use this method to get the current bot description for the given user
@@ -167,7 +222,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetMyDescription)
     }
 
     /// Use this method to change the bot's short description, which is shown on
@@ -179,7 +234,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetMyShortDescription)
     }
 
     /// Use this method to get the current bot short description for the given
@@ -193,7 +248,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetMyShortDescription)
     }
 
     /// Use this method to change the bot's menu button in a private chat, or
@@ -204,7 +259,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatMenuButton)
     }
 
     /// Use this method to get the current value of the bot's menu button in a
@@ -216,7 +271,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetChatMenuButton)
     }
 
     /// Use this method to change the default administrator rights requested by
@@ -232,7 +287,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetMyDefaultAdministratorRights)
     }
 
     /// Use this method to get the current default administrator rights of the
@@ -246,7 +301,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetMyDefaultAdministratorRights)
     }
 
     /// Use this method to delete the list of the bot's commands for the given
@@ -260,7 +315,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteMyCommands)
     }
 
     /// Use this method to forward messages of any kind. On success, the sent
@@ -271,7 +326,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::ForwardMessage)
     }
 
     /// Use this method to copy messages of any kind. The method is analogous to
@@ -283,7 +338,39 @@ pub trait API: Sync {
     async fn copy_message(&self, data: CopyMessage) -> Result<MessageId> {
         self.post(APIEndpoint::CopyMessage, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::CopyMessage)
+    }
+
+    /// Use this method to forward multiple messages of any kind, between 1 and
+    /// 100 of them. If some of the specified messages can't be found or
+    /// forwarded, they are skipped. Album grouping is kept for forwarded
+    /// messages. On success, the [`MessageId`]s of the sent messages are
+    /// returned.
+    async fn forward_messages(&self, data: ForwardMessages) -> Result<Vec<MessageId>> {
+        check_message_count(&data.message_ids, "forward")?;
+
+        self.post(
+            APIEndpoint::ForwardMessages,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::ForwardMessages)
+    }
+
+    /// Use this method to copy multiple messages of any kind, between 1 and 100
+    /// of them. If some of the specified messages can't be found or copied,
+    /// they are skipped. The method is analogous to [`forward_messages`], but
+    /// the copied messages don't have a link to the original message. Album
+    /// grouping is kept for copied messages. On success, the [`MessageId`]s of
+    /// the sent messages are returned.
+    ///
+    /// [`forward_messages`]: API::forward_messages
+    async fn copy_messages(&self, data: CopyMessages) -> Result<Vec<MessageId>> {
+        check_message_count(&data.message_ids, "copy")?;
+
+        self.post(APIEndpoint::CopyMessages, Some(serde_json::to_value(data)?))
+            .await?
+            .into_result(&APIEndpoint::CopyMessages)
     }
 
     /// Use this method to send photos. On success, the sent [`Message`] is
@@ -293,7 +380,7 @@ pub trait API: Sync {
             InputFile::String(_) => self
                 .post(APIEndpoint::SendPhoto, Some(serde_json::to_value(&data)?))
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SendPhoto),
             InputFile::File(f) => self
                 .post_file(
                     APIEndpoint::SendPhoto,
@@ -301,7 +388,7 @@ pub trait API: Sync {
                     Some(vec![f.clone()]),
                 )
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SendPhoto),
         }
     }
 
@@ -310,40 +397,23 @@ pub trait API: Sync {
     /// .M4A format. On success, the sent [`Message`] is returned.
     /// Bots can currently send audio files of up to 50 MB in size, this limit
     /// may be changed in the future.
-    async fn send_audio(&self, data: SendAudio) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.audio {
-            files.push(f.clone());
-        }
-        if data.thumbnail.is_some() {
-            if let InputFile::File(f) = data.thumbnail.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_audio(&self, mut data: SendAudio) -> Result<Message> {
+        let files = files_to_upload(std::iter::once(&mut data.audio).chain(&mut data.thumbnail));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendAudio,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendAudio)
     }
 
     /// Use this method to send general files. On success, the sent [`Message`]
     /// is returned. Bots can currently send files of any type of up to 50
     /// MB in size, this limit may be changed in the future.
-    async fn send_document(&self, data: SendDocument) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.document {
-            files.push(f.clone());
-        }
-
-        if data.thumbnail.is_some() {
-            if let InputFile::File(f) = data.thumbnail.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_document(&self, mut data: SendDocument) -> Result<Message> {
+        let files = files_to_upload(std::iter::once(&mut data.document).chain(&mut data.thumbnail));
 
         self.post_file(
             APIEndpoint::SendDocument,
@@ -351,57 +421,40 @@ pub trait API: Sync {
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendDocument)
     }
 
     /// Use this method to send video files, Telegram clients support mp4 videos
     /// (other formats may be sent as [`Document`]). On success, the sent
     /// [`Message`] is returned. Bots can currently send video files of up to 50
     /// MB in size, this limit may be changed in the future.
-    async fn send_video(&self, data: SendVideo) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.video {
-            files.push(f.clone());
-        }
-
-        if data.thumbnail.is_some() {
-            if let InputFile::File(f) = data.thumbnail.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_video(&self, mut data: SendVideo) -> Result<Message> {
+        let files = files_to_upload(std::iter::once(&mut data.video).chain(&mut data.thumbnail));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVideo,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendVideo)
     }
 
     /// Use this method to send animation files (GIF or H.264/MPEG-4 AVC video
     /// without sound). On success, the sent [`Message`] is returned. Bots
     /// can currently send animation files of up to 50 MB in size, this limit
     /// may be changed in the future.
-    async fn send_animation(&self, data: SendAnimation) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.animation {
-            files.push(f.clone());
-        }
-
-        if data.thumbnail.is_some() {
-            if let InputFile::File(f) = data.thumbnail.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_animation(&self, mut data: SendAnimation) -> Result<Message> {
+        let files =
+            files_to_upload(std::iter::once(&mut data.animation).chain(&mut data.thumbnail));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendAnimation,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendAnimation)
     }
 
     /// Use this method to send audio files, if you want Telegram clients to
@@ -417,49 +470,34 @@ pub trait API: Sync {
         }
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVoice,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendVoice)
     }
 
     /// As of v.4.0, Telegram clients support rounded square mp4 videos of up to
     /// 1 minute long. Use this method to send video messages. On success,
     /// the sent [`Message`] is returned.
-    async fn send_video_note(&self, data: SendVideoNote) -> Result<Message> {
-        let mut files = Vec::new();
-        if let InputFile::File(f) = &data.video_note {
-            files.push(f.clone());
-        }
-
-        if data.thumbnail.is_some() {
-            if let InputFile::File(f) = data.thumbnail.as_ref().unwrap() {
-                files.push(f.clone());
-            }
-        }
+    async fn send_video_note(&self, mut data: SendVideoNote) -> Result<Message> {
+        let files =
+            files_to_upload(std::iter::once(&mut data.video_note).chain(&mut data.thumbnail));
 
         self.post_file(
-            APIEndpoint::SendDocument,
+            APIEndpoint::SendVideoNote,
             Some(serde_json::to_value(&data)?),
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendVideoNote)
     }
 
     /// Use this method to send a group of photos or videos as an album.
     /// On success, a [`Vec<Message>`] is returned.
-    async fn send_media_group(&self, data: SendMediaGroup) -> Result<Vec<Message>> {
-        let mut files = Vec::new();
-        for media in &data.media {
-            if let InputFile::File(f) = media.get_media() {
-                files.push(f.clone());
-            }
-        }
-
-        files.dedup_by(|f1, f2| f1 == f2);
+    async fn send_media_group(&self, mut data: SendMediaGroup) -> Result<Vec<Message>> {
+        let files = files_to_upload(data.media.iter_mut().flat_map(InputMedia::inputs_mut));
 
         self.post_file(
             APIEndpoint::SendMediaGroup,
@@ -467,7 +505,22 @@ pub trait API: Sync {
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendMediaGroup)
+    }
+
+    /// Use this method to send paid media, which users have to pay the given
+    /// amount of Telegram Stars for to see. On success, the sent [`Message`]
+    /// is returned.
+    async fn send_paid_media(&self, mut data: SendPaidMedia) -> Result<Message> {
+        let files = files_to_upload(data.media.iter_mut().flat_map(InputPaidMedia::inputs_mut));
+
+        self.post_file(
+            APIEndpoint::SendPaidMedia,
+            Some(serde_json::to_value(&data)?),
+            Some(files),
+        )
+        .await?
+        .into_result(&APIEndpoint::SendPaidMedia)
     }
 
     /// Use this method to send a point on the map. On success, the sent
@@ -475,7 +528,7 @@ pub trait API: Sync {
     async fn send_location(&self, data: SendLocation) -> Result<Message> {
         self.post(APIEndpoint::SendLocation, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendLocation)
     }
 
     /// Use this method to send information about a venue. On success, the sent
@@ -483,7 +536,7 @@ pub trait API: Sync {
     async fn send_venue(&self, data: SendVenue) -> Result<Message> {
         self.post(APIEndpoint::SendVenue, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendVenue)
     }
 
     /// Use this method to send phone contacts. On success, the sent [`Message`]
@@ -491,7 +544,7 @@ pub trait API: Sync {
     async fn send_contact(&self, data: SendContact) -> Result<Message> {
         self.post(APIEndpoint::SendContact, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendContact)
     }
 
     /// Use this method to send a native poll. On success, the sent [`Message`]
@@ -499,7 +552,7 @@ pub trait API: Sync {
     async fn send_poll(&self, data: SendPoll) -> Result<Message> {
         self.post(APIEndpoint::SendPoll, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendPoll)
     }
 
     /// Use this method to send a dice, which will have a random value from 1 to
@@ -507,7 +560,7 @@ pub trait API: Sync {
     async fn send_dice(&self, data: SendDice) -> Result<Message> {
         self.post(APIEndpoint::SendDice, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendDice)
     }
 
     /// Use this method when you need to tell the user that something is
@@ -520,19 +573,32 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SendChatAction)
     }
 
-    /// Use this method to edit text and game messages. On success, if edited
-    /// message is sent by the bot, the edited [`Message`] is returned,
-    /// otherwise True is returned.
-    async fn edit_message_text(&self, data: EditMessageText) -> Result<TrueOrObject<Message>> {
+    /// Use this method to change the chosen reactions on a message. Service
+    /// messages can't be reacted to. Automatically forwarded messages from a
+    /// channel to its discussion group have the same available reactions as
+    /// messages in the channel. Returns True on success.
+    async fn set_message_reaction(&self, data: SetMessageReaction) -> Result<bool> {
         self.post(
-            APIEndpoint::EditMessageText,
+            APIEndpoint::SetMessageReaction,
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetMessageReaction)
+    }
+
+    /// Use this method to edit text and game messages. On success, if edited
+    /// message is sent by the bot, the edited [`Message`] is returned,
+    /// otherwise True is returned.
+    async fn edit_message_text(&self, data: EditMessageText) -> Result<TrueOrObject<Message>> {
+        let mut data = serde_json::to_value(data)?;
+        strip_superseded_link_preview(&mut data);
+
+        self.post(APIEndpoint::EditMessageText, Some(data))
+            .await?
+            .into_result(&APIEndpoint::EditMessageText)
     }
 
     /// Use this method to edit captions of messages. On success, if edited
@@ -547,7 +613,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditMessageCaption)
     }
 
     /// Use this method to edit animation, audio, document, photo, or video
@@ -557,13 +623,28 @@ pub trait API: Sync {
     /// uploaded. Use previously uploaded file via its file_id or specify a
     /// URL. On success, if the edited message was sent by the bot, the
     /// edited [`Message`] is returned, otherwise True is returned.
-    async fn edit_message_media(&self, data: EditMessageMedia) -> Result<TrueOrObject<Message>> {
-        self.post(
+    async fn edit_message_media(
+        &self,
+        mut data: EditMessageMedia,
+    ) -> Result<TrueOrObject<Message>> {
+        let files = files_to_upload(data.media.inputs_mut());
+        if files.is_empty() {
+            return self
+                .post(
+                    APIEndpoint::EditMessageMedia,
+                    Some(serde_json::to_value(data)?),
+                )
+                .await?
+                .into_result(&APIEndpoint::EditMessageMedia);
+        }
+
+        self.post_file(
             APIEndpoint::EditMessageMedia,
-            Some(serde_json::to_value(data)?),
+            Some(serde_json::to_value(&data)?),
+            Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditMessageMedia)
     }
 
     /// Use this method to edit only the reply markup of messages. On success,
@@ -578,7 +659,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditMessageReplyMarkup)
     }
 
     /// Use this method to stop a poll which was sent by the bot. On success,
@@ -586,7 +667,7 @@ pub trait API: Sync {
     async fn stop_poll(&self, data: StopPoll) -> Result<Poll> {
         self.post(APIEndpoint::StopPoll, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::StopPoll)
     }
 
     /// Use this method to delete a message, including service messages, with
@@ -610,7 +691,22 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteMessage)
+    }
+
+    /// Use this method to delete multiple messages simultaneously, between 1
+    /// and 100 of them. If some of the specified messages can't be found,
+    /// they are skipped. The limitations of [`API::delete_message`] apply to
+    /// every message. Returns True on success.
+    async fn delete_messages(&self, data: DeleteMessages) -> Result<bool> {
+        check_message_count(&data.message_ids, "delete")?;
+
+        self.post(
+            APIEndpoint::DeleteMessages,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::DeleteMessages)
     }
 
     /// Use this method to edit live location messages.
@@ -627,7 +723,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditMessageLiveLocation)
     }
 
     /// Use this method to stop updating a live location message before
@@ -642,7 +738,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::StopMessageLiveLocation)
     }
 
     /// Use this method to get a list of profile pictures for a user. Returns a
@@ -656,7 +752,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetUserProfilePhotos)
     }
 
     /// Use this method to get basic info about a file and prepare it for
@@ -668,7 +764,7 @@ pub trait API: Sync {
     async fn get_file(&self, data: GetFile) -> Result<File> {
         self.post(APIEndpoint::GetFile, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::GetFile)
     }
 
     /// Use this method to unban a previously kicked user in a supergroup or
@@ -681,7 +777,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnbanChatMember)
     }
 
     /// Use this method to ban a user from a group, a supergroup or a channel.
@@ -696,7 +792,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::BanChatMember)
     }
 
     /// Use this method to restrict a user in a supergroup.
@@ -709,7 +805,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::RestrictChatMember)
     }
 
     /// Use this method to promote or demote a user in a supergroup or a
@@ -722,7 +818,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::PromoteChatMember)
     }
 
     /// Use this method to set a custom title for an administrator in a
@@ -736,7 +832,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatAdministratorCustomTitle)
     }
 
     /// Use this method to ban a channel chat in a supergroup or a channel.
@@ -750,7 +846,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::BanChatSenderChat)
     }
 
     /// Use this method to unban a previously banned channel chat in a
@@ -763,7 +859,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnbanChatSenderChat)
     }
 
     /// Use this method to set default chat permissions for all members.
@@ -776,7 +872,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatPermissions)
     }
 
     /// Use this method to generate a new invite link for a chat; any previously
@@ -797,7 +893,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::ExportChatInviteLink)
     }
 
     /// Use this method to create an additional invite link for a chat. The bot
@@ -811,7 +907,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CreateChatInviteLink)
     }
 
     /// Use this method to edit a non-primary invite link created by the bot.
@@ -824,7 +920,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditChatInviteLink)
     }
 
     /// Use this method to revoke an invite link created by the bot. If the
@@ -838,7 +934,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::RevokeChatInviteLink)
     }
 
     /// Use this method to approve a chat join request. The bot must be an
@@ -850,7 +946,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::ApproveChatJoinRequest)
     }
 
     /// Use this method to decline a chat join request. The bot must be an
@@ -862,7 +958,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeclineChatJoinRequest)
     }
 
     /// Use this method to set a new profile photo for the chat. Photos can't be
@@ -887,7 +983,7 @@ pub trait API: Sync {
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatPhoto)
     }
 
     /// Use this method to delete a chat photo. Photos can't be changed for
@@ -900,7 +996,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteChatPhoto)
     }
 
     /// Use this method to change the title of a chat. Titles can't be changed
@@ -910,7 +1006,7 @@ pub trait API: Sync {
     async fn set_chat_title(&self, data: SetChatTitle) -> Result<bool> {
         self.post(APIEndpoint::SetChatTitle, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SetChatTitle)
     }
 
     /// Use this method to change the description of a group, a supergroup or a
@@ -923,7 +1019,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatDescription)
     }
 
     /// Use this method to pin a message in a group, a supergroup, or a channel.
@@ -937,7 +1033,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::PinChatMessage)
     }
 
     /// Use this method to remove a message from the list of pinned messages in
@@ -951,7 +1047,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnpinChatMessage)
     }
 
     /// Use this method to clear the list of pinned messages in a chat. If the
@@ -965,7 +1061,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnpinAllChatMessages)
     }
 
     /// Use this method for your bot to leave a group, supergroup or channel.
@@ -973,7 +1069,7 @@ pub trait API: Sync {
     async fn leave_chat(&self, data: LeaveChat) -> Result<bool> {
         self.post(APIEndpoint::LeaveChat, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::LeaveChat)
     }
 
     /// Use this method to get up to date information about the chat
@@ -981,10 +1077,11 @@ pub trait API: Sync {
     /// of a user, group or channel, etc.). Returns a [`Chat`] object on
     /// success.
     async fn get_chat(&self, data: GetChat) -> Result<Chat> {
-        Ok(Into::<Chat>::into(Into::<Result<RawChat>>::into(
+        Ok(Into::<Chat>::into(
             self.get(APIEndpoint::GetChat, Some(serde_json::to_value(data)?))
-                .await?,
-        )?))
+                .await?
+                .into_result::<RawChat>(&APIEndpoint::GetChat)?,
+        ))
     }
 
     /// Use this method to get a list of administrators in a chat.
@@ -1001,7 +1098,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetChatAdministrators)
     }
 
     /// Use this method to get the number of members in a chat. Returns i64 on
@@ -1012,7 +1109,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetChatMemberCount)
     }
 
     /// Use this method to get information about a member of a chat. Returns a
@@ -1023,7 +1120,34 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetChatMember)
+    }
+
+    /// Use this method to get the list of boosts added to a chat by a user.
+    /// Requires administrator rights in the chat. Returns a [`UserChatBoosts`]
+    /// object.
+    async fn get_user_chat_boosts(&self, data: GetUserChatBoosts) -> Result<UserChatBoosts> {
+        self.get(
+            APIEndpoint::GetUserChatBoosts,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::GetUserChatBoosts)
+    }
+
+    /// Use this method to get information about the connection of the bot
+    /// with a business account. Returns a [`BusinessConnection`] object on
+    /// success.
+    async fn get_business_connection(
+        &self,
+        data: GetBusinessConnection,
+    ) -> Result<BusinessConnection> {
+        self.get(
+            APIEndpoint::GetBusinessConnection,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::GetBusinessConnection)
     }
 
     /// Use this method to set a new group sticker set for a supergroup.
@@ -1037,7 +1161,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetChatStickerSet)
     }
 
     /// Use this method to delete a group sticker set from a supergroup.
@@ -1051,7 +1175,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteChatStickerSet)
     }
 
     /// Use this method to get custom emoji stickers, which can be used as
@@ -1059,7 +1183,7 @@ pub trait API: Sync {
     async fn get_forum_topic_icon_stickers(&self) -> Result<Vec<Sticker>> {
         self.get(APIEndpoint::GetForumTopicIconStickers, None)
             .await?
-            .into()
+            .into_result(&APIEndpoint::GetForumTopicIconStickers)
     }
 
     /// Use this method to create a topic in a forum supergroup chat.
@@ -1072,7 +1196,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CreateForumTopic)
     }
 
     /// Use this method to edit name and icon of a topic in a forum supergroup
@@ -1085,7 +1209,42 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditForumTopic)
+    }
+
+    /// Use this method to change the icon of a topic in a forum supergroup
+    /// chat to the forum topic icon sticker associated with the given emoji.
+    ///
+    /// The available icons are retrieved using
+    /// [`API::get_forum_topic_icon_stickers`], so implementations caching
+    /// that method will avoid the extra request. Returns
+    /// [`TelegramError::InvalidArgument`] listing the available emoji if
+    /// none of the icons match the given emoji, otherwise returns True on
+    /// success.
+    async fn edit_forum_topic_icon(
+        &self,
+        chat_id: IntegerOrString,
+        message_thread_id: i64,
+        emoji: &str,
+    ) -> Result<bool> {
+        let stickers = self.get_forum_topic_icon_stickers().await?;
+
+        let Some(custom_emoji_id) = stickers
+            .iter()
+            .find(|s| s.emoji.as_deref() == Some(emoji))
+            .and_then(|s| s.custom_emoji_id.clone())
+        else {
+            let available: Vec<&str> = stickers.iter().filter_map(|s| s.emoji.as_deref()).collect();
+            return Err(TelegramError::InvalidArgument(format!(
+                "no forum topic icon found for {emoji}, available emoji are: {}",
+                available.join(" ")
+            ))
+            .into());
+        };
+
+        let mut data = EditForumTopic::new(chat_id, message_thread_id.into());
+        data.set_icon_custom_emoji_id(custom_emoji_id);
+        self.edit_forum_topic(data).await
     }
 
     /// Use this method to close an open topic in a forum supergroup chat.
@@ -1098,7 +1257,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CloseForumTopic)
     }
 
     /// Use this method to reopen a closed topic in a forum supergroup chat.
@@ -1111,7 +1270,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::ReopenForumTopic)
     }
 
     /// Use this method to delete a forum topic along with all its messages in a
@@ -1124,7 +1283,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteForumTopic)
     }
 
     /// Use this method to clear the list of pinned messages in a forum topic.
@@ -1140,7 +1299,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnpinAllForumTopicMessages)
     }
 
     /// Use this method to edit the name of the 'General' topic in a forum
@@ -1153,7 +1312,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::EditGeneralForumTopic)
     }
 
     /// Use this method to close an open 'General' topic in a forum supergroup
@@ -1166,7 +1325,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CloseGeneralForumTopic)
     }
 
     /// Use this method to reopen a closed 'General' topic in a forum supergroup
@@ -1179,7 +1338,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::ReopenGeneralForumTopic)
     }
 
     /// Use this method to hide the 'General' topic in a forum supergroup chat.
@@ -1192,7 +1351,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::HideGeneralForumTopic)
     }
 
     /// Use this method to unhide the 'General' topic in a forum supergroup
@@ -1205,7 +1364,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnhideGeneralForumTopic)
     }
 
     /// Use this method to clear the list of pinned messages in a General forum
@@ -1221,7 +1380,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::UnpinAllGeneralForumTopicMessages)
     }
 
     /// Use this method to send answers to callback queries sent from [inline keyboards](https://core.telegram.org/bots#inline-keyboards-and-on-the-fly-updating).
@@ -1233,7 +1392,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::AnswerCallbackQuery)
     }
 
     /// Use this method to send static .WEBP or animated .TGS stickers. On
@@ -1243,7 +1402,7 @@ pub trait API: Sync {
             InputFile::String(_) => self
                 .post(APIEndpoint::SendSticker, Some(serde_json::to_value(&data)?))
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SendSticker),
             InputFile::File(f) => self
                 .post_file(
                     APIEndpoint::SendSticker,
@@ -1251,7 +1410,7 @@ pub trait API: Sync {
                     Some(vec![f.clone()]),
                 )
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SendSticker),
         }
     }
 
@@ -1263,7 +1422,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetStickerSet)
     }
 
     /// Use this method to get information about custom emoji stickers by their
@@ -1284,7 +1443,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetCustomEmojiStickers)
     }
 
     /// Use this method to upload a .PNG file with a sticker for later use in
@@ -1299,7 +1458,7 @@ pub trait API: Sync {
                     Some(vec![f.clone()]),
                 )
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::UploadStickerFile),
             InputFile::String(_) => Err(TelegramError::InvalidArgument(
                 "upload_sticker_file only accepts files, not urls/ids".to_owned(),
             )
@@ -1309,9 +1468,8 @@ pub trait API: Sync {
 
     /// Use this method to create a new sticker set owned by a user.
     /// The bot will be able to edit the sticker set thus created.
-    /// You must use exactly one of the fields png_sticker or tgs_sticker.
     /// Returns True on success.
-    async fn create_new_sticker_set(&self, data: CreateNewStickerSet) -> Result<bool> {
+    async fn create_new_sticker_set(&self, mut data: CreateNewStickerSet) -> Result<bool> {
         if data.stickers.is_empty() || data.stickers.len() > 50 {
             return Err(TelegramError::InvalidArgument(
                 "You must pass between 1 and 50 initial stickers for the set".to_owned(),
@@ -1319,20 +1477,7 @@ pub trait API: Sync {
             .into());
         }
 
-        let mut files = Vec::new();
-
-        for sticker in &data.stickers {
-            match sticker.sticker {
-                InputFile::File(ref f) => files.push(f.clone()),
-                InputFile::String(_) if data.sticker_format != StickerFormat::Static => {
-                    return Err(TelegramError::InvalidArgument(
-                        "video or animated stickers only accept files, not urls/ids".to_owned(),
-                    )
-                    .into())
-                },
-                InputFile::String(_) => {},
-            }
-        }
+        let files = files_to_upload(data.stickers.iter_mut().map(|s| &mut s.sticker));
 
         self.post_file(
             APIEndpoint::CreateNewStickerSet,
@@ -1340,14 +1485,12 @@ pub trait API: Sync {
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CreateNewStickerSet)
     }
 
     /// Use this method to add a new sticker to a set created by the bot.
-    /// You must use exactly one of the fields png_sticker or tgs_sticker.
-    /// Animated stickers can be added to animated sticker sets and only to
-    /// them. Animated sticker sets can have up to 50 stickers. Static
-    /// sticker sets can have up to 120 stickers. Returns True on success.
+    /// Emoji sticker sets can have up to 200 stickers. Other sticker sets can
+    /// have up to 120 stickers. Returns True on success.
     async fn add_sticker_to_set(&self, data: AddStickerToSet) -> Result<bool> {
         let mut files = Vec::new();
         if let InputFile::File(ref f) = data.sticker.sticker {
@@ -1360,7 +1503,31 @@ pub trait API: Sync {
             Some(files),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::AddStickerToSet)
+    }
+
+    /// Use this method to replace an existing sticker in a sticker set with a
+    /// new one. The method is equivalent to calling
+    /// [`API::delete_sticker_from_set`], then [`API::add_sticker_to_set`],
+    /// then [`API::set_sticker_position_in_set`]. Returns True on success.
+    async fn replace_sticker_in_set(&self, data: ReplaceStickerInSet) -> Result<bool> {
+        match &data.sticker.sticker {
+            InputFile::File(f) => self
+                .post_file(
+                    APIEndpoint::ReplaceStickerInSet,
+                    Some(serde_json::to_value(&data)?),
+                    Some(vec![f.clone()]),
+                )
+                .await?
+                .into_result(&APIEndpoint::ReplaceStickerInSet),
+            InputFile::String(_) => self
+                .post(
+                    APIEndpoint::ReplaceStickerInSet,
+                    Some(serde_json::to_value(&data)?),
+                )
+                .await?
+                .into_result(&APIEndpoint::ReplaceStickerInSet),
+        }
     }
 
     /// Use this method to move a sticker in a set created by the bot to a
@@ -1371,7 +1538,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetStickerPositionInSet)
     }
 
     /// Use this method to delete a sticker from a set created by the bot.
@@ -1382,7 +1549,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteStickerFromSet)
     }
 
     /// Use this method to change the list of emoji assigned to a regular or
@@ -1394,7 +1561,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetStickerEmojiList)
     }
 
     /// Use this method to change search keywords assigned to a regular or
@@ -1406,7 +1573,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetStickerKeywords)
     }
 
     /// Use this method to change the [mask position] of a mask sticker. The
@@ -1420,7 +1587,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetStickerMaskPosition)
     }
 
     /// Use this method to set the title of a created sticker set. Returns True
@@ -1431,7 +1598,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetStickerSetTitle)
     }
 
     /// Use this method to set the thumbnail of a sticker set.
@@ -1445,7 +1612,7 @@ pub trait API: Sync {
                     Some(serde_json::to_value(&data)?),
                 )
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SetStickerSetThumbnail),
             Some(InputFile::File(f)) => self
                 .post_file(
                     APIEndpoint::SetStickerSetThumbnail,
@@ -1453,7 +1620,7 @@ pub trait API: Sync {
                     Some(vec![f.clone()]),
                 )
                 .await?
-                .into(),
+                .into_result(&APIEndpoint::SetStickerSetThumbnail),
         }
     }
 
@@ -1468,7 +1635,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetCustomEmojiStickerSetThumbnail)
     }
 
     /// Use this method to delete a sticker set that was created by the bot.
@@ -1479,7 +1646,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::DeleteStickerSet)
     }
 
     /// Use this method to send answers to an inline query. On success, True is
@@ -1492,12 +1659,12 @@ pub trait API: Sync {
             .into());
         }
 
-        self.post(
-            APIEndpoint::AnswerInlineQuery,
-            Some(serde_json::to_value(data)?),
-        )
-        .await?
-        .into()
+        let mut data = serde_json::to_value(data)?;
+        strip_superseded_link_preview(&mut data);
+
+        self.post(APIEndpoint::AnswerInlineQuery, Some(data))
+            .await?
+            .into_result(&APIEndpoint::AnswerInlineQuery)
     }
 
     /// Use this method to set the result of an interaction with a [Web App] and
@@ -1507,12 +1674,12 @@ pub trait API: Sync {
     ///
     /// [Web App]: https://core.telegram.org/bots/webapps
     async fn answer_web_app_query(&self, data: AnswerWebAppQuery) -> Result<SentWebAppMessage> {
-        self.post(
-            APIEndpoint::AnswerWebAppQuery,
-            Some(serde_json::to_value(data)?),
-        )
-        .await?
-        .into()
+        let mut data = serde_json::to_value(data)?;
+        strip_superseded_link_preview(&mut data);
+
+        self.post(APIEndpoint::AnswerWebAppQuery, Some(data))
+            .await?
+            .into_result(&APIEndpoint::AnswerWebAppQuery)
     }
 
     /// Use this method to send invoices. On success, the sent [Message] is
@@ -1520,7 +1687,7 @@ pub trait API: Sync {
     async fn send_invoice(&self, data: SendInvoice) -> Result<Message> {
         self.post(APIEndpoint::SendInvoice, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendInvoice)
     }
 
     /// Use this method to create a link for an invoice. Returns the created
@@ -1531,7 +1698,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::CreateInvoiceLink)
     }
 
     /// If you sent an invoice requesting a shipping address and the parameter
@@ -1544,7 +1711,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::AnswerShippingQuery)
     }
 
     /// Once the user has confirmed their payment and shipping details, the Bot
@@ -1559,7 +1726,31 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::AnswerPreCheckoutQuery)
+    }
+
+    /// Refunds a successful payment in [Telegram Stars]. Returns True on
+    /// success.
+    ///
+    /// [Telegram Stars]: https://t.me/BotNews/90
+    async fn refund_star_payment(&self, data: RefundStarPayment) -> Result<bool> {
+        self.post(
+            APIEndpoint::RefundStarPayment,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::RefundStarPayment)
+    }
+
+    /// Returns the bot's Telegram Star transactions in chronological order.
+    /// On success, returns a [`StarTransactions`] object.
+    async fn get_star_transactions(&self, data: GetStarTransactions) -> Result<StarTransactions> {
+        self.post(
+            APIEndpoint::GetStarTransactions,
+            Some(serde_json::to_value(data)?),
+        )
+        .await?
+        .into_result(&APIEndpoint::GetStarTransactions)
     }
 
     /// Use this method to send a game. On success, the sent [Message] is
@@ -1567,7 +1758,7 @@ pub trait API: Sync {
     async fn send_game(&self, data: SendGame) -> Result<Message> {
         self.post(APIEndpoint::SendGame, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SendGame)
     }
 
     /// Use this method to set the score of the specified user in a game.
@@ -1578,7 +1769,7 @@ pub trait API: Sync {
     async fn set_game_score(&self, data: SetGameScore) -> Result<TrueOrObject<Message>> {
         self.post(APIEndpoint::SetGameScore, Some(serde_json::to_value(data)?))
             .await?
-            .into()
+            .into_result(&APIEndpoint::SetGameScore)
     }
 
     /// Use this method to get data for high score tables. Will return the score
@@ -1590,7 +1781,7 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::GetGameHighScores)
     }
 
     /// Informs a user that some of the Telegram Passport elements they provided
@@ -1610,6 +1801,74 @@ pub trait API: Sync {
             Some(serde_json::to_value(data)?),
         )
         .await?
-        .into()
+        .into_result(&APIEndpoint::SetPassportDataErrors)
+    }
+}
+
+/// Checks that between 1 and 100 messages are passed to a bulk method, which
+/// is the amount telegram accepts
+fn check_message_count(message_ids: &[i64], action: &str) -> Result<()> {
+    if message_ids.is_empty() || message_ids.len() > 100 {
+        return Err(TelegramError::InvalidArgument(format!(
+            "You must pass between 1 and 100 messages to {action}"
+        ))
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Collects the files of the given inputs that have to be uploaded, every file
+/// is uploaded once even if it is used multiple times. A different file
+/// attached under the name of an earlier one, like `cat.jpg` after `cat.png`,
+/// is renamed by adding an index to its name, in the input as well.
+fn files_to_upload<'a>(inputs: impl IntoIterator<Item = &'a mut InputFile>) -> Vec<FormDataFile> {
+    // the files to upload, with the names they had before being renamed
+    let mut files: Vec<(FormDataFile, String)> = Vec::new();
+
+    for input in inputs {
+        let InputFile::File(file) = input else {
+            continue;
+        };
+
+        let original = file.name.clone();
+        let uploaded = files.iter().find(|(f, name)| {
+            *name == original
+                && FormDataFile {
+                    name: original.clone(),
+                    ..f.clone()
+                } == *file
+        });
+        if let Some((f, _)) = uploaded {
+            file.name.clone_from(&f.name);
+            continue;
+        }
+
+        let mut index = 0;
+        while files.iter().any(|(f, _)| f.name == file.name) {
+            index += 1;
+            file.name = format!("{original}_{index}");
+        }
+        files.push((file.clone(), original));
+    }
+
+    files.into_iter().map(|(f, _)| f).collect()
+}
+
+/// Removes the deprecated `disable_web_page_preview` field from every object
+/// that also has `link_preview_options` set, as telegram only expects one of
+/// them
+fn strip_superseded_link_preview(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if map.contains_key("link_preview_options") {
+                map.remove("disable_web_page_preview");
+            }
+            map.values_mut().for_each(strip_superseded_link_preview);
+        },
+        serde_json::Value::Array(values) => {
+            values.iter_mut().for_each(strip_superseded_link_preview);
+        },
+        _ => {},
     }
 }
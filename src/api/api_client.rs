@@ -1,16 +1,33 @@
-use super::{api::API, endpoints::APIEndpoint, response::Response};
-use crate::utils::{
-    encode_multipart_form_data,
-    result::Result,
-    AsFormData,
-    FormDataFile,
-    BOUNDARY,
+use super::{
+    api::API,
+    endpoints::APIEndpoint,
+    media_cache::MediaCache,
+    response::Response,
+    types::{GeneralTopicEncoding, TopicId},
+    usage::UsageTracker,
+};
+use crate::{
+    model::{File, ParseMode},
+    utils::{
+        prepare_attachments,
+        result::{Result, TelegramError},
+        AsFormData,
+        FormDataFile,
+        MultipartBody,
+        BOUNDARY,
+    },
 };
 use async_trait::async_trait;
 use hyper::{body::HttpBody, client::HttpConnector, Body, Client, Request};
-use std::io::Write;
+use serde_json::Value;
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{io::AsyncWriteExt, sync::Semaphore};
 
-static TELEGRAM_API: &str = "https://api.telegram.org/bot";
+static TELEGRAM_API: &str = "https://api.telegram.org";
 
 #[cfg(feature = "native-tls")]
 pub type TlsClient = Client<hyper_tls::HttpsConnector<HttpConnector>>;
@@ -22,6 +39,9 @@ pub type TlsClient = Client<hyper_rustls::HttpsConnector<HttpConnector>>;
 /// It requires your bot token in order to interact with the telegram API and
 /// also allows you to configure your own [`hyper::Client`] for it to use.
 ///
+/// With the `rustls` feature the default hyper client negotiates HTTP/2 with
+/// telegram, so concurrent requests share a single connection.
+///
 /// Using the default `APIClient` is as easy as:
 /// ```no_run
 /// use telexide::api::{APIClient, API, types::SendMessage};
@@ -44,6 +64,13 @@ pub type TlsClient = Client<hyper_rustls::HttpsConnector<HttpConnector>>;
 pub struct APIClient {
     hyper_client: TlsClient,
     token: String,
+    api_url: String,
+    timeout: Option<Duration>,
+    max_retries: u32,
+    usage: UsageTracker,
+    default_parse_mode: Option<ParseMode>,
+    request_limit: Option<Semaphore>,
+    media_cache: Option<MediaCache>,
 }
 
 impl APIClient {
@@ -55,30 +82,69 @@ impl APIClient {
             || Self {
                 hyper_client: Self::make_default_client(),
                 token: token.to_string(),
+                api_url: TELEGRAM_API.to_owned(),
+                timeout: None,
+                max_retries: 0,
+                usage: UsageTracker::new(),
+                default_parse_mode: None,
+                request_limit: None,
+                media_cache: None,
             },
             |c| Self {
                 hyper_client: c,
                 token: token.to_string(),
+                api_url: TELEGRAM_API.to_owned(),
+                timeout: None,
+                max_retries: 0,
+                usage: UsageTracker::new(),
+                default_parse_mode: None,
+                request_limit: None,
+                media_cache: None,
             },
         )
     }
 
-    #[cfg(feature = "native-tls")]
     fn make_default_client() -> TlsClient {
-        hyper::Client::builder().build(hyper_tls::HttpsConnector::new())
+        Self::build_hyper_client(&hyper::Client::builder())
     }
 
+    /// Builds a hyper client with the given settings, for example for its
+    /// connection pool, using the same connector as the default hyper client
+    /// of the `APIClient`.
+    #[cfg(feature = "native-tls")]
+    pub fn build_hyper_client(builder: &hyper::client::Builder) -> TlsClient {
+        builder.build(hyper_tls::HttpsConnector::new())
+    }
+
+    /// Builds a hyper client with the given settings, for example for its
+    /// connection pool, using the same connector as the default hyper client
+    /// of the `APIClient`.
     #[cfg(all(feature = "rustls", not(feature = "native-tls")))]
-    fn make_default_client() -> TlsClient {
-        hyper::Client::builder().build(
+    pub fn build_hyper_client(builder: &hyper::client::Builder) -> TlsClient {
+        builder.build(
             hyper_rustls::HttpsConnectorBuilder::new()
                 .with_native_roots()
                 .https_or_http()
                 .enable_http1()
+                .enable_http2()
                 .build(),
         )
     }
 
+    /// Creates a new `APIClient` with the provided token that sends its
+    /// requests using the given pre-built hyper client, for example one with
+    /// a proxying connector, custom DNS resolution or a tuned connection pool.
+    ///
+    /// The client is used by every request of the `APIClient`, which can be
+    /// sent from many tasks at the same time, so it must be `Send + Sync` and
+    /// its connector must support being cloned for every connection. Cloning a
+    /// hyper client is cheap and the clones share the same connection pool,
+    /// so the same client can be passed to multiple `APIClient`s.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn with_client(token: impl ToString, client: TlsClient) -> Self {
+        Self::new(Some(client), token)
+    }
+
     /// Creates a new `APIClient` with the provided token and the default hyper
     /// client.
     #[allow(clippy::needless_pass_by_value)]
@@ -86,8 +152,209 @@ impl APIClient {
         Self::new(None, token)
     }
 
+    /// Sets the base url of the bot api server to send requests to, for
+    /// example when using a [local bot api server]. Defaults to
+    /// `https://api.telegram.org`.
+    ///
+    /// [local bot api server]: https://core.telegram.org/bots/api#using-a-local-bot-api-server
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_api_url(&mut self, api_url: impl ToString) -> &mut Self {
+        self.api_url = api_url.to_string();
+        self.api_url
+            .truncate(self.api_url.trim_end_matches('/').len());
+        self
+    }
+
+    /// Sets the maximum duration a single request to the telegram api may
+    /// take before it is aborted with a [`TelegramError::Timeout`]. Telegram
+    /// may still carry out a request that timed out, see the [timeouts] notes
+    /// of the [`API`] trait.
+    ///
+    /// [`TelegramError::Timeout`]: ../enum.TelegramError.html#variant.Timeout
+    /// [`API`]: trait.API.html
+    /// [timeouts]: trait.API.html#timeouts
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many times a request is repeated when it fails because
+    /// telegram's flood control was exceeded, waiting for the amount of time
+    /// telegram asks for before every retry. Defaults to 0, in which case a
+    /// [`TelegramError::RateLimited`] is returned right away.
+    ///
+    /// [`TelegramError::RateLimited`]: ../enum.TelegramError.html#variant.RateLimited
+    pub fn set_max_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Sets the parse mode used for the text and captions of outgoing
+    /// messages that neither set a parse mode nor entities themselves.
+    pub fn set_default_parse_mode(&mut self, parse_mode: ParseMode) -> &mut Self {
+        self.default_parse_mode = Some(parse_mode);
+        self
+    }
+
+    /// Sets the maximum amount of requests that may be in flight to the
+    /// telegram api at the same time, further requests wait until one of them
+    /// finishes. This is separate from the limit on concurrent handlers of the
+    /// [`Client`]. By default the amount is unbounded, a maximum of 0 is
+    /// treated as 1.
+    ///
+    /// [`Client`]: ../client/struct.Client.html
+    pub fn set_max_concurrent_requests(&mut self, max: usize) -> &mut Self {
+        self.request_limit = Some(Semaphore::new(max.max(1)));
+        self
+    }
+
+    /// Sets the [`UsageTracker`] keeping track of how often the endpoints of
+    /// the telegram api are called, replacing the default one.
+    ///
+    /// [`UsageTracker`]: struct.UsageTracker.html
+    pub fn set_usage_tracker(&mut self, usage: UsageTracker) -> &mut Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Gets the [`UsageTracker`] of this client, which can be used to read
+    /// how often the tracked endpoints were called within their window.
+    ///
+    /// [`UsageTracker`]: struct.UsageTracker.html
+    pub fn usage(&self) -> &UsageTracker {
+        &self.usage
+    }
+
+    /// Sets the [`MediaCache`] remembering the `file_id`s of uploaded files,
+    /// so they aren't uploaded again when they are sent another time. By
+    /// default files are always uploaded.
+    ///
+    /// [`MediaCache`]: struct.MediaCache.html
+    pub fn set_media_cache(&mut self, cache: MediaCache) -> &mut Self {
+        self.media_cache = Some(cache);
+        self
+    }
+
+    /// Gets the [`MediaCache`] of this client, if one is set.
+    ///
+    /// [`MediaCache`]: struct.MediaCache.html
+    pub fn media_cache(&self) -> Option<&MediaCache> {
+        self.media_cache.as_ref()
+    }
+
     fn parse_endpoint(&self, endpoint: &APIEndpoint) -> String {
-        format!("{}{}/{}", TELEGRAM_API, self.token, endpoint)
+        format!("{}/bot{}/{}", self.api_url, self.token, endpoint)
+    }
+
+    fn encode_general_topic(endpoint: &APIEndpoint, data: &mut Option<Value>) {
+        let Some(Value::Object(map)) = data else {
+            return;
+        };
+
+        if endpoint.general_topic_encoding() == GeneralTopicEncoding::Omit
+            && map.get("message_thread_id").and_then(Value::as_i64)
+                == Some(TopicId::GENERAL_THREAD_ID)
+        {
+            map.remove("message_thread_id");
+        }
+    }
+
+    fn apply_default_parse_mode(&self, endpoint: &APIEndpoint, data: &mut Option<Value>) {
+        let (Some(parse_mode), Some(Value::Object(map))) = (&self.default_parse_mode, data) else {
+            return;
+        };
+
+        // SendMessage serializes its entities under the misspelled key
+        let (text_key, entities_keys): (_, &[_]) = match endpoint {
+            APIEndpoint::SendMessage | APIEndpoint::EditMessageText => {
+                ("text", &["entities", "enitites"])
+            },
+            APIEndpoint::SendPhoto
+            | APIEndpoint::SendAudio
+            | APIEndpoint::SendDocument
+            | APIEndpoint::SendVideo
+            | APIEndpoint::SendAnimation
+            | APIEndpoint::SendVoice
+            | APIEndpoint::SendPaidMedia
+            | APIEndpoint::CopyMessage
+            | APIEndpoint::EditMessageCaption => ("caption", &["caption_entities"]),
+            _ => return,
+        };
+
+        if map.contains_key(text_key)
+            && !map.contains_key("parse_mode")
+            && !entities_keys.iter().any(|k| map.contains_key(*k))
+        {
+            map.insert(
+                "parse_mode".to_owned(),
+                serde_json::to_value(parse_mode).expect("a parse mode is always valid json"),
+            );
+        }
+    }
+
+    async fn execute_with_retries<F>(
+        &self,
+        endpoint: &APIEndpoint,
+        make_request: F,
+    ) -> Result<Response>
+    where
+        F: Fn() -> Result<Request<Body>> + Send + Sync,
+    {
+        let request = async {
+            let mut retries = 0;
+            loop {
+                self.usage.record(endpoint)?;
+                let response = self.execute(endpoint, make_request()?).await?;
+
+                match response.retry_after() {
+                    Some(retry_after) if retries < self.max_retries => {
+                        log::warn!("rate limited by telegram, retrying in {retry_after} seconds");
+                        tokio::time::sleep(Duration::from_secs(retry_after)).await;
+                        retries += 1;
+                    },
+                    _ => return Ok(response),
+                }
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        let request = crate::utils::spans::instrument_request(endpoint, request);
+
+        request.await
+    }
+
+    async fn execute(&self, endpoint: &APIEndpoint, request: Request<Body>) -> Result<Response> {
+        self.limited(endpoint.as_str(), async {
+            let response = self.hyper_client.request(request).await?;
+            let status = response.status().as_u16();
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+
+            Response::new(status, &body)
+        })
+        .await
+    }
+
+    /// Runs the request to the endpoint within the limit on concurrent
+    /// requests and the timeout of the client
+    async fn limited<T>(&self, endpoint: &str, fut: impl Future<Output = Result<T>>) -> Result<T> {
+        let _permit = match &self.request_limit {
+            Some(limit) => Some(
+                limit
+                    .acquire()
+                    .await
+                    .expect("the request limit is never closed"),
+            ),
+            None => None,
+        };
+
+        match self.timeout {
+            Some(t) => tokio::time::timeout(t, fut)
+                .await
+                .map_err(|_| TelegramError::Timeout {
+                    endpoint: endpoint.to_owned(),
+                })?,
+            None => fut.await,
+        }
     }
 
     /// Sends a request to the provided `APIEndpoint` with the data provided
@@ -108,11 +375,125 @@ impl APIClient {
         }
     }
 
+    /// Downloads the given file, which has to be retrieved using [`get_file`]
+    /// first so its `file_path` is set. The download counts towards the limit
+    /// on concurrent requests and has to finish within the timeout of the
+    /// client.
+    ///
+    /// [`get_file`]: trait.API.html#method.get_file
+    pub async fn download_file(&self, file: &File) -> Result<Vec<u8>> {
+        self.limited(&download_endpoint(file), async {
+            let body = self.request_file(file).await?;
+            Ok(hyper::body::to_bytes(body).await?.to_vec())
+        })
+        .await
+    }
+
+    /// Downloads the given file to the provided path, writing it to disk while
+    /// it is being received. The file has to be retrieved using [`get_file`]
+    /// first so its `file_path` is set.
+    ///
+    /// Like [`download_file`], the download is subject to the limit on
+    /// concurrent requests and the timeout of the client. The file is written
+    /// next to the destination with `.part` appended to its name, and only
+    /// moved to the destination once it is complete, so a failed download
+    /// leaves nothing behind.
+    ///
+    /// [`get_file`]: trait.API.html#method.get_file
+    /// [`download_file`]: #method.download_file
+    pub async fn download_file_to<P: AsRef<Path>>(&self, file: &File, dest: P) -> Result<()> {
+        let dest = dest.as_ref();
+        let mut part = dest.as_os_str().to_owned();
+        part.push(".part");
+        let part = PathBuf::from(part);
+
+        let res = self
+            .limited(&download_endpoint(file), async {
+                let mut body = self.request_file(file).await?;
+                let mut out = tokio::fs::File::create(&part).await?;
+
+                while let Some(chunk) = body.data().await {
+                    out.write_all(&chunk?).await?;
+                }
+                out.flush().await?;
+                Ok(())
+            })
+            .await;
+
+        match res {
+            Ok(()) => Ok(tokio::fs::rename(&part, dest).await?),
+            Err(e) => {
+                // the part may not have been created yet
+                let _ = tokio::fs::remove_file(&part).await;
+                Err(e)
+            },
+        }
+    }
+
+    async fn request_file(&self, file: &File) -> Result<Body> {
+        let path = file.file_path.as_ref().ok_or_else(|| {
+            TelegramError::InvalidArgument(format!(
+                "file {} has no file_path, retrieve it using get_file before downloading it",
+                file.file_id
+            ))
+        })?;
+        let url = format!("{}/file/bot{}/{}", self.api_url, self.token, path);
+
+        log::debug!("downloading file {}", &file.file_id);
+        let response = self.hyper_client.get(url.parse()?).await?;
+        if !response.status().is_success() {
+            return Err(TelegramError::APIResponseError(format!(
+                "downloading file {} failed with status {}",
+                file.file_id,
+                response.status()
+            ))
+            .into());
+        }
+
+        Ok(response.into_body())
+    }
+
     /// gets a reference to the underlying hyper client, for example so you can
     /// make custom api requests
     pub fn get_hyper(&self) -> &TlsClient {
         &self.hyper_client
     }
+
+    async fn send_files(
+        &self,
+        endpoint: APIEndpoint,
+        mut data: Option<serde_json::Value>,
+        mut files: Vec<FormDataFile>,
+    ) -> Result<Response> {
+        if files.is_empty() {
+            return self.post(endpoint, data).await;
+        }
+
+        Self::encode_general_topic(&endpoint, &mut data);
+        self.apply_default_parse_mode(&endpoint, &mut data);
+        if let Some(mut data) = data {
+            prepare_attachments(&mut data, &mut files);
+            files.append(&mut data.as_form_data()?);
+        }
+
+        let body = MultipartBody::new(&files)?;
+
+        log::debug!("POST request with files to {}", &endpoint);
+        self.execute_with_retries(&endpoint, || {
+            let mut req_builder = Request::post(self.parse_endpoint(&endpoint))
+                .header(
+                    "content-type",
+                    format!("multipart/form-data; boundary={BOUNDARY}"),
+                )
+                .header("accept", "application/json");
+            if let Some(length) = body.len() {
+                req_builder = req_builder.header("content-length", length);
+            }
+
+            Ok(req_builder.body(body.body())?)
+        })
+        .await
+    }
 }
 
 #[async_trait]
@@ -120,53 +501,40 @@ impl API for APIClient {
     async fn get(
         &self,
         endpoint: APIEndpoint,
-        data: Option<serde_json::Value>,
+        mut data: Option<serde_json::Value>,
     ) -> Result<Response> {
-        let req_builder = Request::get(self.parse_endpoint(&endpoint))
-            .header("content-type", "application/json")
-            .header("accept", "application/json");
-
-        let request = if let Some(d) = data {
-            req_builder.body(Body::from(serde_json::to_string(&d)?))?
-        } else {
-            req_builder.body(Body::empty())?
-        };
+        Self::encode_general_topic(&endpoint, &mut data);
+        let body = data.map(|d| serde_json::to_string(&d)).transpose()?;
 
         log::debug!("GET request to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
-
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?;
-        }
+        self.execute_with_retries(&endpoint, || {
+            let req_builder = Request::get(self.parse_endpoint(&endpoint))
+                .header("content-type", "application/json")
+                .header("accept", "application/json");
 
-        Ok(serde_json::from_slice(&res)?)
+            Ok(req_builder.body(body.clone().map_or_else(Body::empty, Body::from))?)
+        })
+        .await
     }
 
     async fn post(
         &self,
         endpoint: APIEndpoint,
-        data: Option<serde_json::Value>,
+        mut data: Option<serde_json::Value>,
     ) -> Result<Response> {
-        let req_builder = Request::post(self.parse_endpoint(&endpoint))
-            .header("content-type", "application/json")
-            .header("accept", "application/json");
-
-        let request = if let Some(d) = data {
-            req_builder.body(Body::from(serde_json::to_string(&d)?))?
-        } else {
-            req_builder.body(Body::empty())?
-        };
+        Self::encode_general_topic(&endpoint, &mut data);
+        self.apply_default_parse_mode(&endpoint, &mut data);
+        let body = data.map(|d| serde_json::to_string(&d)).transpose()?;
 
         log::debug!("POST request to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
-
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?;
-        }
+        self.execute_with_retries(&endpoint, || {
+            let req_builder = Request::post(self.parse_endpoint(&endpoint))
+                .header("content-type", "application/json")
+                .header("accept", "application/json");
 
-        Ok(serde_json::from_slice(&res)?)
+            Ok(req_builder.body(body.clone().map_or_else(Body::empty, Body::from))?)
+        })
+        .await
     }
 
     async fn post_file(
@@ -175,37 +543,57 @@ impl API for APIClient {
         data: Option<serde_json::Value>,
         files: Option<Vec<FormDataFile>>,
     ) -> Result<Response> {
-        if files.is_none() {
-            return self.post(endpoint, data).await;
-        }
-
-        let mut files = files.expect("no files");
-        if files.is_empty() {
-            return self.post(endpoint, data).await;
-        }
-
-        let req_builder = Request::post(self.parse_endpoint(&endpoint))
-            .header(
-                "content-type",
-                format!("multipart/form-data; boundary={BOUNDARY}"),
-            )
-            .header("accept", "application/json");
-
-        if data.is_some() {
-            files.append(&mut data.expect("no data").as_form_data()?);
-        }
+        let files = files.unwrap_or_default();
+        let Some(cache) = &self.media_cache else {
+            return self.send_files(endpoint, data, files).await;
+        };
+        let data = data.unwrap_or_else(|| Value::Object(serde_json::Map::new()));
 
-        let bytes = encode_multipart_form_data(&files)?;
-        let request = req_builder.body(Body::from(bytes))?;
+        let hashes = MediaCache::hash_files(&files).await;
+        let mut cached_data = data.clone();
+        let mut cached_files = files.clone();
+        let mut cached_hashes = hashes.clone();
+        let substituted = cache
+            .substitute(&mut cached_data, &mut cached_files, &mut cached_hashes)
+            .await;
+        if !substituted.is_empty() {
+            let response = self
+                .send_files(
+                    endpoint.clone(),
+                    Some(cached_data.clone()),
+                    cached_files.clone(),
+                )
+                .await?;
+            if !MediaCache::is_stale(&response) {
+                cache
+                    .record(
+                        &endpoint,
+                        &cached_data,
+                        &cached_files,
+                        &cached_hashes,
+                        &response,
+                    )
+                    .await;
+                return Ok(response);
+            }
 
-        log::debug!("POST request with files to {}", &endpoint);
-        let mut response = self.hyper_client.request(request).await?;
-
-        let mut res: Vec<u8> = Vec::new();
-        while let Some(chunk) = response.body_mut().data().await {
-            res.write_all(&chunk?)?;
+            log::warn!("telegram refused cached file ids, uploading the files again");
+            for hash in &substituted {
+                cache.remove_hash(hash).await;
+            }
         }
 
-        Ok(serde_json::from_slice(&res)?)
+        let response = self
+            .send_files(endpoint.clone(), Some(data.clone()), files.clone())
+            .await?;
+        cache
+            .record(&endpoint, &data, &files, &hashes, &response)
+            .await;
+        Ok(response)
     }
 }
+
+/// The name of a download of the file in errors, as it has no endpoint
+fn download_endpoint(file: &File) -> String {
+    format!("file {}", file.file_id)
+}
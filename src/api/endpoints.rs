@@ -1,7 +1,13 @@
+use super::types::GeneralTopicEncoding;
+use crate::utils::result::TelegramError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::str::FromStr;
+
 /// This enum represents all the telegram API endpoints.
 ///
 /// It is mostly used for letting the get and post methods in the API trait know
 /// how to form the endpoint path
+#[derive(Clone)]
 pub enum APIEndpoint {
     GetUpdates,
     GetMe,
@@ -22,7 +28,9 @@ pub enum APIEndpoint {
     GetMyDefaultAdministratorRights,
     DeleteMyCommands,
     ForwardMessage,
+    ForwardMessages,
     CopyMessage,
+    CopyMessages,
     SendPhoto,
     SendAudio,
     SendDocument,
@@ -31,6 +39,7 @@ pub enum APIEndpoint {
     SendVoice,
     SendVideoNote,
     SendMediaGroup,
+    SendPaidMedia,
     SendLocation,
     EditMessageLiveLocation,
     StopMessageLiveLocation,
@@ -39,6 +48,7 @@ pub enum APIEndpoint {
     SendPoll,
     SendDice,
     SendChatAction,
+    SetMessageReaction,
     GetUserProfilePhotos,
     GetFile,
     BanChatMember,
@@ -67,6 +77,8 @@ pub enum APIEndpoint {
     GetChatAdministrators,
     GetChatMemberCount,
     GetChatMember,
+    GetUserChatBoosts,
+    GetBusinessConnection,
     SetChatStickerSet,
     DeleteChatStickerSet,
     GetForumTopicIconStickers,
@@ -89,12 +101,14 @@ pub enum APIEndpoint {
     EditMessageReplyMarkup,
     StopPoll,
     DeleteMessage,
+    DeleteMessages,
     SendSticker,
     GetStickerSet,
     GetCustomEmojiStickers,
     UploadStickerFile,
     CreateNewStickerSet,
     AddStickerToSet,
+    ReplaceStickerInSet,
     SetStickerPositionInSet,
     DeleteStickerFromSet,
     SetStickerEmojiList,
@@ -110,6 +124,8 @@ pub enum APIEndpoint {
     CreateInvoiceLink,
     AnswerShippingQuery,
     AnswerPreCheckoutQuery,
+    RefundStarPayment,
+    GetStarTransactions,
     SendGame,
     SetGameScore,
     GetGameHighScores,
@@ -121,6 +137,135 @@ pub enum APIEndpoint {
 }
 
 impl APIEndpoint {
+    /// Every endpoint of the telegram api, which excludes
+    /// [`APIEndpoint::Other`]
+    pub const ALL: [APIEndpoint; 124] = [
+        Self::GetUpdates,
+        Self::GetMe,
+        Self::LogOut,
+        Self::Close,
+        Self::SendMessage,
+        Self::SetMyCommands,
+        Self::GetMyCommands,
+        Self::SetMyName,
+        Self::GetMyName,
+        Self::SetMyDescription,
+        Self::GetMyDescription,
+        Self::SetMyShortDescription,
+        Self::GetMyShortDescription,
+        Self::SetChatMenuButton,
+        Self::GetChatMenuButton,
+        Self::SetMyDefaultAdministratorRights,
+        Self::GetMyDefaultAdministratorRights,
+        Self::DeleteMyCommands,
+        Self::ForwardMessage,
+        Self::ForwardMessages,
+        Self::CopyMessage,
+        Self::CopyMessages,
+        Self::SendPhoto,
+        Self::SendAudio,
+        Self::SendDocument,
+        Self::SendVideo,
+        Self::SendAnimation,
+        Self::SendVoice,
+        Self::SendVideoNote,
+        Self::SendMediaGroup,
+        Self::SendPaidMedia,
+        Self::SendLocation,
+        Self::EditMessageLiveLocation,
+        Self::StopMessageLiveLocation,
+        Self::SendVenue,
+        Self::SendContact,
+        Self::SendPoll,
+        Self::SendDice,
+        Self::SendChatAction,
+        Self::SetMessageReaction,
+        Self::GetUserProfilePhotos,
+        Self::GetFile,
+        Self::BanChatMember,
+        Self::UnbanChatMember,
+        Self::RestrictChatMember,
+        Self::PromoteChatMember,
+        Self::SetChatAdministratorCustomTitle,
+        Self::BanChatSenderChat,
+        Self::UnbanChatSenderChat,
+        Self::SetChatPermissions,
+        Self::ExportChatInviteLink,
+        Self::CreateChatInviteLink,
+        Self::EditChatInviteLink,
+        Self::RevokeChatInviteLink,
+        Self::ApproveChatJoinRequest,
+        Self::DeclineChatJoinRequest,
+        Self::SetChatPhoto,
+        Self::DeleteChatPhoto,
+        Self::SetChatTitle,
+        Self::SetChatDescription,
+        Self::PinChatMessage,
+        Self::UnpinChatMessage,
+        Self::UnpinAllChatMessages,
+        Self::LeaveChat,
+        Self::GetChat,
+        Self::GetChatAdministrators,
+        Self::GetChatMemberCount,
+        Self::GetChatMember,
+        Self::GetUserChatBoosts,
+        Self::GetBusinessConnection,
+        Self::SetChatStickerSet,
+        Self::DeleteChatStickerSet,
+        Self::GetForumTopicIconStickers,
+        Self::CreateForumTopic,
+        Self::EditForumTopic,
+        Self::CloseForumTopic,
+        Self::ReopenForumTopic,
+        Self::DeleteForumTopic,
+        Self::EditGeneralForumTopic,
+        Self::CloseGeneralForumTopic,
+        Self::ReopenGeneralForumTopic,
+        Self::HideGeneralForumTopic,
+        Self::UnhideGeneralForumTopic,
+        Self::UnpinAllForumTopicMessages,
+        Self::UnpinAllGeneralForumTopicMessages,
+        Self::AnswerCallbackQuery,
+        Self::EditMessageText,
+        Self::EditMessageCaption,
+        Self::EditMessageMedia,
+        Self::EditMessageReplyMarkup,
+        Self::StopPoll,
+        Self::DeleteMessage,
+        Self::DeleteMessages,
+        Self::SendSticker,
+        Self::GetStickerSet,
+        Self::GetCustomEmojiStickers,
+        Self::UploadStickerFile,
+        Self::CreateNewStickerSet,
+        Self::AddStickerToSet,
+        Self::ReplaceStickerInSet,
+        Self::SetStickerPositionInSet,
+        Self::DeleteStickerFromSet,
+        Self::SetStickerEmojiList,
+        Self::SetStickerKeywords,
+        Self::SetStickerMaskPosition,
+        Self::SetStickerSetTitle,
+        Self::SetStickerSetThumbnail,
+        Self::SetCustomEmojiStickerSetThumbnail,
+        Self::DeleteStickerSet,
+        Self::AnswerInlineQuery,
+        Self::AnswerWebAppQuery,
+        Self::SendInvoice,
+        Self::CreateInvoiceLink,
+        Self::AnswerShippingQuery,
+        Self::AnswerPreCheckoutQuery,
+        Self::RefundStarPayment,
+        Self::GetStarTransactions,
+        Self::SendGame,
+        Self::SetGameScore,
+        Self::GetGameHighScores,
+        Self::SetWebhook,
+        Self::SetPassportDataErrors,
+        Self::DeleteWebhook,
+        Self::GetWebhookInfo,
+    ];
+
     pub fn as_str(&self) -> &str {
         match *self {
             Self::GetUpdates => "getUpdates",
@@ -142,7 +287,9 @@ impl APIEndpoint {
             Self::GetMyDefaultAdministratorRights => "getMyDefaultAdministratorRights",
             Self::DeleteMyCommands => "deleteMyCommands",
             Self::CopyMessage => "copyMessage",
+            Self::CopyMessages => "copyMessages",
             Self::ForwardMessage => "forwardMessage",
+            Self::ForwardMessages => "forwardMessages",
             Self::SendPhoto => "sendPhoto",
             Self::SendAudio => "sendAudio",
             Self::SendDocument => "sendDocument",
@@ -151,6 +298,7 @@ impl APIEndpoint {
             Self::SendVoice => "sendVoice",
             Self::SendVideoNote => "sendVideoNote",
             Self::SendMediaGroup => "sendMediaGroup",
+            Self::SendPaidMedia => "sendPaidMedia",
             Self::SendLocation => "sendLocation",
             Self::EditMessageLiveLocation => "editMessageLiveLocation",
             Self::StopMessageLiveLocation => "stopMessageLiveLocation",
@@ -159,6 +307,7 @@ impl APIEndpoint {
             Self::SendPoll => "sendPoll",
             Self::SendDice => "sendDice",
             Self::SendChatAction => "sendChatAction",
+            Self::SetMessageReaction => "setMessageReaction",
             Self::GetUserProfilePhotos => "getUserProfilePhotos",
             Self::GetFile => "getFile",
             Self::BanChatMember => "banChatMember",
@@ -187,6 +336,8 @@ impl APIEndpoint {
             Self::GetChatAdministrators => "getChatAdministrators",
             Self::GetChatMemberCount => "getChatMemberCount",
             Self::GetChatMember => "getChatMember",
+            Self::GetUserChatBoosts => "getUserChatBoosts",
+            Self::GetBusinessConnection => "getBusinessConnection",
             Self::SetChatStickerSet => "setChatStickerSet",
             Self::DeleteChatStickerSet => "deleteChatStickerSet",
             Self::GetForumTopicIconStickers => "getForumTopicIconStickers",
@@ -209,12 +360,14 @@ impl APIEndpoint {
             Self::EditMessageReplyMarkup => "editMessageReplyMarkup",
             Self::StopPoll => "stopPoll",
             Self::DeleteMessage => "deleteMessage",
+            Self::DeleteMessages => "deleteMessages",
             Self::SendSticker => "sendSticker",
             Self::GetStickerSet => "getStickerSet",
             Self::GetCustomEmojiStickers => "getCustomEmojiStickers",
             Self::UploadStickerFile => "uploadStickerFile",
             Self::CreateNewStickerSet => "createNewStickerSet",
             Self::AddStickerToSet => "addStickerToSet",
+            Self::ReplaceStickerInSet => "replaceStickerInSet",
             Self::SetStickerPositionInSet => "setStickerPositionInSet",
             Self::DeleteStickerFromSet => "deleteStickerFromSet",
             Self::SetStickerEmojiList => "setStickerEmojiList",
@@ -233,6 +386,8 @@ impl APIEndpoint {
             Self::CreateInvoiceLink => "createInvoiceLink",
             Self::AnswerShippingQuery => "answerShippingQuery",
             Self::AnswerPreCheckoutQuery => "answerPreCheckoutQuery",
+            Self::RefundStarPayment => "refundStarPayment",
+            Self::GetStarTransactions => "getStarTransactions",
             Self::SetWebhook => "setWebHook",
             Self::SetPassportDataErrors => "setPassportDataErrors",
             Self::DeleteWebhook => "deleteWebhook",
@@ -240,6 +395,21 @@ impl APIEndpoint {
             Self::Other(ref e) => e,
         }
     }
+
+    /// Gets how the endpoint expects the "General" topic of a forum to be
+    /// passed. The forum topic management endpoints require the literal
+    /// thread id, while all other endpoints, including those added in the
+    /// future, expect it to be left out.
+    pub fn general_topic_encoding(&self) -> GeneralTopicEncoding {
+        match *self {
+            Self::EditForumTopic
+            | Self::CloseForumTopic
+            | Self::ReopenForumTopic
+            | Self::DeleteForumTopic
+            | Self::UnpinAllForumTopicMessages => GeneralTopicEncoding::Literal,
+            _ => GeneralTopicEncoding::Omit,
+        }
+    }
 }
 
 impl std::fmt::Display for APIEndpoint {
@@ -261,3 +431,86 @@ impl From<String> for APIEndpoint {
         APIEndpoint::Other(string)
     }
 }
+
+impl FromStr for APIEndpoint {
+    type Err = TelegramError;
+
+    /// Parses a method name of the telegram api, which like telegram itself
+    /// ignores casing. Unknown method names are refused with an error listing
+    /// the most similar known method names, use [`APIEndpoint::Other`] for
+    /// endpoints not known to telexide.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(endpoint) = Self::ALL
+            .into_iter()
+            .find(|e| e.as_str().eq_ignore_ascii_case(s))
+        {
+            return Ok(endpoint);
+        }
+
+        let lowercase = s.to_lowercase();
+        let mut matches: Vec<(usize, &str)> = Self::ALL
+            .iter()
+            .map(|e| {
+                (
+                    edit_distance(&e.as_str().to_lowercase(), &lowercase),
+                    e.as_str(),
+                )
+            })
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .collect();
+        matches.sort_unstable();
+
+        let names: Vec<&str> = matches.into_iter().take(3).map(|(_, name)| name).collect();
+        Err(TelegramError::InvalidArgument(
+            if names.is_empty() {
+                format!("{s} is not a known telegram api method")
+            } else {
+                format!(
+                    "{s} is not a known telegram api method, did you mean {}?",
+                    names.join(", ")
+                )
+            },
+        ))
+    }
+}
+
+impl Serialize for APIEndpoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for APIEndpoint {
+    /// Deserializes a method name of the telegram api, unknown method names
+    /// become an [`APIEndpoint::Other`]
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Ok(name.parse().unwrap_or(APIEndpoint::Other(name)))
+    }
+}
+
+/// The maximum amount of edits for a method name to be suggested
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+/// The levenshtein distance between the two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
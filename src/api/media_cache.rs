@@ -0,0 +1,390 @@
+use super::{endpoints::APIEndpoint, response::Response};
+use crate::utils::{result::Result, FormDataFile};
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Stores the `file_id`s of uploaded files for a [`MediaCache`], keyed by the
+/// SHA-256 hash of the contents of the files.
+///
+/// Implement this to keep the `file_id`s in your own database, or use the
+/// [`MemoryMediaStore`] or [`JsonFileMediaStore`].
+#[async_trait]
+pub trait MediaStore: Send + Sync {
+    /// Gets the `file_id` of the file with the given hash
+    async fn get(&self, hash: &str) -> Option<String>;
+    /// Stores the `file_id` of the file with the given hash
+    async fn insert(&self, hash: String, file_id: String);
+    /// Removes the `file_id` of the file with the given hash
+    async fn remove(&self, hash: &str);
+}
+
+/// A [`MediaStore`] keeping the `file_id`s in memory, so they are lost when
+/// the bot restarts.
+#[derive(Debug, Default)]
+pub struct MemoryMediaStore {
+    file_ids: Mutex<HashMap<String, String>>,
+}
+
+#[async_trait]
+impl MediaStore for MemoryMediaStore {
+    async fn get(&self, hash: &str) -> Option<String> {
+        self.file_ids.lock().get(hash).cloned()
+    }
+
+    async fn insert(&self, hash: String, file_id: String) {
+        self.file_ids.lock().insert(hash, file_id);
+    }
+
+    async fn remove(&self, hash: &str) {
+        self.file_ids.lock().remove(hash);
+    }
+}
+
+/// A [`MediaStore`] keeping the `file_id`s in a json file, so they persist
+/// across restarts of the bot. The file is rewritten on every change.
+#[derive(Debug)]
+pub struct JsonFileMediaStore {
+    path: PathBuf,
+    file_ids: Mutex<HashMap<String, String>>,
+    /// Held while writing the file, so an older version never overwrites a
+    /// newer one
+    saving: tokio::sync::Mutex<()>,
+}
+
+impl JsonFileMediaStore {
+    /// Opens the store saved in the file at the given path, which is created
+    /// once the first `file_id` is stored if it doesn't exist yet.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let file_ids = match std::fs::read(&path) {
+            Ok(contents) => serde_json::from_slice(&contents)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            file_ids: Mutex::new(file_ids),
+            saving: tokio::sync::Mutex::new(()),
+        })
+    }
+
+    async fn save(&self) {
+        let _saving = self.saving.lock().await;
+        let contents = serde_json::to_vec(&*self.file_ids.lock());
+        let res = match contents {
+            Ok(contents) => tokio::fs::write(&self.path, contents).await,
+            Err(e) => Err(e.into()),
+        };
+
+        if let Err(e) = res {
+            log::warn!(
+                "failed to save the media cache to {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+#[async_trait]
+impl MediaStore for JsonFileMediaStore {
+    async fn get(&self, hash: &str) -> Option<String> {
+        self.file_ids.lock().get(hash).cloned()
+    }
+
+    async fn insert(&self, hash: String, file_id: String) {
+        let changed = self.file_ids.lock().insert(hash, file_id.clone()) != Some(file_id);
+        if changed {
+            self.save().await;
+        }
+    }
+
+    async fn remove(&self, hash: &str) {
+        let removed = self.file_ids.lock().remove(hash).is_some();
+        if removed {
+            self.save().await;
+        }
+    }
+}
+
+/// Remembers the `file_id`s telegram returns for uploaded files, so sending
+/// the same file again refers to the `file_id` instead of uploading it again.
+///
+/// When set on an [`APIClient`] using [`APIClient::set_media_cache`], every
+/// file about to be uploaded is looked up by the hash of its contents. Known
+/// files are replaced by their `file_id`, while the `file_id`s of newly
+/// uploaded photos, videos, documents, audio, animations, voice notes, video
+/// notes and stickers are recorded once they are sent. If telegram rejects a
+/// cached `file_id`, it is removed and the files are uploaded again.
+///
+/// Thumbnails are always uploaded, as telegram doesn't allow reusing them.
+/// Clones of a `MediaCache` share the same store.
+///
+/// [`APIClient`]: struct.APIClient.html
+/// [`APIClient::set_media_cache`]: struct.APIClient.html#method.set_media_cache
+#[derive(Clone)]
+pub struct MediaCache {
+    store: Arc<dyn MediaStore>,
+}
+
+impl MediaCache {
+    /// Creates a new `MediaCache` keeping the `file_id`s in memory.
+    pub fn new() -> Self {
+        Self::with_store(MemoryMediaStore::default())
+    }
+
+    /// Creates a new `MediaCache` keeping the `file_id`s in the given store.
+    pub fn with_store(store: impl MediaStore + 'static) -> Self {
+        Self {
+            store: Arc::new(store),
+        }
+    }
+
+    /// Gets the hash of the contents of the file, by which its `file_id` is
+    /// stored. Files on disk are read in chunks to hash them, which blocks
+    /// the current thread; use [`MediaCache::hash_file`] in async code.
+    pub fn hash(file: &FormDataFile) -> String {
+        let mut hasher = Sha256::new();
+        match &file.path {
+            Some(path) => {
+                let read =
+                    std::fs::File::open(path).and_then(|mut f| std::io::copy(&mut f, &mut hasher));
+                if let Err(e) = read {
+                    // the upload fails as well then, the path only keeps the hash from
+                    // matching that of another file
+                    log::warn!("failed to hash {}: {e}", path.display());
+                    hasher = Sha256::new_with_prefix(path.to_string_lossy().as_bytes());
+                }
+            },
+            None => hasher.update(&file.bytes),
+        }
+
+        hasher.finalize().iter().fold(String::new(), |mut hash, b| {
+            let _ = write!(hash, "{b:02x}");
+            hash
+        })
+    }
+
+    /// Gets the hash of the contents of the file like [`MediaCache::hash`],
+    /// reading files on disk on the blocking thread pool.
+    pub async fn hash_file(file: &FormDataFile) -> String {
+        if file.path.is_none() {
+            return Self::hash(file);
+        }
+
+        let file = file.clone();
+        match tokio::task::spawn_blocking(move || Self::hash(&file)).await {
+            Ok(hash) => hash,
+            Err(e) => std::panic::resume_unwind(e.into_panic()),
+        }
+    }
+
+    /// Gets the hashes of the files, in the same order as the files
+    pub(crate) async fn hash_files(files: &[FormDataFile]) -> Vec<String> {
+        let mut hashes = Vec::with_capacity(files.len());
+        for file in files {
+            hashes.push(Self::hash_file(file).await);
+        }
+        hashes
+    }
+
+    /// Gets the `file_id` of the file, if it was uploaded before.
+    pub async fn get(&self, file: &FormDataFile) -> Option<String> {
+        self.store.get(&Self::hash_file(file).await).await
+    }
+
+    /// Stores the `file_id` of the file.
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn insert(&self, file: &FormDataFile, file_id: impl ToString) {
+        self.store
+            .insert(Self::hash_file(file).await, file_id.to_string())
+            .await;
+    }
+
+    /// Removes the `file_id` of the file, so it is uploaded again next time.
+    pub async fn remove(&self, file: &FormDataFile) {
+        self.store.remove(&Self::hash_file(file).await).await;
+    }
+
+    /// Removes the `file_id` stored under the hash.
+    pub(crate) async fn remove_hash(&self, hash: &str) {
+        self.store.remove(hash).await;
+    }
+
+    /// Replaces the files with a known `file_id` by that `file_id` in the
+    /// data, removing them and their hashes from the files to upload. Returns
+    /// the hashes of the replaced files.
+    pub(crate) async fn substitute(
+        &self,
+        data: &mut Value,
+        files: &mut Vec<FormDataFile>,
+        hashes: &mut Vec<String>,
+    ) -> Vec<String> {
+        let mut substituted = Vec::new();
+        let mut kept_files = Vec::with_capacity(files.len());
+        let mut kept_hashes = Vec::with_capacity(hashes.len());
+
+        for (file, hash) in files.drain(..).zip(hashes.drain(..)) {
+            let reference = format!("attach://{}", file.name);
+            let replaced = match self.store.get(&hash).await {
+                Some(file_id) => replace_attachment(data, &reference, &file_id),
+                None => false,
+            };
+
+            // the file is still uploaded when it is also used as a thumbnail
+            if !replaced || is_referenced(data, &reference) {
+                kept_files.push(file);
+                kept_hashes.push(hash.clone());
+            }
+            if replaced {
+                substituted.push(hash);
+            }
+        }
+
+        *files = kept_files;
+        *hashes = kept_hashes;
+        substituted
+    }
+
+    /// Records the `file_id`s of the uploaded files, whose hashes are in the
+    /// same order as the files, from the response to the request sending
+    /// them.
+    pub(crate) async fn record(
+        &self,
+        endpoint: &APIEndpoint,
+        data: &Value,
+        files: &[FormDataFile],
+        hashes: &[String],
+        response: &Response,
+    ) {
+        let Some(result) = response.result.as_ref().filter(|_| response.ok) else {
+            return;
+        };
+
+        let sent: Vec<(&Value, &str, &Value)> = match endpoint {
+            APIEndpoint::SendMediaGroup => data["media"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .zip(result.as_array().into_iter().flatten())
+                .filter_map(|(media, message)| {
+                    Some((&media["media"], media["type"].as_str()?, message))
+                })
+                .collect(),
+            e => match single_media_field(e) {
+                Some(field) => vec![(&data[field], field, result)],
+                None => Vec::new(),
+            },
+        };
+
+        for (reference, field, message) in sent {
+            let Some(hash) = files
+                .iter()
+                .position(|f| reference.as_str() == Some(&format!("attach://{}", f.name)))
+                .and_then(|i| hashes.get(i))
+            else {
+                continue;
+            };
+
+            let media = match &message[field] {
+                Value::Array(sizes) => sizes.last(),
+                media => Some(media),
+            };
+            if let Some(file_id) = media.and_then(|m| m["file_id"].as_str()) {
+                self.store.insert(hash.clone(), file_id.to_owned()).await;
+            }
+        }
+    }
+
+    /// Whether telegram refused the request because a `file_id` is no longer
+    /// valid
+    pub(crate) fn is_stale(response: &Response) -> bool {
+        !response.ok
+            && response.error_code == Some(400)
+            && response
+                .description
+                .as_deref()
+                .is_some_and(|d| STALE_FILE_ID_ERRORS.iter().any(|e| d.starts_with(e)))
+    }
+}
+
+impl Default for MediaCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for MediaCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MediaCache").finish_non_exhaustive()
+    }
+}
+
+/// The errors telegram returns when a `file_id` can't be used (anymore)
+const STALE_FILE_ID_ERRORS: &[&str] = &[
+    "Bad Request: wrong file identifier/HTTP URL specified",
+    "Bad Request: wrong remote file identifier specified",
+    "Bad Request: wrong file_id or the file is temporarily unavailable",
+];
+
+/// The field holding the media in the message sent by the endpoint
+fn single_media_field(endpoint: &APIEndpoint) -> Option<&'static str> {
+    Some(match endpoint {
+        APIEndpoint::SendPhoto => "photo",
+        APIEndpoint::SendAudio => "audio",
+        APIEndpoint::SendDocument => "document",
+        APIEndpoint::SendVideo => "video",
+        APIEndpoint::SendAnimation => "animation",
+        APIEndpoint::SendVoice => "voice",
+        APIEndpoint::SendVideoNote => "video_note",
+        APIEndpoint::SendSticker => "sticker",
+        _ => return None,
+    })
+}
+
+/// Replaces the attachment reference by the `file_id`, except for thumbnails,
+/// returning whether it was referenced
+fn replace_attachment(data: &mut Value, reference: &str, file_id: &str) -> bool {
+    match data {
+        Value::Object(map) => {
+            let mut replaced = false;
+            for (key, value) in map.iter_mut() {
+                if key == "thumbnail" || key == "thumb" {
+                    continue;
+                }
+                if value.as_str() == Some(reference) {
+                    *value = Value::String(file_id.to_owned());
+                    replaced = true;
+                } else {
+                    replaced |= replace_attachment(value, reference, file_id);
+                }
+            }
+            replaced
+        },
+        Value::Array(values) => {
+            let mut replaced = false;
+            for value in values {
+                replaced |= replace_attachment(value, reference, file_id);
+            }
+            replaced
+        },
+        _ => false,
+    }
+}
+
+/// Whether the attachment is referenced anywhere in the data
+fn is_referenced(data: &Value, reference: &str) -> bool {
+    match data {
+        Value::String(s) => s == reference,
+        Value::Object(map) => map.values().any(|v| is_referenced(v, reference)),
+        Value::Array(values) => values.iter().any(|v| is_referenced(v, reference)),
+        _ => false,
+    }
+}
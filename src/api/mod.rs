@@ -7,10 +7,15 @@
 mod api;
 mod api_client;
 mod endpoints;
+mod media_cache;
 mod response;
 pub mod types;
+mod usage;
 
+pub use crate::utils::FormDataFile;
 pub use api::API;
 pub use api_client::{APIClient, TlsClient};
 pub use endpoints::APIEndpoint;
-pub use response::Response;
+pub use media_cache::{JsonFileMediaStore, MediaCache, MediaStore, MemoryMediaStore};
+pub use response::{Response, ResponseParameters};
+pub use usage::{UsageLimit, UsageTracker};
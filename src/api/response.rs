@@ -1,28 +1,158 @@
+use super::APIEndpoint;
 use crate::utils::result::{Result, TelegramError};
 use serde::{Deserialize, Serialize};
 
 /// The response object that gets returned from the telegram API
+///
+/// When implementing the [`API`] trait yourself, the responses it returns
+/// have to follow the same contract as the ones of the bot api, as the
+/// methods of the trait and the [`Client`] rely on it:
+///
+/// - a successful request has `ok` set to true and the json returned by the
+///   method as its `result`, which gets deserialized into the return type of
+///   the method, or returned as a [`TelegramError::Deserialization`] holding
+///   the raw json when that fails
+/// - an unsuccessful request has `ok` set to false and a `description` of the
+///   error, which gets returned as a [`TelegramError::EndpointError`] holding
+///   the endpoint
+/// - a request that exceeded flood control has its `parameters` contain the
+///   `retry_after` seconds, which gets returned as a
+///   [`TelegramError::RateLimited`] instead
+///
+/// [`Response::new`] creates a response following this contract from the
+/// status and body of an http response of the bot api.
+///
+/// [`API`]: trait.API.html
+/// [`Client`]: ../client/struct.Client.html
+/// [`TelegramError::EndpointError`]: ../enum.TelegramError.html#variant.EndpointError
+/// [`TelegramError::RateLimited`]: ../enum.TelegramError.html#variant.RateLimited
+/// [`TelegramError::Deserialization`]: ../enum.TelegramError.html#variant.Deserialization
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Response {
+    /// Whether the request was successful
     pub ok: bool,
+    /// A human-readable description of the result
     pub description: Option<String>,
+    /// The result of a successful request
     pub result: Option<serde_json::Value>,
+    /// The error code of an unsuccessful request
+    pub error_code: Option<i64>,
+    /// Information on why a request was unsuccessful, which can help to
+    /// automatically handle the error
+    pub parameters: Option<ResponseParameters>,
 }
 
-impl<T> From<Response> for Result<T>
-where
-    T: serde::de::DeserializeOwned,
-{
-    fn from(resp: Response) -> Result<T> {
-        if resp.ok {
-            Ok(serde_json::from_value(resp.result.ok_or_else(|| {
-                TelegramError::Unknown("response had no result".to_owned())
-            })?)?)
-        } else if resp.description.is_some() {
-            Err(TelegramError::APIResponseError(
-                resp.description
-                    .unwrap_or_else(|| "api error does not contain description".to_owned()),
-            )
+/// Describes why a request was unsuccessful.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ResponseParameters {
+    /// The group has been migrated to a supergroup with the specified
+    /// identifier
+    pub migrate_to_chat_id: Option<i64>,
+    /// In case of exceeding flood control, the number of seconds left to wait
+    /// before the request can be repeated
+    pub retry_after: Option<u64>,
+}
+
+impl Response {
+    /// Creates a `Response` from the status code and body of an http response
+    /// of the bot api.
+    ///
+    /// If the body is not a valid response object, an unsuccessful status
+    /// results in an unsuccessful response with the body as its description
+    /// and the status as its error code, while a successful status results in
+    /// an error.
+    pub fn new(status: u16, body: &[u8]) -> Result<Self> {
+        match serde_json::from_slice::<Self>(body) {
+            Ok(mut response) => {
+                if !response.ok && response.error_code.is_none() {
+                    response.error_code = Some(i64::from(status));
+                }
+                Ok(response)
+            },
+            Err(_) if !(200..300).contains(&status) => Ok(Self {
+                ok: false,
+                description: Some(String::from_utf8_lossy(body).into_owned()),
+                result: None,
+                error_code: Some(i64::from(status)),
+                parameters: None,
+            }),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Whether the request was successful
+    pub fn ok(&self) -> bool {
+        self.ok
+    }
+
+    /// The raw json result of a successful request
+    pub fn result_raw(&self) -> Option<&serde_json::Value> {
+        self.result.as_ref()
+    }
+
+    /// A human-readable description of the result
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// The error code of an unsuccessful request
+    pub fn error_code(&self) -> Option<i64> {
+        self.error_code
+    }
+
+    /// Information on why a request was unsuccessful
+    pub fn parameters(&self) -> Option<&ResponseParameters> {
+        self.parameters.as_ref()
+    }
+
+    /// The number of seconds to wait before repeating the request, if it
+    /// failed because of exceeding flood control
+    pub fn retry_after(&self) -> Option<u64> {
+        if self.ok {
+            return None;
+        }
+        self.parameters.as_ref()?.retry_after
+    }
+
+    /// Turns the response to a request to the given endpoint into the result
+    /// of that request, deserializing the result of a successful request and
+    /// returning the error of an unsuccessful one, which holds the endpoint.
+    pub fn into_result<T>(self, endpoint: &APIEndpoint) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.into_result_for(Some(endpoint))
+    }
+
+    fn into_result_for<T>(self, endpoint: Option<&APIEndpoint>) -> Result<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        if let Some(retry_after) = self.retry_after() {
+            Err(TelegramError::RateLimited {
+                endpoint: endpoint.map_or_else(|| "unknown".to_owned(), ToString::to_string),
+                retry_after,
+            }
+            .into())
+        } else if self.ok {
+            let result = self
+                .result
+                .ok_or_else(|| TelegramError::Unknown("response had no result".to_owned()))?;
+            T::deserialize(&result).map_err(|source| {
+                TelegramError::Deserialization {
+                    source,
+                    raw_json: result.to_string(),
+                }
+                .into()
+            })
+        } else if let Some(description) = self.description {
+            Err(match endpoint {
+                Some(endpoint) => TelegramError::EndpointError {
+                    endpoint: endpoint.to_string(),
+                    description,
+                },
+                None => TelegramError::APIResponseError(description),
+            }
             .into())
         } else {
             Err(TelegramError::Unknown(
@@ -32,3 +162,21 @@ where
         }
     }
 }
+
+/// Turns the response into the result of the request without knowing its
+/// endpoint, so an error telegram returned is a
+/// [`TelegramError::APIResponseError`] without the endpoint.
+///
+/// Deprecated: use [`Response::into_result`] instead, which includes the
+/// endpoint in the errors.
+///
+/// [`TelegramError::APIResponseError`]: ../enum.TelegramError.html#variant.APIResponseError
+/// [`Response::into_result`]: struct.Response.html#method.into_result
+impl<T> From<Response> for Result<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn from(resp: Response) -> Result<T> {
+        resp.into_result_for(None)
+    }
+}
@@ -387,6 +387,32 @@ pub struct GetChatMember {
     pub user_id: i64,
 }
 
+/// struct for holding data needed to call
+/// [`get_user_chat_boosts`]
+///
+/// [`get_user_chat_boosts`]:
+/// ../../api/trait.API.html#method.get_user_chat_boosts
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetUserChatBoosts {
+    /// Unique identifier for the chat
+    pub chat_id: IntegerOrString,
+    /// Unique identifier of the target user
+    pub user_id: i64,
+}
+
+/// struct for holding data needed to call
+/// [`get_business_connection`]
+///
+/// [`get_business_connection`]:
+/// ../../api/trait.API.html#method.get_business_connection
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetBusinessConnection {
+    /// Unique identifier of the business connection
+    pub business_connection_id: String,
+}
+
 /// struct for holding data needed to call
 /// [`set_chat_sticker_set`]
 ///
@@ -1,6 +1,7 @@
 use crate::{
     framework::types::TelegramCommand,
     model::{BotCommand, BotCommandScope},
+    utils::result::{Result, TelegramError},
 };
 use serde::{Deserialize, Serialize};
 use telexide_proc_macros::build_struct;
@@ -20,6 +21,33 @@ pub struct SetMyCommands {
     pub scope: Option<BotCommandScope>,
 }
 
+/// The maximum amount of commands telegram allows a bot to register for a
+/// single scope and language
+pub const MAX_BOT_COMMANDS: usize = 100;
+
+impl SetMyCommands {
+    /// Checks whether telegram will accept the amount of commands, returning
+    /// an [`TelegramError::InvalidArgument`] listing the commands over the
+    /// limit of [`MAX_BOT_COMMANDS`] otherwise
+    pub fn validate(&self) -> Result<()> {
+        if self.commands.len() <= MAX_BOT_COMMANDS {
+            return Ok(());
+        }
+
+        let overflowing: Vec<&str> = self.commands[MAX_BOT_COMMANDS..]
+            .iter()
+            .map(|c| c.command.as_str())
+            .collect();
+        Err(TelegramError::InvalidArgument(format!(
+            "telegram allows at most {MAX_BOT_COMMANDS} commands, but {} were provided; the \
+             commands over the limit are: {}",
+            self.commands.len(),
+            overflowing.join(", ")
+        ))
+        .into())
+    }
+}
+
 impl From<Vec<BotCommand>> for SetMyCommands {
     fn from(commands: Vec<BotCommand>) -> Self {
         Self {
@@ -2,6 +2,7 @@ use super::InputMedia;
 use crate::model::{
     utils::IntegerOrString,
     InlineKeyboardMarkup,
+    LinkPreviewOptions,
     Message,
     MessageEntity,
     ParseMode,
@@ -38,7 +39,13 @@ pub struct EditMessageText {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub entities: Option<Vec<MessageEntity>>,
+    /// Link preview generation options for this message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_preview_options: Option<LinkPreviewOptions>,
     /// Disables link previews for links in this message
+    ///
+    /// **Note:** this is deprecated in favour of `link_preview_options`, and
+    /// will not be sent if `link_preview_options` is set
     #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_web_page_preview: Option<bool>,
     /// Inline keyboard
@@ -55,6 +62,7 @@ impl EditMessageText {
             inline_message_id: None,
             parse_mode: None,
             entities: None,
+            link_preview_options: None,
             disable_web_page_preview: None,
             reply_markup: None,
         }
@@ -234,6 +242,20 @@ impl DeleteMessage {
     }
 }
 
+/// struct for holding data needed to call
+/// [`delete_messages`]
+///
+/// [`delete_messages`]:
+/// ../../api/trait.API.html#method.delete_messages
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeleteMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: IntegerOrString,
+    /// Identifiers of 1-100 messages to delete
+    pub message_ids: Vec<i64>,
+}
+
 /// struct for holding data needed to call
 /// [`edit_message_live_location`]
 ///
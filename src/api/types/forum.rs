@@ -1,7 +1,75 @@
 use crate::model::{utils::IntegerOrString, SuperGroupChat};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use telexide_proc_macros::build_struct;
 
+/// Identifies a topic of a forum supergroup.
+///
+/// Telegram treats the "General" topic specially: some endpoints expect its
+/// `message_thread_id` to be left out, while others expect the literal
+/// thread id 1. A `TopicId` always serializes to its thread id, after which
+/// the `APIClient` leaves it out again for the endpoints that expect that, as
+/// described by [`APIEndpoint::general_topic_encoding`].
+///
+/// [`APIEndpoint::general_topic_encoding`]: ../enum.APIEndpoint.html#method.general_topic_encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TopicId {
+    /// The "General" topic of the forum
+    General,
+    /// Any other topic, identified by its message thread id
+    Topic(i64),
+}
+
+impl TopicId {
+    /// The message thread id of the "General" topic
+    pub const GENERAL_THREAD_ID: i64 = 1;
+
+    /// Gets the message thread id of the topic
+    pub fn thread_id(self) -> i64 {
+        match self {
+            Self::General => Self::GENERAL_THREAD_ID,
+            Self::Topic(id) => id,
+        }
+    }
+}
+
+impl From<i64> for TopicId {
+    fn from(id: i64) -> Self {
+        if id == Self::GENERAL_THREAD_ID {
+            Self::General
+        } else {
+            Self::Topic(id)
+        }
+    }
+}
+
+impl Serialize for TopicId {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(self.thread_id())
+    }
+}
+
+impl<'de> Deserialize<'de> for TopicId {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        i64::deserialize(deserializer).map(Into::into)
+    }
+}
+
+/// How an endpoint expects the "General" topic of a forum to be passed as its
+/// `message_thread_id`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GeneralTopicEncoding {
+    /// The `message_thread_id` has to be left out
+    Omit,
+    /// The `message_thread_id` has to be the literal thread id 1
+    Literal,
+}
+
 /// struct for holding data needed to call
 /// [`create_forum_topic`]
 ///
@@ -41,13 +109,15 @@ pub struct EditForumTopic {
     /// supergroup
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread of the forum topic
-    pub message_thread_id: i64,
-    /// Topic name, 1-128 characters
+    pub message_thread_id: TopicId,
+    /// New topic name, 0-128 characters. If not specified or empty, the
+    /// current name of the topic will be kept
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
-    /// Unique identifier of the custom emoji shown as the topic icon.
+    /// New unique identifier of the custom emoji shown as the topic icon.
     /// Use [`get_forum_topic_icon_stickers`] to get all allowed custom emoji
-    /// identifiers.
+    /// identifiers. Pass an empty string to remove the icon. If not specified,
+    /// the current icon will be kept
     ///
     /// [`get_forum_topic_icon_stickers`]: ../../api/trait.API.html#method.get_forum_topic_icon_stickers
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -66,7 +136,7 @@ pub struct CloseForumTopic {
     /// supergroup
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread of the forum topic
-    pub message_thread_id: i64,
+    pub message_thread_id: TopicId,
 }
 
 /// struct for holding data needed to call
@@ -81,7 +151,7 @@ pub struct ReopenForumTopic {
     /// supergroup
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread of the forum topic
-    pub message_thread_id: i64,
+    pub message_thread_id: TopicId,
 }
 
 /// struct for holding data needed to call
@@ -96,7 +166,7 @@ pub struct DeleteForumTopic {
     /// supergroup
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread of the forum topic
-    pub message_thread_id: i64,
+    pub message_thread_id: TopicId,
 }
 
 /// struct for holding data needed to call
@@ -111,7 +181,7 @@ pub struct UnpinAllForumTopicMessages {
     /// supergroup
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread of the forum topic
-    pub message_thread_id: i64,
+    pub message_thread_id: TopicId,
 }
 
 /// struct for holding data needed to call
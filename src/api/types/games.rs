@@ -10,6 +10,10 @@ use telexide_proc_macros::build_struct;
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendGame {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
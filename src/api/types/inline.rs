@@ -1,7 +1,19 @@
-use crate::model::{InlineKeyboardMarkup, LabeledPrice, MessageEntity, ParseMode, WebAppInfo};
+use crate::model::{
+    InlineKeyboardMarkup,
+    LabeledPrice,
+    LinkPreviewOptions,
+    MessageEntity,
+    ParseMode,
+    WebAppInfo,
+};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use telexide_proc_macros::build_struct;
 
+/// The maximum amount of results telegram allows a bot to answer a single
+/// inline query with
+pub const MAX_INLINE_QUERY_RESULTS: usize = 50;
+
 /// struct for holding data needed to call
 /// [`answer_inline_query`]
 ///
@@ -34,6 +46,24 @@ pub struct AnswerInlineQuery {
     pub button: Option<InlineQueryResultsButton>,
 }
 
+impl AnswerInlineQuery {
+    /// Gets the text of the button shown above the results, which was
+    /// previously the `switch_pm_text` field
+    #[deprecated(note = "telegram replaced `switch_pm_text` with `button`")]
+    pub fn switch_pm_text(&self) -> Option<&str> {
+        self.button.as_ref().map(|b| b.text.as_str())
+    }
+
+    /// Gets the start parameter of the button shown above the results, which
+    /// was previously the `switch_pm_parameter` field
+    #[deprecated(note = "telegram replaced `switch_pm_parameter` with `button`")]
+    pub fn switch_pm_parameter(&self) -> Option<&str> {
+        self.button
+            .as_ref()
+            .and_then(|b| b.start_parameter.as_deref())
+    }
+}
+
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct InlineQueryResultsButton {
@@ -62,9 +92,12 @@ pub struct InlineQueryResultsButton {
 }
 
 /// This object represents one result of an inline query.
+///
+/// The `Cached` variants refer to files already stored on the telegram
+/// servers by their `file_id`, instead of by a URL.
 #[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-#[serde(tag = "type")]
+#[serde(tag = "type", try_from = "Value")]
 pub enum InlineQueryResult {
     #[serde(rename = "article")]
     Article(InlineQueryResultArticle),
@@ -90,10 +123,68 @@ pub enum InlineQueryResult {
     Video(InlineQueryResultVideo),
     #[serde(rename = "voice")]
     Voice(InlineQueryResultVoice),
+    #[serde(rename = "photo")]
+    CachedPhoto(InlineQueryResultCachedPhoto),
+    #[serde(rename = "gif")]
+    CachedGif(InlineQueryResultCachedGif),
+    #[serde(rename = "mpeg4_gif")]
+    CachedMpeg4Gif(InlineQueryResultCachedMpeg4Gif),
+    #[serde(rename = "sticker")]
+    CachedSticker(InlineQueryResultCachedSticker),
+    #[serde(rename = "document")]
+    CachedDocument(InlineQueryResultCachedDocument),
+    #[serde(rename = "video")]
+    CachedVideo(InlineQueryResultCachedVideo),
+    #[serde(rename = "voice")]
+    CachedVoice(InlineQueryResultCachedVoice),
+    #[serde(rename = "audio")]
+    CachedAudio(InlineQueryResultCachedAudio),
 }
 
-// TODO: add support for the cached types too. Add enum with url and cache
-// variant?
+impl TryFrom<Value> for InlineQueryResult {
+    type Error = serde_json::Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        // the results using a file stored on the telegram servers have the same
+        // type as the ones using a url, so they're told apart by their file id
+        let cached = value
+            .as_object()
+            .is_some_and(|o| o.keys().any(|k| k.ends_with("_file_id")));
+        let kind = value
+            .get("type")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+
+        Ok(match (kind.as_str(), cached) {
+            ("article", _) => Self::Article(serde_json::from_value(value)?),
+            ("contact", _) => Self::Contact(serde_json::from_value(value)?),
+            ("game", _) => Self::Game(serde_json::from_value(value)?),
+            ("location", _) => Self::Location(serde_json::from_value(value)?),
+            ("venue", _) => Self::Venue(serde_json::from_value(value)?),
+            ("audio", false) => Self::Audio(serde_json::from_value(value)?),
+            ("document", false) => Self::Document(serde_json::from_value(value)?),
+            ("gif", false) => Self::Gif(serde_json::from_value(value)?),
+            ("mpeg4_gif", false) => Self::Mpeg4Gif(serde_json::from_value(value)?),
+            ("photo", false) => Self::Photo(serde_json::from_value(value)?),
+            ("video", false) => Self::Video(serde_json::from_value(value)?),
+            ("voice", false) => Self::Voice(serde_json::from_value(value)?),
+            ("photo", true) => Self::CachedPhoto(serde_json::from_value(value)?),
+            ("gif", true) => Self::CachedGif(serde_json::from_value(value)?),
+            ("mpeg4_gif", true) => Self::CachedMpeg4Gif(serde_json::from_value(value)?),
+            ("sticker", _) => Self::CachedSticker(serde_json::from_value(value)?),
+            ("document", true) => Self::CachedDocument(serde_json::from_value(value)?),
+            ("video", true) => Self::CachedVideo(serde_json::from_value(value)?),
+            ("voice", true) => Self::CachedVoice(serde_json::from_value(value)?),
+            ("audio", true) => Self::CachedAudio(serde_json::from_value(value)?),
+            _ => {
+                return Err(serde::de::Error::custom(format!(
+                    "unknown inline query result type {kind:?}"
+                )))
+            },
+        })
+    }
+}
 
 /// Represents a link to an article or web page.
 #[build_struct]
@@ -595,6 +686,267 @@ pub struct InlineQueryResultGame {
     pub reply_markup: Option<InlineKeyboardMarkup>,
 }
 
+/// Represents a link to a photo stored on the Telegram servers. By default,
+/// this photo will be sent by the user with an optional caption. Alternatively,
+/// you can use `input_message_content` to send a message with the specified
+/// content instead of the photo.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedPhoto {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier of the photo
+    pub photo_file_id: String,
+    /// Title of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Short description of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Caption of the photo to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the photo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to an animated GIF file stored on the Telegram servers. By
+/// default, this animated GIF file will be sent by the user with an optional
+/// caption. Alternatively, you can use `input_message_content` to send a
+/// message with the specified content instead of the animation.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedGif {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the GIF file
+    pub gif_file_id: String,
+    /// Title of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Caption of the GIF to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the GIF animation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to a video animation (H.264/MPEG-4 AVC video without
+/// sound) stored on the Telegram servers. By default, this animated MPEG-4 file
+/// will be sent by the user with an optional caption. Alternatively, you can
+/// use `input_message_content` to send a message with the specified content
+/// instead of the animation.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedMpeg4Gif {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the MPEG4 file
+    pub mpeg4_file_id: String,
+    /// Title of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Caption of the MPEG-4 file to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the video animation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to a sticker stored on the Telegram servers. By default,
+/// this sticker will be sent by the user. Alternatively, you can use
+/// `input_message_content` to send a message with the specified content instead
+/// of the sticker.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedSticker {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier of the sticker
+    pub sticker_file_id: String,
+    /// Content of the message to be sent instead of the sticker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to a file stored on the Telegram servers. By default, this
+/// file will be sent by the user with an optional caption. Alternatively, you
+/// can use `input_message_content` to send a message with the specified content
+/// instead of the file.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedDocument {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the file
+    pub document_file_id: String,
+    /// Title of the result
+    pub title: String,
+    /// Short description of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Caption of the document to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to a video file stored on the Telegram servers. By
+/// default, this video file will be sent by the user with an optional caption.
+/// Alternatively, you can use `input_message_content` to send a message with
+/// the specified content instead of the video.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedVideo {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the video file
+    pub video_file_id: String,
+    /// Title of the result
+    pub title: String,
+    /// Short description of the result
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Caption of the video to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the video
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to a voice message stored on the Telegram servers. By
+/// default, this voice message will be sent by the user. Alternatively, you can
+/// use `input_message_content` to send a message with the specified content
+/// instead of the voice message.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedVoice {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the voice message
+    pub voice_file_id: String,
+    /// Title of the result
+    pub title: String,
+    /// Caption of the voice message to be sent, 0-1024 characters after
+    /// entities parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the voice message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
+/// Represents a link to an MP3 audio file stored on the Telegram servers. By
+/// default, this audio file will be sent by the user. Alternatively, you can
+/// use `input_message_content` to send a message with the specified content
+/// instead of the audio.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct InlineQueryResultCachedAudio {
+    /// Unique identifier for this result, 1-64 bytes
+    pub id: String,
+    /// A valid file identifier for the audio file
+    pub audio_file_id: String,
+    /// Caption of the audio to be sent, 0-1024 characters after entities
+    /// parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in your bot's message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Content of the message to be sent instead of the audio
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input_message_content: Option<InputMessageContent>,
+    /// Inline keyboard attached to the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<InlineKeyboardMarkup>,
+}
+
 /// This object represents the content of a message to be sent as a result of an
 /// inline query.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -617,7 +969,13 @@ pub struct InputTextMessageContent {
     /// fixed-width text or inline URLs in your bot's message.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parse_mode: Option<ParseMode>,
+    /// Link preview generation options for the sent message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_preview_options: Option<LinkPreviewOptions>,
     /// Disables link previews for links in the sent message
+    ///
+    /// **Note:** this is deprecated in favour of `link_preview_options`, and
+    /// will not be sent if `link_preview_options` is set
     #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_web_page_preview: Option<bool>,
 }
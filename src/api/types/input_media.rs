@@ -39,6 +39,9 @@ pub struct InputMediaPhoto {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Pass True if the photo needs to be covered with a spoiler animation.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub has_spoiler: Option<bool>,
@@ -51,6 +54,11 @@ pub struct InputMediaVideo {
     /// servers (recommended), pass an HTTP URL for Telegram to get a file
     /// from the Internet
     pub media: InputFile,
+    /// Thumbnail of the file sent; can be ignored if thumbnail generation for
+    /// the file is supported server-side. The thumbnail should be in JPEG
+    /// format and less than 200 kB in size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
     /// Caption of the video to be sent, 0-1024 characters after entities
     /// parsing
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -63,6 +71,9 @@ pub struct InputMediaVideo {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Duration of the video in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<i64>,
@@ -89,6 +100,11 @@ pub struct InputMediaAnimation {
     /// servers (recommended), pass an HTTP URL for Telegram to get a file
     /// from the Internet
     pub media: InputFile,
+    /// Thumbnail of the file sent; can be ignored if thumbnail generation for
+    /// the file is supported server-side. The thumbnail should be in JPEG
+    /// format and less than 200 kB in size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
     /// Caption of the animation to be sent, 0-1024 characters after entities
     /// parsing
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -101,6 +117,9 @@ pub struct InputMediaAnimation {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Duration of the animation in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<i64>,
@@ -123,6 +142,11 @@ pub struct InputMediaAudio {
     /// servers (recommended), pass an HTTP URL for Telegram to get a file
     /// from the Internet
     pub media: InputFile,
+    /// Thumbnail of the file sent; can be ignored if thumbnail generation for
+    /// the file is supported server-side. The thumbnail should be in JPEG
+    /// format and less than 200 kB in size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
     /// Caption of the audio file to be sent, 0-1024 characters after entities
     /// parsing
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -154,6 +178,11 @@ pub struct InputMediaDocument {
     /// servers (recommended), pass an HTTP URL for Telegram to get a file
     /// from the Internet
     pub media: InputFile,
+    /// Thumbnail of the file sent; can be ignored if thumbnail generation for
+    /// the file is supported server-side. The thumbnail should be in JPEG
+    /// format and less than 200 kB in size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
     /// Caption of the document to be sent, 0-1024 characters after entities
     /// parsing
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -183,4 +212,100 @@ impl InputMedia {
             InputMedia::Document(m) => &m.media,
         }
     }
+
+    pub fn get_thumbnail(&self) -> Option<&InputFile> {
+        match self {
+            InputMedia::Photo(_) => None,
+            InputMedia::Video(m) => m.thumbnail.as_ref(),
+            InputMedia::Audio(m) => m.thumbnail.as_ref(),
+            InputMedia::Animation(m) => m.thumbnail.as_ref(),
+            InputMedia::Document(m) => m.thumbnail.as_ref(),
+        }
+    }
+
+    /// Gets the media and the thumbnail, so the files among them can be
+    /// attached to the request
+    pub(crate) fn inputs_mut(&mut self) -> impl Iterator<Item = &mut InputFile> {
+        let (media, thumbnail) = match self {
+            InputMedia::Photo(m) => (&mut m.media, None),
+            InputMedia::Video(m) => (&mut m.media, m.thumbnail.as_mut()),
+            InputMedia::Audio(m) => (&mut m.media, m.thumbnail.as_mut()),
+            InputMedia::Animation(m) => (&mut m.media, m.thumbnail.as_mut()),
+            InputMedia::Document(m) => (&mut m.media, m.thumbnail.as_mut()),
+        };
+        std::iter::once(media).chain(thumbnail)
+    }
+}
+
+/// This object describes the paid media to be sent
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum InputPaidMedia {
+    #[serde(rename = "photo")]
+    Photo(InputPaidMediaPhoto),
+    #[serde(rename = "video")]
+    Video(InputPaidMediaVideo),
+}
+
+/// The paid media to send is a photo.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InputPaidMediaPhoto {
+    /// File to send. Pass a `file_id` to send a file that exists on the
+    /// Telegram servers (recommended), pass an HTTP URL for Telegram to get
+    /// a file from the Internet
+    pub media: InputFile,
+}
+
+/// The paid media to send is a video.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InputPaidMediaVideo {
+    /// File to send. Pass a `file_id` to send a file that exists on the
+    /// Telegram servers (recommended), pass an HTTP URL for Telegram to get
+    /// a file from the Internet
+    pub media: InputFile,
+    /// Thumbnail of the file sent; can be ignored if thumbnail generation for
+    /// the file is supported server-side. The thumbnail should be in JPEG
+    /// format and less than 200 kB in size
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<InputFile>,
+    /// Video width
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<i64>,
+    /// Video height
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<i64>,
+    /// Duration of the video in seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration: Option<i64>,
+    /// If the uploaded video is suitable for streaming
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_streaming: Option<bool>,
+}
+
+impl InputPaidMedia {
+    pub fn get_media(&self) -> &InputFile {
+        match self {
+            InputPaidMedia::Photo(m) => &m.media,
+            InputPaidMedia::Video(m) => &m.media,
+        }
+    }
+
+    pub fn get_thumbnail(&self) -> Option<&InputFile> {
+        match self {
+            InputPaidMedia::Photo(_) => None,
+            InputPaidMedia::Video(m) => m.thumbnail.as_ref(),
+        }
+    }
+
+    /// Gets the media and the thumbnail, so the files among them can be
+    /// attached to the request
+    pub(crate) fn inputs_mut(&mut self) -> impl Iterator<Item = &mut InputFile> {
+        let (media, thumbnail) = match self {
+            InputPaidMedia::Photo(m) => (&mut m.media, None),
+            InputPaidMedia::Video(m) => (&mut m.media, m.thumbnail.as_mut()),
+        };
+        std::iter::once(media).chain(thumbnail)
+    }
 }
@@ -1,12 +1,9 @@
 use crate::{
     model::MenuButton,
-    utils::{
-        result::{Result, TelegramError},
-        FormDataFile,
-    },
+    utils::{result::Result, FormDataFile},
 };
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{fs::File, path::Path};
+use std::{fs::File, io::Read, path::Path};
 use telexide_proc_macros::build_struct;
 
 /// struct for holding data needed to call
@@ -101,18 +98,25 @@ impl InputFile {
         Self::String(string.to_owned())
     }
 
+    /// Creates a file to be uploaded from its contents, the media type is
+    /// based on the extension of the file name
+    pub fn from_bytes(bytes: Vec<u8>, file_name: &str) -> Result<Self> {
+        Ok(Self::File(FormDataFile::new_from_bytes(bytes, file_name)?))
+    }
+
+    /// Creates a file to be uploaded from everything the reader returns, the
+    /// media type is based on the extension of the file name
+    pub fn from_reader<R: Read>(reader: R, file_name: &str) -> Result<Self> {
+        Ok(Self::File(FormDataFile::new_from_reader(
+            reader, file_name,
+        )?))
+    }
+
+    /// Creates a file to be uploaded from the file at the path, its contents
+    /// are streamed from disk when uploading it instead of being read into
+    /// memory
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut file = File::open(&path)?;
-        let file_name = path.as_ref().file_name().ok_or_else(|| {
-            TelegramError::InvalidArgument("file doesn't have a valid file name".to_owned())
-        })?;
-
-        Self::new_file(
-            &mut file,
-            file_name.to_str().ok_or_else(|| {
-                TelegramError::InvalidArgument("file doesn't have a valid file name".to_owned())
-            })?,
-        )
+        Ok(Self::File(FormDataFile::new_from_path(path)?))
     }
 }
 
@@ -141,14 +145,8 @@ impl Serialize for InputFile {
     {
         match self {
             Self::String(ref c) => serializer.serialize_str(c),
-            Self::File(ref c) => serializer.serialize_str(&format!(
-                "attach://{}",
-                &c.file_name
-                    .as_ref()
-                    .ok_or_else(|| serde::ser::Error::custom(
-                        "file name doesn't exist for the InputFile file"
-                    ))?
-            )),
+            // the file gets uploaded as the form data part with its name
+            Self::File(ref c) => serializer.serialize_str(&format!("attach://{}", c.name)),
         }
     }
 }
@@ -241,3 +241,34 @@ pub struct CreateInvoiceLink {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_flexible: Option<bool>,
 }
+
+/// struct for holding data needed to call
+/// [`refund_star_payment`]
+///
+/// [`refund_star_payment`]:
+/// ../../api/trait.API.html#method.refund_star_payment
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RefundStarPayment {
+    /// Identifier of the user whose payment will be refunded
+    pub user_id: i64,
+    /// Telegram payment identifier
+    pub telegram_payment_charge_id: String,
+}
+
+/// struct for holding data needed to call
+/// [`get_star_transactions`]
+///
+/// [`get_star_transactions`]:
+/// ../../api/trait.API.html#method.get_star_transactions
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GetStarTransactions {
+    /// Number of transactions to skip in the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offset: Option<i64>,
+    /// The maximum number of transactions to be retrieved. Values between
+    /// 1-100 are accepted. Defaults to 100.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
+}
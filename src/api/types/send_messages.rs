@@ -1,4 +1,4 @@
-use super::{InputFile, InputMedia};
+use super::{InputFile, InputMedia, InputPaidMedia, TopicId};
 use crate::{
     model::{
         utils::{unix_date_formatting, IntegerOrString},
@@ -7,6 +7,7 @@ use crate::{
         ParseMode,
         PhotoSize,
         PollType,
+        ReactionType,
         ReplyMarkup,
     },
     prelude::Message,
@@ -17,6 +18,52 @@ use serde::{Deserialize, Serialize};
 use std::path::Path;
 use telexide_proc_macros::build_struct;
 
+pub use crate::model::LinkPreviewOptions;
+
+/// Describes reply parameters for the message that is being sent.
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReplyParameters {
+    /// Identifier of the message that will be replied to in the current chat,
+    /// or in the chat `chat_id` if it is specified
+    pub message_id: i64,
+    /// If the message to be replied to is from a different chat, unique
+    /// identifier for the chat or username of the channel
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_id: Option<IntegerOrString>,
+    /// Pass True if the message should be sent even if the specified message
+    /// to be replied to is not found; can be used only for replies in the
+    /// same chat and forum topic.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_sending_without_reply: Option<bool>,
+    /// Quoted part of the message to be replied to; 0-1024 characters after
+    /// entities parsing. The quote must be an exact substring of the message
+    /// to be replied to, including bold, italic, underline, strikethrough,
+    /// spoiler, and `custom_emoji` entities.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote: Option<String>,
+    /// Mode for parsing entities in the quote
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the quote, which can be
+    /// specified instead of `quote_parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_entities: Option<Vec<MessageEntity>>,
+    /// Position of the quote in the original message in UTF-16 code units
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quote_position: Option<i64>,
+}
+
+impl ReplyParameters {
+    /// Creates the `ReplyParameters` for replying to the given message, which
+    /// may be from a different chat than the one the reply is sent to
+    pub fn to_message(message: &Message) -> Self {
+        let mut params = Self::new(message.message_id);
+        params.set_chat_id(IntegerOrString::Integer(message.chat.get_id()));
+        params
+    }
+}
+
 /// struct for holding data needed to call
 /// [`send_message`]
 ///
@@ -25,6 +72,10 @@ use telexide_proc_macros::build_struct;
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendMessage {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -41,7 +92,13 @@ pub struct SendMessage {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub enitites: Option<Vec<MessageEntity>>,
+    /// Link preview generation options for this message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_preview_options: Option<LinkPreviewOptions>,
     /// Disables link previews for links in this message
+    ///
+    /// **Note:** this is deprecated in favour of `link_preview_options`, and
+    /// will not be sent if `link_preview_options` is set
     #[serde(skip_serializing_if = "Option::is_none")]
     pub disable_web_page_preview: Option<bool>,
     /// Sends the message silently. Users will receive a notification with no
@@ -51,6 +108,10 @@ pub struct SendMessage {
     /// Protects the contents of the sent message from forwarding and saving
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protect_content: Option<bool>,
+    /// Unique identifier of the message effect to be added to the message;
+    /// for private chats only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_effect_id: Option<String>,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -58,11 +119,38 @@ pub struct SendMessage {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl SendMessage {
+    /// Creates a new `SendMessage` which will be sent in the given forum topic
+    pub fn new_in_thread(chat_id: IntegerOrString, topic: impl Into<TopicId>, text: &str) -> Self {
+        let mut data = Self::new(chat_id, text);
+        data.set_message_thread_id(topic.into().thread_id());
+        data
+    }
+
+    /// Creates a new `SendMessage` replying to the given message, which will
+    /// be sent in the same forum topic or through the same business connection
+    /// as the message if it was sent in one
+    pub fn reply_to(message: &Message, text: &str) -> Self {
+        let mut data = Self::new(message.chat.get_id().into(), text);
+        data.set_reply_parameters(ReplyParameters::new(message.message_id));
+        if let Some(thread_id) = message.topic_thread_id() {
+            data.set_message_thread_id(thread_id);
+        }
+        if let Some(connection_id) = &message.business_connection_id {
+            data.set_business_connection_id(connection_id);
+        }
+        data
+    }
+}
+
 /// struct for holding data needed to call
 /// [`forward_message`]
 ///
@@ -103,6 +191,35 @@ impl ForwardMessage {
     }
 }
 
+/// struct for holding data needed to call
+/// [`forward_messages`]
+///
+/// [`forward_messages`]:
+/// ../../api/trait.API.html#method.forward_messages
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ForwardMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: IntegerOrString,
+    /// Unique identifier for the target message thread (topic) of the forum;
+    /// for forum supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+    /// Unique identifier for the chat where the original messages were sent.
+    pub from_chat_id: IntegerOrString,
+    /// Identifiers of 1-100 messages in the chat specified in `from_chat_id` to
+    /// forward, in strictly increasing order
+    pub message_ids: Vec<i64>,
+    /// Sends the messages silently. Users will receive a notification with no
+    /// sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+    /// Protects the contents of the forwarded messages from forwarding and
+    /// saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+}
+
 /// struct for holding data needed to call [`copy_message`]
 ///
 /// [`copy_message`]: ../../api/trait.API.html#method.copy_message
@@ -127,6 +244,9 @@ pub struct CopyMessage {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Mode for parsing entities in the new caption.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parse_mode: Option<ParseMode>,
@@ -144,11 +264,43 @@ pub struct CopyMessage {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+/// struct for holding data needed to call [`copy_messages`]
+///
+/// [`copy_messages`]: ../../api/trait.API.html#method.copy_messages
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CopyMessages {
+    /// Unique identifier for the target chat
+    pub chat_id: IntegerOrString,
+    /// Unique identifier for the target message thread (topic) of the forum;
+    /// for forum supergroups only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_thread_id: Option<i64>,
+    /// Unique identifier for the chat where the original messages were sent.
+    pub from_chat_id: IntegerOrString,
+    /// Identifiers of 1-100 messages in the chat specified in `from_chat_id` to
+    /// copy, in strictly increasing order
+    pub message_ids: Vec<i64>,
+    /// Sends the messages silently. Users will receive a notification with no
+    /// sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+    /// Protects the contents of the sent messages from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+    /// Pass True to copy the messages without their captions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remove_caption: Option<bool>,
+}
+
 impl CopyMessage {
     pub fn from_message(chat_id: IntegerOrString, from: &Message) -> Self {
         Self {
@@ -158,11 +310,13 @@ impl CopyMessage {
             message_thread_id: from.message_thread_id,
             caption: None,
             caption_entities: None,
+            show_caption_above_media: None,
             parse_mode: None,
             disable_notification: None,
             protect_content: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         }
     }
@@ -176,6 +330,10 @@ impl CopyMessage {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendPhoto {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -194,6 +352,9 @@ pub struct SendPhoto {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
     /// fixed-width text or inline URLs in your bot's message.
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -208,6 +369,10 @@ pub struct SendPhoto {
     /// Protects the contents of the sent message from forwarding and saving
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protect_content: Option<bool>,
+    /// Unique identifier of the message effect to be added to the message;
+    /// for private chats only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_effect_id: Option<String>,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -215,6 +380,9 @@ pub struct SendPhoto {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -223,34 +391,42 @@ pub struct SendPhoto {
 impl SendPhoto {
     pub fn from_photo_size(chat_id: IntegerOrString, photo: &PhotoSize) -> Self {
         Self {
+            business_connection_id: None,
             chat_id,
             photo: InputFile::String(photo.file_id.clone()),
             message_thread_id: None,
             caption: None,
             caption_entities: None,
+            show_caption_above_media: None,
             parse_mode: None,
             has_spoiler: None,
             disable_notification: None,
             protect_content: None,
+            message_effect_id: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         }
     }
 
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             photo: InputFile::from_path(path)?,
             message_thread_id: None,
             caption: None,
             caption_entities: None,
+            show_caption_above_media: None,
             parse_mode: None,
             has_spoiler: None,
             disable_notification: None,
             protect_content: None,
+            message_effect_id: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -264,6 +440,10 @@ impl SendPhoto {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendAudio {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -316,6 +496,9 @@ pub struct SendAudio {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -324,6 +507,7 @@ pub struct SendAudio {
 impl SendAudio {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             audio: InputFile::from_path(path)?,
             message_thread_id: None,
@@ -338,6 +522,7 @@ impl SendAudio {
             protect_content: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -351,6 +536,10 @@ impl SendAudio {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendDocument {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -398,6 +587,9 @@ pub struct SendDocument {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -406,6 +598,7 @@ pub struct SendDocument {
 impl SendDocument {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             document: InputFile::from_path(path)?,
             message_thread_id: None,
@@ -418,6 +611,7 @@ impl SendDocument {
             disable_content_type_detection: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -431,6 +625,10 @@ impl SendDocument {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendVideo {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -456,6 +654,9 @@ pub struct SendVideo {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Duration of the video in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<i64>,
@@ -485,6 +686,10 @@ pub struct SendVideo {
     /// Protects the contents of the sent message from forwarding and saving
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protect_content: Option<bool>,
+    /// Unique identifier of the message effect to be added to the message;
+    /// for private chats only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_effect_id: Option<String>,
     /// If the uploaded video is suitable for streaming
     #[serde(skip_serializing_if = "Option::is_none")]
     pub supports_streaming: Option<bool>,
@@ -495,6 +700,9 @@ pub struct SendVideo {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -503,12 +711,14 @@ pub struct SendVideo {
 impl SendVideo {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             video: InputFile::from_path(path)?,
             message_thread_id: None,
             thumbnail: None,
             caption: None,
             caption_entities: None,
+            show_caption_above_media: None,
             duration: None,
             width: None,
             height: None,
@@ -519,8 +729,10 @@ impl SendVideo {
             has_spoiler: None,
             disable_notification: None,
             protect_content: None,
+            message_effect_id: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -534,6 +746,10 @@ impl SendVideo {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendAnimation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -559,6 +775,9 @@ pub struct SendAnimation {
     /// specified instead of parse_mode
     #[serde(skip_serializing_if = "Option::is_none")]
     pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
     /// Duration of the animation in seconds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub duration: Option<i64>,
@@ -588,6 +807,10 @@ pub struct SendAnimation {
     /// Protects the contents of the sent message from forwarding and saving
     #[serde(skip_serializing_if = "Option::is_none")]
     pub protect_content: Option<bool>,
+    /// Unique identifier of the message effect to be added to the message;
+    /// for private chats only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message_effect_id: Option<String>,
     /// If the message is a reply, ID of the original message
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_to_message_id: Option<i64>,
@@ -595,6 +818,9 @@ pub struct SendAnimation {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -603,12 +829,14 @@ pub struct SendAnimation {
 impl SendAnimation {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             animation: InputFile::from_path(path)?,
             message_thread_id: None,
             thumbnail: None,
             caption: None,
             caption_entities: None,
+            show_caption_above_media: None,
             duration: None,
             width: None,
             height: None,
@@ -618,8 +846,10 @@ impl SendAnimation {
             has_spoiler: None,
             disable_notification: None,
             protect_content: None,
+            message_effect_id: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -633,6 +863,10 @@ impl SendAnimation {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendVoice {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -672,6 +906,9 @@ pub struct SendVoice {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -680,6 +917,7 @@ pub struct SendVoice {
 impl SendVoice {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             voice: InputFile::from_path(path)?,
             message_thread_id: None,
@@ -691,6 +929,7 @@ impl SendVoice {
             protect_content: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -704,6 +943,10 @@ impl SendVoice {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendVideoNote {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -741,6 +984,9 @@ pub struct SendVideoNote {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -749,6 +995,7 @@ pub struct SendVideoNote {
 impl SendVideoNote {
     pub fn from_file<P: AsRef<Path>>(chat_id: IntegerOrString, path: P) -> Result<Self> {
         Ok(Self {
+            business_connection_id: None,
             chat_id,
             video_note: InputFile::from_path(path)?,
             message_thread_id: None,
@@ -759,6 +1006,7 @@ impl SendVideoNote {
             protect_content: None,
             reply_to_message_id: None,
             allow_sending_without_reply: None,
+            reply_parameters: None,
             reply_markup: None,
         })
     }
@@ -772,6 +1020,10 @@ impl SendVideoNote {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendMediaGroup {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -795,6 +1047,63 @@ pub struct SendMediaGroup {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+}
+
+/// struct for holding data needed to call
+/// [`send_paid_media`]
+///
+/// [`send_paid_media`]:
+/// ../../api/trait.API.html#method.send_paid_media
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SendPaidMedia {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
+    /// Unique identifier for the target chat. If the chat is a channel, all
+    /// Telegram Star proceeds from this media will be credited to the chat's
+    /// balance. Otherwise, they will be credited to the bot's balance.
+    pub chat_id: IntegerOrString,
+    /// The number of Telegram Stars that must be paid to buy access to the
+    /// media
+    pub star_count: i64,
+    /// The photos and videos to be sent, amount must be 1-10
+    pub media: Vec<InputPaidMedia>,
+    /// Bot-defined paid media payload, 0-128 bytes. This will not be displayed
+    /// to the user, use it for your internal processes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<String>,
+    /// Media caption, 0-1024 characters after entities parsing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+    /// Send Markdown or HTML, if you want Telegram apps to show bold, italic,
+    /// fixed-width text or inline URLs in the media caption.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parse_mode: Option<ParseMode>,
+    /// List of special entities that appear in the caption, which can be
+    /// specified instead of `parse_mode`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption_entities: Option<Vec<MessageEntity>>,
+    /// Pass True if the caption must be shown above the message media
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_caption_above_media: Option<bool>,
+    /// Sends the message silently. Users will receive a notification with no
+    /// sound.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub disable_notification: Option<bool>,
+    /// Protects the contents of the sent message from forwarding and saving
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protect_content: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
+    /// Additional interface options.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_markup: Option<ReplyMarkup>,
 }
 
 /// struct for holding data needed to call
@@ -805,6 +1114,10 @@ pub struct SendMediaGroup {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendLocation {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -841,6 +1154,9 @@ pub struct SendLocation {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -854,6 +1170,10 @@ pub struct SendLocation {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SendVenue {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -890,6 +1210,9 @@ pub struct SendVenue {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -903,6 +1226,10 @@ pub struct SendVenue {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendContact {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -933,6 +1260,9 @@ pub struct SendContact {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -946,6 +1276,10 @@ pub struct SendContact {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendPoll {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -991,6 +1325,7 @@ pub struct SendPoll {
     /// Point in time (Unix timestamp) when the poll will be automatically
     /// closed. Must be at least 5 and no more than 600 seconds in the future.
     /// Can't be used together with open_period.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     #[serde(with = "unix_date_formatting::optional")]
     pub close_date: Option<DateTime<Utc>>,
     /// Pass True, if the poll needs to be immediately closed.
@@ -1010,6 +1345,9 @@ pub struct SendPoll {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -1023,6 +1361,10 @@ pub struct SendPoll {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendDice {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -1050,6 +1392,9 @@ pub struct SendDice {
     /// replied-to message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -1066,6 +1411,10 @@ pub struct SendDice {
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendChatAction {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread; supergroups only
@@ -1074,3 +1423,51 @@ pub struct SendChatAction {
     /// Type of action to broadcast.
     pub action: ChatAction,
 }
+
+impl SendChatAction {
+    /// Creates a new `SendChatAction` which will be broadcast in the given
+    /// forum topic
+    pub fn new_in_thread(
+        chat_id: IntegerOrString,
+        topic: impl Into<TopicId>,
+        action: ChatAction,
+    ) -> Self {
+        let mut data = Self::new(chat_id, action);
+        data.set_message_thread_id(topic.into().thread_id());
+        data
+    }
+}
+
+/// struct for holding data needed to call
+/// [`set_message_reaction`]
+///
+/// [`set_message_reaction`]:
+/// ../../api/trait.API.html#method.set_message_reaction
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SetMessageReaction {
+    /// Unique identifier for the target chat
+    pub chat_id: IntegerOrString,
+    /// Identifier of the target message. If the message belongs to a media
+    /// group, the reaction is set to the first non-deleted message in the
+    /// group instead.
+    pub message_id: i64,
+    /// New list of reaction types to set on the message. Currently, as
+    /// non-premium users, bots can set up to one reaction per message. A
+    /// custom emoji reaction can be used if it is either already present on
+    /// the message or explicitly allowed by chat administrators.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reaction: Option<Vec<ReactionType>>,
+    /// Pass True to set the reaction with a big animation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_big: Option<bool>,
+}
+
+crate::utils::macros::impl_thumb_accessor!(
+    InputFile,
+    SendAudio,
+    SendDocument,
+    SendVideo,
+    SendAnimation,
+    SendVideoNote
+);
@@ -1,4 +1,4 @@
-use super::InputFile;
+use super::{InputFile, ReplyParameters};
 use crate::model::{
     utils::IntegerOrString,
     InputSticker,
@@ -18,6 +18,10 @@ use telexide_proc_macros::build_struct;
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SendSticker {
+    /// Unique identifier of the business connection on behalf of which the
+    /// message will be sent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub business_connection_id: Option<String>,
     /// Unique identifier for the target chat
     pub chat_id: IntegerOrString,
     /// Unique identifier for the target message thread (topic) of the forum;
@@ -45,6 +49,9 @@ pub struct SendSticker {
     /// message is not found
     #[serde(skip_serializing_if = "Option::is_none")]
     pub allow_sending_without_reply: Option<bool>,
+    /// Description of the message to reply to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reply_parameters: Option<ReplyParameters>,
     /// Additional interface options.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reply_markup: Option<ReplyMarkup>,
@@ -99,8 +106,6 @@ pub struct CreateNewStickerSet {
     pub title: String,
     /// A list of 1-50 initial stickers to be added to the sticker set
     pub stickers: Vec<InputSticker>,
-    /// Format of stickers in the set
-    pub sticker_format: StickerFormat,
     /// Type of stickers in the set, pass “regular” or “mask”. Custom emoji
     /// sticker sets can't be created via the Bot API at the moment. By default,
     /// a regular sticker set is created.
@@ -130,6 +135,26 @@ pub struct AddStickerToSet {
     pub sticker: InputSticker,
 }
 
+/// struct for holding data needed to call
+/// [`replace_sticker_in_set`]
+///
+/// [`replace_sticker_in_set`]:
+/// ../../api/trait.API.html#method.replace_sticker_in_set
+#[build_struct]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReplaceStickerInSet {
+    /// User identifier of the sticker set owner
+    pub user_id: i64,
+    /// Name of the sticker set
+    pub name: String,
+    /// File identifier of the replaced sticker
+    pub old_sticker: String,
+    /// An object with information about the added sticker. If exactly the same
+    /// sticker had already been added to the set, then the set remains
+    /// unchanged.
+    pub sticker: InputSticker,
+}
+
 /// struct for holding data needed to call
 /// [`set_sticker_position_in_set`]
 ///
@@ -277,3 +302,5 @@ pub struct GetCustomEmojiStickers {
     /// can be specified.
     pub custom_emoji_ids: Vec<String>,
 }
+
+crate::utils::macros::impl_thumb_accessor!(InputFile, SetStickerSetThumbnail);
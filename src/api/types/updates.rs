@@ -69,4 +69,20 @@ pub enum UpdateType {
     ChatMember,
     #[serde(rename = "chat_join_request")]
     ChatJoinRequest,
+    #[serde(rename = "message_reaction")]
+    MessageReaction,
+    #[serde(rename = "message_reaction_count")]
+    MessageReactionCount,
+    #[serde(rename = "chat_boost")]
+    ChatBoost,
+    #[serde(rename = "removed_chat_boost")]
+    RemovedChatBoost,
+    #[serde(rename = "business_connection")]
+    BusinessConnection,
+    #[serde(rename = "business_message")]
+    BusinessMessage,
+    #[serde(rename = "edited_business_message")]
+    EditedBusinessMessage,
+    #[serde(rename = "deleted_business_messages")]
+    DeletedBusinessMessages,
 }
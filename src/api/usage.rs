@@ -0,0 +1,183 @@
+use super::endpoints::APIEndpoint;
+use crate::utils::result::{Result, TelegramError};
+use parking_lot::Mutex;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// The endpoints that change the profile of the bot or a chat, which have low
+/// (undocumented) rate limits on telegram's side.
+const SENSITIVE_ENDPOINTS: [APIEndpoint; 11] = [
+    APIEndpoint::SetMyCommands,
+    APIEndpoint::DeleteMyCommands,
+    APIEndpoint::SetMyName,
+    APIEndpoint::SetMyDescription,
+    APIEndpoint::SetMyShortDescription,
+    APIEndpoint::SetMyDefaultAdministratorRights,
+    APIEndpoint::SetChatMenuButton,
+    APIEndpoint::SetChatTitle,
+    APIEndpoint::SetChatDescription,
+    APIEndpoint::SetChatPhoto,
+    APIEndpoint::DeleteChatPhoto,
+];
+
+const SECONDS_PER_MINUTE: u64 = 60;
+
+/// The maximum amount of calls to an endpoint within a sliding window of time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsageLimit {
+    /// The amount of calls allowed within the window
+    pub max_calls: usize,
+    /// The length of the sliding window
+    pub window: Duration,
+}
+
+impl UsageLimit {
+    /// Creates a new `UsageLimit` allowing `max_calls` calls within `window`.
+    pub fn new(max_calls: usize, window: Duration) -> Self {
+        Self {
+            max_calls,
+            window,
+        }
+    }
+
+    /// Creates a new `UsageLimit` allowing `max_calls` calls per minute.
+    pub fn per_minute(max_calls: usize) -> Self {
+        Self::new(max_calls, Duration::from_secs(SECONDS_PER_MINUTE))
+    }
+}
+
+/// Keeps track of how often the endpoints of the telegram api with a
+/// [`UsageLimit`] have been called within their sliding window.
+///
+/// When a limit is exceeded a warning is logged, or if the tracker is set to
+/// enforce its limits, a [`TelegramError::LocalRateLimit`] is returned without
+/// the request being sent to telegram.
+///
+/// By default the endpoints changing the profile of the bot or a chat, like
+/// `setMyCommands` and `setChatTitle`, are limited to 10 calls per minute.
+///
+/// [`TelegramError::LocalRateLimit`]: ../enum.TelegramError.html#variant.LocalRateLimit
+#[derive(Debug)]
+pub struct UsageTracker {
+    limits: HashMap<String, UsageLimit>,
+    enforce: bool,
+    calls: Mutex<HashMap<String, VecDeque<Instant>>>,
+}
+
+impl UsageTracker {
+    /// Creates a new `UsageTracker` with the default limits, which only logs a
+    /// warning when they are exceeded.
+    pub fn new() -> Self {
+        Self {
+            limits: SENSITIVE_ENDPOINTS
+                .iter()
+                .map(|e| (e.as_str().to_owned(), UsageLimit::per_minute(10)))
+                .collect(),
+            enforce: false,
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Sets the limit for the given endpoint, replacing its existing limit.
+    pub fn set_limit(&mut self, endpoint: &APIEndpoint, limit: UsageLimit) -> &mut Self {
+        self.limits.insert(endpoint.as_str().to_owned(), limit);
+        self
+    }
+
+    /// Removes the limit for the given endpoint, after which its calls are no
+    /// longer tracked.
+    pub fn remove_limit(&mut self, endpoint: &APIEndpoint) -> &mut Self {
+        self.limits.remove(endpoint.as_str());
+        self.calls.lock().remove(endpoint.as_str());
+        self
+    }
+
+    /// Sets whether calls exceeding a limit are refused with a
+    /// [`TelegramError::LocalRateLimit`] instead of only logging a warning.
+    ///
+    /// [`TelegramError::LocalRateLimit`]: ../enum.TelegramError.html#variant.LocalRateLimit
+    pub fn set_enforce(&mut self, enforce: bool) -> &mut Self {
+        self.enforce = enforce;
+        self
+    }
+
+    /// Records a call to the given endpoint, returning an error if the limit
+    /// of the endpoint is exceeded and the tracker enforces its limits.
+    pub fn record(&self, endpoint: &APIEndpoint) -> Result<()> {
+        let Some(limit) = self.limits.get(endpoint.as_str()) else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut calls = self.calls.lock();
+        let window = calls.entry(endpoint.as_str().to_owned()).or_default();
+        Self::prune(window, now, limit.window);
+
+        if window.len() >= limit.max_calls {
+            if self.enforce {
+                return Err(TelegramError::LocalRateLimit {
+                    endpoint: endpoint.as_str().to_owned(),
+                    calls: window.len(),
+                }
+                .into());
+            }
+
+            log::warn!(
+                "{} calls to {endpoint} within {:?}, telegram may temporarily block the bot from \
+                 using it",
+                window.len() + 1,
+                limit.window
+            );
+        }
+
+        window.push_back(now);
+        Ok(())
+    }
+
+    /// Gets the amount of calls made to the given endpoint within its current
+    /// window, which is always 0 for endpoints without a limit.
+    pub fn usage(&self, endpoint: &APIEndpoint) -> usize {
+        let Some(limit) = self.limits.get(endpoint.as_str()) else {
+            return 0;
+        };
+
+        let mut calls = self.calls.lock();
+        calls.get_mut(endpoint.as_str()).map_or(0, |window| {
+            Self::prune(window, Instant::now(), limit.window);
+            window.len()
+        })
+    }
+
+    /// Gets the amount of calls made within the current window for every
+    /// endpoint that has been called since its window started.
+    pub fn snapshot(&self) -> HashMap<String, usize> {
+        let now = Instant::now();
+        let mut calls = self.calls.lock();
+
+        calls
+            .iter_mut()
+            .filter_map(|(endpoint, window)| {
+                Self::prune(window, now, self.limits.get(endpoint)?.window);
+                (!window.is_empty()).then(|| (endpoint.clone(), window.len()))
+            })
+            .collect()
+    }
+
+    fn prune(window: &mut VecDeque<Instant>, now: Instant, length: Duration) {
+        while window
+            .front()
+            .is_some_and(|t| now.duration_since(*t) >= length)
+        {
+            window.pop_front();
+        }
+    }
+}
+
+impl Default for UsageTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
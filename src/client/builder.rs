@@ -1,11 +1,25 @@
-use super::{APIConnector, Client, EventHandlerFunc, RawEventHandlerFunc, WebhookOptions};
+use super::{
+    APIConnector,
+    ChatMigratedHandlerFunc,
+    Client,
+    Context,
+    ErrorHandlerFunc,
+    EventHandlerFunc,
+    EventListener,
+    RawEventHandlerFunc,
+    SmartReplyPolicy,
+    WebhookOptions,
+};
 use crate::{
-    api::{types::UpdateType, APIClient, TlsClient},
-    framework::Framework,
+    api::{types::UpdateType, APIClient, MediaCache, TlsClient},
+    framework::{CallbackRouter, DialogueStorage, Framework, MemoryDialogueStorage},
+    model::ParseMode,
+    Error,
 };
 
-use parking_lot::RwLock;
-use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
 use typemap_rev::TypeMap;
 
 /// A builder for the [`Client`] object to make customisation easier
@@ -14,10 +28,45 @@ pub struct ClientBuilder {
     api_client: Option<Arc<Box<APIConnector>>>,
     webhook: Option<WebhookOptions>,
     framework: Option<Arc<Framework>>,
+    callback_router: Option<Arc<CallbackRouter>>,
     token: Option<String>,
+    api_url: Option<String>,
+    request_timeout: Option<Duration>,
+    max_retries: Option<u32>,
+    pool_max_idle_per_host: Option<usize>,
+    pool_idle_timeout: Option<Duration>,
+    max_concurrent_requests: Option<usize>,
+    polling_limit: Option<usize>,
+    polling_timeout: Option<usize>,
+    skip_unparseable_updates: bool,
+    auto_answer_callback_queries: Option<bool>,
+    max_concurrent_handlers: Option<usize>,
+    keep_alive_interval: Option<Duration>,
+    drop_pending_updates: bool,
+    smart_reply_policy: SmartReplyPolicy,
+    parse_mode: Option<ParseMode>,
+    media_cache: Option<MediaCache>,
+    dialogue_storage: Option<Arc<dyn DialogueStorage>>,
     allowed_updates: Vec<UpdateType>,
-    event_handler_funcs: Vec<EventHandlerFunc>,
+    event_handler_funcs: Vec<EventListener>,
     raw_event_handler_funcs: Vec<RawEventHandlerFunc>,
+    chat_migrated_handler_funcs: Vec<ChatMigratedHandlerFunc>,
+    error_handler: Option<ErrorHandlerFunc>,
+}
+
+// public only for testing purposes
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct ClientBuilderState<'a> {
+    pub token: Option<&'a str>,
+    pub api_url: Option<&'a str>,
+    pub webhook: Option<&'a WebhookOptions>,
+    pub request_timeout: Option<Duration>,
+    pub max_retries: Option<u32>,
+    pub polling_limit: Option<usize>,
+    pub polling_timeout: Option<usize>,
+    pub parse_mode: Option<&'a ParseMode>,
+    pub allowed_updates: &'a [UpdateType],
 }
 
 impl ClientBuilder {
@@ -30,10 +79,30 @@ impl ClientBuilder {
             hyper_client: None,
             webhook: None,
             framework: None,
+            callback_router: None,
             token: None,
+            api_url: None,
+            request_timeout: None,
+            max_retries: None,
+            pool_max_idle_per_host: None,
+            pool_idle_timeout: None,
+            max_concurrent_requests: None,
+            polling_limit: None,
+            polling_timeout: None,
+            skip_unparseable_updates: true,
+            auto_answer_callback_queries: None,
+            max_concurrent_handlers: None,
+            keep_alive_interval: None,
+            drop_pending_updates: false,
+            smart_reply_policy: SmartReplyPolicy::default(),
+            parse_mode: None,
+            media_cache: None,
+            dialogue_storage: None,
             allowed_updates: Vec::new(),
             event_handler_funcs: Vec::new(),
             raw_event_handler_funcs: Vec::new(),
+            chat_migrated_handler_funcs: Vec::new(),
+            error_handler: None,
         }
     }
 
@@ -52,6 +121,17 @@ impl ClientBuilder {
         self
     }
 
+    /// Sets the [`CallbackRouter`] routing the callback queries to their
+    /// handlers by the prefix of their data. This router takes precedence over
+    /// the callback query handlers of the framework, which only receive the
+    /// queries this router has no route for.
+    ///
+    /// [`CallbackRouter`]: ../framework/struct.CallbackRouter.html
+    pub fn set_callback_router(&mut self, router: Arc<CallbackRouter>) -> &mut Self {
+        self.callback_router = Some(router);
+        self
+    }
+
     /// Sets the token to be used in authorizing the API requests of your bot
     #[allow(clippy::needless_pass_by_value)] // Otherwise string literals don't work
     pub fn set_token(&mut self, token: impl ToString) -> &mut Self {
@@ -59,12 +139,181 @@ impl ClientBuilder {
         self
     }
 
-    /// Sets the custom hyper client for the `APIClient` to use
-    pub fn set_hyper_client(&mut self, client: TlsClient) -> &mut Self {
+    /// Sets the base url of the bot api server the `APIClient` will send its
+    /// requests to, defaults to `https://api.telegram.org`
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_api_url(&mut self, api_url: impl ToString) -> &mut Self {
+        self.api_url = Some(api_url.to_string());
+        self
+    }
+
+    /// Sets the maximum duration a single request of the `APIClient` may take
+    pub fn set_request_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets how many times the `APIClient` retries a request that was rate
+    /// limited by telegram, after waiting the amount of time telegram asks for
+    pub fn set_max_retries(&mut self, max_retries: u32) -> &mut Self {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Sets the maximum amount of idle connections to the telegram api that
+    /// the hyper client of the `APIClient` keeps open, by default this is
+    /// unbounded.
+    pub fn set_pool_max_idle_per_host(&mut self, max: usize) -> &mut Self {
+        self.pool_max_idle_per_host = Some(max);
+        self
+    }
+
+    /// Sets how long an idle connection to the telegram api is kept open by
+    /// the hyper client of the `APIClient`, defaults to 90 seconds.
+    pub fn set_pool_idle_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.pool_idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the maximum amount of requests the `APIClient` may have in flight
+    /// to the telegram api at the same time, separate from
+    /// [`ClientBuilder::set_max_concurrent_handlers`]. By default the amount
+    /// is unbounded, a maximum of 0 is treated as 1.
+    pub fn set_max_concurrent_requests(&mut self, max: usize) -> &mut Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Sets the maximum amount of updates retrieved in one long polling call
+    pub fn set_polling_limit(&mut self, limit: usize) -> &mut Self {
+        self.polling_limit = Some(limit);
+        self
+    }
+
+    /// Sets the timeout in seconds used for long polling
+    pub fn set_polling_timeout(&mut self, timeout: usize) -> &mut Self {
+        self.polling_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets whether updates received through long polling that can't be
+    /// parsed should be logged and skipped, instead of stopping the client
+    /// with an error. Defaults to true.
+    #[deprecated(note = "use `set_strict_update_parsing` instead")]
+    pub fn set_skip_unparseable_updates(&mut self, skip: bool) -> &mut Self {
+        self.set_strict_update_parsing(!skip)
+    }
+
+    /// Sets whether an update received through long polling that can't be
+    /// parsed should stop the client with an error, instead of being logged
+    /// and skipped. Defaults to false.
+    pub fn set_strict_update_parsing(&mut self, strict: bool) -> &mut Self {
+        self.skip_unparseable_updates = !strict;
+        self
+    }
+
+    /// Sets whether callback queries should be answered automatically once
+    /// all handlers are done with them, if none of them answered it using the
+    /// api of its [`Context`]. If a handler panicked or returned an error, the
+    /// query gets answered with an "error" notification. Defaults to true when
+    /// a [`CallbackRouter`] is set or the framework has callback query
+    /// handlers, and to false otherwise.
+    ///
+    /// [`Context`]: struct.Context.html
+    /// [`CallbackRouter`]: ../framework/struct.CallbackRouter.html
+    pub fn set_auto_answer_callback_queries(&mut self, auto_answer: bool) -> &mut Self {
+        self.auto_answer_callback_queries = Some(auto_answer);
+        self
+    }
+
+    /// Sets the maximum amount of event handlers, raw event handlers and
+    /// commands that may be running at the same time. Handlers for updates
+    /// received while the limit is reached are queued and started in order
+    /// once running handlers finish. By default the amount is unbounded, a
+    /// maximum of 0 is treated as 1.
+    pub fn set_max_concurrent_handlers(&mut self, max: usize) -> &mut Self {
+        self.max_concurrent_handlers = Some(max);
+        self
+    }
+
+    /// Sets the interval at which the client sends a lightweight `getMe`
+    /// request while it is running, which keeps a connection to telegram open
+    /// so requests after an idle period don't have to set up a new one first.
+    /// Disabled by default, an interval shorter than a second is treated as a
+    /// second.
+    pub fn set_keep_alive_interval(&mut self, interval: Duration) -> &mut Self {
+        self.keep_alive_interval = Some(interval);
+        self
+    }
+
+    /// Sets whether the updates that are waiting to be retrieved when the
+    /// client starts should be dropped instead of handled. Defaults to false.
+    ///
+    /// When polling, the newest pending update is retrieved with an offset of
+    /// -1 before the client starts handling updates, which makes telegram
+    /// forget all pending updates, and that update is skipped as well.
+    /// When using a webhook, `drop_pending_updates` is sent along when the
+    /// client sets the webhook, which requires the url of the
+    /// [`WebhookOptions`] to be set.
+    pub fn set_drop_pending_updates(&mut self, drop: bool) -> &mut Self {
+        self.drop_pending_updates = drop;
+        self
+    }
+
+    /// Sets how [`Context::smart_reply`] answers messages, see
+    /// [`SmartReplyPolicy`] for the defaults
+    ///
+    /// [`Context::smart_reply`]: struct.Context.html#method.smart_reply
+    pub fn set_smart_reply_policy(&mut self, policy: SmartReplyPolicy) -> &mut Self {
+        self.smart_reply_policy = policy;
+        self
+    }
+
+    /// Sets the parse mode the `APIClient` uses for the text and captions of
+    /// outgoing messages that do not specify a parse mode or entities
+    /// themselves
+    pub fn set_default_parse_mode(&mut self, parse_mode: ParseMode) -> &mut Self {
+        self.parse_mode = Some(parse_mode);
+        self
+    }
+
+    /// Sets the [`MediaCache`] the `APIClient` uses to send the `file_id`s of
+    /// files it uploaded before instead of uploading them again
+    ///
+    /// [`MediaCache`]: ../api/struct.MediaCache.html
+    pub fn set_media_cache(&mut self, cache: MediaCache) -> &mut Self {
+        self.media_cache = Some(cache);
+        self
+    }
+
+    /// Sets the [`DialogueStorage`] the states of dialogues are kept in,
+    /// defaults to a [`MemoryDialogueStorage`]
+    ///
+    /// [`DialogueStorage`]: ../framework/trait.DialogueStorage.html
+    /// [`MemoryDialogueStorage`]: ../framework/struct.MemoryDialogueStorage.html
+    pub fn set_dialogue_storage(&mut self, storage: impl DialogueStorage + 'static) -> &mut Self {
+        self.dialogue_storage = Some(Arc::new(storage));
+        self
+    }
+
+    /// Sets the pre-built hyper client for the `APIClient` to send its
+    /// requests with, for example one using a proxying connector or custom
+    /// DNS resolution, see [`APIClient::with_client`] for its requirements.
+    ///
+    /// The pool settings of the builder are not applied to a custom client.
+    ///
+    /// [`APIClient::with_client`]: ../api/struct.APIClient.html#method.with_client
+    pub fn set_http_client(&mut self, client: TlsClient) -> &mut Self {
         self.hyper_client = Some(client);
         self
     }
 
+    /// Sets the custom hyper client for the `APIClient` to use
+    #[deprecated(note = "use `set_http_client` instead")]
+    pub fn set_hyper_client(&mut self, client: TlsClient) -> &mut Self {
+        self.set_http_client(client)
+    }
+
     /// Sets the custom API client
     pub fn set_api_client(&mut self, client: Arc<Box<APIConnector>>) -> &mut Self {
         self.api_client = Some(client);
@@ -98,7 +347,14 @@ impl ClientBuilder {
 
     /// Adds an [`EventHandlerFunc`] function for handling incoming updates
     pub fn add_handler_func(&mut self, handler: EventHandlerFunc) -> &mut Self {
-        self.event_handler_funcs.push(handler);
+        self.event_handler_funcs.push(handler.into());
+        self
+    }
+
+    /// Adds an [`EventListener`] for handling incoming updates of its update
+    /// types
+    pub fn add_listener(&mut self, listener: &EventListener) -> &mut Self {
+        self.event_handler_funcs.push(*listener);
         self
     }
 
@@ -108,6 +364,103 @@ impl ClientBuilder {
         self
     }
 
+    /// Adds a [`ChatMigratedHandlerFunc`] function to the handlers, which is
+    /// ran whenever a group migrates to a supergroup
+    pub fn add_chat_migrated_handler_func(
+        &mut self,
+        handler: ChatMigratedHandlerFunc,
+    ) -> &mut Self {
+        self.chat_migrated_handler_funcs.push(handler);
+        self
+    }
+
+    /// Sets the function called when a handler panics or a command or
+    /// listener returns an error, with the [`Context`] the handler was called
+    /// with and the error.
+    /// The update being handled is available through [`Context::update`].
+    ///
+    /// By default the error is logged. Errors of commands only reach this
+    /// function when the framework uses its default error handler, see
+    /// [`Framework::set_error_handler`].
+    ///
+    /// [`Context::update`]: struct.Context.html#method.update
+    /// [`Framework::set_error_handler`]: ../framework/struct.Framework.html#method.set_error_handler
+    pub fn set_error_handler(
+        &mut self,
+        handler: impl Fn(&Context, Error) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.error_handler = Some(Arc::new(handler));
+        self
+    }
+
+    // public only for testing purposes
+    #[doc(hidden)]
+    pub fn inspect(&self) -> ClientBuilderState<'_> {
+        ClientBuilderState {
+            token: self.token.as_deref(),
+            api_url: self.api_url.as_deref(),
+            webhook: self.webhook.as_ref(),
+            request_timeout: self.request_timeout,
+            max_retries: self.max_retries,
+            polling_limit: self.polling_limit,
+            polling_timeout: self.polling_timeout,
+            parse_mode: self.parse_mode.as_ref(),
+            allowed_updates: &self.allowed_updates,
+        }
+    }
+
+    fn make_hyper_client(&self) -> Option<TlsClient> {
+        if self.hyper_client.is_some() {
+            if self.pool_max_idle_per_host.is_some() || self.pool_idle_timeout.is_some() {
+                log::warn!("pool settings were set with a custom hyper client, they are not used");
+            }
+            return self.hyper_client.clone();
+        }
+
+        if self.pool_max_idle_per_host.is_none() && self.pool_idle_timeout.is_none() {
+            return None;
+        }
+
+        let mut builder = hyper::Client::builder();
+        if let Some(max) = self.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.pool_idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        Some(APIClient::build_hyper_client(&builder))
+    }
+
+    fn make_api_client(&self) -> APIClient {
+        let mut client = APIClient::new(
+            self.make_hyper_client(),
+            self.token
+                .as_ref()
+                .expect("A token must be provided for the telegram bot to work"),
+        );
+
+        if let Some(api_url) = &self.api_url {
+            client.set_api_url(api_url);
+        }
+        if let Some(timeout) = self.request_timeout {
+            client.set_timeout(timeout);
+        }
+        if let Some(max_retries) = self.max_retries {
+            client.set_max_retries(max_retries);
+        }
+        if let Some(max) = self.max_concurrent_requests {
+            client.set_max_concurrent_requests(max);
+        }
+        if let Some(parse_mode) = &self.parse_mode {
+            client.set_default_parse_mode(parse_mode.clone());
+        }
+        if let Some(cache) = &self.media_cache {
+            client.set_media_cache(cache.clone());
+        }
+
+        client
+    }
+
     /// Creates the [`Client`] object from the settings set in the
     /// [`ClientBuilder`] object
     ///
@@ -121,31 +474,91 @@ impl ClientBuilder {
         {
             self.allowed_updates.push(UpdateType::Message);
         }
+        if self
+            .framework
+            .as_ref()
+            .is_some_and(|fr| fr.handles_inline_queries())
+            && !self.allowed_updates.is_empty()
+            && !self.allowed_updates.contains(&UpdateType::InlineQuery)
+        {
+            self.allowed_updates.push(UpdateType::InlineQuery);
+        }
+        if self
+            .framework
+            .as_ref()
+            .is_some_and(|fr| fr.handles_edited_messages())
+            && !self.allowed_updates.is_empty()
+            && !self.allowed_updates.contains(&UpdateType::EditedMessage)
+        {
+            self.allowed_updates.push(UpdateType::EditedMessage);
+        }
+        if self
+            .framework
+            .as_ref()
+            .is_some_and(|fr| fr.handles_chosen_inline_results())
+            && !self.allowed_updates.is_empty()
+            && !self
+                .allowed_updates
+                .contains(&UpdateType::ChosenInlineResult)
+        {
+            self.allowed_updates.push(UpdateType::ChosenInlineResult);
+        }
+        if self.callback_router.is_some()
+            && !self.allowed_updates.is_empty()
+            && !self.allowed_updates.contains(&UpdateType::CallbackQuery)
+        {
+            self.allowed_updates.push(UpdateType::CallbackQuery);
+        }
+        if self.api_client.is_some() && self.parse_mode.is_some() {
+            log::warn!("a default parse mode was set with a custom api client, it is not used");
+        }
+        if self.api_client.is_some() && self.media_cache.is_some() {
+            log::warn!("a media cache was set with a custom api client, it is not used");
+        }
+        if self.api_client.is_some()
+            && (self.max_concurrent_requests.is_some()
+                || self.pool_max_idle_per_host.is_some()
+                || self.pool_idle_timeout.is_some())
+        {
+            log::warn!("connection settings were set with a custom api client, they are not used");
+        }
 
-        self.api_client.clone().map_or_else(
-            || Client {
-                api_client: Arc::new(Box::new(APIClient::new(
-                    self.hyper_client.clone(),
-                    self.token
+        Client {
+            api_client: self
+                .api_client
+                .clone()
+                .unwrap_or_else(|| Arc::new(Box::new(self.make_api_client()))),
+            event_handlers: self.event_handler_funcs.clone(),
+            raw_event_handlers: self.raw_event_handler_funcs.clone(),
+            chat_migrated_handlers: self.chat_migrated_handler_funcs.clone(),
+            migrated_chats: Arc::new(Mutex::new(HashMap::new())),
+            data: Arc::new(RwLock::new(TypeMap::custom())),
+            framework: self.framework.clone(),
+            callback_router: self.callback_router.clone(),
+            webhook_opts: self.webhook.clone(),
+            polling_limit: self.polling_limit,
+            polling_timeout: self.polling_timeout,
+            skip_unparseable_updates: self.skip_unparseable_updates,
+            auto_answer_callback_queries: self.auto_answer_callback_queries.unwrap_or_else(|| {
+                self.callback_router.as_ref().is_some_and(|r| !r.is_empty())
+                    || self
+                        .framework
                         .as_ref()
-                        .expect("A token must be provided for the telegram bot to work"),
-                ))),
-                event_handlers: self.event_handler_funcs.clone(),
-                raw_event_handlers: self.raw_event_handler_funcs.clone(),
-                data: Arc::new(RwLock::new(TypeMap::custom())),
-                framework: self.framework.clone(),
-                webhook_opts: self.webhook.clone(),
-                allowed_updates: self.allowed_updates.clone(),
-            },
-            |c| Client {
-                api_client: c,
-                event_handlers: self.event_handler_funcs.clone(),
-                webhook_opts: self.webhook.clone(),
-                raw_event_handlers: self.raw_event_handler_funcs.clone(),
-                data: Arc::new(RwLock::new(TypeMap::custom())),
-                framework: self.framework.clone(),
-                allowed_updates: self.allowed_updates.clone(),
-            },
-        )
+                        .is_some_and(|fr| fr.handles_callback_queries())
+            }),
+            handler_limit: self
+                .max_concurrent_handlers
+                .map(|max| Arc::new(Semaphore::new(max.max(1)))),
+            keep_alive_interval: self.keep_alive_interval,
+            drop_pending_updates: self.drop_pending_updates,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: self.smart_reply_policy,
+            dialogue_storage: self
+                .dialogue_storage
+                .clone()
+                .unwrap_or_else(|| Arc::new(MemoryDialogueStorage::default())),
+            error_handler: self.error_handler.clone(),
+            allowed_updates: self.allowed_updates.clone(),
+        }
     }
 }
@@ -1,25 +1,36 @@
 use super::{
+    command_sync::CommandSync,
+    keep_alive::KeepAlive,
+    spawn_reporting_handler,
     APIConnector,
+    ChatMigrated,
+    ChatMigratedHandlerFunc,
     ClientBuilder,
     Context,
+    ErrorHandlerFunc,
     EventHandlerFunc,
+    EventListener,
     RawEventHandlerFunc,
+    SmartReplyPolicy,
+    StartupInfo,
+    UpdatesMode,
     UpdatesStream,
     Webhook,
     WebhookOptions,
 };
 use crate::{
     api::{
-        types::{SetWebhook, UpdateType},
+        types::{AnswerCallbackQuery, SetWebhook, UpdateType},
         APIClient,
     },
-    framework::Framework,
-    model::Update,
+    framework::{CallbackRouter, DialogueStorage, Framework, MemoryDialogueStorage},
+    model::{MessageContent, Update, UpdateContent, User},
     Result,
 };
 use futures::StreamExt;
-use parking_lot::RwLock;
-use std::sync::Arc;
+use parking_lot::{Mutex, RwLock};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::Semaphore;
 use typemap_rev::TypeMap;
 
 /// The Client is the main object to manage your interaction with telegram.
@@ -76,15 +87,30 @@ pub struct Client {
     /// When using a [`Context`], this data will be available as
     /// [`Context::data`].
     ///
-    /// Refer to the [repeat_image_bot] example for an example on using the
-    /// `data` field
+    /// To keep track of where a user is in a conversation with the bot, use a
+    /// [`Dialogue`] instead, as shown in the [repeat_image] example.
     ///
-    /// [repeat_image_bot]: https://github.com/callieve/telexide/tree/master/examples/repeat_image_bot.rs
+    /// [`Dialogue`]: ../framework/struct.Dialogue.html
+    /// [repeat_image]: https://github.com/callieve/telexide/tree/master/examples/repeat_image
     pub data: Arc<RwLock<TypeMap>>,
-    pub(super) event_handlers: Vec<EventHandlerFunc>,
+    pub(super) event_handlers: Vec<EventListener>,
     pub(super) raw_event_handlers: Vec<RawEventHandlerFunc>,
+    pub(super) chat_migrated_handlers: Vec<ChatMigratedHandlerFunc>,
+    pub(super) migrated_chats: Arc<Mutex<HashMap<i64, i64>>>,
     pub(super) framework: Option<Arc<Framework>>,
+    pub(super) callback_router: Option<Arc<CallbackRouter>>,
     pub(super) webhook_opts: Option<WebhookOptions>,
+    pub(super) polling_limit: Option<usize>,
+    pub(super) polling_timeout: Option<usize>,
+    pub(super) skip_unparseable_updates: bool,
+    pub(super) auto_answer_callback_queries: bool,
+    pub(super) handler_limit: Option<Arc<Semaphore>>,
+    pub(super) keep_alive_interval: Option<Duration>,
+    pub(super) drop_pending_updates: bool,
+    pub(super) bot_user: Arc<RwLock<Option<User>>>,
+    pub(super) smart_reply_policy: SmartReplyPolicy,
+    pub(super) dialogue_storage: Arc<dyn DialogueStorage>,
+    pub(super) error_handler: Option<ErrorHandlerFunc>,
     /// The update types that you want to receive, see the documentation of
     /// [`UpdateType`] for more information
     pub allowed_updates: Vec<UpdateType>,
@@ -97,22 +123,51 @@ impl Client {
             api_client: Arc::new(Box::new(APIClient::new(None, token))),
             event_handlers: Vec::new(),
             raw_event_handlers: Vec::new(),
+            chat_migrated_handlers: Vec::new(),
+            migrated_chats: Arc::new(Mutex::new(HashMap::new())),
             data: Arc::new(RwLock::new(TypeMap::custom())),
             framework: None,
+            callback_router: None,
             webhook_opts: None,
+            polling_limit: None,
+            polling_timeout: None,
+            skip_unparseable_updates: true,
+            auto_answer_callback_queries: false,
+            handler_limit: None,
+            keep_alive_interval: None,
+            drop_pending_updates: false,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: SmartReplyPolicy::default(),
+            dialogue_storage: Arc::new(MemoryDialogueStorage::default()),
+            error_handler: None,
             allowed_updates: Vec::new(),
         }
     }
 
     /// Creates a Client object with default values, but with a [`Framework`]
     pub fn with_framework(fr: Arc<Framework>, token: impl ToString) -> Self {
+        let auto_answer_callback_queries = fr.handles_callback_queries();
         Self {
             api_client: Arc::new(Box::new(APIClient::new(None, token))),
             event_handlers: Vec::new(),
             raw_event_handlers: Vec::new(),
+            chat_migrated_handlers: Vec::new(),
+            migrated_chats: Arc::new(Mutex::new(HashMap::new())),
             data: Arc::new(RwLock::new(TypeMap::custom())),
             webhook_opts: None,
             framework: Some(fr),
+            callback_router: None,
+            polling_limit: None,
+            polling_timeout: None,
+            skip_unparseable_updates: true,
+            auto_answer_callback_queries,
+            handler_limit: None,
+            keep_alive_interval: None,
+            drop_pending_updates: false,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: SmartReplyPolicy::default(),
+            dialogue_storage: Arc::new(MemoryDialogueStorage::default()),
+            error_handler: None,
             allowed_updates: Vec::new(),
         }
     }
@@ -132,7 +187,15 @@ impl Client {
             self.start_with_webhook(opts).await
         } else {
             let mut stream = UpdatesStream::new(self.api_client.clone());
-            stream.set_allowed_updates(self.allowed_updates.clone());
+            stream
+                .set_allowed_updates(self.allowed_updates.clone())
+                .set_skip_unparseable_updates(self.skip_unparseable_updates);
+            if let Some(limit) = self.polling_limit {
+                stream.set_limit(limit);
+            }
+            if let Some(timeout) = self.polling_timeout {
+                stream.set_timout(timeout);
+            }
 
             self.start_with_stream(&mut stream).await
         }
@@ -143,12 +206,18 @@ impl Client {
     /// If using the framework, it will update your commands in telegram
     /// You have to provide your own [`UpdatesStream`] object
     pub async fn start_with_stream(&self, stream: &mut UpdatesStream) -> Result<()> {
-        if let Some(fr) = self.framework.clone() {
-            self.api_client
-                .set_my_commands(fr.get_commands().into())
-                .await?;
+        let _command_sync = self.sync_commands().await?;
+
+        if self.drop_pending_updates {
+            stream.drop_pending_updates().await?;
         }
 
+        self.log_startup_info(UpdatesMode::Polling {
+            timeout: self.polling_timeout,
+        })
+        .await;
+        let _keep_alive = self.start_keep_alive();
+
         log::info!("starting long polling to listen for updates from telegram api");
         while let Some(poll) = stream.next().await {
             match poll {
@@ -167,11 +236,7 @@ impl Client {
     /// If using the framework, it will update your commands in telegram
     /// You have to provide your own [`WebhookOptions`] object
     pub async fn start_with_webhook(&self, opts: &WebhookOptions) -> Result<()> {
-        if let Some(fr) = self.framework.clone() {
-            self.api_client
-                .set_my_commands(fr.get_commands().into())
-                .await?;
-        }
+        let _command_sync = self.sync_commands().await?;
 
         if let Some(webhook_url) = &opts.url {
             self.api_client
@@ -180,13 +245,21 @@ impl Client {
                     certificate: None,
                     max_connections: None,
                     allowed_updates: Some(self.allowed_updates.clone()),
-                    drop_pending_updates: None,
+                    drop_pending_updates: self.drop_pending_updates.then_some(true),
                     ip_address: None, // TODO: add opts for these
                     secret_token: opts.secret_token.clone(),
                 })
                 .await?;
+        } else if self.drop_pending_updates {
+            log::warn!("pending updates can only be dropped when the client sets the webhook url");
         }
 
+        self.log_startup_info(UpdatesMode::Webhook {
+            url: opts.url.as_ref().map(ToString::to_string),
+        })
+        .await;
+        let _keep_alive = self.start_keep_alive();
+
         log::info!("starting to listen on the webhook");
         let mut receiver = Webhook::new(opts).start();
         while let Some(u) = receiver.recv().await {
@@ -201,10 +274,77 @@ impl Client {
         Ok(())
     }
 
+    /// Retrieves the identity of the bot using [`API::get_me`] and summarizes
+    /// it together with the effective configuration of the client, as is
+    /// logged when the client starts.
+    ///
+    /// [`API::get_me`]: ../api/trait.API.html#method.get_me
+    pub async fn startup_info(&self) -> Result<StartupInfo> {
+        let mode = match &self.webhook_opts {
+            Some(opts) => UpdatesMode::Webhook {
+                url: opts.url.as_ref().map(ToString::to_string),
+            },
+            None => UpdatesMode::Polling {
+                timeout: self.polling_timeout,
+            },
+        };
+
+        self.make_startup_info(mode).await
+    }
+
+    async fn make_startup_info(&self, mode: UpdatesMode) -> Result<StartupInfo> {
+        let me = self.api_client.get_me().await?;
+        *self.bot_user.write() = Some(me.clone());
+
+        Ok(StartupInfo {
+            bot_id: me.id,
+            bot_username: me.username,
+            mode,
+            allowed_updates: self.allowed_updates.clone(),
+            commands: self
+                .framework
+                .as_ref()
+                .map_or(0, |fr| fr.get_commands().len()),
+            listeners: self.event_handlers.len()
+                + self.raw_event_handlers.len()
+                + self.chat_migrated_handlers.len(),
+        })
+    }
+
+    async fn log_startup_info(&self, mode: UpdatesMode) {
+        match self.make_startup_info(mode).await {
+            Ok(info) => log::info!("starting client: {info}"),
+            Err(e) => log::warn!("failed to retrieve the bot's identity from telegram: {e}"),
+        }
+    }
+
+    /// Registers the commands of the framework with telegram, and again every
+    /// time they change until the returned guard is dropped
+    async fn sync_commands(&self) -> Result<Option<CommandSync>> {
+        let Some(fr) = self.framework.clone() else {
+            return Ok(None);
+        };
+
+        fr.sync_all_commands(&**self.api_client).await?;
+        Ok(Some(CommandSync::start(self.api_client.clone(), fr)))
+    }
+
+    fn start_keep_alive(&self) -> Option<KeepAlive> {
+        self.keep_alive_interval
+            .map(|interval| KeepAlive::start(self.api_client.clone(), interval))
+    }
+
     /// Subscribes an update event handler function ([`EventHandlerFunc`]) to
     /// the client and will be ran whenever a new update is received
     pub fn subscribe_handler_func(&mut self, handler: EventHandlerFunc) {
-        self.event_handlers.push(handler);
+        self.event_handlers.push(handler.into());
+    }
+
+    /// Subscribes an [`EventListener`] to the client, of which the handler
+    /// will be ran whenever a new update of one of its update types is
+    /// received
+    pub fn subscribe_listener(&mut self, listener: &EventListener) {
+        self.event_handlers.push(*listener);
     }
 
     /// Subscribes a raw update event handler function ([`RawEventHandlerFunc`])
@@ -213,29 +353,208 @@ impl Client {
         self.raw_event_handlers.push(handler);
     }
 
+    /// Subscribes a handler function ([`ChatMigratedHandlerFunc`]) to the
+    /// client, which will be ran whenever a group migrates to a supergroup
+    pub fn subscribe_chat_migrated_handler(&mut self, handler: ChatMigratedHandlerFunc) {
+        self.chat_migrated_handlers.push(handler);
+    }
+
+    /// Gets the id of the supergroup the group with the given id migrated to,
+    /// if the client received the migration.
+    pub fn migrated_chat_id(&self, chat_id: i64) -> Option<i64> {
+        self.migrated_chats.lock().get(&chat_id).copied()
+    }
+
+    /// Records the migration if the update is a service message about a group
+    /// migrating to a supergroup, firing the chat migrated handlers the first
+    /// time the migration is seen
+    fn handle_chat_migration(&self, update: &Update) {
+        let UpdateContent::Message(message) = &update.content else {
+            return;
+        };
+        let migration = match message.content {
+            MessageContent::MigrateToChatID {
+                content,
+            } => ChatMigrated {
+                old_id: message.chat.get_id(),
+                new_id: content,
+            },
+            MessageContent::MigrateFromChatID {
+                content,
+            } => ChatMigrated {
+                old_id: content,
+                new_id: message.chat.get_id(),
+            },
+            _ => return,
+        };
+
+        let previous = self
+            .migrated_chats
+            .lock()
+            .insert(migration.old_id, migration.new_id);
+        if previous == Some(migration.new_id) {
+            return;
+        }
+
+        log::debug!(
+            "chat {} migrated to chat {}",
+            migration.old_id,
+            migration.new_id
+        );
+        if let Some(fr) = &self.framework {
+            fr.migrate_chat(migration.old_id, migration.new_id);
+        }
+        let storage = self.dialogue_storage.clone();
+        tokio::spawn(async move {
+            if let Err(e) = storage
+                .migrate_chat(migration.old_id, migration.new_id)
+                .await
+            {
+                log::warn!(
+                    "failed to move the dialogues of chat {} to chat {}: {e}",
+                    migration.old_id,
+                    migration.new_id
+                );
+            }
+        });
+        for h in self.chat_migrated_handlers.clone() {
+            let ctx = self.context();
+            spawn_reporting_handler(self.handler_limit.as_ref(), ctx.clone(), h(ctx, migration));
+        }
+    }
+
+    fn context(&self) -> Context {
+        Context::new(self.api_client.clone(), self.data.clone())
+            .with_bot_user(self.bot_user.clone())
+            .with_smart_reply_policy(self.smart_reply_policy)
+            .with_dialogue_storage(self.dialogue_storage.clone())
+            .with_error_handler(self.error_handler.clone())
+    }
+
     // public only for testing purposes
     #[doc(hidden)]
     pub fn fire_handlers(&self, update: Update) {
+        #[cfg(feature = "tracing")]
+        let _span = crate::utils::spans::dispatch_span(&update).entered();
+
+        self.handle_chat_migration(&update);
+
+        if self.auto_answer_callback_queries {
+            if let UpdateContent::CallbackQuery(query) = &update.content {
+                let id = query.id.clone();
+                self.fire_callback_query_handlers(id, update);
+                return;
+            }
+        }
+
+        let handled = Arc::new(update.clone());
         for h in self.raw_event_handlers.clone() {
-            let ctx = Context::new(self.api_client.clone(), self.data.clone());
+            let ctx = self.context().with_update(handled.clone());
             let u = update.clone();
-            tokio::spawn(h(ctx, u.into()));
+            spawn_reporting_handler(self.handler_limit.as_ref(), ctx.clone(), h(ctx, u.into()));
         }
 
-        for h in self.event_handlers.clone() {
-            let ctx = Context::new(self.api_client.clone(), self.data.clone());
+        for l in self.event_handlers.iter().filter(|l| l.handles(&update)) {
+            let ctx = self.context().with_update(handled.clone());
             let u = update.clone();
-            tokio::spawn(h(ctx, u));
+            spawn_reporting_handler(
+                self.handler_limit.as_ref(),
+                ctx.clone(),
+                (l.handler)(ctx, u),
+            );
+        }
+
+        // the callback router of the client takes precedence over the callback
+        // query handlers of the framework
+        if let (Some(router), UpdateContent::CallbackQuery(query)) =
+            (&self.callback_router, &update.content)
+        {
+            let ctx = self.context().with_update(handled.clone());
+            if router
+                .fire(ctx, query.clone(), self.handler_limit.as_ref())
+                .is_some()
+            {
+                return;
+            }
         }
 
         if self.framework.is_some() {
-            let ctx = Context::new(self.api_client.clone(), self.data.clone());
+            let ctx = self.context().with_update(handled);
             let fr = self.framework.clone();
             fr.as_ref()
                 .expect("Framework needs to be set before trying to fire commands")
-                .fire_commands(ctx, update);
+                .fire_commands_limited(ctx, update, self.handler_limit.as_ref());
         }
     }
+
+    /// Fires the handlers for a callback query and answers it once they are
+    /// done, if none of them did so already
+    fn fire_callback_query_handlers(&self, query_id: String, update: Update) {
+        let ctx = Context::for_callback_query(self.api_client.clone(), self.data.clone())
+            .with_bot_user(self.bot_user.clone())
+            .with_smart_reply_policy(self.smart_reply_policy)
+            .with_dialogue_storage(self.dialogue_storage.clone())
+            .with_error_handler(self.error_handler.clone())
+            .with_update(Arc::new(update.clone()));
+        let mut handles = Vec::new();
+
+        for h in self.raw_event_handlers.clone() {
+            handles.push(spawn_reporting_handler(
+                self.handler_limit.as_ref(),
+                ctx.clone(),
+                h(ctx.clone(), update.clone().into()),
+            ));
+        }
+
+        for l in self.event_handlers.iter().filter(|l| l.handles(&update)) {
+            handles.push(spawn_reporting_handler(
+                self.handler_limit.as_ref(),
+                ctx.clone(),
+                (l.handler)(ctx.clone(), update.clone()),
+            ));
+        }
+
+        let routed = match (&self.callback_router, &update.content) {
+            (Some(router), UpdateContent::CallbackQuery(query)) => {
+                router.fire(ctx.clone(), query.clone(), self.handler_limit.as_ref())
+            },
+            _ => None,
+        };
+
+        // the framework's handlers are only used when the callback router of
+        // the client has no route for the query
+        if let Some(handle) = routed {
+            handles.push(handle);
+        } else if let (Some(fr), UpdateContent::CallbackQuery(query)) =
+            (&self.framework, update.content)
+        {
+            handles.extend(fr.fire_callback_query_handler(
+                ctx.clone(),
+                query,
+                self.handler_limit.as_ref(),
+            ));
+        }
+
+        tokio::spawn(async move {
+            let mut failed = false;
+            for handle in handles {
+                failed |= handle.await.is_err();
+            }
+            failed |= ctx.callback_query_failed();
+
+            if ctx.callback_query_answered() {
+                return;
+            }
+
+            let mut data = AnswerCallbackQuery::new(query_id);
+            if failed {
+                data.set_text("error");
+            }
+            if let Err(err) = ctx.api.answer_callback_query(data).await {
+                log::warn!("failed to automatically answer callback query: {err}");
+            }
+        });
+    }
 }
 
 impl From<Box<APIConnector>> for Client {
@@ -244,9 +563,23 @@ impl From<Box<APIConnector>> for Client {
             api_client: Arc::new(api),
             event_handlers: Vec::new(),
             raw_event_handlers: Vec::new(),
+            chat_migrated_handlers: Vec::new(),
+            migrated_chats: Arc::new(Mutex::new(HashMap::new())),
             data: Arc::new(RwLock::new(TypeMap::custom())),
             framework: None,
+            callback_router: None,
             webhook_opts: None,
+            polling_limit: None,
+            polling_timeout: None,
+            skip_unparseable_updates: true,
+            auto_answer_callback_queries: false,
+            handler_limit: None,
+            keep_alive_interval: None,
+            drop_pending_updates: false,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: SmartReplyPolicy::default(),
+            dialogue_storage: Arc::new(MemoryDialogueStorage::default()),
+            error_handler: None,
             allowed_updates: Vec::new(),
         }
     }
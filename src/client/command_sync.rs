@@ -0,0 +1,39 @@
+use super::APIConnector;
+use crate::framework::Framework;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+
+/// Registers the commands of the framework with telegram again every time
+/// commands are added to or removed from it while the client is running. It
+/// stops once it is dropped.
+pub(crate) struct CommandSync {
+    handle: JoinHandle<()>,
+}
+
+impl CommandSync {
+    /// Starts waiting for the commands of the framework to change
+    pub(crate) fn start(api: Arc<Box<APIConnector>>, framework: Arc<Framework>) -> Self {
+        let handle = tokio::spawn(async move {
+            loop {
+                framework.commands_changed().await;
+                if framework.commands_synced() {
+                    continue;
+                }
+
+                if let Err(e) = framework.sync_all_commands(&**api).await {
+                    log::warn!("failed to register the changed commands with telegram: {e}");
+                }
+            }
+        });
+
+        Self {
+            handle,
+        }
+    }
+}
+
+impl Drop for CommandSync {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
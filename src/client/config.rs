@@ -0,0 +1,280 @@
+use super::{ClientBuilder, WebhookOptions};
+use crate::{
+    api::types::UpdateType,
+    model::ParseMode,
+    utils::result::{Error, Result, TelegramError},
+};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{env, net::IpAddr, time::Duration};
+
+/// The configuration of a [`Client`], which can be loaded from a toml file or
+/// from environment variables and turned into a [`ClientBuilder`] using
+/// [`ClientBuilder::from_config`].
+///
+/// An example toml configuration:
+///
+/// ```toml
+/// token = "123456:ABC-DEF1234ghIkl-zyx57W2v1u123ew11"
+/// api_url = "https://api.telegram.org"
+/// parse_mode = "MarkdownV2"
+/// allowed_updates = ["message", "callback_query"]
+///
+/// [webhook]
+/// url = "https://example.com/bot"
+/// port = 8006
+///
+/// [polling]
+/// limit = 100
+/// timeout = 5
+///
+/// [timeouts]
+/// request = 30
+/// ```
+///
+/// A proxy can't be configured in the configuration, it has to be set up by
+/// giving the builder a hyper client using a proxying connector with
+/// [`ClientBuilder::set_http_client`].
+///
+/// [`Client`]: struct.Client.html
+/// [`ClientBuilder::set_http_client`]: struct.ClientBuilder.html#method.set_http_client
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The token of the bot
+    pub token: Option<String>,
+    /// The base url of the bot api server to use
+    pub api_url: Option<String>,
+    /// The options for receiving updates through a webhook, if set the client
+    /// will use a webhook instead of long polling
+    pub webhook: Option<WebhookConfig>,
+    /// The options for receiving updates through long polling
+    #[serde(default)]
+    pub polling: PollingConfig,
+    /// The parse mode to use for outgoing messages that do not specify one
+    pub parse_mode: Option<ParseMode>,
+    /// The update types the bot wants to receive
+    #[serde(default)]
+    pub allowed_updates: Vec<UpdateType>,
+    /// The timeouts used when talking to the telegram api
+    #[serde(default)]
+    pub timeouts: TimeoutConfig,
+}
+
+/// The webhook section of a [`ClientConfig`]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// The url telegram will send the updates to
+    pub url: Option<String>,
+    /// The path the webhook will be listening on
+    pub path: Option<String>,
+    /// The port the webhook will be listening on
+    pub port: Option<u16>,
+    /// The IP the webhook will be listening on
+    pub ip: Option<IpAddr>,
+    /// The secret token telegram will send along with every update
+    pub secret_token: Option<String>,
+}
+
+/// The polling section of a [`ClientConfig`]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PollingConfig {
+    /// The maximum amount of updates retrieved in one call, between 1 and 100
+    pub limit: Option<usize>,
+    /// The timeout in seconds used for long polling
+    pub timeout: Option<usize>,
+}
+
+/// The timeouts section of a [`ClientConfig`]
+#[derive(Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TimeoutConfig {
+    /// The maximum duration in seconds a single api request may take
+    pub request: Option<u64>,
+}
+
+impl ClientConfig {
+    /// Parses a `ClientConfig` from the contents of a toml file
+    pub fn from_toml_str(toml: &str) -> Result<Self> {
+        let config: Self = toml::from_str(toml).map_err(|e| {
+            TelegramError::InvalidArgument(format!("invalid config: {}", e.message()))
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Loads a `ClientConfig` from the environment, the following variables
+    /// are used when they are set:
+    ///
+    /// | variable | field |
+    /// |---|---|
+    /// | `TELEXIDE_TOKEN` | `token` |
+    /// | `TELEXIDE_API_URL` | `api_url` |
+    /// | `TELEXIDE_WEBHOOK_URL` | `webhook.url` |
+    /// | `TELEXIDE_WEBHOOK_PATH` | `webhook.path` |
+    /// | `TELEXIDE_WEBHOOK_PORT` | `webhook.port` |
+    /// | `TELEXIDE_WEBHOOK_IP` | `webhook.ip` |
+    /// | `TELEXIDE_WEBHOOK_SECRET_TOKEN` | `webhook.secret_token` |
+    /// | `TELEXIDE_POLLING_LIMIT` | `polling.limit` |
+    /// | `TELEXIDE_POLLING_TIMEOUT` | `polling.timeout` |
+    /// | `TELEXIDE_PARSE_MODE` | `parse_mode` |
+    /// | `TELEXIDE_ALLOWED_UPDATES` | `allowed_updates`, comma separated |
+    /// | `TELEXIDE_REQUEST_TIMEOUT` | `timeouts.request` |
+    pub fn from_env() -> Result<Self> {
+        let webhook = WebhookConfig {
+            url: env_var("TELEXIDE_WEBHOOK_URL"),
+            path: env_var("TELEXIDE_WEBHOOK_PATH"),
+            port: parse_env("TELEXIDE_WEBHOOK_PORT", "webhook.port")?,
+            ip: parse_env("TELEXIDE_WEBHOOK_IP", "webhook.ip")?,
+            secret_token: env_var("TELEXIDE_WEBHOOK_SECRET_TOKEN"),
+        };
+
+        let config = Self {
+            token: env_var("TELEXIDE_TOKEN"),
+            api_url: env_var("TELEXIDE_API_URL"),
+            webhook: (webhook != WebhookConfig::default()).then_some(webhook),
+            polling: PollingConfig {
+                limit: parse_env("TELEXIDE_POLLING_LIMIT", "polling.limit")?,
+                timeout: parse_env("TELEXIDE_POLLING_TIMEOUT", "polling.timeout")?,
+            },
+            parse_mode: env_var("TELEXIDE_PARSE_MODE")
+                .map(|m| deserialize_str(&m, "parse_mode"))
+                .transpose()?,
+            allowed_updates: env_var("TELEXIDE_ALLOWED_UPDATES")
+                .map(|updates| {
+                    updates
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|u| !u.is_empty())
+                        .map(|u| deserialize_str(u, "allowed_updates"))
+                        .collect::<Result<Vec<_>>>()
+                })
+                .transpose()?
+                .unwrap_or_default(),
+            timeouts: TimeoutConfig {
+                request: parse_env("TELEXIDE_REQUEST_TIMEOUT", "timeouts.request")?,
+            },
+        };
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks whether the values of the config are valid, returning an
+    /// [`TelegramError::InvalidArgument`] naming the first invalid field
+    /// otherwise
+    pub fn validate(&self) -> Result<()> {
+        if self.token.as_deref().unwrap_or_default().is_empty() {
+            return Err(invalid_field("token", "a bot token must be provided"));
+        }
+        if let Some(url) = &self.api_url {
+            check_uri(url, "api_url")?;
+        }
+        if let Some(webhook) = &self.webhook {
+            if let Some(url) = &webhook.url {
+                check_uri(url, "webhook.url")?;
+            }
+            if let Some(path) = &webhook.path {
+                if !path.starts_with('/') {
+                    return Err(invalid_field("webhook.path", "must start with a '/'"));
+                }
+            }
+        }
+        if let Some(limit) = self.polling.limit {
+            if !(1..=100).contains(&limit) {
+                return Err(invalid_field("polling.limit", "must be between 1 and 100"));
+            }
+        }
+        if self.timeouts.request == Some(0) {
+            return Err(invalid_field(
+                "timeouts.request",
+                "must be at least 1 second",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl ClientBuilder {
+    /// Creates a [`ClientBuilder`] with all the settings of the provided
+    /// [`ClientConfig`] applied
+    pub fn from_config(config: ClientConfig) -> Result<Self> {
+        config.validate()?;
+
+        let mut builder = Self::new();
+        if let Some(token) = config.token {
+            builder.set_token(&token);
+        }
+        if let Some(api_url) = config.api_url {
+            builder.set_api_url(api_url);
+        }
+        if let Some(webhook) = config.webhook {
+            let mut opts = WebhookOptions::new();
+            if let Some(url) = &webhook.url {
+                opts.set_url(url)?;
+            }
+            if let Some(path) = &webhook.path {
+                opts.set_path(path);
+            }
+            if let Some(port) = webhook.port {
+                opts.set_port(port);
+            }
+            if let Some(ip) = webhook.ip {
+                opts.set_ip(ip);
+            }
+            if let Some(secret_token) = &webhook.secret_token {
+                opts.set_secret_token(secret_token)?;
+            }
+            builder.set_webhook(&opts);
+        }
+        if let Some(limit) = config.polling.limit {
+            builder.set_polling_limit(limit);
+        }
+        if let Some(timeout) = config.polling.timeout {
+            builder.set_polling_timeout(timeout);
+        }
+        if let Some(parse_mode) = config.parse_mode {
+            builder.set_default_parse_mode(parse_mode);
+        }
+        if !config.allowed_updates.is_empty() {
+            builder.set_allowed_updates(config.allowed_updates);
+        }
+        if let Some(timeout) = config.timeouts.request {
+            builder.set_request_timeout(Duration::from_secs(timeout));
+        }
+
+        Ok(builder)
+    }
+}
+
+fn invalid_field(field: &str, reason: &str) -> Error {
+    TelegramError::InvalidArgument(format!("{field}: {reason}")).into()
+}
+
+fn check_uri(uri: &str, field: &str) -> Result<()> {
+    match uri.parse::<hyper::Uri>() {
+        Ok(u) if u.scheme().is_some() && u.host().is_some() => Ok(()),
+        _ => Err(invalid_field(field, &format!("'{uri}' is not a valid url"))),
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parse_env<T: std::str::FromStr>(name: &str, field: &str) -> Result<Option<T>> {
+    env_var(name)
+        .map(|v| {
+            v.parse()
+                .map_err(|_| invalid_field(field, &format!("'{v}' is not a valid value")))
+        })
+        .transpose()
+}
+
+fn deserialize_str<T: DeserializeOwned>(value: &str, field: &str) -> Result<T> {
+    serde_json::from_value(serde_json::Value::String(value.to_owned()))
+        .map_err(|_| invalid_field(field, &format!("'{value}' is not a valid value")))
+}
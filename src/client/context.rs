@@ -1,6 +1,42 @@
-use super::APIConnector;
+use super::{APIConnector, ErrorHandlerFunc, SmartReplyPolicy};
+use crate::{
+    api::{
+        types::{
+            AnswerCallbackQuery,
+            AnswerInlineQuery,
+            DeleteMessage,
+            InlineQueryResult,
+            InputMedia,
+            ReplyParameters,
+            SendMediaGroup,
+            SendMessage,
+        },
+        APIEndpoint,
+        FormDataFile,
+        Response,
+        API,
+    },
+    framework::{
+        handlers::CommandArgs,
+        Dialogue,
+        DialogueKey,
+        DialogueState,
+        DialogueStorage,
+        MemoryDialogueStorage,
+    },
+    model::{IntegerOrString, Message, ReplyMarkup, Update, User},
+    utils::result::{Error, Result, TelegramError},
+};
+use async_trait::async_trait;
 use parking_lot::RwLock;
-use std::sync::Arc;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use typemap_rev::TypeMap;
 
 /// The context object is an utility object that gets passed to all event
@@ -16,6 +52,17 @@ pub struct Context {
     ///
     /// [`Client::data`]: struct.Client.html#structfield.data
     pub data: Arc<RwLock<TypeMap>>,
+    callback_answered: Option<Arc<AtomicBool>>,
+    /// Set once a handler of the callback query reported an error
+    callback_failed: Option<Arc<AtomicBool>>,
+    command_text: Option<String>,
+    command_name: Option<String>,
+    edited: bool,
+    bot_user: Arc<RwLock<Option<User>>>,
+    smart_reply_policy: SmartReplyPolicy,
+    dialogue_storage: Arc<dyn DialogueStorage>,
+    update: Option<Arc<Update>>,
+    error_handler: Option<ErrorHandlerFunc>,
 }
 
 impl Context {
@@ -23,6 +70,462 @@ impl Context {
         Self {
             api,
             data,
+            callback_answered: None,
+            callback_failed: None,
+            command_text: None,
+            command_name: None,
+            edited: false,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: SmartReplyPolicy::default(),
+            dialogue_storage: Arc::new(MemoryDialogueStorage::default()),
+            update: None,
+            error_handler: None,
         }
     }
+
+    /// Creates a context of which the api keeps track of whether a callback
+    /// query got answered through it
+    pub(crate) fn for_callback_query(
+        api: Arc<Box<APIConnector>>,
+        data: Arc<RwLock<TypeMap>>,
+    ) -> Self {
+        let answered = Arc::new(AtomicBool::new(false));
+        Self {
+            api: Arc::new(Box::new(AnswerTrackingAPI {
+                api,
+                answered: answered.clone(),
+            })),
+            data,
+            callback_answered: Some(answered),
+            callback_failed: Some(Arc::new(AtomicBool::new(false))),
+            command_text: None,
+            command_name: None,
+            edited: false,
+            bot_user: Arc::new(RwLock::new(None)),
+            smart_reply_policy: SmartReplyPolicy::default(),
+            dialogue_storage: Arc::new(MemoryDialogueStorage::default()),
+            update: None,
+            error_handler: None,
+        }
+    }
+
+    /// Sets the cache of the bot's user shared with the client
+    pub(crate) fn with_bot_user(mut self, bot_user: Arc<RwLock<Option<User>>>) -> Self {
+        self.bot_user = bot_user;
+        self
+    }
+
+    /// Sets the text following the command the context is created for
+    pub(crate) fn with_command_text(mut self, text: String) -> Self {
+        self.command_text = Some(text);
+        self
+    }
+
+    /// Sets the name of the command the context is created for
+    pub(crate) fn with_command_name(mut self, name: String) -> Self {
+        self.command_name = Some(name);
+        self
+    }
+
+    /// Sets whether the context is created for an edited message
+    pub(crate) fn with_edited(mut self, edited: bool) -> Self {
+        self.edited = edited;
+        self
+    }
+
+    /// Returns whether the command was invoked by editing a message, which
+    /// only happens when the [`Framework`] is set to match edited messages.
+    /// Commands can use this to avoid repeating what they did for the
+    /// original message.
+    ///
+    /// [`Framework`]: ../framework/struct.Framework.html
+    pub fn is_edited(&self) -> bool {
+        self.edited
+    }
+
+    /// Returns the name of the command the message invoked, without the
+    /// prefix and bot name. This is the full name like `config get` for
+    /// subcommands, or the name of the unknown command for the unknown
+    /// command handler.
+    ///
+    /// This is only set for the contexts passed to commands of the
+    /// [`Framework`], it always returns `None` otherwise.
+    ///
+    /// [`Framework`]: ../framework/struct.Framework.html
+    pub fn command_name(&self) -> Option<&str> {
+        self.command_name.as_deref()
+    }
+
+    /// Returns the text following the command in the message that invoked
+    /// it, without the surrounding whitespace.
+    ///
+    /// This is only set for the contexts passed to commands of the
+    /// [`Framework`], it always returns an empty string otherwise.
+    ///
+    /// [`Framework`]: ../framework/struct.Framework.html
+    pub fn rest(&self) -> &str {
+        self.command_text.as_deref().unwrap_or_default().trim()
+    }
+
+    /// Returns the arguments following the command in the message that
+    /// invoked it, split on whitespace. Text in double quotes is kept
+    /// together as a single argument, without the quotes.
+    ///
+    /// This is only set for the contexts passed to commands of the
+    /// [`Framework`], it always returns no arguments otherwise.
+    ///
+    /// [`Framework`]: ../framework/struct.Framework.html
+    pub fn args(&self) -> Vec<String> {
+        CommandArgs::new(self.rest()).collect()
+    }
+
+    /// Sets the policy used by [`Context::smart_reply`]
+    pub(crate) fn with_smart_reply_policy(mut self, policy: SmartReplyPolicy) -> Self {
+        self.smart_reply_policy = policy;
+        self
+    }
+
+    /// Sets the storage of the dialogues shared with the client
+    pub(crate) fn with_dialogue_storage(mut self, storage: Arc<dyn DialogueStorage>) -> Self {
+        self.dialogue_storage = storage;
+        self
+    }
+
+    /// Sets the update the context is created for
+    pub(crate) fn with_update(mut self, update: Arc<Update>) -> Self {
+        self.update = Some(update);
+        self
+    }
+
+    /// Sets the error handler of the client
+    pub(crate) fn with_error_handler(mut self, handler: Option<ErrorHandlerFunc>) -> Self {
+        self.error_handler = handler;
+        self
+    }
+
+    /// Returns the update the handler was called for.
+    ///
+    /// This is set for the contexts passed to the handlers of updates, it
+    /// returns `None` for other contexts like the ones created by
+    /// [`Context::new`].
+    pub fn update(&self) -> Option<&Update> {
+        self.update.as_deref()
+    }
+
+    /// Returns whether the client has an error handler, set using
+    /// [`ClientBuilder::set_error_handler`]
+    ///
+    /// [`ClientBuilder::set_error_handler`]: struct.ClientBuilder.html#method.set_error_handler
+    pub fn has_error_handler(&self) -> bool {
+        self.error_handler.is_some()
+    }
+
+    /// Passes the error to the error handler of the client, set using
+    /// [`ClientBuilder::set_error_handler`], or logs it when the client has
+    /// none. A callback query that is answered automatically gets answered
+    /// with an "error" notification after an error was reported for it.
+    ///
+    /// [`ClientBuilder::set_error_handler`]: struct.ClientBuilder.html#method.set_error_handler
+    pub fn report_error(&self, err: Error) {
+        if let Some(failed) = &self.callback_failed {
+            failed.store(true, Ordering::Release);
+        }
+
+        match &self.error_handler {
+            Some(handler) => handler(self, err),
+            None => match &self.update {
+                Some(update) => log::error!("handler of update {} failed: {err}", update.update_id),
+                None => log::error!("handler failed: {err}"),
+            },
+        }
+    }
+
+    /// Gets the dialogue with the state `S` the message is part of, which is
+    /// kept in the [`DialogueStorage`] of the client
+    ///
+    /// [`DialogueStorage`]: ../framework/trait.DialogueStorage.html
+    pub fn dialogue<S: DialogueState>(&self, message: &Message) -> Dialogue<S> {
+        self.dialogue_for(DialogueKey::for_message(message, S::SCOPE))
+    }
+
+    /// Gets the dialogue with the state `S` held in the conversation with the
+    /// given key
+    pub fn dialogue_for<S: DialogueState>(&self, key: DialogueKey) -> Dialogue<S> {
+        Dialogue::new(self.dialogue_storage.clone(), key)
+    }
+
+    /// Gets the user of the bot itself, for example to detect when the bot is
+    /// mentioned.
+    ///
+    /// The client retrieves it using [`API::get_me`] when it starts, after
+    /// which it is cached. If it isn't cached yet, it is retrieved and cached
+    /// now.
+    ///
+    /// [`API::get_me`]: ../api/trait.API.html#method.get_me
+    pub async fn bot_user(&self) -> Result<User> {
+        if let Some(user) = self.bot_user.read().clone() {
+            return Ok(user);
+        }
+
+        let user = self.api.get_me().await?;
+        *self.bot_user.write() = Some(user.clone());
+        Ok(user)
+    }
+
+    /// Returns true if a callback query has been answered using the api of
+    /// this context.
+    ///
+    /// This is only tracked for the contexts passed to handlers of callback
+    /// queries when [`ClientBuilder::set_auto_answer_callback_queries`] is
+    /// enabled, it always returns false otherwise.
+    ///
+    /// [`ClientBuilder::set_auto_answer_callback_queries`]: struct.ClientBuilder.html#method.set_auto_answer_callback_queries
+    pub fn callback_query_answered(&self) -> bool {
+        self.callback_answered
+            .as_ref()
+            .is_some_and(|a| a.load(Ordering::Acquire))
+    }
+
+    /// Whether a handler of the callback query reported an error through
+    /// [`Context::report_error`]
+    pub(crate) fn callback_query_failed(&self) -> bool {
+        self.callback_failed
+            .as_ref()
+            .is_some_and(|f| f.load(Ordering::Acquire))
+    }
+
+    /// Gets an [`API`] that gives up on every call that takes longer than the
+    /// given duration, returning a [`TelegramError::Timeout`].
+    ///
+    /// Telegram may still carry out a request that timed out, like delivering
+    /// a message, see the [timeouts] notes of the [`API`] trait.
+    ///
+    /// [`API`]: ../api/trait.API.html
+    /// [timeouts]: ../api/trait.API.html#timeouts
+    /// [`TelegramError::Timeout`]: ../enum.TelegramError.html#variant.Timeout
+    pub fn api_with_timeout(&self, timeout: Duration) -> impl API + Send {
+        TimeoutAPI {
+            api: self.api.clone(),
+            timeout,
+        }
+    }
+
+    /// Sends a text message to the given chat
+    pub async fn send(
+        &self,
+        chat_id: impl Into<IntegerOrString>,
+        text: impl ToString,
+    ) -> Result<Message> {
+        self.api
+            .send_message(SendMessage::new(chat_id.into(), text))
+            .await
+    }
+
+    /// Answers the callback query with the given id, showing the text as a
+    /// notification at the top of the chat screen. Use
+    /// [`API::answer_callback_query`] for more options.
+    ///
+    /// [`API::answer_callback_query`]: ../api/trait.API.html#method.answer_callback_query
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn answer_callback(
+        &self,
+        callback_query_id: impl ToString,
+        text: impl ToString,
+    ) -> Result<bool> {
+        let mut data = AnswerCallbackQuery::new(callback_query_id);
+        data.set_text(text);
+        self.api.answer_callback_query(data).await
+    }
+
+    /// Answers the inline query with the given id with the results, use
+    /// [`API::answer_inline_query`] for more options
+    ///
+    /// [`API::answer_inline_query`]: ../api/trait.API.html#method.answer_inline_query
+    #[allow(clippy::needless_pass_by_value)]
+    pub async fn answer_inline(
+        &self,
+        inline_query_id: impl ToString,
+        results: Vec<InlineQueryResult>,
+    ) -> Result<bool> {
+        self.api
+            .answer_inline_query(AnswerInlineQuery::new(inline_query_id, results))
+            .await
+    }
+
+    /// Sends a text message replying to the given message, in the chat (and
+    /// business connection) the message was received in
+    pub async fn reply(&self, message: &Message, text: impl ToString) -> Result<Message> {
+        let mut data = SendMessage::new(message.chat.get_id().into(), text);
+        data.set_reply_parameters(ReplyParameters::new(message.message_id));
+        if let Some(id) = &message.business_connection_id {
+            data.set_business_connection_id(id.clone());
+        }
+
+        self.api.send_message(data).await
+    }
+
+    /// Answers the message with a text message, following the
+    /// [`SmartReplyPolicy`] of the client: by default it replies to the
+    /// message in groups, but not in private chats, and the answer is sent in
+    /// the forum topic of the message.
+    pub async fn smart_reply(&self, message: &Message, text: impl ToString) -> Result<Message> {
+        self.api
+            .send_message(self.smart_reply_policy.build(message, text))
+            .await
+    }
+
+    /// Sends the media as an album, followed by a message with the given
+    /// text and keyboard replying to the first message of the album, as
+    /// telegram does not allow albums to have a keyboard themselves.
+    ///
+    /// Returns the messages of the album and the message with the keyboard.
+    /// If sending the message with the keyboard fails and
+    /// `rollback_on_failure` is set, the album gets deleted again before the
+    /// error is returned.
+    pub async fn send_album_with_keyboard(
+        &self,
+        chat_id: IntegerOrString,
+        media: Vec<InputMedia>,
+        text: &str,
+        markup: ReplyMarkup,
+        rollback_on_failure: bool,
+    ) -> Result<(Vec<Message>, Message)> {
+        let album = self
+            .api
+            .send_media_group(SendMediaGroup::new(chat_id.clone(), media))
+            .await?;
+
+        let mut data = SendMessage::new(chat_id.clone(), text);
+        data.set_reply_markup(markup);
+        if let Some(first) = album.first() {
+            data.set_reply_parameters(ReplyParameters::new(first.message_id));
+        }
+
+        match self.api.send_message(data).await {
+            Ok(message) => Ok((album, message)),
+            Err(err) => {
+                if rollback_on_failure {
+                    for message in &album {
+                        let data = DeleteMessage::new(chat_id.clone(), message.message_id);
+                        if let Err(e) = self.api.delete_message(data).await {
+                            log::warn!(
+                                "failed to delete message {} of the album: {e}",
+                                message.message_id
+                            );
+                        }
+                    }
+                }
+                Err(err)
+            },
+        }
+    }
+}
+
+struct TimeoutAPI {
+    api: Arc<Box<APIConnector>>,
+    timeout: Duration,
+}
+
+impl TimeoutAPI {
+    async fn with_timeout(
+        &self,
+        endpoint: String,
+        fut: impl Future<Output = Result<Response>>,
+    ) -> Result<Response> {
+        tokio::time::timeout(self.timeout, fut)
+            .await
+            .map_err(|_| TelegramError::Timeout {
+                endpoint,
+            })?
+    }
+}
+
+#[async_trait]
+impl API for TimeoutAPI {
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.with_timeout(endpoint.to_string(), self.api.get(endpoint, data))
+            .await
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        self.with_timeout(endpoint.to_string(), self.api.post(endpoint, data))
+            .await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.with_timeout(
+            endpoint.to_string(),
+            self.api.post_file(endpoint, data, files),
+        )
+        .await
+    }
+}
+
+struct AnswerTrackingAPI {
+    api: Arc<Box<APIConnector>>,
+    answered: Arc<AtomicBool>,
+}
+
+impl AnswerTrackingAPI {
+    fn set_answered(&self, tracked: bool, response: Result<Response>) -> Result<Response> {
+        if tracked
+            && matches!(
+                response,
+                Ok(Response {
+                    ok: true,
+                    ..
+                })
+            )
+        {
+            self.answered.store(true, Ordering::Release);
+        }
+        response
+    }
+}
+
+#[async_trait]
+impl API for AnswerTrackingAPI {
+    async fn get(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let tracked = matches!(endpoint, APIEndpoint::AnswerCallbackQuery);
+        let res = self.api.get(endpoint, data).await;
+        self.set_answered(tracked, res)
+    }
+
+    async fn post(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+    ) -> Result<Response> {
+        let tracked = matches!(endpoint, APIEndpoint::AnswerCallbackQuery);
+        let res = self.api.post(endpoint, data).await;
+        self.set_answered(tracked, res)
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<serde_json::Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        let tracked = matches!(endpoint, APIEndpoint::AnswerCallbackQuery);
+        let res = self.api.post_file(endpoint, data, files).await;
+        self.set_answered(tracked, res)
+    }
 }
@@ -1,12 +1,84 @@
 use super::{Context, FutureOutcome};
-use crate::model::{raw::RawUpdate, Update};
+use crate::{
+    api::types::UpdateType,
+    model::{raw::RawUpdate, Update},
+    Error,
+};
+use std::sync::Arc;
 
 /// A function that handles a new update, it receives a [`Context`] and
 /// [`Update`] and returns a pinned future. Wrap an async function with
-/// `#[prepare_listener]` for easier development.
+/// `#[prepare_listener]` for easier development, which also allows it to
+/// return a `Result<(), E>` of which the errors are passed to the error
+/// handler of the client.
 pub type EventHandlerFunc = fn(Context, Update) -> FutureOutcome;
 
+/// An [`EventHandlerFunc`] that is only called for updates of the given
+/// types, so the client doesn't have to call it for other updates.
+///
+/// Listeners made with `#[prepare_listener(updates = "...")]` get one of these
+/// as a static named after the function with `_LISTENER` appended, which can
+/// be added using [`ClientBuilder::add_listener`] or
+/// [`Client::subscribe_listener`].
+///
+/// [`ClientBuilder::add_listener`]: struct.ClientBuilder.html#method.add_listener
+/// [`Client::subscribe_listener`]: struct.Client.html#method.subscribe_listener
+#[derive(Debug, Clone, Copy)]
+pub struct EventListener {
+    /// The types of the updates the handler is called for, it is called for
+    /// all updates when this is empty
+    pub update_types: &'static [UpdateType],
+    /// The handler itself
+    pub handler: EventHandlerFunc,
+}
+
+impl EventListener {
+    /// Whether the handler should be called for the update
+    pub fn handles(&self, update: &Update) -> bool {
+        self.update_types.is_empty()
+            || update
+                .content
+                .update_type()
+                .is_some_and(|t| self.update_types.contains(&t))
+    }
+}
+
+impl From<EventHandlerFunc> for EventListener {
+    fn from(handler: EventHandlerFunc) -> Self {
+        Self {
+            update_types: &[],
+            handler,
+        }
+    }
+}
+
 /// A function that handles a new raw update, it receives a [`Context`] and
 /// [`RawUpdate`] and returns a pinned future. Wrap an async function with
 /// `#[prepare_listener]` for easier development.
 pub type RawEventHandlerFunc = fn(Context, RawUpdate) -> FutureOutcome;
+
+/// A function that handles a group migrating to a supergroup, it receives a
+/// [`Context`] and the [`ChatMigrated`] event and returns a pinned future.
+/// Wrap an async function with `#[prepare_listener]` for easier development.
+pub type ChatMigratedHandlerFunc = fn(Context, ChatMigrated) -> FutureOutcome;
+
+/// A function that is called when a handler returns an error or panics, it
+/// receives the [`Context`] the handler was called with, through which the
+/// update being handled can be retrieved using [`Context::update`], and the
+/// error.
+///
+/// [`Context::update`]: struct.Context.html#method.update
+pub type ErrorHandlerFunc = Arc<dyn Fn(&Context, Error) + Send + Sync>;
+
+/// A group that got migrated to a supergroup, after which all updates of the
+/// chat use the id of the supergroup.
+///
+/// Telegram sends a service message about the migration in both the group
+/// and the supergroup, but the event is only emitted once per migration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChatMigrated {
+    /// The id of the group
+    pub old_id: i64,
+    /// The id of the supergroup the group migrated to
+    pub new_id: i64,
+}
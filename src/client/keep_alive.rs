@@ -0,0 +1,44 @@
+use super::APIConnector;
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    task::JoinHandle,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+
+/// Keeps the connections of the api client warm by periodically sending a
+/// lightweight `getMe` request, so requests after an idle period don't have to
+/// set up a new connection first. The pings stop once it is dropped.
+pub(crate) struct KeepAlive {
+    handle: JoinHandle<()>,
+}
+
+impl KeepAlive {
+    /// Starts sending a `getMe` request every `interval`, the first one being
+    /// sent one interval from now. An interval shorter than a second is
+    /// treated as a second.
+    pub(crate) fn start(api: Arc<Box<APIConnector>>, interval: Duration) -> Self {
+        let interval = interval.max(Duration::from_secs(1));
+
+        let handle = tokio::spawn(async move {
+            let mut ticker = interval_at(Instant::now() + interval, interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+            loop {
+                ticker.tick().await;
+                if let Err(e) = api.get_me().await {
+                    log::debug!("keep-alive request to telegram failed: {e}");
+                }
+            }
+        });
+
+        Self {
+            handle,
+        }
+    }
+}
+
+impl Drop for KeepAlive {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
@@ -18,21 +18,92 @@
 
 mod builder;
 mod client;
+mod command_sync;
+#[cfg(feature = "config")]
+mod config;
 mod context;
 mod event_handlers;
+mod keep_alive;
+mod smart_reply;
+mod startup;
 mod stream;
 mod webhook_handling;
 
-use crate::api::API;
+use crate::{api::API, Error};
 use core::future::Future;
-use std::pin::Pin;
+use futures::FutureExt;
+use std::{any::Any, panic::AssertUnwindSafe, pin::Pin, sync::Arc};
+use tokio::{sync::Semaphore, task::JoinHandle};
 
 pub use builder::ClientBuilder;
+#[doc(hidden)]
+pub use builder::ClientBuilderState;
 pub use client::Client;
+#[cfg(feature = "config")]
+pub use config::{ClientConfig, PollingConfig, TimeoutConfig, WebhookConfig};
 pub use context::Context;
-pub use event_handlers::{EventHandlerFunc, RawEventHandlerFunc};
+pub use event_handlers::{
+    ChatMigrated,
+    ChatMigratedHandlerFunc,
+    ErrorHandlerFunc,
+    EventHandlerFunc,
+    EventListener,
+    RawEventHandlerFunc,
+};
+pub use smart_reply::SmartReplyPolicy;
+pub use startup::{StartupInfo, UpdatesMode};
 pub use stream::UpdatesStream;
 pub use webhook_handling::{Webhook, WebhookOptions};
 
 type APIConnector = dyn API + Send;
 pub(crate) type FutureOutcome = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Spawns a handler future, waiting for a permit of the semaphore first if the
+/// amount of concurrently running handlers is limited
+pub(crate) fn spawn_handler<F>(limit: Option<&Arc<Semaphore>>, fut: F) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    #[cfg(feature = "tracing")]
+    let fut = tracing::Instrument::instrument(fut, crate::utils::spans::handler_span());
+
+    match limit {
+        Some(limit) => {
+            let limit = limit.clone();
+            tokio::spawn(async move {
+                // the semaphore is never closed, so acquiring only fails if it is
+                let _permit = limit.acquire_owned().await;
+                fut.await;
+            })
+        },
+        None => tokio::spawn(fut),
+    }
+}
+
+/// Spawns a handler future like [`spawn_handler`], reporting it to the error
+/// handler of the context when it panics. The task still panics afterwards,
+/// so its handle resolves to an error.
+pub(crate) fn spawn_reporting_handler<F>(
+    limit: Option<&Arc<Semaphore>>,
+    ctx: Context,
+    fut: F,
+) -> JoinHandle<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    spawn_handler(limit, async move {
+        if let Err(panic) = AssertUnwindSafe(fut).catch_unwind().await {
+            ctx.report_error(Error::Panic(panic_message(&*panic)));
+            std::panic::resume_unwind(panic);
+        }
+    })
+}
+
+/// Gets the message a panic was started with, if it was started with one
+pub(crate) fn panic_message(panic: &(dyn Any + Send)) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
@@ -0,0 +1,63 @@
+use crate::{
+    api::types::{ReplyParameters, SendMessage, TopicId},
+    model::{Chat, Message},
+};
+
+/// Decides how [`Context::smart_reply`] answers a message, set using
+/// [`ClientBuilder::set_smart_reply_policy`].
+///
+/// By default the answer quotes the message in groups and supergroups, so it
+/// keeps its context amid other messages, while it is sent plainly in private
+/// chats and channels. Answers to messages in a forum topic are always sent in
+/// that topic.
+///
+/// [`Context::smart_reply`]: struct.Context.html#method.smart_reply
+/// [`ClientBuilder::set_smart_reply_policy`]: struct.ClientBuilder.html#method.set_smart_reply_policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SmartReplyPolicy {
+    /// Whether to reply to messages in groups and supergroups
+    pub reply_in_groups: bool,
+    /// Whether to reply to messages in private chats and channels
+    pub reply_in_private_chats: bool,
+    /// Whether the answer should still be sent when the message it replies to
+    /// got deleted in the meantime
+    pub allow_sending_without_reply: bool,
+}
+
+impl SmartReplyPolicy {
+    /// Builds the [`SendMessage`] answering the message with the given text
+    pub fn build(&self, message: &Message, text: impl ToString) -> SendMessage {
+        let mut data = SendMessage::new(message.chat.get_id().into(), text);
+
+        let reply = match message.chat {
+            Chat::Group(_) | Chat::SuperGroup(_) => self.reply_in_groups,
+            Chat::Private(_) | Chat::Channel(_) => self.reply_in_private_chats,
+        };
+        if reply {
+            let mut parameters = ReplyParameters::new(message.message_id);
+            if self.allow_sending_without_reply {
+                parameters.set_allow_sending_without_reply(true);
+            }
+            data.set_reply_parameters(parameters);
+        }
+
+        if let Some(TopicId::Topic(thread_id)) = message.topic_id() {
+            data.set_message_thread_id(thread_id);
+        }
+        if let Some(id) = &message.business_connection_id {
+            data.set_business_connection_id(id.clone());
+        }
+
+        data
+    }
+}
+
+impl Default for SmartReplyPolicy {
+    fn default() -> Self {
+        Self {
+            reply_in_groups: true,
+            reply_in_private_chats: false,
+            allow_sending_without_reply: true,
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::api::types::UpdateType;
+use std::fmt;
+
+/// A summary of the effective configuration of a [`Client`] and the identity
+/// of its bot, which is logged when the client starts and can be retrieved
+/// using [`Client::startup_info`], for example for a health endpoint.
+///
+/// [`Client`]: struct.Client.html
+/// [`Client::startup_info`]: struct.Client.html#method.startup_info
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartupInfo {
+    /// The id of the bot
+    pub bot_id: i64,
+    /// The username of the bot
+    pub bot_username: Option<String>,
+    /// How the client receives its updates
+    pub mode: UpdatesMode,
+    /// The update types the client asks telegram for, all update types except
+    /// for the ones that have to be requested explicitly if empty
+    pub allowed_updates: Vec<UpdateType>,
+    /// The amount of commands registered in the framework
+    pub commands: usize,
+    /// The amount of event handlers and raw event handlers subscribed to the
+    /// client
+    pub listeners: usize,
+}
+
+/// The way a [`Client`] receives its updates
+///
+/// [`Client`]: struct.Client.html
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdatesMode {
+    /// The updates are retrieved using long polling
+    Polling {
+        /// The timeout in seconds used for long polling, if it differs from
+        /// the default
+        timeout: Option<usize>,
+    },
+    /// The updates are received through a webhook
+    Webhook {
+        /// The url telegram sends the updates to, if the client registers it
+        url: Option<String>,
+    },
+}
+
+impl fmt::Display for StartupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bot @{} ({})",
+            self.bot_username.as_deref().unwrap_or("unknown"),
+            self.bot_id
+        )?;
+
+        match &self.mode {
+            UpdatesMode::Polling {
+                timeout: Some(timeout),
+            } => write!(f, ", mode: polling with a timeout of {timeout}s")?,
+            UpdatesMode::Polling {
+                timeout: None,
+            } => write!(f, ", mode: polling")?,
+            UpdatesMode::Webhook {
+                url: Some(url),
+            } => write!(f, ", mode: webhook at {url}")?,
+            UpdatesMode::Webhook {
+                url: None,
+            } => write!(f, ", mode: webhook")?,
+        }
+
+        if self.allowed_updates.is_empty() {
+            write!(f, ", allowed updates: default")?;
+        } else {
+            write!(f, ", allowed updates: {:?}", self.allowed_updates)?;
+        }
+
+        write!(
+            f,
+            ", commands: {}, listeners: {}",
+            self.commands, self.listeners
+        )
+    }
+}
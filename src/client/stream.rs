@@ -13,8 +13,11 @@ use crate::{
     model::Update,
     Result,
 };
+use log::warn;
+use serde::Deserialize;
+use serde_json::Value;
 
-type FutureUpdate = Pin<Box<dyn Future<Output = Result<Vec<Update>>> + Send>>;
+type FutureUpdate = Pin<Box<dyn Future<Output = Result<Vec<Value>>> + Send>>;
 
 /// The stream of incoming updates, created by long polling the telegram API
 /// using their getUpdates endpoint.
@@ -54,15 +57,21 @@ type FutureUpdate = Pin<Box<dyn Future<Output = Result<Vec<Update>>> + Send>>;
 /// }
 /// ```
 ///
+/// Updates are parsed one by one, so an update that can't be parsed (for
+/// example because it uses a newer version of the bot api) doesn't stop the
+/// rest of the batch from being delivered. By default such an update gets
+/// logged and skipped, see [`UpdatesStream::set_skip_unparseable_updates`].
+///
 /// [`Client`]: struct.Client.html
 #[must_use = "streams do nothing unless polled"]
 pub struct UpdatesStream {
     api: Arc<Box<APIConnector>>,
-    buffer: VecDeque<Update>,
+    buffer: VecDeque<Result<Update>>,
     allowed_updates: Vec<UpdateType>,
     offset: i64,
     limit: usize,
     timeout: usize,
+    skip_unparseable: bool,
     current_request: Option<FutureUpdate>,
 }
 
@@ -73,7 +82,7 @@ impl Stream for UpdatesStream {
         let ref_mut = self.get_mut();
 
         if let Some(u) = ref_mut.buffer.pop_front() {
-            return Poll::Ready(Some(Ok(u)));
+            return Poll::Ready(Some(u));
         }
 
         if let Some(ref mut request) = ref_mut.current_request {
@@ -84,9 +93,11 @@ impl Stream for UpdatesStream {
                     return Pin::new(ref_mut).poll_next(cx);
                 },
                 Poll::Ready(Ok(res)) => {
-                    for u in res {
-                        ref_mut.offset = max(u.update_id, ref_mut.offset);
-                        ref_mut.buffer.push_back(u);
+                    for raw in res {
+                        if !ref_mut.push_raw_update(&raw) {
+                            // the updates after it are retrieved again
+                            break;
+                        }
                     }
                 },
                 Poll::Ready(Err(err)) => {
@@ -113,7 +124,37 @@ impl UpdatesStream {
             .set_timeout(self.timeout);
 
         let api = self.api.clone();
-        self.current_request = Some(Box::pin(async move { api.get_updates(data).await }));
+        let request = async move { api.get_raw_updates(data).await };
+
+        #[cfg(feature = "tracing")]
+        let request = tracing::Instrument::instrument(
+            request,
+            tracing::debug_span!("poll_updates", offset = self.offset + 1),
+        );
+
+        self.current_request = Some(Box::pin(request));
+    }
+
+    /// Parses the update and adds it to the buffer, returning whether the
+    /// updates following it can be handled. The offset only moves past the
+    /// update once it is parsed or skipped, so an update that fails to parse
+    /// is retrieved again when unparseable updates aren't skipped.
+    fn push_raw_update(&mut self, raw: &Value) -> bool {
+        match Update::deserialize(raw) {
+            Ok(u) => self.buffer.push_back(Ok(u)),
+            Err(err) if self.skip_unparseable => {
+                warn!("skipping update that could not be parsed: {err}, raw update: {raw}");
+            },
+            Err(err) => {
+                self.buffer.push_back(Err(err.into()));
+                return false;
+            },
+        }
+
+        if let Some(id) = raw.get("update_id").and_then(Value::as_i64) {
+            self.offset = max(id, self.offset);
+        }
+        true
     }
 
     /// creates a new update stream using the provided [`API`]
@@ -127,6 +168,7 @@ impl UpdatesStream {
             offset: 0,
             limit: 100,
             timeout: 5,
+            skip_unparseable: true,
             current_request: None,
         }
     }
@@ -145,6 +187,41 @@ impl UpdatesStream {
         self
     }
 
+    /// Set whether updates that can't be parsed should be logged and skipped,
+    /// instead of being returned as an error. Defaults to true.
+    ///
+    /// An update that is returned as an error isn't skipped, so polling the
+    /// stream again retrieves it again together with the updates after it.
+    pub fn set_skip_unparseable_updates(&mut self, skip: bool) -> &mut Self {
+        self.skip_unparseable = skip;
+        self
+    }
+
+    /// Drops the updates that are waiting to be retrieved, so the stream
+    /// continues with the updates that arrive after this call.
+    ///
+    /// This retrieves only the newest pending update using an offset of -1,
+    /// which makes telegram forget all updates before it, after which that
+    /// update gets skipped by the stream as well.
+    pub async fn drop_pending_updates(&mut self) -> Result<()> {
+        let mut data = GetUpdates::new();
+        data.set_limit(1)
+            .set_allowed_updates(self.allowed_updates.clone())
+            .set_offset(-1)
+            .set_timeout(0);
+
+        let pending = self.api.get_raw_updates(data).await?;
+        if let Some(id) = pending
+            .iter()
+            .filter_map(|raw| raw.get("update_id").and_then(Value::as_i64))
+            .max()
+        {
+            self.offset = max(id, self.offset);
+        }
+
+        Ok(())
+    }
+
     /// Set which update types you want to receive
     pub fn set_allowed_updates(&mut self, allowed: Vec<UpdateType>) -> &mut Self {
         self.allowed_updates = allowed;
@@ -1,6 +1,5 @@
 use std::{
     convert::Infallible,
-    io::Write,
     net::{IpAddr, SocketAddr},
 };
 
@@ -9,7 +8,6 @@ use crate::{
     utils::result::{Result as TelegramResult, TelegramError},
 };
 use hyper::{
-    body::HttpBody,
     service::{make_service_fn, service_fn},
     Body,
     Method,
@@ -21,6 +19,8 @@ use hyper::{
 };
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
+static SECRET_TOKEN_HEADER: &str = "x-telegram-bot-api-secret-token";
+
 /// Handles listening to the telegram webhook and will provide you with the
 /// incoming updates
 #[derive(Debug)]
@@ -52,12 +52,7 @@ async fn handle_update(
 ) -> TelegramResult<Response<Body>> {
     let mut response = Response::new(Body::empty());
 
-    let mut raw_body = req.into_body();
-    let mut body: Vec<u8> = Vec::new();
-    while let Some(chunk) = raw_body.data().await {
-        body.write_all(&chunk?)?;
-    }
-
+    let body = hyper::body::to_bytes(req.into_body()).await?;
     let update: Update = serde_json::from_slice(&body)?;
     let send_res = payload.chan.send(Ok(update)).await;
     if send_res.is_err() {
@@ -76,6 +71,12 @@ async fn handle_req(
 
     match (req.method(), req.uri().path()) {
         (&Method::POST, path) if path == payload.path => {
+            if !payload.is_authorized(&req) {
+                log::warn!("rejected a webhook request with a missing or wrong secret token");
+                *response.status_mut() = StatusCode::UNAUTHORIZED;
+                return Ok(response);
+            }
+
             let result = handle_update(payload, req).await;
 
             if result.is_err() {
@@ -177,9 +178,26 @@ impl WebhookOptions {
         Ok(self)
     }
 
-    /// Sets the secret token of the webhook
-    pub fn set_secret_token(&mut self, secret_token: &impl ToString) -> TelegramResult<&mut Self> {
-        self.secret_token = Some(secret_token.to_string());
+    /// Sets the secret token of the webhook, which telegram sends along with
+    /// every update in the `X-Telegram-Bot-Api-Secret-Token` header. Requests
+    /// without the correct token are rejected with a 401 status code.
+    ///
+    /// Returns a [`TelegramError::InvalidArgument`] if the token isn't 1-256
+    /// characters long or contains characters other than `A-Z`, `a-z`, `0-9`,
+    /// `_` and `-`, as telegram doesn't accept those.
+    pub fn set_secret_token(&mut self, secret_token: &str) -> TelegramResult<&mut Self> {
+        if !(1..=256).contains(&secret_token.len())
+            || !secret_token
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-')
+        {
+            return Err(TelegramError::InvalidArgument(
+                "the secret token must be 1-256 characters of A-Z, a-z, 0-9, _ and -".to_owned(),
+            )
+            .into());
+        }
+
+        self.secret_token = Some(secret_token.to_owned());
         Ok(self)
     }
 
@@ -199,6 +217,7 @@ impl Default for WebhookOptions {
 #[derive(Clone, Debug)]
 struct HandlingPayload {
     path: String,
+    secret_token: Option<String>,
     chan: Sender<TelegramResult<Update>>,
 }
 
@@ -206,7 +225,24 @@ impl HandlingPayload {
     fn new(opts: &WebhookOptions, sender: Sender<TelegramResult<Update>>) -> Self {
         Self {
             path: opts.get_path().to_owned(),
+            secret_token: opts.secret_token.clone(),
             chan: sender,
         }
     }
+
+    fn is_authorized(&self, req: &Request<Body>) -> bool {
+        let Some(expected) = &self.secret_token else {
+            return true;
+        };
+
+        req.headers()
+            .get(SECRET_TOKEN_HEADER)
+            .is_some_and(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+    }
+}
+
+/// Compares the given bytes in an amount of time that doesn't depend on where
+/// they differ, so the secret token can't be guessed by timing the responses
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
 }
@@ -0,0 +1,92 @@
+use super::types::CallbackQueryHandlerFunc;
+use crate::{
+    client::{spawn_handler, Context},
+    model::CallbackQuery,
+};
+use log::debug;
+use std::sync::Arc;
+use tokio::{sync::Semaphore, task::JoinHandle};
+
+/// Routes callback queries to handlers by the prefix of their data, so the
+/// presses of inline keyboard buttons don't all have to be matched in one
+/// event handler.
+///
+/// A callback query is only routed to the handler with the longest matching
+/// prefix, the handler registered first wins when several handlers share that
+/// prefix. Queries without a matching prefix are left to the event handlers.
+///
+/// Set it on the client with [`ClientBuilder::set_callback_router`], or
+/// register the handlers on the framework with
+/// [`Framework::add_callback_query_handler`]. When both are used, a query is
+/// only routed to the framework if the router of the client has no route for
+/// it.
+///
+/// [`ClientBuilder::set_callback_router`]: ../client/struct.ClientBuilder.html#method.set_callback_router
+/// [`Framework::add_callback_query_handler`]: struct.Framework.html#method.add_callback_query_handler
+#[derive(Clone, Default)]
+pub struct CallbackRouter {
+    routes: Vec<(String, CallbackQueryHandlerFunc)>,
+}
+
+impl CallbackRouter {
+    /// Creates a new `CallbackRouter` without any routes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a handler for the callback queries of which the data starts with
+    /// the given prefix, like `vote:` for the data `vote:yes`, which receives
+    /// the data following the prefix. It can answer the query using
+    /// [`Context::answer_callback`].
+    ///
+    /// [`Context::answer_callback`]: ../client/struct.Context.html#method.answer_callback
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn on(&mut self, prefix: impl ToString, handler: CallbackQueryHandlerFunc) -> &mut Self {
+        self.routes.push((prefix.to_string(), handler));
+        self
+    }
+
+    /// Whether no routes are registered
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// Gets the route with the longest prefix matching the data, together
+    /// with the data following the prefix
+    fn route<'a>(&self, data: &'a str) -> Option<(&str, CallbackQueryHandlerFunc, &'a str)> {
+        let (prefix, handler) = self
+            .routes
+            .iter()
+            .rev()
+            .filter(|(prefix, _)| data.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())?;
+
+        Some((prefix, *handler, &data[prefix.len()..]))
+    }
+
+    /// Calls the handler matching the data of the callback query, returning
+    /// its handle if there is one
+    pub(crate) fn fire(
+        &self,
+        context: Context,
+        query: CallbackQuery,
+        limit: Option<&Arc<Semaphore>>,
+    ) -> Option<JoinHandle<()>> {
+        let (prefix, handler, rest) = self.route(query.data.as_deref()?)?;
+
+        debug!("calling callback query handler for prefix {prefix:?}");
+        let rest = rest.to_owned();
+        Some(spawn_handler(limit, handler(context, query, rest)))
+    }
+}
+
+impl std::fmt::Debug for CallbackRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackRouter")
+            .field(
+                "prefixes",
+                &self.routes.iter().map(|(p, _)| p).collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
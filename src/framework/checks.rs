@@ -0,0 +1,89 @@
+//! Built-in checks for the `checks` option of the `command` macro, which
+//! can be used by their name like `checks = "dm_only"`
+
+use crate::{
+    api::types::GetChatMember,
+    client::Context,
+    model::{Chat, ChatMember, Message},
+    utils::result::Result,
+};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use typemap_rev::TypeMapKey;
+
+/// How long [`chat_admin_only`] remembers whether a user is an administrator
+/// of a chat
+pub const ADMIN_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Whether users are administrators of chats, keyed by the chat and user id,
+/// stored in the data of the client
+struct AdminCache;
+
+impl TypeMapKey for AdminCache {
+    type Value = HashMap<(i64, i64), (Instant, bool)>;
+}
+
+/// Only allows the command to be used in private chats
+#[allow(clippy::unused_async)]
+pub async fn dm_only(_ctx: Context, message: &Message) -> Result<bool> {
+    Ok(matches!(message.chat, Chat::Private(_)))
+}
+
+/// Only allows the command to be used in groups and supergroups
+#[allow(clippy::unused_async)]
+pub async fn group_only(_ctx: Context, message: &Message) -> Result<bool> {
+    Ok(matches!(message.chat, Chat::Group(_) | Chat::SuperGroup(_)))
+}
+
+/// Only allows the owner and administrators of a group or supergroup to use
+/// the command, including anonymous administrators.
+///
+/// Whether a user is an administrator is looked up using
+/// [`API::get_chat_member`] and remembered for [`ADMIN_CACHE_TTL`].
+///
+/// [`API::get_chat_member`]: ../../api/trait.API.html#method.get_chat_member
+pub async fn chat_admin_only(ctx: Context, message: &Message) -> Result<bool> {
+    if !matches!(message.chat, Chat::Group(_) | Chat::SuperGroup(_)) {
+        return Ok(false);
+    }
+
+    let chat_id = message.chat.get_id();
+    // anonymous administrators send messages on behalf of the group itself
+    if message
+        .sender_chat
+        .as_ref()
+        .is_some_and(|c| c.get_id() == chat_id)
+    {
+        return Ok(true);
+    }
+    let Some(user_id) = message.from.as_ref().map(|u| u.id) else {
+        return Ok(false);
+    };
+
+    let cached = ctx
+        .data
+        .read()
+        .get::<AdminCache>()
+        .and_then(|cache| cache.get(&(chat_id, user_id)).copied())
+        .filter(|(at, _)| at.elapsed() < ADMIN_CACHE_TTL);
+    if let Some((_, is_admin)) = cached {
+        return Ok(is_admin);
+    }
+
+    let member = ctx
+        .api
+        .get_chat_member(GetChatMember::new(chat_id.into(), user_id))
+        .await?;
+    let is_admin = matches!(
+        member,
+        ChatMember::Creator(_) | ChatMember::Administrator(_)
+    );
+
+    let mut data = ctx.data.write();
+    let cache = data.entry::<AdminCache>().or_default();
+    cache.retain(|_, (at, _)| at.elapsed() < ADMIN_CACHE_TTL);
+    cache.insert((chat_id, user_id), (Instant::now(), is_admin));
+    Ok(is_admin)
+}
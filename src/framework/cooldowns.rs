@@ -0,0 +1,90 @@
+use super::types::{Cooldown, CooldownScope};
+use crate::model::Message;
+use parking_lot::Mutex;
+use std::{collections::HashMap, time::Duration};
+use tokio::time::Instant;
+
+/// How often the cooldowns that ran out are removed
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Keeps track of when the commands with a cooldown can be used again
+#[derive(Debug, Default)]
+pub(crate) struct Cooldowns {
+    state: Mutex<CooldownsState>,
+}
+
+#[derive(Debug, Default)]
+struct CooldownsState {
+    /// When the commands can be used again, keyed by the command name, the
+    /// scope of the cooldown and the id of the user or chat
+    ready_at: HashMap<(String, CooldownScope, i64), Instant>,
+    last_cleanup: Option<Instant>,
+}
+
+impl Cooldowns {
+    /// Starts the cooldown of the command invoked by the message, or returns
+    /// how long it takes until the command can be used again if the cooldown
+    /// is still running
+    pub(crate) fn start(
+        &self,
+        command: &str,
+        cooldown: &Cooldown,
+        message: &Message,
+    ) -> Result<(), Duration> {
+        let id = match cooldown.scope {
+            CooldownScope::User => message.from.as_ref().map_or_else(
+                || {
+                    message
+                        .sender_chat
+                        .as_ref()
+                        .unwrap_or(&message.chat)
+                        .get_id()
+                },
+                |u| u.id,
+            ),
+            CooldownScope::Chat => message.chat.get_id(),
+        };
+        let now = Instant::now();
+        let mut state = self.state.lock();
+
+        if state
+            .last_cleanup
+            .map_or(true, |at| now.duration_since(at) >= CLEANUP_INTERVAL)
+        {
+            state.ready_at.retain(|_, ready_at| *ready_at > now);
+            state.last_cleanup = Some(now);
+        }
+
+        let key = (command.to_owned(), cooldown.scope, id);
+        match state.ready_at.get(&key) {
+            Some(ready_at) if *ready_at > now => Err(*ready_at - now),
+            _ => {
+                state.ready_at.insert(key, now + cooldown.duration);
+                Ok(())
+            },
+        }
+    }
+
+    /// Moves the cooldowns running in a group to the supergroup it migrated
+    /// to, including those of messages sent on behalf of the group
+    pub(crate) fn migrate_chat(&self, old_id: i64, new_id: i64) {
+        let mut state = self.state.lock();
+        let migrated: Vec<_> = state
+            .ready_at
+            .keys()
+            .filter(|(_, _, id)| *id == old_id)
+            .cloned()
+            .collect();
+
+        for key in migrated {
+            if let Some(ready_at) = state.ready_at.remove(&key) {
+                state.ready_at.insert((key.0, key.1, new_id), ready_at);
+            }
+        }
+    }
+
+    /// The amount of cooldowns kept track of
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().ready_at.len()
+    }
+}
@@ -0,0 +1,252 @@
+use super::{handlers::CommandOutcome, types::CommandResult};
+use crate::{
+    client::Context,
+    model::{Chat, Message},
+    utils::result::Result,
+};
+use async_trait::async_trait;
+use log::warn;
+use parking_lot::Mutex;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, future::Future, marker::PhantomData, pin::Pin, sync::Arc};
+
+/// The state of a multi-step conversation with the bot, like a `/setup` wizard
+/// asking several questions in a row.
+///
+/// The state is stored serialized in the [`DialogueStorage`] of the client, so
+/// it can be kept in a database by implementing a storage for it.
+pub trait DialogueState: Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// The name the states are stored under, which has to be unique among the
+    /// dialogues of the bot
+    const NAME: &'static str;
+    /// Whether the dialogue is held with every user in a chat separately or
+    /// with the whole chat at once, defaults to every user separately
+    const SCOPE: DialogueScope = DialogueScope::User;
+}
+
+/// Whether a dialogue is held with every user in a chat separately or with the
+/// whole chat at once
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DialogueScope {
+    /// Every user has their own dialogue in every chat
+    User,
+    /// All users in a chat share the same dialogue
+    Chat,
+}
+
+/// Identifies the conversation a dialogue is held in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DialogueKey {
+    /// The id of the chat the dialogue is held in
+    pub chat_id: i64,
+    /// The id of the user the dialogue is held with, `None` for dialogues
+    /// shared by the whole chat
+    pub user_id: Option<i64>,
+}
+
+impl DialogueKey {
+    /// Gets the key of the dialogue the message is part of. Messages sent on
+    /// behalf of a chat continue the dialogue of that chat in the user scope.
+    pub fn for_message(message: &Message, scope: DialogueScope) -> Self {
+        let chat_id = message.chat.get_id();
+        let user_id = match scope {
+            DialogueScope::Chat => None,
+            DialogueScope::User => Some(
+                message
+                    .from
+                    .as_ref()
+                    .map(|u| u.id)
+                    .or_else(|| message.sender_chat.as_ref().map(Chat::get_id))
+                    .unwrap_or(chat_id),
+            ),
+        };
+
+        Self {
+            chat_id,
+            user_id,
+        }
+    }
+}
+
+/// Stores the states of the dialogues, keyed by the [`DialogueState::NAME`]
+/// and the [`DialogueKey`] of the dialogue.
+///
+/// Implement this to keep the states in your own database, or use the
+/// [`MemoryDialogueStorage`].
+#[async_trait]
+pub trait DialogueStorage: Send + Sync {
+    /// Gets the state of the dialogue, if it is active
+    async fn get(&self, name: &str, key: DialogueKey) -> Result<Option<Value>>;
+    /// Sets the state of the dialogue
+    async fn set(&self, name: &str, key: DialogueKey, state: Value) -> Result<()>;
+    /// Removes the state of the dialogue, ending it
+    async fn remove(&self, name: &str, key: DialogueKey) -> Result<()>;
+    /// Moves the dialogues held in a group to the supergroup it migrated to,
+    /// so they continue there. By default the dialogues are not moved.
+    async fn migrate_chat(&self, _old_chat_id: i64, _new_chat_id: i64) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl DialogueKey {
+    /// Gets the key the dialogue has after the chat with the old id migrated
+    /// to the chat with the new id, if it is held in that chat
+    fn migrated(self, old_chat_id: i64, new_chat_id: i64) -> Option<Self> {
+        if self.chat_id != old_chat_id {
+            return None;
+        }
+
+        // messages sent on behalf of the group continue its dialogue as the
+        // supergroup
+        let user_id = self
+            .user_id
+            .map(|id| if id == old_chat_id { new_chat_id } else { id });
+        Some(Self {
+            chat_id: new_chat_id,
+            user_id,
+        })
+    }
+}
+
+/// A [`DialogueStorage`] keeping the states in memory, so they are lost when
+/// the bot restarts.
+#[derive(Debug, Default)]
+pub struct MemoryDialogueStorage {
+    states: Mutex<HashMap<(String, DialogueKey), Value>>,
+}
+
+#[async_trait]
+impl DialogueStorage for MemoryDialogueStorage {
+    async fn get(&self, name: &str, key: DialogueKey) -> Result<Option<Value>> {
+        Ok(self.states.lock().get(&(name.to_owned(), key)).cloned())
+    }
+
+    async fn set(&self, name: &str, key: DialogueKey, state: Value) -> Result<()> {
+        self.states.lock().insert((name.to_owned(), key), state);
+        Ok(())
+    }
+
+    async fn remove(&self, name: &str, key: DialogueKey) -> Result<()> {
+        self.states.lock().remove(&(name.to_owned(), key));
+        Ok(())
+    }
+
+    async fn migrate_chat(&self, old_chat_id: i64, new_chat_id: i64) -> Result<()> {
+        let mut states = self.states.lock();
+        let migrated: Vec<_> = states
+            .keys()
+            .filter_map(|(name, key)| {
+                let new_key = key.migrated(old_chat_id, new_chat_id)?;
+                Some(((name.clone(), *key), (name.clone(), new_key)))
+            })
+            .collect();
+
+        for (old_key, new_key) in migrated {
+            if let Some(state) = states.remove(&old_key) {
+                states.insert(new_key, state);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A handle to a dialogue with the state `S`, as gotten using
+/// [`Context::dialogue`].
+///
+/// [`Context::dialogue`]: ../client/struct.Context.html#method.dialogue
+pub struct Dialogue<S> {
+    storage: Arc<dyn DialogueStorage>,
+    key: DialogueKey,
+    state: PhantomData<fn() -> S>,
+}
+
+impl<S: DialogueState> Dialogue<S> {
+    pub(crate) fn new(storage: Arc<dyn DialogueStorage>, key: DialogueKey) -> Self {
+        Self {
+            storage,
+            key,
+            state: PhantomData,
+        }
+    }
+
+    /// The key identifying the conversation the dialogue is held in
+    pub fn key(&self) -> DialogueKey {
+        self.key
+    }
+
+    /// Gets the current state of the dialogue, `None` if it isn't active
+    pub async fn get(&self) -> Result<Option<S>> {
+        match self.storage.get(S::NAME, self.key).await? {
+            Some(state) => Ok(Some(serde_json::from_value(state)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Moves the dialogue to the given state, starting it if it isn't active
+    pub async fn update(&self, state: S) -> Result<()> {
+        let state = serde_json::to_value(state)?;
+        self.storage.set(S::NAME, self.key, state).await
+    }
+
+    /// Ends the dialogue
+    pub async fn exit(&self) -> Result<()> {
+        self.storage.remove(S::NAME, self.key).await
+    }
+}
+
+impl<S> Clone for Dialogue<S> {
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            key: self.key,
+            state: PhantomData,
+        }
+    }
+}
+
+/// A function handling the messages sent in a dialogue while it is in a
+/// certain state, it receives a [`Context`], the [`Message`], the [`Dialogue`]
+/// and its current state and returns a pinned future. Wrap an async function
+/// returning a [`CommandResult`] with `#[prepare_listener]` for easier
+/// development.
+pub type DialogueHandlerFunc<S> =
+    fn(Context, Message, Dialogue<S>, S) -> Pin<Box<dyn Future<Output = CommandResult> + Send>>;
+
+/// Routes the messages of a dialogue to its handler for the current state
+#[async_trait]
+pub(crate) trait DialogueRoute: Send + Sync {
+    /// Gets the future of the handler if the message is part of an active
+    /// dialogue in a state handled by the route
+    async fn route(&self, ctx: &Context, message: &Message) -> Option<CommandOutcome>;
+}
+
+pub(crate) struct StateRoute<S> {
+    pub(crate) matches: fn(&S) -> bool,
+    pub(crate) handler: DialogueHandlerFunc<S>,
+}
+
+#[async_trait]
+impl<S: DialogueState> DialogueRoute for StateRoute<S> {
+    async fn route(&self, ctx: &Context, message: &Message) -> Option<CommandOutcome> {
+        let dialogue = ctx.dialogue::<S>(message);
+        let state = match dialogue.get().await {
+            Ok(state) => state?,
+            Err(e) => {
+                warn!("failed to get the state of dialogue {}: {e}", S::NAME);
+                return None;
+            },
+        };
+
+        if (self.matches)(&state) {
+            Some((self.handler)(
+                ctx.clone(),
+                message.clone(),
+                dialogue,
+                state,
+            ))
+        } else {
+            None
+        }
+    }
+}
@@ -1,83 +1,1263 @@
-use super::types::{CommandTypes, TelegramCommand};
+use super::{
+    callback_router::CallbackRouter,
+    cooldowns::Cooldowns,
+    dialogue::{DialogueHandlerFunc, DialogueRoute, DialogueState, StateRoute},
+    help::{help_options, Help, HELP_COMMAND},
+    types::{
+        CallbackQueryHandlerFunc,
+        ChosenInlineResultHandlerFunc,
+        CommandError,
+        CommandErrorHandlerFunc,
+        CommandOptions,
+        HookDecision,
+        InlineAnswer,
+        InlineAnswerHandlerFunc,
+        InlineQueryHandlerFunc,
+        Middleware,
+        TelegramCommand,
+        TopicScope,
+    },
+};
 use crate::{
-    client::Context,
-    model::{Message, MessageContent, MessageEntity, Update, UpdateContent},
+    api::{
+        types::{
+            AnswerInlineQuery,
+            DeleteMyCommands,
+            InlineQueryResult,
+            SetMyCommands,
+            MAX_BOT_COMMANDS,
+            MAX_INLINE_QUERY_RESULTS,
+        },
+        API,
+    },
+    client::{panic_message, spawn_handler, Context, FutureOutcome},
+    model::{
+        BotCommand,
+        BotCommandScope,
+        CallbackQuery,
+        InlineQuery,
+        Message,
+        MessageContent,
+        MessageEntity,
+        ParseMode,
+        Update,
+        UpdateContent,
+    },
+    utils::result::{Result, TelegramError},
+};
+use futures::FutureExt;
+use log::{debug, error, warn};
+use parking_lot::RwLock;
+use std::{collections::HashMap, panic::AssertUnwindSafe, sync::Arc, time::Instant};
+use tokio::{
+    sync::{Notify, Semaphore},
+    task::JoinHandle,
 };
-use log::{debug, warn};
 
 /// A utility for easily managing commands.
 ///
 /// Refer to the [module-level documentation](index.html) for more detail
 pub struct Framework {
-    commands: Vec<TelegramCommand>,
+    command_set: RwLock<CommandSet>,
+    commands_changed: Notify,
     bot_name: String,
+    prefix: String,
+    case_insensitive: bool,
+    auto_prioritize: bool,
+    match_captions: bool,
+    match_edited_messages: bool,
+    hooks: CommandHooks,
+    inline_query_handlers: Vec<(Option<String>, InlineQueryHandlerFunc)>,
+    inline_answer_handlers: Vec<(Option<String>, InlineAnswerHandlerFunc)>,
+    inline_cache_time: Option<i64>,
+    inline_is_personal: Option<bool>,
+    chosen_inline_result_handlers: Vec<ChosenInlineResultHandlerFunc>,
+    callback_router: CallbackRouter,
+    unknown_command: Option<TelegramCommand>,
+    dialogue_routes: Vec<Arc<dyn DialogueRoute>>,
+}
+
+/// The registered commands together with the settings that change with them,
+/// kept behind a lock so commands can be added and removed while the client
+/// is running
+#[derive(Default)]
+struct CommandSet {
+    commands: Vec<TelegramCommand>,
+    topic_scopes: HashMap<String, TopicScope>,
+    command_scopes: HashMap<String, Vec<BotCommandScope>>,
+    retired_scopes: Vec<BotCommandScope>,
+    retired_languages: Vec<String>,
+    help_parse_mode: Option<ParseMode>,
+    /// Increases every time a command is added or removed
+    version: u64,
+    /// The version of the commands last registered with telegram
+    synced_version: Option<u64>,
+}
+
+impl CommandSet {
+    fn topic_scope<'a>(&'a self, options: &'a CommandOptions) -> &'a TopicScope {
+        self.topic_scopes
+            .get(&*options.name)
+            .unwrap_or(&options.topics)
+    }
+
+    /// Gets the parse mode of the help command if the command is the help
+    /// command added by [`Framework::add_help_command`]
+    fn help_parse_mode(&self, command: &TelegramCommand) -> Option<&ParseMode> {
+        self.help_parse_mode.as_ref().filter(|_| {
+            command.options.name == HELP_COMMAND
+                && self.commands.iter().any(|c| std::ptr::eq(c, command))
+        })
+    }
 }
 
 impl Framework {
     /// Creates a new framework instance given the bot name
     pub fn new(bot_name: &str) -> Self {
         Self {
-            commands: Vec::new(),
+            command_set: RwLock::new(CommandSet::default()),
+            commands_changed: Notify::new(),
             bot_name: bot_name.to_owned(),
+            prefix: "/".to_owned(),
+            case_insensitive: false,
+            auto_prioritize: false,
+            match_captions: true,
+            match_edited_messages: false,
+            hooks: CommandHooks {
+                middleware: Vec::new(),
+                error_handler: log_command_error,
+                refusal: None,
+                cooldowns: Arc::new(Cooldowns::default()),
+                cooldown_message: None,
+            },
+            inline_query_handlers: Vec::new(),
+            inline_answer_handlers: Vec::new(),
+            inline_cache_time: None,
+            inline_is_personal: None,
+            chosen_inline_result_handlers: Vec::new(),
+            callback_router: CallbackRouter::new(),
+            unknown_command: None,
+            dialogue_routes: Vec::new(),
         }
     }
 
-    fn match_command(&self, message: &Message, name: &str) -> bool {
-        if let MessageContent::Text {
-            entities,
-            content,
-        } = &message.content
+    /// Gets the command invoked by the text or caption of the message,
+    /// without the prefix and bot name, together with the text following it
+    /// and whether the message opens with the command.
+    ///
+    /// With the default `/` prefix the command is the first `bot_command`
+    /// entity of the message, the same one telegram clients highlight.
+    fn parse_command(&self, message: &Message) -> Option<(String, String, bool)> {
+        if !self.match_captions && !matches!(message.content, MessageContent::Text { .. }) {
+            return None;
+        }
+        let (text, entities) = message.text_with_entities()?;
+
+        let (command, rest, opens_message) = if self.prefix == "/" {
+            let block = entities.iter().find_map(|e| match e {
+                MessageEntity::BotCommand(t) => Some(t),
+                _ => None,
+            })?;
+            let opens_message = block.get_str_before(text).chars().all(is_invisible);
+            (
+                block.get_str(text),
+                block.get_str_after(text),
+                opens_message,
+            )
+        } else {
+            let text = text.trim_start_matches(is_invisible);
+            let end = text.find(char::is_whitespace).unwrap_or(text.len());
+            (&text[..end], &text[end..], true)
+        };
+
+        let command = command.strip_prefix(self.prefix.as_str())?;
+        let command = match command.split_once('@') {
+            Some((command, bot_name)) if bot_name.eq_ignore_ascii_case(&self.bot_name) => command,
+            Some(_) => return None,
+            None => command,
+        };
+
+        Some((command.to_owned(), rest.to_owned(), opens_message))
+    }
+
+    fn matches_command(&self, options: &CommandOptions, invoked: &str) -> bool {
+        options.names().any(|name| {
+            if self.case_insensitive {
+                name.to_lowercase() == invoked.to_lowercase()
+            } else {
+                name == invoked
+            }
+        })
+    }
+
+    /// Finds the command or subcommand invoked by the invoked name and the
+    /// text following it, returning it with its full name and the text
+    /// following the subcommand
+    fn resolve_command<'a>(
+        &self,
+        command: &'a TelegramCommand,
+        invoked: &str,
+        rest: &str,
+    ) -> Option<(&'a TelegramCommand, String, String)> {
+        if self.matches_command(&command.options, invoked) {
+            return Some(self.resolve_subcommand(command, command.options.name.to_string(), rest));
+        }
+
+        // the flattened name of a subcommand, like config_get
+        let parent = format!("{}_", command.options.name);
+        let sub_invoked = if self.case_insensitive {
+            invoked
+                .get(..parent.len())
+                .filter(|p| p.to_lowercase() == parent.to_lowercase())
+                .map(|_| &invoked[parent.len()..])
+        } else {
+            invoked.strip_prefix(parent.as_str())
+        }?;
+
+        command.options.subcommands.iter().find_map(|sub| {
+            self.resolve_command(sub, sub_invoked, rest)
+                .map(|(c, name, rest)| (c, format!("{} {name}", command.options.name), rest))
+        })
+    }
+
+    /// Finds the subcommand of the command invoked by the first word of the
+    /// text, falling back to the command itself
+    fn resolve_subcommand<'a>(
+        &self,
+        command: &'a TelegramCommand,
+        name: String,
+        rest: &str,
+    ) -> (&'a TelegramCommand, String, String) {
+        let trimmed = rest.trim_start();
+        let (word, remaining) =
+            trimmed.split_at(trimmed.find(char::is_whitespace).unwrap_or(trimmed.len()));
+
+        match command
+            .options
+            .subcommands
+            .iter()
+            .find(|sub| !word.is_empty() && self.matches_command(&sub.options, word))
         {
-            for entity in entities {
-                if let MessageEntity::BotCommand(ref t) = entity {
-                    let t = t.get_text(content);
-                    return t == format!("/{name}") || t == format!("/{}@{}", name, &self.bot_name);
+            Some(sub) => {
+                self.resolve_subcommand(sub, format!("{name} {}", sub.options.name), remaining)
+            },
+            None => (command, name, rest.to_owned()),
+        }
+    }
+
+    /// Calls the commands invoked by the message, or the handler of the
+    /// dialogue it continues if it doesn't invoke any. Edited messages only
+    /// invoke commands.
+    #[allow(clippy::needless_pass_by_value)]
+    fn fire_message_commands(
+        &self,
+        context: Context,
+        message: Message,
+        edited: bool,
+        limit: Option<&Arc<Semaphore>>,
+    ) {
+        let context = context.with_edited(edited);
+        let Some((invoked, rest, opens_message)) = self.parse_command(&message) else {
+            if !edited {
+                self.spawn_dialogue_handler(context, message, limit);
+            }
+            return;
+        };
+
+        let set = self.command_set.read();
+        let mut known = false;
+        for command in &set.commands {
+            let Some((resolved, command_name, rest)) =
+                self.resolve_command(command, &invoked, &rest)
+            else {
+                continue;
+            };
+            known = true;
+
+            if !set.topic_scope(&command.options).allows(&message)
+                || !(std::ptr::eq(resolved, command) || resolved.options.topics.allows(&message))
+            {
+                debug!("not calling command {command_name} outside of its forum topics");
+                continue;
+            }
+
+            self.spawn_command(
+                &set,
+                resolved,
+                &context,
+                &message,
+                command_name,
+                rest,
+                limit,
+            );
+        }
+
+        match &self.unknown_command {
+            _ if known => (),
+            Some(unknown) if opens_message && !invoked.is_empty() => {
+                debug!("command {invoked} is unknown");
+                self.spawn_command(&set, unknown, &context, &message, invoked, rest, limit);
+            },
+            _ if edited => (),
+            _ => self.spawn_dialogue_handler(context, message, limit),
+        }
+    }
+
+    /// Calls the handler of the first dialogue the message continues, if it
+    /// continues any
+    fn spawn_dialogue_handler(
+        &self,
+        context: Context,
+        message: Message,
+        limit: Option<&Arc<Semaphore>>,
+    ) {
+        if self.dialogue_routes.is_empty() {
+            return;
+        }
+
+        let routes = self.dialogue_routes.clone();
+        let error_handler = self.hooks.error_handler;
+        spawn_handler(limit, async move {
+            for route in routes {
+                let Some(handler) = route.route(&context, &message).await else {
+                    continue;
+                };
+
+                debug!(
+                    "calling dialogue handler for message {}",
+                    message.message_id
+                );
+                if let Err(e) = handler.await {
+                    error_handler(context, message, e).await;
                 }
+                return;
             }
+        });
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn spawn_command(
+        &self,
+        set: &CommandSet,
+        command: &TelegramCommand,
+        context: &Context,
+        message: &Message,
+        command_name: String,
+        rest: String,
+        limit: Option<&Arc<Semaphore>>,
+    ) {
+        let command = match set.help_parse_mode(command) {
+            Some(parse_mode) => Help {
+                commands: set.commands.clone(),
+                prefix: self.prefix.clone(),
+                case_insensitive: self.case_insensitive,
+                parse_mode: parse_mode.clone(),
+            }
+            .into_command(command.options.clone()),
+            None => command.clone(),
+        };
+        let ctx = context
+            .clone()
+            .with_command_text(rest)
+            .with_command_name(command_name.clone());
+        let msg = message.clone();
+        let hooks = self.hooks.clone();
+        debug!("calling command {}", &command_name);
+
+        spawn_handler(limit, async move {
+            hooks.run_command(&command, ctx, msg, &command_name).await;
+        });
+    }
+
+    /// add a command to the registered commands
+    ///
+    /// Commands can also be added while the client is running, after which
+    /// the client registers the changed commands with telegram.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name or an alias of the command is already used by
+    /// another registered command, see [`try_add_command`] for a non-panicking
+    /// version.
+    ///
+    /// [`try_add_command`]: #method.try_add_command
+    pub fn add_command(&self, command: &TelegramCommand) {
+        if let Err(e) = self.try_add_command(command) {
+            panic!("{e}");
         }
-        false
     }
 
+    /// add a command to the registered commands, returning an
+    /// [`TelegramError::InvalidArgument`] if the name or an alias of the
+    /// command is already used by another registered command
+    ///
+    /// [`TelegramError::InvalidArgument`]: ../enum.TelegramError.html#variant.InvalidArgument
+    pub fn try_add_command(&self, command: &TelegramCommand) -> Result<()> {
+        let mut set = self.command_set.write();
+        for existing in &set.commands {
+            let taken = command
+                .options
+                .names()
+                .find(|n| existing.options.names().any(|e| e == *n));
+            if let Some(name) = taken {
+                let kind = if name == command.options.name {
+                    "name"
+                } else {
+                    "alias"
+                };
+                return Err(TelegramError::InvalidArgument(format!(
+                    "the {kind} {name} of the {} command is already used by the {} command",
+                    command.options.name, existing.options.name
+                ))
+                .into());
+            }
+        }
+
+        set.commands.push(command.clone());
+        set.version += 1;
+        self.commands_changed.notify_one();
+        Ok(())
+    }
+
+    /// Removes the command with the given name, returning it if it was
+    /// registered.
+    ///
+    /// Commands can also be removed while the client is running, after which
+    /// the client registers the changed commands with telegram. The command
+    /// lists of the scopes and languages that only the removed command was
+    /// listed in are deleted then too.
+    pub fn remove_command(&self, name: &str) -> Option<TelegramCommand> {
+        let mut set = self.command_set.write();
+        let index = set.commands.iter().position(|c| c.options.name == name)?;
+        let command = set.commands.remove(index);
+
+        if name == HELP_COMMAND {
+            set.help_parse_mode = None;
+        }
+        set.topic_scopes.remove(name);
+        for scope in set.command_scopes.remove(name).unwrap_or_default() {
+            if !set.retired_scopes.contains(&scope) {
+                set.retired_scopes.push(scope);
+            }
+        }
+        for language in command_languages(&command) {
+            if !set.retired_languages.iter().any(|l| l == language) {
+                set.retired_languages.push(language.to_owned());
+            }
+        }
+        set.version += 1;
+        self.commands_changed.notify_one();
+
+        Some(command)
+    }
+
+    /// Adds a `help` command, which replies with the commands the user is
+    /// allowed to use and their descriptions in the language of the user,
+    /// like `/config set <key> <value> - changes a setting`. Commands of
+    /// which a check refuses the user, like admin-only commands, are left
+    /// out.
+    ///
+    /// `/help <command>` replies with the usage, aliases and subcommands of
+    /// a single command instead. The usage of commands made using the
+    /// `command` macro defaults to their arguments.
+    ///
+    /// The reply is sent with the given parse mode, with the text escaped
+    /// accordingly. The help command can be removed again using
+    /// [`remove_command`](#method.remove_command).
+    ///
+    /// # Panics
+    ///
+    /// Panics if a registered command already uses `help` as its name or
+    /// alias.
+    pub fn add_help_command(&mut self, parse_mode: ParseMode) -> &mut Self {
+        // the handler is replaced when invoked, so it lists the commands
+        // registered at that time
+        self.add_command(&TelegramCommand::new(help_options(), |_, _| async {
+            Ok(())
+        }));
+        self.command_set.get_mut().help_parse_mode = Some(parse_mode);
+        self
+    }
+
+    /// Adds middleware with hooks to run before and after every command,
+    /// which run in the order they were added
+    pub fn add_middleware(&mut self, middleware: impl Middleware + 'static) -> &mut Self {
+        self.hooks.middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets the command called when a message starts with a command for this
+    /// bot that isn't registered, for example to suggest similar commands.
+    /// The name of the unknown command is available through
+    /// [`Context::command_name`].
+    ///
+    /// The command is made using the `command` macro like other commands,
+    /// but isn't registered with telegram.
+    ///
+    /// [`Context::command_name`]: ../client/struct.Context.html#method.command_name
+    pub fn set_unknown_command_handler(&mut self, command: &TelegramCommand) -> &mut Self {
+        self.unknown_command = Some(command.clone());
+        self
+    }
+
+    /// Sets the function called when a command returns an error or panics,
+    /// for example to tell the user something went wrong. By default the
+    /// error is passed to the error handler of the client, see
+    /// [`ClientBuilder::set_error_handler`], or logged when it has none.
+    ///
+    /// [`ClientBuilder::set_error_handler`]: ../client/struct.ClientBuilder.html#method.set_error_handler
+    pub fn set_error_handler(&mut self, handler: CommandErrorHandlerFunc) -> &mut Self {
+        self.hooks.error_handler = handler;
+        self
+    }
+
+    /// Sets the message to reply with when a user isn't allowed to use a
+    /// command because one of its checks failed. By default nothing is sent.
     #[allow(clippy::needless_pass_by_value)]
-    fn fire_message_commands(&self, context: Context, message: Message) {
-        for command in &self.commands {
-            match command.command.clone() {
-                CommandTypes::Default(c) if self.match_command(&message, command.options.name) => {
-                    let ctx = context.clone();
-                    let msg = message.clone();
-                    let command_name = command.options.name;
-                    debug!("calling command {}", &command_name);
-
-                    tokio::spawn(async move {
-                        let res = c(ctx, msg).await;
-                        if res.is_err() {
-                            warn!(
-                                "command {} returned error: {}",
-                                &command_name,
-                                res.unwrap_err().0
-                            );
-                        }
-                    });
-                },
-                CommandTypes::Default(_) => (),
+    pub fn set_refusal_message(&mut self, text: impl ToString) -> &mut Self {
+        self.hooks.refusal = Some(text.to_string());
+        self
+    }
+
+    /// Sets the message to reply with when a command is used again before
+    /// its cooldown ran out, in which `{seconds}` is replaced by the amount
+    /// of seconds left, like `try again in {seconds}s`. By default nothing is
+    /// sent.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_cooldown_message(&mut self, text: impl ToString) -> &mut Self {
+        self.hooks.cooldown_message = Some(text.to_string());
+        self
+    }
+
+    // public only for testing purposes
+    #[doc(hidden)]
+    pub fn cooldown_count(&self) -> usize {
+        self.hooks.cooldowns.len()
+    }
+
+    /// Moves the cooldowns running in a group to the supergroup it migrated to
+    pub(crate) fn migrate_chat(&self, old_id: i64, new_id: i64) {
+        self.hooks.cooldowns.migrate_chat(old_id, new_id);
+    }
+
+    /// Adds a handler for all inline queries sent to the bot, which can
+    /// answer them using [`Context::answer_inline`]
+    ///
+    /// [`Context::answer_inline`]: ../client/struct.Context.html#method.answer_inline
+    pub fn add_inline_query_handler(&mut self, handler: InlineQueryHandlerFunc) -> &mut Self {
+        self.inline_query_handlers.push((None, handler));
+        self
+    }
+
+    /// Adds a handler for the inline queries of which the text starts with
+    /// the given prefix, like `@bot gif cats` for the prefix `gif`. The
+    /// handler receives the query as sent, including the prefix.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_prefixed_inline_query_handler(
+        &mut self,
+        prefix: impl ToString,
+        handler: InlineQueryHandlerFunc,
+    ) -> &mut Self {
+        self.inline_query_handlers
+            .push((Some(prefix.to_string()), handler));
+        self
+    }
+
+    /// Adds a handler for the messages sent in a dialogue with the state `S`
+    /// while `matches` returns true for its state, like `|s| matches!(s,
+    /// Setup::AskName)`. See [`Context::dialogue`] for starting a dialogue.
+    ///
+    /// Messages invoking a command are never routed to a dialogue, and a
+    /// message is only routed to the first handler added for its state.
+    ///
+    /// [`Context::dialogue`]: ../client/struct.Context.html#method.dialogue
+    pub fn add_dialogue_handler<S: DialogueState>(
+        &mut self,
+        matches: fn(&S) -> bool,
+        handler: DialogueHandlerFunc<S>,
+    ) -> &mut Self {
+        self.dialogue_routes.push(Arc::new(StateRoute {
+            matches,
+            handler,
+        }));
+        self
+    }
+
+    /// Adds a handler producing the results for all inline queries sent to
+    /// the bot, with which the framework then answers the queries. See
+    /// [`InlineAnswer`] for how the results are paginated.
+    ///
+    /// An inline query is only answered by the handler with the longest
+    /// matching prefix, this handler being used for the queries matching no
+    /// prefix.
+    ///
+    /// [`InlineAnswer`]: types/struct.InlineAnswer.html
+    pub fn add_inline_answer_handler(&mut self, handler: InlineAnswerHandlerFunc) -> &mut Self {
+        self.inline_answer_handlers.push((None, handler));
+        self
+    }
+
+    /// Adds a handler producing the results for the inline queries of which
+    /// the text starts with the given prefix, like `@bot gif cats` for the
+    /// prefix `gif`. The handler receives the query as sent, including the
+    /// prefix.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn add_prefixed_inline_answer_handler(
+        &mut self,
+        prefix: impl ToString,
+        handler: InlineAnswerHandlerFunc,
+    ) -> &mut Self {
+        self.inline_answer_handlers
+            .push((Some(prefix.to_string()), handler));
+        self
+    }
+
+    /// Sets the maximum amount of time in seconds that telegram may cache the
+    /// results of the inline queries answered by the framework, telegram
+    /// defaults to 300
+    pub fn set_inline_cache_time(&mut self, seconds: i64) -> &mut Self {
+        self.inline_cache_time = Some(seconds);
+        self
+    }
+
+    /// Sets whether telegram may only return the cached results of the inline
+    /// queries answered by the framework to the user that sent the query
+    pub fn set_inline_personal(&mut self, is_personal: bool) -> &mut Self {
+        self.inline_is_personal = Some(is_personal);
+        self
+    }
+
+    /// Adds a handler for the inline results chosen by users.
+    ///
+    /// **Note:** telegram only sends these after enabling inline feedback for
+    /// the bot with [@BotFather].
+    ///
+    /// [@BotFather]: https://t.me/botfather
+    pub fn add_chosen_inline_result_handler(
+        &mut self,
+        handler: ChosenInlineResultHandlerFunc,
+    ) -> &mut Self {
+        self.chosen_inline_result_handlers.push(handler);
+        self
+    }
+
+    /// Adds a handler for the callback queries of which the data starts with
+    /// the given prefix, like `vote:` for the data `vote:yes`, which receives
+    /// the data following the prefix. It can answer the query using
+    /// [`Context::answer_callback`].
+    ///
+    /// The queries are routed like a [`CallbackRouter`] does.
+    ///
+    /// [`Context::answer_callback`]: ../client/struct.Context.html#method.answer_callback
+    /// [`CallbackRouter`]: struct.CallbackRouter.html
+    pub fn add_callback_query_handler(
+        &mut self,
+        prefix: impl ToString,
+        handler: CallbackQueryHandlerFunc,
+    ) -> &mut Self {
+        self.callback_router.on(prefix, handler);
+        self
+    }
+
+    /// Whether any callback query handlers are registered
+    pub(crate) fn handles_callback_queries(&self) -> bool {
+        !self.callback_router.is_empty()
+    }
+
+    /// Whether any inline query handlers are registered
+    pub(crate) fn handles_inline_queries(&self) -> bool {
+        !self.inline_query_handlers.is_empty() || !self.inline_answer_handlers.is_empty()
+    }
+
+    /// Whether commands are also matched in edited messages
+    pub(crate) fn handles_edited_messages(&self) -> bool {
+        self.match_edited_messages
+    }
+
+    /// Whether any chosen inline result handlers are registered
+    pub(crate) fn handles_chosen_inline_results(&self) -> bool {
+        !self.chosen_inline_result_handlers.is_empty()
+    }
+
+    /// get all currently registered commands
+    pub fn get_commands(&self) -> Vec<TelegramCommand> {
+        self.command_set.read().commands.clone()
+    }
+
+    /// Sets the prefix commands have to start with, defaults to `/`.
+    ///
+    /// Telegram only recognizes commands starting with `/`, so commands using
+    /// a different prefix are matched on the start of the message text
+    /// instead of on its bot command entity.
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_prefix(&mut self, prefix: impl ToString) -> &mut Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    /// Sets whether command names and aliases should be matched
+    /// case-insensitively. Disabled by default.
+    pub fn set_case_insensitive(&mut self, case_insensitive: bool) -> &mut Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    /// Sets whether only the commands with the highest priority should be
+    /// registered with telegram when more commands are registered than
+    /// telegram allows, instead of returning an error. Disabled by default.
+    pub fn set_auto_prioritize(&mut self, auto_prioritize: bool) -> &mut Self {
+        self.auto_prioritize = auto_prioritize;
+        self
+    }
+
+    /// Sets whether commands in the captions of media messages, like a photo
+    /// sent with `/caption` as caption, should be called. Enabled by default.
+    pub fn set_match_captions(&mut self, match_captions: bool) -> &mut Self {
+        self.match_captions = match_captions;
+        self
+    }
+
+    /// Sets whether commands should be called again when the message invoking
+    /// them is edited, for example to fix a typo in the command. Disabled by
+    /// default, as commands run once more for every edit of their message.
+    ///
+    /// Use [`Context::is_edited`] in a command to tell whether it was invoked
+    /// by an edit.
+    ///
+    /// [`Context::is_edited`]: ../client/struct.Context.html#method.is_edited
+    pub fn set_match_edited_messages(&mut self, match_edited_messages: bool) -> &mut Self {
+        self.match_edited_messages = match_edited_messages;
+        self
+    }
+
+    /// Restricts the command with the given name to the forum topics with the
+    /// given message thread ids, where the "General" topic has thread id 1.
+    /// Uses of the command elsewhere are ignored.
+    ///
+    /// This overrides the `topics` option of the command macro.
+    pub fn restrict_command_to_topics(&mut self, name: &str, topics: Vec<i64>) -> &mut Self {
+        self.set_command_topic_scope(name, TopicScope::Topics(topics.into()))
+    }
+
+    /// Sets the forum topics in which the command with the given name can be
+    /// used, overriding the `topics` option of the command macro.
+    pub fn set_command_topic_scope(&mut self, name: &str, scope: TopicScope) -> &mut Self {
+        self.command_set
+            .get_mut()
+            .topic_scopes
+            .insert(name.to_owned(), scope);
+        self
+    }
+
+    /// Only lists the command with the given name in the given scopes when
+    /// the commands are registered with telegram, for example to only show
+    /// admin commands to the administrators of a chat.
+    ///
+    /// Commands without scopes are listed in every scope. Telegram shows a
+    /// user the list of the most specific scope that applies to them, so a
+    /// command is not shown in a scope that is overruled by another scope.
+    pub fn set_command_scopes(&mut self, name: &str, scopes: Vec<BotCommandScope>) -> &mut Self {
+        self.command_set
+            .get_mut()
+            .command_scopes
+            .insert(name.to_owned(), scopes);
+        self
+    }
+
+    /// get the scopes the commands have to be registered in with telegram,
+    /// which are the default scope and every scope set for a command or for a
+    /// removed command
+    pub fn get_bot_command_scopes(&self) -> Vec<BotCommandScope> {
+        let set = self.command_set.read();
+        let mut scopes = vec![BotCommandScope::Default];
+        for scope in set
+            .command_scopes
+            .values()
+            .flatten()
+            .chain(&set.retired_scopes)
+        {
+            if !scopes.contains(scope) {
+                scopes.push(scope.clone());
             }
         }
+        scopes
     }
 
-    /// add a command to the registered commands
-    pub fn add_command(&mut self, command: &TelegramCommand) {
-        self.commands.push(command.clone());
+    /// get the languages the commands or removed commands have localized
+    /// descriptions for
+    pub fn get_bot_command_languages(&self) -> Vec<String> {
+        let set = self.command_set.read();
+        let mut languages: Vec<String> = set
+            .commands
+            .iter()
+            .flat_map(|c| command_languages(c))
+            .chain(set.retired_languages.iter().map(String::as_str))
+            .map(str::to_owned)
+            .collect();
+        languages.sort_unstable();
+        languages.dedup();
+        languages
+    }
+
+    /// Gets a help text listing the commands and their subcommands with
+    /// their descriptions in the given language, one command per line like
+    /// `/config get - shows a setting`.
+    pub fn get_help_text(&self, language_code: Option<&str>) -> String {
+        fn push_lines(
+            lines: &mut Vec<String>,
+            prefix: &str,
+            command: &TelegramCommand,
+            language_code: Option<&str>,
+        ) {
+            let bot_command = command.get_localized_bot_command(language_code);
+            lines.push(format!(
+                "{prefix}{} - {}",
+                bot_command.command, bot_command.description
+            ));
+            for sub in command.options.subcommands.iter() {
+                let prefix = format!("{prefix}{} ", bot_command.command);
+                push_lines(lines, &prefix, sub, language_code);
+            }
+        }
+
+        let mut lines = Vec::new();
+        for command in &self.command_set.read().commands {
+            push_lines(&mut lines, &self.prefix, command, language_code);
+        }
+        lines.join("\n")
     }
 
-    /// get all registered commands
-    pub fn get_commands(&self) -> &Vec<TelegramCommand> {
-        &self.commands
+    /// get the commands to register with telegram.
+    ///
+    /// Subcommands are listed after their command with their names joined by
+    /// underscores, like `config_get`, as telegram doesn't allow spaces in
+    /// command names. Users can invoke them using either name.
+    ///
+    /// If more than 100 commands are registered, this returns an error
+    /// listing the commands over the limit, unless auto prioritization is
+    /// enabled, in which case the 100 commands with the highest `priority`
+    /// are returned and the omitted ones are logged.
+    pub fn get_bot_commands(&self) -> Result<Vec<BotCommand>> {
+        self.get_scoped_bot_commands(&BotCommandScope::Default, None)
+    }
+
+    /// get the commands to register with telegram for the given scope, with
+    /// their descriptions in the given language.
+    ///
+    /// Like with [`get_bot_commands`], this returns an error when more than
+    /// 100 commands are listed in the scope and auto prioritization is
+    /// disabled.
+    ///
+    /// [`get_bot_commands`]: #method.get_bot_commands
+    pub fn get_scoped_bot_commands(
+        &self,
+        scope: &BotCommandScope,
+        language_code: Option<&str>,
+    ) -> Result<Vec<BotCommand>> {
+        let set = self.command_set.read();
+        let mut commands: Vec<(i64, BotCommand)> = set
+            .commands
+            .iter()
+            .filter(|c| {
+                set.command_scopes
+                    .get(&*c.options.name)
+                    .map_or(true, |scopes| scopes.contains(scope))
+            })
+            .flat_map(|c| {
+                c.get_flattened_bot_commands(language_code)
+                    .into_iter()
+                    .map(|b| (c.options.priority, b))
+            })
+            .collect();
+        drop(set);
+
+        if !self.auto_prioritize || commands.len() <= MAX_BOT_COMMANDS {
+            let data: SetMyCommands = commands
+                .into_iter()
+                .map(|(_, c)| c)
+                .collect::<Vec<_>>()
+                .into();
+            data.validate()?;
+            return Ok(data.commands);
+        }
+
+        commands.sort_by_key(|(priority, _)| std::cmp::Reverse(*priority));
+        let omitted: Vec<String> = commands
+            .split_off(MAX_BOT_COMMANDS)
+            .into_iter()
+            .map(|(_, c)| c.command)
+            .collect();
+
+        warn!(
+            "more than {} commands registered, not registering these with telegram: {}",
+            MAX_BOT_COMMANDS,
+            omitted.join(", ")
+        );
+        Ok(commands.into_iter().map(|(_, c)| c).collect())
+    }
+
+    /// Registers the commands with telegram in each of the given scopes, once
+    /// with their default descriptions and once for every language they have
+    /// localized descriptions for. The commands of a scope and language
+    /// without any commands are deleted instead.
+    ///
+    /// Use [`sync_all_commands`] to register them in every scope from
+    /// [`get_bot_command_scopes`].
+    ///
+    /// [`sync_all_commands`]: #method.sync_all_commands
+    /// [`get_bot_command_scopes`]: #method.get_bot_command_scopes
+    pub async fn sync_commands(
+        &self,
+        api: &(dyn API + Send),
+        scopes: &[BotCommandScope],
+    ) -> Result<()> {
+        let languages = self.get_bot_command_languages();
+
+        for scope in scopes {
+            for language_code in std::iter::once(None).chain(languages.iter().map(|l| Some(&**l))) {
+                let commands = self.get_scoped_bot_commands(scope, language_code)?;
+                debug!(
+                    "registering {} commands for scope {scope:?} and language {}",
+                    commands.len(),
+                    language_code.unwrap_or("default")
+                );
+
+                if commands.is_empty() {
+                    let mut data = DeleteMyCommands::new();
+                    data.set_scope(scope.clone());
+                    if let Some(lang) = language_code {
+                        data.set_language_code(lang);
+                    }
+                    api.delete_my_commands(data).await?;
+                } else {
+                    let mut data = SetMyCommands::new(commands);
+                    data.set_scope(scope.clone());
+                    if let Some(lang) = language_code {
+                        data.set_language_code(lang);
+                    }
+                    api.set_my_commands(data).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Registers the commands with telegram in every scope from
+    /// [`get_bot_command_scopes`] like [`sync_commands`] does, after which the
+    /// scopes and languages of removed commands are no longer synced.
+    ///
+    /// The client calls this when it starts and every time commands are added
+    /// or removed while it is running.
+    ///
+    /// [`get_bot_command_scopes`]: #method.get_bot_command_scopes
+    /// [`sync_commands`]: #method.sync_commands
+    pub async fn sync_all_commands(&self, api: &(dyn API + Send)) -> Result<()> {
+        let version = self.command_set.read().version;
+        self.sync_commands(api, &self.get_bot_command_scopes())
+            .await?;
+
+        let mut set = self.command_set.write();
+        // commands that changed during the sync are synced again afterwards
+        if set.version == version {
+            set.retired_scopes.clear();
+            set.retired_languages.clear();
+            set.synced_version = Some(version);
+        }
+        Ok(())
+    }
+
+    /// Waits until a command is added or removed
+    pub(crate) async fn commands_changed(&self) {
+        self.commands_changed.notified().await;
+    }
+
+    /// Whether the current commands are registered with telegram
+    pub(crate) fn commands_synced(&self) -> bool {
+        let set = self.command_set.read();
+        set.synced_version == Some(set.version)
     }
 
     /// fires off all commands matching the content in the update
     pub fn fire_commands(&self, context: Context, update: Update) {
-        if let UpdateContent::Message(c) = update.content {
-            self.fire_message_commands(context, c);
+        self.fire_commands_limited(context, update, None);
+    }
+
+    pub(crate) fn fire_commands_limited(
+        &self,
+        context: Context,
+        update: Update,
+        limit: Option<&Arc<Semaphore>>,
+    ) {
+        match update.content {
+            UpdateContent::Message(c) => self.fire_message_commands(context, c, false, limit),
+            UpdateContent::EditedMessage(c) if self.match_edited_messages => {
+                self.fire_message_commands(context, c, true, limit);
+            },
+            UpdateContent::InlineQuery(q) => self.fire_inline_query_handlers(&context, &q, limit),
+            UpdateContent::CallbackQuery(q) => {
+                self.fire_callback_query_handler(context, q, limit);
+            },
+            UpdateContent::ChosenInlineResult(r) => {
+                for handler in &self.chosen_inline_result_handlers {
+                    spawn_handler(limit, handler(context.clone(), r.clone()));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    /// Calls the handler with the longest prefix matching the data of the
+    /// callback query, returning its handle if there is one
+    pub(crate) fn fire_callback_query_handler(
+        &self,
+        context: Context,
+        query: CallbackQuery,
+        limit: Option<&Arc<Semaphore>>,
+    ) -> Option<JoinHandle<()>> {
+        self.callback_router.fire(context, query, limit)
+    }
+
+    fn fire_inline_query_handlers(
+        &self,
+        context: &Context,
+        query: &InlineQuery,
+        limit: Option<&Arc<Semaphore>>,
+    ) {
+        for (prefix, handler) in &self.inline_query_handlers {
+            if inline_prefix_matches(prefix.as_deref(), &query.query) {
+                debug!("calling inline query handler for query {:?}", query.query);
+                spawn_handler(limit, handler(context.clone(), query.clone()));
+            }
+        }
+
+        let answer_handler = self
+            .inline_answer_handlers
+            .iter()
+            .filter(|(prefix, _)| inline_prefix_matches(prefix.as_deref(), &query.query))
+            .max_by_key(|(prefix, _)| prefix.as_ref().map(String::len));
+        if let Some((_, handler)) = answer_handler {
+            debug!("calling inline answer handler for query {:?}", query.query);
+            let mut data = AnswerInlineQuery::new(&query.id, Vec::new());
+            data.cache_time = self.inline_cache_time;
+            data.is_personal = self.inline_is_personal;
+            spawn_handler(
+                limit,
+                answer_inline_query(context.clone(), query.clone(), *handler, data),
+            );
         }
     }
 }
+
+/// Whether the character is invisible in telegram clients, like whitespace and
+/// the zero-width and text direction characters
+fn is_invisible(c: char) -> bool {
+    c.is_whitespace()
+        || matches!(
+            c,
+            '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2064}' | '\u{FEFF}'
+        )
+}
+
+/// Whether the text of an inline query starts with the prefix, followed by
+/// whitespace or nothing at all
+fn inline_prefix_matches(prefix: Option<&str>, query: &str) -> bool {
+    prefix.map_or(true, |prefix| {
+        query
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    })
+}
+
+/// Answers the inline query with the results of the handler, adding them to
+/// the given data
+async fn answer_inline_query(
+    ctx: Context,
+    query: InlineQuery,
+    handler: InlineAnswerHandlerFunc,
+    mut data: AnswerInlineQuery,
+) {
+    let answer = match handler(ctx.clone(), query.clone()).await {
+        Ok(answer) => answer,
+        Err(e) => {
+            warn!(
+                "failed to get the results for inline query {:?}: {}",
+                query.query, e.0
+            );
+            return;
+        },
+    };
+
+    let (results, next_offset) = paginate_inline_answer(answer, &query.offset);
+    data.results = results;
+    data.next_offset = next_offset;
+    if let Err(e) = ctx.api.answer_inline_query(data).await {
+        warn!("failed to answer inline query {:?}: {e}", query.query);
+    }
+}
+
+/// Gets the results to send for the given offset, together with the offset of
+/// the next page if there are more results
+fn paginate_inline_answer(
+    answer: InlineAnswer,
+    offset: &str,
+) -> (Vec<InlineQueryResult>, Option<String>) {
+    let InlineAnswer {
+        mut results,
+        next_offset,
+    } = answer;
+
+    if next_offset.is_some() {
+        if results.len() > MAX_INLINE_QUERY_RESULTS {
+            warn!(
+                "an inline answer contained {} results, only the first {MAX_INLINE_QUERY_RESULTS} \
+                 are sent",
+                results.len()
+            );
+            results.truncate(MAX_INLINE_QUERY_RESULTS);
+        }
+        return (results, next_offset);
+    }
+
+    let start = offset.parse::<usize>().unwrap_or(0).min(results.len());
+    let end = results.len().min(start + MAX_INLINE_QUERY_RESULTS);
+    let next_offset = (end < results.len()).then(|| end.to_string());
+    results.truncate(end);
+    results.drain(..start);
+    (results, next_offset)
+}
+
+/// The hooks of the framework ran around every command
+#[derive(Clone)]
+struct CommandHooks {
+    middleware: Vec<Arc<dyn Middleware>>,
+    error_handler: CommandErrorHandlerFunc,
+    refusal: Option<String>,
+    cooldowns: Arc<Cooldowns>,
+    cooldown_message: Option<String>,
+}
+
+impl CommandHooks {
+    /// Whether the checks of the command allow the message to invoke it,
+    /// sending the refusal message or calling the error handler otherwise
+    async fn allows(
+        &self,
+        command: &TelegramCommand,
+        ctx: &Context,
+        message: &Message,
+        name: &str,
+    ) -> bool {
+        for check in command.options.checks.iter() {
+            match check(ctx.clone(), message).await {
+                Ok(true) => continue,
+                Ok(false) => debug!("a check of command {name} refused the message"),
+                Err(e) => (self.error_handler)(ctx.clone(), message.clone(), e.into()).await,
+            }
+
+            if let Some(text) = &self.refusal {
+                if let Err(e) = ctx.reply(message, text).await {
+                    warn!("failed to send the refusal of command {name}: {e}");
+                }
+            }
+            return false;
+        }
+        true
+    }
+
+    /// Calls the command if its checks allow it, surrounded by the hooks of
+    /// the middleware
+    async fn run_command(
+        &self,
+        command: &TelegramCommand,
+        ctx: Context,
+        message: Message,
+        name: &str,
+    ) {
+        if !self.allows(command, &ctx, &message, name).await {
+            return;
+        }
+        if let Some(cooldown) = &command.options.cooldown {
+            if let Err(left) = self.cooldowns.start(name, cooldown, &message) {
+                debug!("command {name} is on cooldown for {left:?}");
+                if let Some(text) = &self.cooldown_message {
+                    let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+                    let text = text.replace("{seconds}", &seconds.to_string());
+                    if let Err(e) = ctx.reply(&message, text).await {
+                        warn!("failed to send the cooldown message of command {name}: {e}");
+                    }
+                }
+                return;
+            }
+        }
+
+        let mut decision = HookDecision::Continue;
+        for m in &self.middleware {
+            decision = m.before(ctx.clone(), &message, name).await;
+            if matches!(decision, HookDecision::Abort(_)) {
+                break;
+            }
+        }
+
+        let started = Instant::now();
+        let res = match decision {
+            HookDecision::Continue => {
+                let res = AssertUnwindSafe(command.command.call(ctx.clone(), message.clone()))
+                    .catch_unwind()
+                    .await
+                    .unwrap_or_else(|panic| {
+                        let reason = panic_message(&*panic);
+                        Err(CommandError(format!("the command panicked: {reason}")))
+                    });
+                if let Err(e) = &res {
+                    (self.error_handler)(ctx.clone(), message.clone(), e.clone()).await;
+                }
+                res
+            },
+            HookDecision::Abort(e) => {
+                debug!("command {name} was aborted by middleware: {}", e.0);
+                Err(e)
+            },
+        };
+        let elapsed = started.elapsed();
+
+        for m in &self.middleware {
+            m.after(ctx.clone(), &message, name, &res, elapsed).await;
+        }
+    }
+}
+
+/// The default error handler of the framework, passing the error to the error
+/// handler of the client or logging it when the client has none
+fn log_command_error(ctx: Context, message: Message, err: CommandError) -> FutureOutcome {
+    Box::pin(async move {
+        if ctx.has_error_handler() {
+            ctx.report_error(err.into());
+            return;
+        }
+
+        error!(
+            "command in message {} of chat {} returned error: {}",
+            message.message_id,
+            message.chat.get_id(),
+            err.0
+        );
+    })
+}
+
+/// The languages of the localized descriptions of the command and its
+/// subcommands
+fn command_languages(command: &TelegramCommand) -> Vec<&str> {
+    command
+        .options
+        .localized_descriptions
+        .iter()
+        .map(|(l, _)| &**l)
+        .chain(
+            command
+                .options
+                .subcommands
+                .iter()
+                .flat_map(|s| command_languages(s)),
+        )
+        .collect()
+}
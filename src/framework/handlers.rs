@@ -1,6 +1,132 @@
-use super::types::CommandResult;
+use super::{
+    types::{CommandError, CommandResult},
+    Respond,
+};
 use crate::{client::Context, model::Message};
-use std::{future::Future, pin::Pin};
+use std::{fmt::Display, future::Future, pin::Pin, str::FromStr};
 
 pub(crate) type CommandOutcome = Pin<Box<dyn Future<Output = CommandResult> + Send>>;
 pub(crate) type CommandHandlerFunc = fn(Context, Message) -> CommandOutcome;
+
+/// Runs the body of a command and sends the [`Respond`] value it returned, as
+/// used by the `command` macro
+pub async fn respond<F, Fut, R>(ctx: Context, message: Message, command: F) -> CommandResult
+where
+    F: FnOnce(Context, Message) -> Fut,
+    Fut: Future<Output = CommandResult<R>>,
+    R: Respond,
+{
+    command(ctx.clone(), message.clone())
+        .await?
+        .respond(&ctx, &message)
+        .await
+}
+
+/// Splits the text following a command into the typed arguments of the
+/// command, as used by the `command` macro.
+///
+/// Arguments are split on whitespace, while text in double quotes is kept
+/// together as a single argument, without the quotes.
+#[derive(Debug, Clone)]
+pub struct CommandArgs<'a> {
+    remaining: &'a str,
+}
+
+impl<'a> CommandArgs<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            remaining: text,
+        }
+    }
+
+    /// Parses the next argument, returning an error if there is none
+    pub fn required<T>(&mut self, name: &str) -> CommandResult<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.optional(name)?
+            .ok_or_else(|| CommandError(format!("missing the {name} argument")))
+    }
+
+    /// Parses the next argument if there is one
+    pub fn optional<T>(&mut self, name: &str) -> CommandResult<Option<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.next().map(|arg| parse_arg(name, &arg)).transpose()
+    }
+
+    /// Parses all remaining arguments
+    pub fn all<T>(&mut self, name: &str) -> CommandResult<Vec<T>>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.map(|arg| parse_arg(name, &arg)).collect()
+    }
+
+    /// Takes the rest of the text as is, returning an error if it is empty
+    pub fn required_rest(&mut self, name: &str) -> CommandResult<String> {
+        self.optional_rest()
+            .ok_or_else(|| CommandError(format!("missing the {name} argument")))
+    }
+
+    /// Takes the rest of the text as is, if it isn't empty
+    pub fn optional_rest(&mut self) -> Option<String> {
+        let rest = std::mem::take(&mut self.remaining).trim();
+        (!rest.is_empty()).then(|| rest.to_owned())
+    }
+
+    /// Returns an error if there are arguments left that weren't parsed
+    pub fn finish(mut self) -> CommandResult {
+        let unexpected: Vec<String> = self.by_ref().collect();
+        if unexpected.is_empty() {
+            return Ok(());
+        }
+
+        Err(CommandError(format!(
+            "unexpected arguments: {}",
+            unexpected.join(", ")
+        )))
+    }
+}
+
+impl Iterator for CommandArgs<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        let text = self.remaining.trim_start();
+        if text.is_empty() {
+            self.remaining = text;
+            return None;
+        }
+
+        let mut arg = String::new();
+        let mut quoted = false;
+        let mut end = text.len();
+        for (i, c) in text.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                c if c.is_whitespace() && !quoted => {
+                    end = i;
+                    break;
+                },
+                c => arg.push(c),
+            }
+        }
+
+        self.remaining = &text[end..];
+        Some(arg)
+    }
+}
+
+fn parse_arg<T>(name: &str, arg: &str) -> CommandResult<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    arg.parse()
+        .map_err(|e| CommandError(format!("invalid {name} argument {arg:?}: {e}")))
+}
@@ -0,0 +1,204 @@
+use super::types::{CommandOptions, CommandResult, TelegramCommand};
+use crate::{
+    api::types::SendMessage,
+    client::Context,
+    model::{Message, ParseMode},
+};
+use std::{borrow::Cow, sync::Arc};
+
+/// The name of the command added by `Framework::add_help_command`
+pub(crate) const HELP_COMMAND: &str = "help";
+
+/// The options of the help command
+pub(crate) fn help_options() -> CommandOptions {
+    let mut options = CommandOptions::new(HELP_COMMAND, "lists the commands");
+    options.usage = Some(Cow::Borrowed("[command]"));
+    options
+}
+
+/// Lists the commands that were registered when the help command was invoked
+pub(crate) struct Help {
+    pub(crate) commands: Vec<TelegramCommand>,
+    pub(crate) prefix: String,
+    pub(crate) case_insensitive: bool,
+    pub(crate) parse_mode: ParseMode,
+}
+
+impl Help {
+    /// Makes the help command with the given options, replying with the help
+    /// text when called
+    pub(crate) fn into_command(self, options: Cow<'static, CommandOptions>) -> TelegramCommand {
+        let help = Arc::new(self);
+        let handler = move |ctx, message| {
+            let help = help.clone();
+            async move { help.reply(ctx, message).await }
+        };
+
+        TelegramCommand {
+            options,
+            command: handler.into(),
+        }
+    }
+
+    async fn reply(&self, ctx: Context, message: Message) -> CommandResult {
+        let text = self.render(&ctx, &message).await;
+        let mut data = SendMessage::reply_to(&message, &text);
+        data.set_parse_mode(self.parse_mode.clone());
+        ctx.api.send_message(data).await?;
+        Ok(())
+    }
+
+    /// Gets the help text for the message: the commands the sender is allowed
+    /// to use when no command is asked for, like `/help`, or the usage,
+    /// aliases and subcommands of the command asked for, like `/help config`.
+    ///
+    /// Commands of which a check refuses the message are left out, as if they
+    /// don't exist.
+    async fn render(&self, ctx: &Context, message: &Message) -> String {
+        let language = message
+            .from
+            .as_ref()
+            .and_then(|u| u.language_code.as_deref());
+
+        let query = ctx.rest().split_whitespace().collect::<Vec<_>>().join(" ");
+        let query = query.strip_prefix(self.prefix.as_str()).unwrap_or(&query);
+        if query.is_empty() {
+            let mut listed = Vec::new();
+            for command in &self.commands {
+                flatten(command, None, &mut listed);
+            }
+            return self.lines(ctx, message, listed, language).await;
+        }
+
+        let query = if self.case_insensitive {
+            query.to_lowercase()
+        } else {
+            query.to_owned()
+        };
+        let Some((name, command)) = self.commands.iter().find_map(|c| self.find(c, &query)) else {
+            return self.not_found(&query);
+        };
+        if !allows(command, ctx, message).await {
+            return self.not_found(&query);
+        }
+
+        let mut text = self.line(&name, command, language);
+        if !command.options.aliases.is_empty() {
+            // the names of the parent commands, like `config ` for `config get`
+            let parents = &name[..name.len() - command.options.name.len()];
+            let aliases: Vec<String> = command
+                .options
+                .aliases
+                .iter()
+                .map(|a| format!("{}{parents}{a}", self.prefix))
+                .collect();
+            text.push('\n');
+            text.push_str(
+                &self
+                    .parse_mode
+                    .escape(&format!("Aliases: {}", aliases.join(", "))),
+            );
+        }
+
+        let mut listed = Vec::new();
+        for sub in command.options.subcommands.iter() {
+            flatten(sub, Some(&name), &mut listed);
+        }
+        let subcommands = self.lines(ctx, message, listed, language).await;
+        if !subcommands.is_empty() {
+            text.push('\n');
+            text.push_str(&self.parse_mode.escape("Subcommands:"));
+            text.push('\n');
+            text.push_str(&subcommands);
+        }
+        text
+    }
+
+    /// Finds the command or subcommand with the given name, like `config get`
+    /// or `config_get`, returning it with its full name
+    fn find<'a>(
+        &self,
+        command: &'a TelegramCommand,
+        query: &str,
+    ) -> Option<(String, &'a TelegramCommand)> {
+        let name = &command.options.name;
+        command.options.names().find_map(|n| {
+            let n = if self.case_insensitive {
+                n.to_lowercase()
+            } else {
+                n.to_owned()
+            };
+            if query == n {
+                return Some((name.to_string(), command));
+            }
+
+            let rest = query.strip_prefix(n.as_str())?.strip_prefix([' ', '_'])?;
+            command
+                .options
+                .subcommands
+                .iter()
+                .find_map(|sub| self.find(sub, rest))
+                .map(|(sub, c)| (format!("{name} {sub}"), c))
+        })
+    }
+
+    /// The lines of the listed commands the message is allowed to invoke
+    async fn lines(
+        &self,
+        ctx: &Context,
+        message: &Message,
+        listed: Vec<(String, &TelegramCommand)>,
+        language: Option<&str>,
+    ) -> String {
+        let mut lines = Vec::new();
+        for (name, command) in listed {
+            if allows(command, ctx, message).await {
+                lines.push(self.line(&name, command, language));
+            }
+        }
+        lines.join("\n")
+    }
+
+    /// The escaped line describing the command, like
+    /// `/config set <key> <value> - changes a setting`
+    fn line(&self, name: &str, command: &TelegramCommand, language: Option<&str>) -> String {
+        let description = command.get_localized_bot_command(language).description;
+        let line = match &command.options.usage {
+            Some(usage) => format!("{}{name} {usage} - {description}", self.prefix),
+            None => format!("{}{name} - {description}", self.prefix),
+        };
+        self.parse_mode.escape(&line)
+    }
+
+    fn not_found(&self, query: &str) -> String {
+        self.parse_mode
+            .escape(&format!("There is no {}{query} command", self.prefix))
+    }
+}
+
+/// Lists the command followed by its subcommands, each with the full name
+/// it is invoked by, like `config get`
+fn flatten<'a>(
+    command: &'a TelegramCommand,
+    parent: Option<&str>,
+    listed: &mut Vec<(String, &'a TelegramCommand)>,
+) {
+    let name = match parent {
+        Some(parent) => format!("{parent} {}", command.options.name),
+        None => command.options.name.to_string(),
+    };
+    listed.push((name.clone(), command));
+    for sub in command.options.subcommands.iter() {
+        flatten(sub, Some(&name), listed);
+    }
+}
+
+/// Whether all checks of the command allow the message to invoke it
+async fn allows(command: &TelegramCommand, ctx: &Context, message: &Message) -> bool {
+    for check in command.options.checks.iter() {
+        if !matches!(check(ctx.clone(), message).await, Ok(true)) {
+            return false;
+        }
+    }
+    true
+}
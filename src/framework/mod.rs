@@ -1,6 +1,12 @@
 //! The framework provides a customizable way to manage your bots commands
 
+mod callback_router;
+pub mod checks;
+mod cooldowns;
+mod dialogue;
 pub(crate) mod framework;
+mod help;
+mod respond;
 
 // made public for the procedural macros to use
 #[doc(hidden)]
@@ -8,5 +14,36 @@ pub mod handlers;
 #[doc(hidden)]
 pub mod types;
 
+pub use callback_router::CallbackRouter;
+pub use dialogue::{
+    Dialogue,
+    DialogueHandlerFunc,
+    DialogueKey,
+    DialogueScope,
+    DialogueState,
+    DialogueStorage,
+    MemoryDialogueStorage,
+};
 pub use framework::Framework;
-pub use types::{CommandError, CommandResult};
+pub use respond::{NamedBytes, Respond, MAX_DOCUMENT_SIZE, MAX_PHOTO_SIZE};
+pub use types::{
+    CallbackQueryHandlerFunc,
+    CheckOutcome,
+    ChosenInlineResultHandlerFunc,
+    CommandCheckFunc,
+    CommandError,
+    CommandErrorHandlerFunc,
+    CommandOptions,
+    CommandResult,
+    CommandTypes,
+    Cooldown,
+    CooldownScope,
+    HookDecision,
+    InlineAnswer,
+    InlineAnswerHandlerFunc,
+    InlineAnswerOutcome,
+    InlineQueryHandlerFunc,
+    Middleware,
+    TelegramCommand,
+    TopicScope,
+};
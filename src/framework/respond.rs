@@ -0,0 +1,160 @@
+use super::types::{CommandError, CommandResult};
+use crate::{
+    api::types::{InputFile, SendDocument, SendPhoto},
+    client::Context,
+    model::Message,
+};
+use async_trait::async_trait;
+use std::path::PathBuf;
+
+/// The maximum size in bytes of a document that bots can upload
+pub const MAX_DOCUMENT_SIZE: usize = 50 * 1024 * 1024;
+/// The maximum size in bytes of a photo that bots can upload
+pub const MAX_PHOTO_SIZE: usize = 10 * 1024 * 1024;
+
+/// A value that can be returned by a command in a [`CommandResult`], which is
+/// sent to the chat the command was used in once the command finishes.
+///
+/// Returning `()` sends nothing, a [`PathBuf`] or [`NamedBytes`] is sent as a
+/// document (or photo) and a [`SendDocument`] or [`SendPhoto`] is sent as is.
+/// Files are checked against the upload limits of telegram before they are
+/// uploaded.
+///
+/// ```rust,no_run
+/// use telexide::{framework::NamedBytes, prelude::*};
+///
+/// #[command(description = "sends a report")]
+/// async fn report(_ctx: Context, _message: Message) -> CommandResult<NamedBytes> {
+///     Ok(NamedBytes::new("report.csv", b"name,count\nping,1\n".to_vec()))
+/// }
+/// ```
+///
+/// [`SendDocument`]: ../api/types/struct.SendDocument.html
+/// [`SendPhoto`]: ../api/types/struct.SendPhoto.html
+#[async_trait]
+pub trait Respond: Send {
+    /// Sends the response to the chat of the message that invoked the command
+    async fn respond(self, ctx: &Context, message: &Message) -> CommandResult;
+}
+
+/// A file created by a command, like a generated report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamedBytes {
+    /// The name of the file, of which the extension determines its media type
+    pub name: String,
+    /// The contents of the file
+    pub bytes: Vec<u8>,
+    /// Whether the file should be sent as a photo instead of as a document
+    pub as_photo: bool,
+}
+
+impl NamedBytes {
+    /// Creates a file that is sent as a document
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(name: impl ToString, bytes: Vec<u8>) -> Self {
+        Self {
+            name: name.to_string(),
+            bytes,
+            as_photo: false,
+        }
+    }
+
+    /// Creates a file that is sent as a photo
+    pub fn photo(name: impl ToString, bytes: Vec<u8>) -> Self {
+        Self {
+            as_photo: true,
+            ..Self::new(name, bytes)
+        }
+    }
+}
+
+#[async_trait]
+impl Respond for () {
+    async fn respond(self, _ctx: &Context, _message: &Message) -> CommandResult {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Respond for PathBuf {
+    async fn respond(self, ctx: &Context, message: &Message) -> CommandResult {
+        let size = tokio::fs::metadata(&self).await?.len();
+        check_size(&self.to_string_lossy(), size, MAX_DOCUMENT_SIZE)?;
+
+        SendDocument::new(message.chat.get_id().into(), InputFile::from_path(&self)?)
+            .respond(ctx, message)
+            .await
+    }
+}
+
+#[async_trait]
+impl Respond for NamedBytes {
+    async fn respond(self, ctx: &Context, message: &Message) -> CommandResult {
+        let chat_id = message.chat.get_id().into();
+        let file = InputFile::from_bytes(self.bytes, &self.name)?;
+
+        if self.as_photo {
+            SendPhoto::new(chat_id, file).respond(ctx, message).await
+        } else {
+            SendDocument::new(chat_id, file).respond(ctx, message).await
+        }
+    }
+}
+
+#[async_trait]
+impl Respond for SendDocument {
+    async fn respond(mut self, ctx: &Context, message: &Message) -> CommandResult {
+        check_file_size(&self.document, MAX_DOCUMENT_SIZE)?;
+        if let Some(thumbnail) = &self.thumbnail {
+            check_file_size(thumbnail, MAX_PHOTO_SIZE)?;
+        }
+
+        if self.message_thread_id.is_none() {
+            self.message_thread_id = message.topic_thread_id();
+        }
+        if self.business_connection_id.is_none() {
+            self.business_connection_id = message.business_connection_id.clone();
+        }
+
+        ctx.api.send_document(self).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Respond for SendPhoto {
+    async fn respond(mut self, ctx: &Context, message: &Message) -> CommandResult {
+        check_file_size(&self.photo, MAX_PHOTO_SIZE)?;
+
+        if self.message_thread_id.is_none() {
+            self.message_thread_id = message.topic_thread_id();
+        }
+        if self.business_connection_id.is_none() {
+            self.business_connection_id = message.business_connection_id.clone();
+        }
+
+        ctx.api.send_photo(self).await?;
+        Ok(())
+    }
+}
+
+fn check_file_size(file: &InputFile, max: usize) -> CommandResult {
+    match file {
+        InputFile::File(f) => check_size(
+            f.file_name.as_ref().unwrap_or(&f.name),
+            f.size().map_err(|e| CommandError(e.to_string()))?,
+            max,
+        ),
+        InputFile::String(_) => Ok(()),
+    }
+}
+
+fn check_size(name: &str, size: u64, max: usize) -> CommandResult {
+    if size > max as u64 {
+        return Err(CommandError(format!(
+            "{name} is {size} bytes, while at most {max} bytes can be uploaded"
+        )));
+    }
+
+    Ok(())
+}
@@ -1,32 +1,218 @@
-use super::handlers::CommandHandlerFunc;
-use crate::{model::BotCommand, utils::result::Error};
+use super::handlers::{CommandHandlerFunc, CommandOutcome};
+use crate::{
+    api::types::{InlineQueryResult, TopicId},
+    client::{Context, FutureOutcome},
+    model::{BotCommand, CallbackQuery, ChosenInlineResult, InlineQuery, Message},
+    utils::result::{Error, Result},
+};
+use async_trait::async_trait;
+use std::{borrow::Cow, future::Future, pin::Pin, sync::Arc, time::Duration};
 
+/// The function called when a command is invoked
 #[derive(Clone)]
 pub enum CommandTypes {
+    /// A handler made by the `command` macro
     Default(CommandHandlerFunc),
+    /// A handler made at runtime, like a closure capturing its configuration
+    Dynamic(Arc<dyn Fn(Context, Message) -> CommandOutcome + Send + Sync>),
 }
 
+impl CommandTypes {
+    /// Calls the handler with the context and the message invoking the command
+    pub fn call(&self, ctx: Context, message: Message) -> CommandOutcome {
+        match self {
+            Self::Default(handler) => handler(ctx, message),
+            Self::Dynamic(handler) => handler(ctx, message),
+        }
+    }
+}
+
+impl<F, Fut> From<F> for CommandTypes
+where
+    F: Fn(Context, Message) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = CommandResult> + Send + 'static,
+{
+    fn from(handler: F) -> Self {
+        Self::Dynamic(Arc::new(move |ctx, message| {
+            Box::pin(handler(ctx, message))
+        }))
+    }
+}
+
+/// The options of a command, as set by the `command` macro or at runtime.
+///
+/// The fields borrow static data when made by the macro and own their data
+/// when made at runtime, for example from a configuration file.
 #[derive(Clone)]
 pub struct CommandOptions {
-    pub name: &'static str,
-    pub description: &'static str,
+    pub name: Cow<'static, str>,
+    pub description: Cow<'static, str>,
+    /// The arguments the command takes, like `<user> [reason]`, as shown by
+    /// the help command
+    pub usage: Option<Cow<'static, str>>,
+    pub priority: i64,
+    pub aliases: Cow<'static, [Cow<'static, str>]>,
+    pub topics: TopicScope,
+    /// The descriptions for users with other languages, as pairs of a
+    /// two-letter ISO 639-1 language code and the description
+    pub localized_descriptions: Cow<'static, [(Cow<'static, str>, Cow<'static, str>)]>,
+    /// The commands invoked when the first word following this command is
+    /// their name or one of their aliases, like `/config get`
+    pub subcommands: Cow<'static, [Cow<'static, TelegramCommand>]>,
+    /// The checks that all have to allow a message before it can invoke the
+    /// command
+    pub checks: Cow<'static, [CommandCheckFunc]>,
+    /// How long a user or chat has to wait before using the command again
+    pub cooldown: Option<Cooldown>,
 }
 
+impl CommandOptions {
+    /// Creates the options of a command with the given name and description,
+    /// without aliases, subcommands, checks or a cooldown
+    pub fn new(
+        name: impl Into<Cow<'static, str>>,
+        description: impl Into<Cow<'static, str>>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            description: description.into(),
+            usage: None,
+            priority: 0,
+            aliases: Cow::Borrowed(&[]),
+            topics: TopicScope::Everywhere,
+            localized_descriptions: Cow::Borrowed(&[]),
+            subcommands: Cow::Borrowed(&[]),
+            checks: Cow::Borrowed(&[]),
+            cooldown: None,
+        }
+    }
+
+    /// The name of the command followed by its aliases
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(&*self.name).chain(self.aliases.iter().map(|a| &**a))
+    }
+}
+
+/// How long has to be waited before a command can be used again, as set by
+/// the `cooldown` option of the `command` macro
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cooldown {
+    /// The time between uses of the command
+    pub duration: Duration,
+    /// Who shares the cooldown
+    pub scope: CooldownScope,
+}
+
+/// Who shares the cooldown of a command
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CooldownScope {
+    /// Every user has their own cooldown, shared between all chats
+    User,
+    /// Every chat has its own cooldown, shared between all users in it
+    Chat,
+}
+
+/// The future returned by a [`CommandCheckFunc`]
+pub type CheckOutcome<'a> = Pin<Box<dyn Future<Output = Result<bool>> + Send + 'a>>;
+
+/// A function checking whether a message is allowed to invoke a command, as
+/// made by the `checks` option of the `command` macro from an async function
+/// taking a [`Context`] and `&Message` and returning a `Result<bool>`
+pub type CommandCheckFunc = for<'a> fn(Context, &'a Message) -> CheckOutcome<'a>;
+
+/// The forum topics in which a command can be used
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TopicScope {
+    /// The command can be used in any chat, which is the default
+    #[default]
+    Everywhere,
+    /// The command can only be used in the forum topics with the given message
+    /// thread ids, where the "General" topic has thread id 1
+    Topics(Cow<'static, [i64]>),
+    /// The command can only be used in forum topics other than the "General"
+    /// topic
+    ExceptGeneral,
+}
+
+impl TopicScope {
+    /// Checks whether a command with this scope can be invoked by the message
+    pub fn allows(&self, message: &Message) -> bool {
+        match self {
+            Self::Everywhere => true,
+            Self::Topics(topics) => message
+                .topic_id()
+                .is_some_and(|t| topics.contains(&t.thread_id())),
+            Self::ExceptGeneral => matches!(message.topic_id(), Some(TopicId::Topic(_))),
+        }
+    }
+}
+
+/// A command of the framework, made by the `command` macro as a static named
+/// after the function with a `_COMMAND` suffix, or at runtime using
+/// [`TelegramCommand::new`]
 #[derive(Clone)]
 pub struct TelegramCommand {
-    pub options: &'static CommandOptions,
+    pub options: Cow<'static, CommandOptions>,
     pub command: CommandTypes,
 }
 
 impl TelegramCommand {
+    /// Creates a command at runtime from its options and a handler, which can
+    /// be an async function or closure taking a [`Context`] and the
+    /// [`Message`] that invoked the command and returning a [`CommandResult`]
+    pub fn new(options: CommandOptions, handler: impl Into<CommandTypes>) -> Self {
+        Self {
+            options: Cow::Owned(options),
+            command: handler.into(),
+        }
+    }
+
     pub fn get_bot_command(&self) -> BotCommand {
+        self.get_localized_bot_command(None)
+    }
+
+    /// Gets the command as displayed to users with the given language, using
+    /// the default description if the command has none for the language
+    pub fn get_localized_bot_command(&self, language_code: Option<&str>) -> BotCommand {
+        let description = language_code
+            .and_then(|lang| {
+                self.options
+                    .localized_descriptions
+                    .iter()
+                    .find(|(l, _)| *l == lang)
+            })
+            .map_or(&self.options.description, |(_, d)| d);
+
         BotCommand {
-            command: self.options.name.to_owned(),
-            description: self.options.description.to_owned(),
+            command: self.options.name.to_string(),
+            description: description.to_string(),
         }
     }
+
+    /// Gets the command followed by its subcommands as displayed to users
+    /// with the given language.
+    ///
+    /// Telegram doesn't allow spaces in command names, so the subcommands are
+    /// listed with their names joined by underscores, like `/config_get`,
+    /// which invokes the subcommand just like `/config get` does. Subcommands
+    /// of which the joined name is longer than telegram allows are left out.
+    pub fn get_flattened_bot_commands(&self, language_code: Option<&str>) -> Vec<BotCommand> {
+        let mut commands = vec![self.get_localized_bot_command(language_code)];
+        for sub in self.options.subcommands.iter() {
+            for mut command in sub.get_flattened_bot_commands(language_code) {
+                command.command = format!("{}_{}", self.options.name, command.command);
+                if command.command.len() <= MAX_COMMAND_NAME_LENGTH {
+                    commands.push(command);
+                }
+            }
+        }
+        commands
+    }
 }
 
+/// The maximum length of the name of a command registered with telegram
+const MAX_COMMAND_NAME_LENGTH: usize = 32;
+
 /// The error to be returned from a command.
 ///
 /// It can be formed from anything implementing [`std::fmt::Display`], but won't
@@ -42,8 +228,10 @@ impl<T: std::fmt::Display> From<T> for CommandError {
 }
 
 /// A type alias for an std Result with the [`CommandError`] as the Err and ()
-/// as the Ok
-pub type CommandResult = ::std::result::Result<(), CommandError>;
+/// as the Ok, or a [`Respond`] value to be sent once the command finishes
+///
+/// [`Respond`]: trait.Respond.html
+pub type CommandResult<T = ()> = ::std::result::Result<T, CommandError>;
 
 impl From<CommandError> for Error {
     #[inline]
@@ -51,3 +239,115 @@ impl From<CommandError> for Error {
         Error::Command(d)
     }
 }
+
+/// A function handling the error a command returned or the panic of a
+/// command, it receives a [`Context`], the [`Message`] that invoked the command
+/// and the [`CommandError`] and returns a pinned future. Wrap an async function
+/// with `#[prepare_listener]` for easier development.
+pub type CommandErrorHandlerFunc = fn(Context, Message, CommandError) -> FutureOutcome;
+
+/// A function handling the inline queries routed to it by the framework, it
+/// receives a [`Context`] and the [`InlineQuery`] and returns a pinned future.
+/// Wrap an async function with `#[prepare_listener]` for easier development.
+pub type InlineQueryHandlerFunc = fn(Context, InlineQuery) -> FutureOutcome;
+
+/// The results an inline answer handler answers an inline query with.
+///
+/// When no next offset is given the results are treated as all results for
+/// the query, of which the framework sends at most 50 at a time while the user
+/// scrolls through them. Handlers that set a next offset paginate the results
+/// themselves using the offset of the [`InlineQuery`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InlineAnswer {
+    /// The results of the query
+    pub results: Vec<InlineQueryResult>,
+    /// The offset the next inline query for more results will be sent with
+    pub next_offset: Option<String>,
+}
+
+impl InlineAnswer {
+    /// Creates an answer containing all results for the query
+    pub fn new(results: Vec<InlineQueryResult>) -> Self {
+        Self {
+            results,
+            next_offset: None,
+        }
+    }
+
+    /// Sets the offset the next inline query for more results will be sent
+    /// with, the results may then contain at most 50 results
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set_next_offset(&mut self, next_offset: impl ToString) -> &mut Self {
+        self.next_offset = Some(next_offset.to_string());
+        self
+    }
+}
+
+impl From<Vec<InlineQueryResult>> for InlineAnswer {
+    fn from(results: Vec<InlineQueryResult>) -> Self {
+        Self::new(results)
+    }
+}
+
+/// The future returned by an [`InlineAnswerHandlerFunc`]
+pub type InlineAnswerOutcome = Pin<Box<dyn Future<Output = CommandResult<InlineAnswer>> + Send>>;
+
+/// A function producing the results the framework answers an inline query
+/// with, it receives a [`Context`] and the [`InlineQuery`] and returns a pinned
+/// future resolving to a [`CommandResult`] with the [`InlineAnswer`]. Wrap an
+/// async function returning `CommandResult<InlineAnswer>` with
+/// `#[prepare_listener]` for easier development.
+pub type InlineAnswerHandlerFunc = fn(Context, InlineQuery) -> InlineAnswerOutcome;
+
+/// A function handling the inline results chosen by users, it receives a
+/// [`Context`] and the [`ChosenInlineResult`] and returns a pinned future.
+/// Wrap an async function with `#[prepare_listener]` for easier development.
+pub type ChosenInlineResultHandlerFunc = fn(Context, ChosenInlineResult) -> FutureOutcome;
+
+/// A function handling the callback queries routed to it by the framework, it
+/// receives a [`Context`], the [`CallbackQuery`] and the data of the query
+/// following the prefix the handler was registered with, and returns a pinned
+/// future. Wrap an async function with `#[prepare_listener]` for easier
+/// development.
+pub type CallbackQueryHandlerFunc = fn(Context, CallbackQuery, String) -> FutureOutcome;
+
+/// Whether a command should be called, as decided by the [`Middleware`] of the
+/// framework before calling it
+#[derive(Debug, Clone)]
+pub enum HookDecision {
+    /// Call the remaining middleware and then the command
+    Continue,
+    /// Don't call the command, the after hooks receive the error as its result
+    Abort(CommandError),
+}
+
+/// Hooks run by the framework around every command it calls, for example to
+/// log the commands used, measure how long they take or to refuse banned
+/// users.
+///
+/// Middleware is added using [`Framework::add_middleware`] and runs in the
+/// order it was added. Once a before hook aborts the command, the remaining
+/// before hooks and the command are skipped, while the after hooks are still
+/// called.
+///
+/// [`Framework::add_middleware`]: ../struct.Framework.html#method.add_middleware
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    /// Called before the command with the given name is called, which is the
+    /// full name like `config get` for subcommands
+    async fn before(&self, _ctx: Context, _message: &Message, _command: &str) -> HookDecision {
+        HookDecision::Continue
+    }
+
+    /// Called after the command with the given name finished or was aborted,
+    /// with its result and how long it ran
+    async fn after(
+        &self,
+        _ctx: Context,
+        _message: &Message,
+        _command: &str,
+        _result: &CommandResult,
+        _elapsed: Duration,
+    ) {
+    }
+}
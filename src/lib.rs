@@ -68,7 +68,9 @@ pub mod api;
 pub mod client;
 pub mod framework;
 pub mod model;
-mod utils;
+#[cfg(feature = "testing")]
+pub mod test_utils;
+pub mod utils;
 
 /// Macros for using the framework and helping with adding listeners
 pub mod macros {
@@ -0,0 +1,97 @@
+use super::Document;
+use serde::{Deserialize, Serialize};
+
+/// This object describes the way a background is filled based on the selected
+/// colors.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum BackgroundFill {
+    /// The background is filled using the selected color.
+    #[serde(rename = "solid")]
+    Solid {
+        /// The color of the background fill in the RGB24 format
+        color: i64,
+    },
+    /// The background is a gradient fill.
+    #[serde(rename = "gradient")]
+    Gradient {
+        /// Top color of the gradient in the RGB24 format
+        top_color: i64,
+        /// Bottom color of the gradient in the RGB24 format
+        bottom_color: i64,
+        /// Clockwise rotation angle of the background fill in degrees; 0-359
+        rotation_angle: i64,
+    },
+    /// The background is a freeform gradient that rotates after every message
+    /// in the chat.
+    #[serde(rename = "freeform_gradient")]
+    FreeformGradient {
+        /// A list of the 3 or 4 base colors that are used to generate the
+        /// freeform gradient in the RGB24 format
+        colors: Vec<i64>,
+    },
+}
+
+/// This object describes the type of a background.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type")]
+pub enum BackgroundType {
+    /// The background is automatically filled based on the selected colors.
+    #[serde(rename = "fill")]
+    Fill {
+        /// The background fill
+        fill: BackgroundFill,
+        /// Dimming of the background in dark themes, as a percentage; 0-100
+        dark_theme_dimming: i64,
+    },
+    /// The background is a wallpaper in the JPEG format.
+    #[serde(rename = "wallpaper")]
+    Wallpaper {
+        /// Document with the wallpaper
+        document: Document,
+        /// Dimming of the background in dark themes, as a percentage; 0-100
+        dark_theme_dimming: i64,
+        /// True, if the wallpaper is downscaled to fit in a 450x450 square and
+        /// then box-blurred with radius 12
+        #[serde(default)]
+        is_blurred: bool,
+        /// True, if the background moves slightly when the device is tilted
+        #[serde(default)]
+        is_moving: bool,
+    },
+    /// The background is a PNG or TGV (gzipped subset of SVG with MIME type
+    /// “application/x-tgwallpattern”) pattern to be combined with the
+    /// background fill chosen by the user.
+    #[serde(rename = "pattern")]
+    Pattern {
+        /// Document with the pattern
+        document: Document,
+        /// The background fill that is combined with the pattern
+        fill: BackgroundFill,
+        /// Intensity of the pattern when it is shown above the filled
+        /// background; 0-100
+        intensity: i64,
+        /// True, if the background fill must be applied only to the pattern
+        /// itself. All other pixels are black in this case. For dark themes
+        /// only
+        #[serde(default)]
+        is_inverted: bool,
+        /// True, if the background moves slightly when the device is tilted
+        #[serde(default)]
+        is_moving: bool,
+    },
+    /// The background is taken directly from a built-in chat theme.
+    #[serde(rename = "chat_theme")]
+    ChatTheme {
+        /// Name of the chat theme, which is usually an emoji
+        theme_name: String,
+    },
+}
+
+/// This object represents a chat background.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChatBackground {
+    /// Type of the background
+    #[serde(rename = "type")]
+    pub background_type: BackgroundType,
+}
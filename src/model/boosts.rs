@@ -0,0 +1,99 @@
+use super::{utils::unix_date_formatting, Chat, User};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// This object describes the source of a chat boost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "source")]
+pub enum ChatBoostSource {
+    /// The boost was obtained by subscribing to Telegram Premium or by gifting
+    /// a Telegram Premium subscription to another user.
+    #[serde(rename = "premium")]
+    Premium {
+        /// User that boosted the chat
+        user: User,
+    },
+    /// The boost was obtained by the creation of Telegram Premium gift codes
+    /// to boost a chat.
+    #[serde(rename = "gift_code")]
+    GiftCode {
+        /// User for which the gift code was created
+        user: User,
+    },
+    /// The boost was obtained by the creation of a Telegram Premium giveaway.
+    #[serde(rename = "giveaway")]
+    Giveaway {
+        /// Identifier of a message in the chat with the giveaway; the message
+        /// could have been deleted already. May be 0 if the message isn't sent
+        /// yet.
+        giveaway_message_id: i64,
+        /// User that won the prize in the giveaway if any
+        user: Option<User>,
+        /// True, if the giveaway was completed, but there was no user to win
+        /// the prize
+        #[serde(default)]
+        is_unclaimed: bool,
+    },
+}
+
+/// This object contains information about a chat boost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatBoost {
+    /// Unique identifier of the boost
+    pub boost_id: String,
+    /// Point in time when the chat was boosted
+    #[serde(with = "unix_date_formatting")]
+    pub add_date: DateTime<Utc>,
+    /// Point in time when the boost will automatically expire, unless the
+    /// booster's Telegram Premium subscription is prolonged
+    #[serde(with = "unix_date_formatting")]
+    pub expiration_date: DateTime<Utc>,
+    /// Source of the added boost
+    pub source: ChatBoostSource,
+}
+
+/// This object represents a boost added to a chat or changed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatBoostUpdated {
+    /// Chat which was boosted
+    pub chat: Chat,
+    /// Information about the chat boost
+    pub boost: ChatBoost,
+}
+
+/// This object represents a boost removed from a chat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChatBoostRemoved {
+    /// Chat which was boosted
+    pub chat: Chat,
+    /// Unique identifier of the boost
+    pub boost_id: String,
+    /// Point in time when the boost was removed
+    #[serde(with = "unix_date_formatting")]
+    pub remove_date: DateTime<Utc>,
+    /// Source of the removed boost
+    pub source: ChatBoostSource,
+}
+
+/// This object represents a service message about a user boosting a chat.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChatBoostAdded {
+    /// Number of boosts added by the user
+    pub boost_count: i64,
+}
+
+/// This object represents a list of boosts added to a chat by a user.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UserChatBoosts {
+    /// The list of boosts added to the chat by the user
+    pub boosts: Vec<ChatBoost>,
+}
+
+impl UserChatBoosts {
+    /// Whether any of the boosts of the user has not expired yet, meaning the
+    /// user is currently boosting the chat
+    pub fn is_boosting(&self) -> bool {
+        let now = Utc::now();
+        self.boosts.iter().any(|b| b.expiration_date > now)
+    }
+}
@@ -0,0 +1,39 @@
+use super::{utils::unix_date_formatting, Chat, User};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Describes the connection of the bot with a business account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BusinessConnection {
+    /// Unique identifier of the business connection
+    pub id: String,
+    /// Business account user that created the business connection
+    pub user: User,
+    /// Identifier of a private chat with the user who created the business
+    /// connection
+    pub user_chat_id: i64,
+    /// Date the connection was established
+    #[serde(with = "unix_date_formatting")]
+    pub date: DateTime<Utc>,
+    /// True, if the bot can act on behalf of the business account in chats
+    /// that were active in the last 24 hours
+    #[serde(default)]
+    pub can_reply: bool,
+    /// True, if the connection is active
+    #[serde(default)]
+    pub is_enabled: bool,
+}
+
+/// This object is received when messages are deleted from a connected
+/// business account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BusinessMessagesDeleted {
+    /// Unique identifier of the business connection
+    pub business_connection_id: String,
+    /// Information about a chat in the business account. The bot may not have
+    /// access to the chat or the corresponding user.
+    pub chat: Chat,
+    /// The list of identifiers of deleted messages in the chat of the business
+    /// account
+    pub message_ids: Vec<i64>,
+}
@@ -11,6 +11,7 @@ pub struct BotCommand {
     pub description: String,
 }
 
+/// The scope of users and chats a list of bot commands applies to
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 #[serde(tag = "type")]
 pub enum BotCommandScope {
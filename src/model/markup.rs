@@ -119,6 +119,17 @@ pub struct InlineKeyboardButton {
     pub pay: bool,
 }
 
+impl InlineKeyboardButton {
+    /// Creates a button that authorizes the user using the given
+    /// [`LoginUrl`] when pressed
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn login(text: impl ToString, login_url: LoginUrl) -> Self {
+        let mut button = Self::new(text, false);
+        button.set_login_url(login_url);
+        button
+    }
+}
+
 /// This object represents an inline button that switches the current user to
 /// inline mode in a chosen chat, with an optional default inline query.
 #[build_struct]
@@ -188,6 +199,35 @@ pub struct ReplyKeyboardMarkup {
     pub selective: Option<bool>,
 }
 
+impl ReplyKeyboardMarkup {
+    /// Adds a button to the last row of the keyboard
+    pub fn add_button(&mut self, button: KeyboardButton) -> &mut Self {
+        match self.keyboard.last_mut() {
+            Some(row) => row.push(button),
+            None => self.keyboard.push(vec![button]),
+        }
+        self
+    }
+
+    /// Starts a new row, to which following buttons will be added
+    pub fn add_new_row(&mut self) -> &mut Self {
+        self.keyboard.push(Vec::new());
+        self
+    }
+
+    /// Adds a complete row of buttons to the keyboard
+    pub fn add_row(&mut self, buttons: Vec<KeyboardButton>) -> &mut Self {
+        self.keyboard.push(buttons);
+        self
+    }
+}
+
+impl Default for ReplyKeyboardMarkup {
+    fn default() -> Self {
+        Self::new(vec![Vec::new()])
+    }
+}
+
 /// Upon receiving a message with this object, Telegram clients will remove
 /// the current custom keyboard and display the default letter-keyboard.
 /// By default, custom keyboards are displayed until a new keyboard is sent by a
@@ -265,10 +305,10 @@ pub struct KeyboardButton {
     /// it will be sent as a message when the button is pressed
     pub text: String,
     /// If specified, pressing the button will open a list of suitable users.
-    /// Tapping on any user will send their identifier to the bot in a
-    /// “user_shared” service message. Available in private chats only.
+    /// Identifiers of selected users will be sent to the bot in a
+    /// “`users_shared`” service message. Available in private chats only.
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub request_user: Option<KeyboardButtonRequestUser>,
+    pub request_users: Option<KeyboardButtonRequestUsers>,
     /// If specified, pressing the button will open a list of suitable chats.
     /// Tapping on a chat will send its identifier to the bot in a “chat_shared”
     /// service message. Available in private chats only.
@@ -293,6 +333,47 @@ pub struct KeyboardButton {
     pub web_app: Option<WebAppInfo>,
 }
 
+impl KeyboardButton {
+    /// Creates a button that sends the user's phone number as a contact when
+    /// pressed
+    pub fn contact(text: impl ToString) -> Self {
+        let mut button = Self::new(text);
+        button.set_request_contact(true);
+        button
+    }
+
+    /// Creates a button that sends the user's current location when pressed
+    pub fn location(text: impl ToString) -> Self {
+        let mut button = Self::new(text);
+        button.set_request_location(true);
+        button
+    }
+
+    /// Creates a button that asks the user to create a poll of the given type
+    /// and send it to the bot when pressed
+    pub fn poll(text: impl ToString, poll_type: super::PollType) -> Self {
+        let mut button = Self::new(text);
+        button.set_request_poll(KeyboardButtonPollType::new(poll_type));
+        button
+    }
+
+    /// Creates a button that opens a list of users matching the given
+    /// criteria when pressed
+    pub fn users(text: impl ToString, request: KeyboardButtonRequestUsers) -> Self {
+        let mut button = Self::new(text);
+        button.set_request_users(request);
+        button
+    }
+
+    /// Creates a button that opens a list of chats matching the given
+    /// criteria when pressed
+    pub fn chat(text: impl ToString, request: KeyboardButtonRequestChat) -> Self {
+        let mut button = Self::new(text);
+        button.set_request_chat(request);
+        button
+    }
+}
+
 /// This object represents type of a poll, which is allowed to be created and
 /// sent when the corresponding button is pressed.
 #[build_struct]
@@ -318,27 +399,46 @@ pub struct WebAppInfo {
     pub url: String,
 }
 
-/// This object defines the criteria used to request a suitable user. The
-/// identifier of the selected user will be shared with the bot when the
+/// This object defines the criteria used to request suitable users. The
+/// identifiers of the selected users will be shared with the bot when the
 /// corresponding button is pressed.
 #[build_struct]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct KeyboardButtonRequestUser {
+pub struct KeyboardButtonRequestUsers {
     /// Signed 32-bit identifier of the request, which will be received back in
-    /// the [`UserShared`] object. Must be unique within the message
+    /// the [`UsersShared`] object. Must be unique within the message
     ///
-    /// [`UserShared`]: ../model/struct.UserShared.html
+    /// [`UsersShared`]: ../model/struct.UsersShared.html
     pub request_id: i32,
-    /// Pass True to request a bot, pass False to request a regular user. If not
+    /// Pass True to request bots, pass False to request regular users. If not
     /// specified, no additional restrictions are applied.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_is_bot: Option<bool>,
-    /// Pass True to request a premium user, pass False to request a non-premium
-    /// user. If not specified, no additional restrictions are applied.
+    /// Pass True to request premium users, pass False to request non-premium
+    /// users. If not specified, no additional restrictions are applied.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_is_premium: Option<bool>,
+    /// The maximum number of users to be selected; 1-10. Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_quantity: Option<i64>,
+    /// Pass True to request the users' first and last names
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_name: Option<bool>,
+    /// Pass True to request the users' usernames
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_username: Option<bool>,
+    /// Pass True to request the users' photos
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_photo: Option<bool>,
 }
 
+/// The criteria used to request a suitable user, which has been superseded by
+/// [`KeyboardButtonRequestUsers`].
+///
+/// [`KeyboardButtonRequestUsers`]: ../model/struct.KeyboardButtonRequestUsers.html
+#[deprecated(note = "use `KeyboardButtonRequestUsers` instead")]
+pub type KeyboardButtonRequestUser = KeyboardButtonRequestUsers;
+
 /// This object defines the criteria used to request a suitable chat. The
 /// identifier of the selected chat will be shared with the bot when the
 /// corresponding button is pressed.
@@ -5,14 +5,21 @@ use super::{
     message_contents::*,
     message_entity::*,
     raw::*,
+    utils::unix_date_formatting,
+    Chat,
+    ChatBackground,
+    ChatBoostAdded,
     Game,
     InlineKeyboardMarkup,
     Invoice,
+    LinkPreviewOptions,
+    PaidMediaInfo,
     PassportData,
     Sticker,
     SuccessfulPayment,
     User,
 };
+use crate::api::types::TopicId;
 
 /// This object represents a message.
 #[derive(Debug, Clone, PartialEq)]
@@ -29,10 +36,21 @@ pub struct Message {
     /// group administrators. The linked channel for messages automatically
     /// forwarded to the discussion group
     pub sender_chat: Option<super::Chat>,
+    /// If the sender of the message boosted the chat, the number of boosts
+    /// added by the user
+    pub sender_boost_count: Option<i64>,
+    /// The bot that actually sent the message on behalf of the business
+    /// account. Available only for outgoing messages sent on behalf of the
+    /// connected business account.
+    pub sender_business_bot: Option<User>,
     /// Date the message was sent
     pub date: DateTime<Utc>,
     /// Conversation the message belongs to
     pub chat: super::Chat,
+    /// Unique identifier of the business connection from which the message
+    /// was received, replies to it should be sent through the same
+    /// connection
+    pub business_connection_id: Option<String>,
 
     /// Data about what message it was forwarded from
     pub forward_data: Option<ForwardData>,
@@ -43,6 +61,8 @@ pub struct Message {
     /// Note that the Message object in this field will not contain further
     /// reply_to_message fields even if it itself is a reply.
     pub reply_to_message: Option<Box<Message>>,
+    /// For replies to a story, the original story
+    pub reply_to_story: Option<Story>,
     /// Bot through which the message was sent
     pub via_bot: Option<User>,
     /// Date the message was last edited in Unix time
@@ -54,6 +74,9 @@ pub struct Message {
 
     /// The content of the message
     pub content: MessageContent,
+    /// Options used for link preview generation for the message, if it is a
+    /// text message and link preview options were changed
+    pub link_preview_options: Option<LinkPreviewOptions>,
 
     /// The domain name of the website on which the user has logged in.
     pub connected_website: Option<String>,
@@ -102,6 +125,8 @@ pub enum MessageContent {
         /// appear in the caption
         caption_entities: Option<Vec<MessageEntity>>,
         /// If the message media is covered by a spoiler animation
+        /// If the caption is shown above the message media
+        show_caption_above_media: bool,
         has_spoiler: bool,
     },
     Video {
@@ -116,6 +141,8 @@ pub enum MessageContent {
         /// to
         media_group_id: Option<String>,
         /// If the message media is covered by a spoiler animation
+        /// If the caption is shown above the message media
+        show_caption_above_media: bool,
         has_spoiler: bool,
     },
     Voice {
@@ -139,8 +166,21 @@ pub enum MessageContent {
         /// to
         media_group_id: Option<String>,
         /// If the message media is covered by a spoiler animation
+        /// If the caption is shown above the message media
+        show_caption_above_media: bool,
         has_spoiler: bool,
     },
+    PaidMedia {
+        /// Information about the paid media
+        content: PaidMediaInfo,
+        /// The caption, 0-1024 characters
+        caption: Option<String>,
+        /// Special entities like usernames, URLs, bot commands, etc. that
+        /// appear in the caption
+        caption_entities: Option<Vec<MessageEntity>>,
+        /// If the caption is shown above the message media
+        show_caption_above_media: bool,
+    },
 
     Game {
         /// Information about the game
@@ -233,10 +273,38 @@ pub enum MessageContent {
         /// Service message: a user was shared with the bot
         content: UserShared,
     },
+    UsersShared {
+        /// Service message: users were shared with the bot
+        content: UsersShared,
+    },
     ChatShared {
         /// Service message: a chat was shared with the bot
         content: ChatShared,
     },
+    ChatBackgroundSet {
+        /// Service message: chat background set
+        content: ChatBackground,
+    },
+    BoostAdded {
+        /// Service message: user boosted the chat
+        content: ChatBoostAdded,
+    },
+    Giveaway {
+        /// Message is a scheduled giveaway
+        content: Giveaway,
+    },
+    GiveawayCreated {
+        /// Service message: a scheduled giveaway was created
+        content: GiveawayCreated,
+    },
+    GiveawayWinners {
+        /// A giveaway with public winners was completed
+        content: GiveawayWinners,
+    },
+    GiveawayCompleted {
+        /// Service message: a giveaway without public winners was completed
+        content: GiveawayCompleted,
+    },
     ProximityAlertTriggered {
         /// Service message. A user in the chat triggered another user's
         /// proximity alert while sharing Live Location.
@@ -330,6 +398,123 @@ pub struct ForwardData {
     /// True, if the message is a channel post that was automatically forwarded
     /// to the connected discussion group
     pub is_automatic_forward: bool,
+    /// Information about the origin of the forwarded message, the other
+    /// fields are filled in from this when present
+    pub origin: Option<MessageOrigin>,
+}
+
+/// This object describes the origin of a message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum MessageOrigin {
+    /// The message was originally sent by a known user.
+    #[serde(rename = "user")]
+    User {
+        /// Date the message was sent originally
+        #[serde(with = "unix_date_formatting")]
+        date: DateTime<Utc>,
+        /// User that sent the message originally
+        sender_user: User,
+    },
+    /// The message was originally sent by an unknown user.
+    #[serde(rename = "hidden_user")]
+    HiddenUser {
+        /// Date the message was sent originally
+        #[serde(with = "unix_date_formatting")]
+        date: DateTime<Utc>,
+        /// Name of the user that sent the message originally
+        sender_user_name: String,
+    },
+    /// The message was originally sent on behalf of a chat to a group chat.
+    #[serde(rename = "chat")]
+    Chat {
+        /// Date the message was sent originally
+        #[serde(with = "unix_date_formatting")]
+        date: DateTime<Utc>,
+        /// Chat that sent the message originally
+        sender_chat: Chat,
+        /// For messages originally sent by an anonymous chat administrator,
+        /// original message author signature
+        author_signature: Option<String>,
+    },
+    /// The message was originally sent to a channel chat.
+    #[serde(rename = "channel")]
+    Channel {
+        /// Date the message was sent originally
+        #[serde(with = "unix_date_formatting")]
+        date: DateTime<Utc>,
+        /// Channel chat to which the message was originally sent
+        chat: Chat,
+        /// Unique message identifier inside the chat
+        message_id: i64,
+        /// Signature of the original post author
+        author_signature: Option<String>,
+    },
+}
+
+impl MessageOrigin {
+    /// Gets the date the message was sent originally
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            Self::User {
+                date, ..
+            }
+            | Self::HiddenUser {
+                date, ..
+            }
+            | Self::Chat {
+                date, ..
+            }
+            | Self::Channel {
+                date, ..
+            } => *date,
+        }
+    }
+}
+
+impl From<MessageOrigin> for ForwardData {
+    fn from(origin: MessageOrigin) -> Self {
+        let mut data = Self {
+            from: None,
+            from_chat: None,
+            from_message_id: None,
+            signature: None,
+            sender_name: None,
+            date: origin.date(),
+            is_automatic_forward: false,
+            origin: None,
+        };
+
+        match origin.clone() {
+            MessageOrigin::User {
+                sender_user, ..
+            } => data.from = Some(sender_user),
+            MessageOrigin::HiddenUser {
+                sender_user_name, ..
+            } => data.sender_name = Some(sender_user_name),
+            MessageOrigin::Chat {
+                sender_chat,
+                author_signature,
+                ..
+            } => {
+                data.from_chat = Some(sender_chat);
+                data.signature = author_signature;
+            },
+            MessageOrigin::Channel {
+                chat,
+                message_id,
+                author_signature,
+                ..
+            } => {
+                data.from_chat = Some(chat);
+                data.from_message_id = Some(message_id);
+                data.signature = author_signature;
+            },
+        }
+
+        data.origin = Some(origin);
+        data
+    }
 }
 
 impl Message {
@@ -355,10 +540,173 @@ impl Message {
             }
             | MessageContent::Photo {
                 ref caption, ..
+            }
+            | MessageContent::PaidMedia {
+                ref caption, ..
             } => caption.clone(),
             _ => None,
         }
     }
+
+    /// Gets the text or caption of the message together with the entities
+    /// that appear in it
+    pub(crate) fn text_with_entities(&self) -> Option<(&str, &[MessageEntity])> {
+        match self.content {
+            MessageContent::Text {
+                ref content,
+                ref entities,
+            } => Some((content, entities)),
+            MessageContent::Audio {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::Document {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::Animation {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::Video {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::Voice {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::Photo {
+                ref caption,
+                ref caption_entities,
+                ..
+            }
+            | MessageContent::PaidMedia {
+                ref caption,
+                ref caption_entities,
+                ..
+            } => Some((
+                caption.as_deref()?,
+                caption_entities.as_deref().unwrap_or_default(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Gets the special entities that appear in the text or caption of the
+    /// message
+    pub fn entities(&self) -> &[MessageEntity] {
+        self.text_with_entities()
+            .map_or(&[], |(_, entities)| entities)
+    }
+
+    /// Gets the part of the text or caption of the message that the given
+    /// entity applies to, which is empty if the message has no text
+    pub fn entity_text(&self, entity: &MessageEntity) -> &str {
+        self.text_with_entities()
+            .map_or("", |(text, _)| entity.text_block().get_str(text))
+    }
+
+    /// Gets the usernames mentioned in the message, including the `@`
+    pub fn mentions(&self) -> Vec<String> {
+        self.entity_texts(|e| matches!(e, MessageEntity::Mention(_)))
+    }
+
+    /// Gets the urls in the message, both the ones written out in the text and
+    /// the ones behind clickable text links
+    pub fn urls(&self) -> Vec<String> {
+        self.entities()
+            .iter()
+            .filter_map(|e| match e {
+                MessageEntity::Url(_) => Some(self.entity_text(e).to_owned()),
+                MessageEntity::TextLink(link) => Some(link.url.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Gets the bot commands in the message, like `/start@bot_name`
+    pub fn bot_commands(&self) -> Vec<String> {
+        self.entity_texts(|e| matches!(e, MessageEntity::BotCommand(_)))
+    }
+
+    fn entity_texts(&self, filter: impl Fn(&MessageEntity) -> bool) -> Vec<String> {
+        self.entities()
+            .iter()
+            .filter(|e| filter(e))
+            .map(|e| self.entity_text(e).to_owned())
+            .collect()
+    }
+
+    /// Gets the identifier of the forum topic the message was sent in, if it
+    /// was sent in one
+    pub fn topic_thread_id(&self) -> Option<i64> {
+        if self.is_topic_message {
+            self.message_thread_id
+        } else {
+            None
+        }
+    }
+
+    /// Gets the forum topic the message was sent in, which is the "General"
+    /// topic for messages in a forum that weren't sent in a specific topic.
+    /// Returns `None` if the message wasn't sent in a forum.
+    pub fn topic_id(&self) -> Option<TopicId> {
+        match &self.chat {
+            Chat::SuperGroup(c) if c.is_forum => Some(
+                self.topic_thread_id()
+                    .map_or(TopicId::General, TopicId::from),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Gets the sender of the original message, which was previously the
+    /// `forward_from` field
+    #[deprecated(note = "telegram replaced `forward_from` with `forward_origin`")]
+    pub fn forward_from(&self) -> Option<&User> {
+        self.forward_data.as_ref()?.from.as_ref()
+    }
+
+    /// Gets the channel the original message was sent in, which was previously
+    /// the `forward_from_chat` field
+    #[deprecated(note = "telegram replaced `forward_from_chat` with `forward_origin`")]
+    pub fn forward_from_chat(&self) -> Option<&Chat> {
+        self.forward_data.as_ref()?.from_chat.as_ref()
+    }
+
+    /// Gets the identifier of the original message in its channel, which was
+    /// previously the `forward_from_message_id` field
+    #[deprecated(note = "telegram replaced `forward_from_message_id` with `forward_origin`")]
+    pub fn forward_from_message_id(&self) -> Option<i64> {
+        self.forward_data.as_ref()?.from_message_id
+    }
+
+    /// Gets the signature of the author of the original post, which was
+    /// previously the `forward_signature` field
+    #[deprecated(note = "telegram replaced `forward_signature` with `forward_origin`")]
+    pub fn forward_signature(&self) -> Option<&str> {
+        self.forward_data.as_ref()?.signature.as_deref()
+    }
+
+    /// Gets the name of the sender of the original message if they hide their
+    /// account, which was previously the `forward_sender_name` field
+    #[deprecated(note = "telegram replaced `forward_sender_name` with `forward_origin`")]
+    pub fn forward_sender_name(&self) -> Option<&str> {
+        self.forward_data.as_ref()?.sender_name.as_deref()
+    }
+
+    /// Gets the date the original message was sent, which was previously the
+    /// `forward_date` field
+    #[deprecated(note = "telegram replaced `forward_date` with `forward_origin`")]
+    pub fn forward_date(&self) -> Option<DateTime<Utc>> {
+        self.forward_data.as_ref().map(|d| d.date)
+    }
 }
 
 impl From<RawMessage> for Message {
@@ -368,9 +716,13 @@ impl From<RawMessage> for Message {
         let message_thread_id = raw.message_thread_id;
         let from = raw.from;
         let sender_chat = raw.sender_chat.map(Into::into);
+        let sender_boost_count = raw.sender_boost_count;
+        let sender_business_bot = raw.sender_business_bot;
         let date = raw.date;
         let chat = raw.chat.into();
+        let business_connection_id = raw.business_connection_id;
         let reply_to_message = raw.reply_to_message.map(|r| Box::new((*r).into()));
+        let reply_to_story = raw.reply_to_story;
         let via_bot = raw.via_bot;
         let edit_date = raw.edit_date;
         let author_signature = raw.author_signature;
@@ -379,8 +731,14 @@ impl From<RawMessage> for Message {
         let reply_markup = raw.reply_markup;
         let has_protected_content = raw.has_protected_content;
         let is_topic_message = raw.is_topic_message;
+        let link_preview_options = raw.link_preview_options;
 
-        let forward_data = if let Some(d) = raw.forward_date {
+        let forward_data = if let Some(origin) = raw.forward_origin {
+            Some(ForwardData {
+                is_automatic_forward: raw.is_automatic_forward,
+                ..origin.into()
+            })
+        } else if let Some(d) = raw.forward_date {
             Some(ForwardData {
                 from: raw.forward_from,
                 from_chat: raw.forward_from_chat.map(Into::into),
@@ -389,6 +747,7 @@ impl From<RawMessage> for Message {
                 sender_name: raw.forward_sender_name,
                 date: d,
                 is_automatic_forward: raw.is_automatic_forward,
+                origin: None,
             })
         } else {
             None
@@ -399,16 +758,21 @@ impl From<RawMessage> for Message {
             message_thread_id,
             from,
             sender_chat,
+            sender_boost_count,
+            sender_business_bot,
             date,
             chat,
+            business_connection_id,
             forward_data,
             is_topic_message,
             reply_to_message,
+            reply_to_story,
             via_bot,
             edit_date,
             author_signature,
             has_protected_content,
             content,
+            link_preview_options,
             connected_website,
             passport_data,
             reply_markup,
@@ -426,6 +790,7 @@ impl From<RawMessage> for Message {
                 caption_entities: raw.caption_entities,
                 media_group_id: raw.media_group_id,
                 has_spoiler: raw.has_media_spoiler,
+                show_caption_above_media: raw.show_caption_above_media,
             });
         } else if let Some(c) = raw.animation {
             return fill_in_content(MessageContent::Animation {
@@ -433,6 +798,7 @@ impl From<RawMessage> for Message {
                 caption: raw.caption,
                 caption_entities: raw.caption_entities,
                 has_spoiler: raw.has_media_spoiler,
+                show_caption_above_media: raw.show_caption_above_media,
             });
         } else if let Some(c) = raw.photo {
             return fill_in_content(MessageContent::Photo {
@@ -441,6 +807,14 @@ impl From<RawMessage> for Message {
                 caption_entities: raw.caption_entities,
                 media_group_id: raw.media_group_id,
                 has_spoiler: raw.has_media_spoiler,
+                show_caption_above_media: raw.show_caption_above_media,
+            });
+        } else if let Some(c) = raw.paid_media {
+            return fill_in_content(MessageContent::PaidMedia {
+                content: c,
+                caption: raw.caption,
+                caption_entities: raw.caption_entities,
+                show_caption_above_media: raw.show_caption_above_media,
             });
         } else if let Some(c) = raw.pinned_message {
             return fill_in_content(MessageContent::PinnedMessage {
@@ -511,7 +885,14 @@ impl From<RawMessage> for Message {
         content!(raw.invoice, Invoice);
         content!(raw.successful_payment, SuccessfulPayment);
         content!(raw.user_shared, UserShared);
+        content!(raw.users_shared, UsersShared);
         content!(raw.chat_shared, ChatShared);
+        content!(raw.chat_background_set, ChatBackgroundSet);
+        content!(raw.boost_added, BoostAdded);
+        content!(raw.giveaway, Giveaway);
+        content!(raw.giveaway_created, GiveawayCreated);
+        content!(raw.giveaway_winners, GiveawayWinners);
+        content!(raw.giveaway_completed, GiveawayCompleted);
         content!(raw.proximity_alert_triggered, ProximityAlertTriggered);
         content!(raw.voice_chat_scheduled, VideoChatScheduled);
         content!(raw.voice_chat_started, VideoChatStarted);
@@ -548,14 +929,19 @@ impl From<Message> for RawMessage {
             message_thread_id: message.message_thread_id,
             from: message.from,
             sender_chat: message.sender_chat.map(Into::into),
+            sender_boost_count: message.sender_boost_count,
+            sender_business_bot: message.sender_business_bot,
             date: message.date,
             chat: message.chat.into(),
+            business_connection_id: message.business_connection_id,
             reply_to_message: message.reply_to_message.map(|r| Box::new((*r).into())),
+            reply_to_story: message.reply_to_story,
             via_bot: message.via_bot,
             edit_date: message.edit_date,
             media_group_id: None,
             author_signature: message.author_signature,
 
+            forward_origin: None,
             forward_date: None,
             forward_sender_name: None,
             forward_signature: None,
@@ -565,17 +951,20 @@ impl From<Message> for RawMessage {
             is_topic_message: message.is_topic_message,
             is_automatic_forward: false,
             has_media_spoiler: false,
+            show_caption_above_media: false,
 
             has_protected_content: message.has_protected_content,
 
             text: None,
             entities: None,
+            link_preview_options: message.link_preview_options,
             caption_entities: None,
             audio: None,
             document: None,
             animation: None,
             game: None,
             photo: None,
+            paid_media: None,
             sticker: None,
             video: None,
             story: None,
@@ -602,7 +991,14 @@ impl From<Message> for RawMessage {
             invoice: None,
             successful_payment: None,
             user_shared: None,
+            users_shared: None,
             chat_shared: None,
+            chat_background_set: None,
+            boost_added: None,
+            giveaway: None,
+            giveaway_created: None,
+            giveaway_winners: None,
+            giveaway_completed: None,
             proximity_alert_triggered: None,
             voice_chat_scheduled: None,
             voice_chat_started: None,
@@ -631,6 +1027,7 @@ impl From<Message> for RawMessage {
             ret.forward_from = d.from;
             ret.forward_from_chat = d.from_chat.map(Into::into);
             ret.is_automatic_forward = d.is_automatic_forward;
+            ret.forward_origin = d.origin;
         }
 
         match message.content {
@@ -667,11 +1064,13 @@ impl From<Message> for RawMessage {
                 caption,
                 caption_entities,
                 has_spoiler,
+                show_caption_above_media,
             } => {
                 ret.animation = Some(content);
                 ret.caption = caption;
                 ret.caption_entities = caption_entities;
                 ret.has_media_spoiler = has_spoiler;
+                ret.show_caption_above_media = show_caption_above_media;
                 ret
             },
             MessageContent::Voice {
@@ -690,12 +1089,14 @@ impl From<Message> for RawMessage {
                 caption_entities,
                 media_group_id,
                 has_spoiler,
+                show_caption_above_media,
             } => {
                 ret.video = Some(content);
                 ret.caption = caption;
                 ret.caption_entities = caption_entities;
                 ret.media_group_id = media_group_id;
                 ret.has_media_spoiler = has_spoiler;
+                ret.show_caption_above_media = show_caption_above_media;
                 ret
             },
             MessageContent::Photo {
@@ -704,12 +1105,26 @@ impl From<Message> for RawMessage {
                 caption_entities,
                 media_group_id,
                 has_spoiler,
+                show_caption_above_media,
             } => {
                 ret.photo = Some(content);
                 ret.caption = caption;
                 ret.caption_entities = caption_entities;
                 ret.media_group_id = media_group_id;
                 ret.has_media_spoiler = has_spoiler;
+                ret.show_caption_above_media = show_caption_above_media;
+                ret
+            },
+            MessageContent::PaidMedia {
+                content,
+                caption,
+                caption_entities,
+                show_caption_above_media,
+            } => {
+                ret.paid_media = Some(content);
+                ret.caption = caption;
+                ret.caption_entities = caption_entities;
+                ret.show_caption_above_media = show_caption_above_media;
                 ret
             },
             MessageContent::Game {
@@ -820,12 +1235,30 @@ impl From<Message> for RawMessage {
                 ret.user_shared = Some(content);
                 ret
             },
+            MessageContent::UsersShared {
+                content,
+            } => {
+                ret.users_shared = Some(content);
+                ret
+            },
             MessageContent::ChatShared {
                 content,
             } => {
                 ret.chat_shared = Some(content);
                 ret
             },
+            MessageContent::ChatBackgroundSet {
+                content,
+            } => {
+                ret.chat_background_set = Some(content);
+                ret
+            },
+            MessageContent::BoostAdded {
+                content,
+            } => {
+                ret.boost_added = Some(content);
+                ret
+            },
             MessageContent::PinnedMessage {
                 content,
             } => {
@@ -892,6 +1325,30 @@ impl From<Message> for RawMessage {
                 ret.story = Some(content);
                 ret
             },
+            MessageContent::Giveaway {
+                content,
+            } => {
+                ret.giveaway = Some(content);
+                ret
+            },
+            MessageContent::GiveawayCreated {
+                content,
+            } => {
+                ret.giveaway_created = Some(content);
+                ret
+            },
+            MessageContent::GiveawayWinners {
+                content,
+            } => {
+                ret.giveaway_winners = Some(content);
+                ret
+            },
+            MessageContent::GiveawayCompleted {
+                content,
+            } => {
+                ret.giveaway_completed = Some(content);
+                ret
+            },
             MessageContent::DeleteChatPhoto => {
                 ret.delete_chat_photo = true;
                 ret
@@ -949,6 +1406,106 @@ impl Serialize for Message {
     }
 }
 
+/// This object describes a message that can be inaccessible to the bot, which
+/// is the case when the message is too old. Telegram then only sends the chat
+/// and the identifier of the message, with a date of 0.
+#[allow(clippy::large_enum_variant)] // Using a box makes it more user-unfriendly
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaybeInaccessibleMessage {
+    /// The message is accessible to the bot
+    Message(Message),
+    /// The message was deleted or is otherwise inaccessible to the bot
+    Inaccessible {
+        /// Chat the message belonged to
+        chat: Chat,
+        /// Unique message identifier inside the chat
+        message_id: i64,
+    },
+}
+
+impl MaybeInaccessibleMessage {
+    /// Gets the id of the chat the message belongs to
+    pub fn get_chat_id(&self) -> i64 {
+        match self {
+            Self::Message(m) => m.chat.get_id(),
+            Self::Inaccessible {
+                chat, ..
+            } => chat.get_id(),
+        }
+    }
+
+    /// Gets the unique identifier of the message inside its chat
+    pub fn get_message_id(&self) -> i64 {
+        match self {
+            Self::Message(m) => m.message_id,
+            Self::Inaccessible {
+                message_id, ..
+            } => *message_id,
+        }
+    }
+
+    /// Gets the message if it is accessible to the bot
+    pub fn accessible(&self) -> Option<&Message> {
+        match self {
+            Self::Message(m) => Some(m),
+            Self::Inaccessible {
+                ..
+            } => None,
+        }
+    }
+}
+
+impl From<Message> for MaybeInaccessibleMessage {
+    fn from(message: Message) -> Self {
+        Self::Message(message)
+    }
+}
+
+impl<'de> Deserialize<'de> for MaybeInaccessibleMessage {
+    fn deserialize<D>(deserializer: D) -> Result<MaybeInaccessibleMessage, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let raw: RawMessage = Deserialize::deserialize(deserializer)?;
+
+        if raw.date.timestamp() == 0 {
+            Ok(Self::Inaccessible {
+                chat: raw.chat.into(),
+                message_id: raw.message_id,
+            })
+        } else {
+            Ok(Self::Message(raw.into()))
+        }
+    }
+}
+
+impl Serialize for MaybeInaccessibleMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[derive(Serialize)]
+        struct InaccessibleMessage {
+            chat: RawChat,
+            message_id: i64,
+            date: i64,
+        }
+
+        match self {
+            Self::Message(m) => m.serialize(serializer),
+            Self::Inaccessible {
+                chat,
+                message_id,
+            } => InaccessibleMessage {
+                chat: chat.clone().into(),
+                message_id: *message_id,
+                date: 0,
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
 /// This object represents a unique message identifier.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct MessageId {
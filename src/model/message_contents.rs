@@ -329,6 +329,7 @@ pub struct LoginUrl {
     /// [Checking authorization]: https://core.telegram.org/widgets/login#checking-authorization
     pub url: String,
     /// New text of the button in forwarded messages.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub forward_text: Option<String>,
     /// Username of a bot, which will be used for user authorization. See
     /// [Setting up a bot][setup] for more details. If not specified, the
@@ -338,6 +339,7 @@ pub struct LoginUrl {
     ///
     /// [setup]: https://core.telegram.org/widgets/login#setting-up-a-bot
     /// [linking]: https://core.telegram.org/widgets/login#linking-your-domain-to-the-bot
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub bot_username: Option<String>,
     /// Pass True to request the permission for your bot to send messages to the
     /// user.
@@ -345,6 +347,22 @@ pub struct LoginUrl {
     pub request_write_access: bool,
 }
 
+impl LoginUrl {
+    /// Creates a `LoginUrl` for the given url, the authorization data added to
+    /// it can be checked using [`verify_login_data`].
+    ///
+    /// [`verify_login_data`]: ../utils/login/fn.verify_login_data.html
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn new(url: impl ToString) -> Self {
+        Self {
+            url: url.to_string(),
+            forward_text: None,
+            bot_username: None,
+            request_write_access: false,
+        }
+    }
+}
+
 /// This object represents the content of a service message, sent whenever a
 /// user in the chat triggers a proximity alert set by another user.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -452,27 +470,38 @@ pub struct GeneralForumTopicHidden {}
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct GeneralForumTopicUnhidden {}
 
-/// This object represents a service message about a user allowing a bot added
-/// to the attachment menu to write messages. Currently holds no information.
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+/// This object represents a service message about a user allowing a bot to
+/// write messages after adding it to the attachment menu, launching a Web App
+/// from a link, or accepting an explicit request from a Web App sent by the
+/// method [requestWriteAccess].
+///
+/// [requestWriteAccess]: https://core.telegram.org/bots/webapps#initializing-mini-apps
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
 pub struct WriteAccessAllowed {
     /// True, if the access was granted after the user accepted an explicit
     /// request from a Web App sent by the method [requestWriteAccess]
     ///
     /// [requestWriteAccess]: https://core.telegram.org/bots/webapps#initializing-mini-apps
+    #[serde(default)]
     pub from_request: bool,
-    /// Name of the Web App which was launched from a link
+    /// Name of the Web App, if the access was granted when the Web App was
+    /// launched from a link
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub web_app_name: Option<String>,
-    /// If the access was granted when the bot was added to the attachment or
-    /// side menu
+    /// True, if the access was granted when the bot was added to the
+    /// attachment or side menu
     #[serde(default)]
-    pub rom_attachment_menu: bool,
+    pub from_attachment_menu: bool,
 }
 
 /// This object contains information about the user whose identifier was shared
-/// with the bot using a [`KeyboardButtonRequestUser`] button.
+/// with the bot using a [`KeyboardButtonRequestUsers`] button.
 ///
-/// [`KeyboardButtonRequestUser`]: ../model/struct.KeyboardButtonRequestUser.html
+/// **Note:** this is only sent by older versions of the bot api, which have
+/// been superseded by [`UsersShared`].
+///
+/// [`KeyboardButtonRequestUsers`]: ../model/struct.KeyboardButtonRequestUsers.html
+/// [`UsersShared`]: ../model/struct.UsersShared.html
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UserShared {
     /// Identifier of the request
@@ -484,6 +513,39 @@ pub struct UserShared {
     pub user_id: i64,
 }
 
+/// This object contains information about a user that was shared with the bot
+/// using a [`KeyboardButtonRequestUsers`] button.
+///
+/// [`KeyboardButtonRequestUsers`]: ../model/struct.KeyboardButtonRequestUsers.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SharedUser {
+    /// Identifier of the shared user.
+    /// The bot may not have access to the user and could be unable to use this
+    /// identifier, unless the user is already known to the bot by some other
+    /// means.
+    pub user_id: i64,
+    /// First name of the user, if the name was requested by the bot
+    pub first_name: Option<String>,
+    /// Last name of the user, if the name was requested by the bot
+    pub last_name: Option<String>,
+    /// Username of the user, if the username was requested by the bot
+    pub username: Option<String>,
+    /// Available sizes of the chat photo, if the photo was requested by the bot
+    pub photo: Option<Vec<PhotoSize>>,
+}
+
+/// This object contains information about the users whose identifiers were
+/// shared with the bot using a [`KeyboardButtonRequestUsers`] button.
+///
+/// [`KeyboardButtonRequestUsers`]: ../model/struct.KeyboardButtonRequestUsers.html
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UsersShared {
+    /// Identifier of the request
+    pub request_id: i64,
+    /// Information about users shared with the bot
+    pub users: Vec<SharedUser>,
+}
+
 /// This object contains information about the user whose identifier was shared
 /// with the bot using a [`KeyboardButtonRequestChat`] button.
 ///
@@ -497,9 +559,105 @@ pub struct ChatShared {
     /// identifier, unless the chat is already known to the bot by some other
     /// means.
     pub chat_id: i64,
+    /// Title of the chat, if the title was requested by the bot
+    pub title: Option<String>,
+    /// Username of the chat, if the username was requested by the bot and
+    /// available
+    pub username: Option<String>,
+    /// Available sizes of the chat photo, if the photo was requested by the bot
+    pub photo: Option<Vec<PhotoSize>>,
+}
+
+/// This object represents a story.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Story {
+    /// Chat that posted the story
+    pub chat: Chat,
+    /// Unique identifier for the story in the chat
+    pub id: i64,
+}
+
+/// This object represents a message about a scheduled giveaway.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Giveaway {
+    /// The list of chats which the user must join to participate in the
+    /// giveaway
+    pub chats: Vec<Chat>,
+    /// Point in time when winners of the giveaway will be selected
+    #[serde(with = "unix_date_formatting")]
+    pub winners_selection_date: DateTime<Utc>,
+    /// The number of users which are supposed to be selected as winners of
+    /// the giveaway
+    pub winner_count: i64,
+    /// True, if only users who join the chats after the giveaway started
+    /// should be eligible to win
+    #[serde(default)]
+    pub only_new_members: bool,
+    /// True, if the list of giveaway winners will be visible to everyone
+    #[serde(default)]
+    pub has_public_winners: bool,
+    /// Description of additional giveaway prize
+    pub prize_description: Option<String>,
+    /// A list of two-letter ISO 3166-1 alpha-2 country codes indicating the
+    /// countries from which eligible users for the giveaway must come. If
+    /// empty, then all users can participate in the giveaway.
+    #[serde(default)]
+    pub country_codes: Vec<String>,
+    /// The number of months the Telegram Premium subscription won from the
+    /// giveaway will be active for
+    pub premium_subscription_month_count: Option<i64>,
 }
 
-/// This object represents a message about a forwarded story in the chat.
-/// Currently holds no information.
+/// This object represents a service message about the creation of a scheduled
+/// giveaway. Currently holds no information.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-pub struct Story {}
+pub struct GiveawayCreated {}
+
+/// This object represents a message about the completion of a giveaway with
+/// public winners.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GiveawayWinners {
+    /// The chat that created the giveaway
+    pub chat: Chat,
+    /// Identifier of the message with the giveaway in the chat
+    pub giveaway_message_id: i64,
+    /// Point in time when winners of the giveaway were selected
+    #[serde(with = "unix_date_formatting")]
+    pub winners_selection_date: DateTime<Utc>,
+    /// Total number of winners in the giveaway
+    pub winner_count: i64,
+    /// List of up to 100 winners of the giveaway
+    pub winners: Vec<User>,
+    /// The number of other chats the user had to join in order to be eligible
+    /// for the giveaway
+    pub additional_chat_count: Option<i64>,
+    /// The number of months the Telegram Premium subscription won from the
+    /// giveaway will be active for
+    pub premium_subscription_month_count: Option<i64>,
+    /// Number of undistributed prizes
+    pub unclaimed_prize_count: Option<i64>,
+    /// True, if only users who had joined the chats after the giveaway started
+    /// were eligible to win
+    #[serde(default)]
+    pub only_new_members: bool,
+    /// True, if the giveaway was canceled because the payment for it was
+    /// refunded
+    #[serde(default)]
+    pub was_refunded: bool,
+    /// Description of additional giveaway prize
+    pub prize_description: Option<String>,
+}
+
+/// This object represents a service message about the completion of a
+/// giveaway without public winners.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GiveawayCompleted {
+    /// Number of winners in the giveaway
+    pub winner_count: i64,
+    /// Number of undistributed prizes
+    pub unclaimed_prize_count: Option<i64>,
+    /// Message with the giveaway that was completed, if it wasn't deleted
+    pub giveaway_message: Option<Box<super::Message>>,
+}
+
+crate::utils::macros::impl_thumb_accessor!(PhotoSize, Audio, Document, Animation, Video, VideoNote);
@@ -60,6 +60,39 @@ pub enum MessageEntity {
     CustomEmoji(InlineCustomEmoji),
 }
 
+impl MessageEntity {
+    /// Gets the part of the text the entity applies to
+    pub fn text_block(&self) -> &TextBlock {
+        match self {
+            Self::Mention(t)
+            | Self::HashTag(t)
+            | Self::CashTag(t)
+            | Self::BotCommand(t)
+            | Self::Url(t)
+            | Self::Email(t)
+            | Self::PhoneNumber(t)
+            | Self::Bold(t)
+            | Self::Italic(t)
+            | Self::Underline(t)
+            | Self::StrikeThrough(t)
+            | Self::Spoiler(t)
+            | Self::Code(t) => t,
+            Self::Pre(Pre {
+                text_block, ..
+            })
+            | Self::TextLink(TextLink {
+                text_block, ..
+            })
+            | Self::TextMention(TextMention {
+                text_block, ..
+            })
+            | Self::CustomEmoji(InlineCustomEmoji {
+                text_block, ..
+            }) => text_block,
+        }
+    }
+}
+
 /// A monowidth code block
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Pre {
@@ -2,6 +2,9 @@
 
 pub(crate) mod utils;
 
+mod background;
+mod boosts;
+mod business;
 mod chat;
 mod commands;
 mod games;
@@ -11,13 +14,18 @@ mod message;
 mod message_contents;
 mod message_entity;
 mod other;
+mod paid_media;
 mod payments;
 pub mod raw;
+mod reactions;
 mod stickers;
 mod telegram_passport;
 mod update;
 mod user;
 
+pub use background::*;
+pub use boosts::*;
+pub use business::*;
 pub use chat::*;
 pub use commands::*;
 pub use games::*;
@@ -27,7 +35,9 @@ pub use message::*;
 pub use message_contents::*;
 pub use message_entity::*;
 pub use other::*;
+pub use paid_media::*;
 pub use payments::*;
+pub use reactions::*;
 pub use stickers::*;
 pub use telegram_passport::*;
 pub use update::*;
@@ -2,6 +2,7 @@ use super::{
     utils::unix_date_formatting,
     ForceReply,
     InlineKeyboardMarkup,
+    MaybeInaccessibleMessage,
     Message,
     ReplyKeyboardMarkup,
     ReplyKeyboardRemove,
@@ -28,9 +29,9 @@ pub struct CallbackQuery {
     /// Sender
     pub from: User,
     /// Message with the callback button that originated the query.
-    /// Note that message content and message date will not be available if the
-    /// message is too old
-    pub message: Option<Message>,
+    /// Note that only the chat and message id will be available if the message
+    /// is too old
+    pub message: Option<MaybeInaccessibleMessage>,
     /// Identifier of the message sent via the bot in inline mode, that
     /// originated the query.
     pub inline_message_id: Option<Message>,
@@ -62,6 +63,104 @@ pub enum ParseMode {
     HTML,
 }
 
+impl ParseMode {
+    /// Escapes the characters in the text that have a meaning in this parse
+    /// mode, so the text is shown as is
+    pub fn escape(&self, text: &str) -> String {
+        let special: &[char] = match self {
+            Self::MarkdownV2 => &[
+                '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}',
+                '.', '!', '\\',
+            ],
+            Self::Markdown => &['_', '*', '`', '['],
+            Self::HTML => {
+                return text
+                    .replace('&', "&amp;")
+                    .replace('<', "&lt;")
+                    .replace('>', "&gt;")
+            },
+        };
+
+        let mut escaped = String::with_capacity(text.len());
+        for c in text.chars() {
+            if special.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+/// Describes the options used for link preview generation.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkPreviewOptions {
+    /// True, if the link preview is disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_disabled: Option<bool>,
+    /// URL to use for the link preview. If empty, then the first URL found in
+    /// the message text will be used
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// True, if the media in the link preview is supposed to be shrunk;
+    /// ignored if the URL isn't explicitly specified or media size change
+    /// isn't supported for the preview
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefer_small_media: Option<bool>,
+    /// True, if the media in the link preview is supposed to be enlarged;
+    /// ignored if the URL isn't explicitly specified or media size change
+    /// isn't supported for the preview
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefer_large_media: Option<bool>,
+    /// True, if the link preview must be shown above the message text;
+    /// otherwise, the link preview will be shown below the message text
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub show_above_text: Option<bool>,
+}
+
+impl LinkPreviewOptions {
+    /// Creates a new `LinkPreviewOptions` with none of the options set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether the link preview is disabled
+    #[must_use]
+    pub fn is_disabled(mut self, is_disabled: bool) -> Self {
+        self.is_disabled = Some(is_disabled);
+        self
+    }
+
+    /// Sets the url to use for the link preview
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn url(mut self, url: impl ToString) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// Sets whether the media in the link preview should be shrunk
+    #[must_use]
+    pub fn prefer_small_media(mut self, prefer_small_media: bool) -> Self {
+        self.prefer_small_media = Some(prefer_small_media);
+        self
+    }
+
+    /// Sets whether the media in the link preview should be enlarged
+    #[must_use]
+    pub fn prefer_large_media(mut self, prefer_large_media: bool) -> Self {
+        self.prefer_large_media = Some(prefer_large_media);
+        self
+    }
+
+    /// Sets whether the link preview should be shown above the message text
+    #[must_use]
+    pub fn show_above_text(mut self, show_above_text: bool) -> Self {
+        self.show_above_text = Some(show_above_text);
+        self
+    }
+}
+
 /// An action indicating to a user what they are about to receive
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum ChatAction {
@@ -1,4 +1,5 @@
-use super::User;
+use super::{utils::unix_date_formatting, User};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 /// This object contains basic information about an invoice.
@@ -133,3 +134,78 @@ pub struct LabeledPrice {
     /// (2 for the majority of currencies).
     pub amount: i64,
 }
+
+/// Contains a list of Telegram Star transactions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StarTransactions {
+    /// The list of transactions
+    pub transactions: Vec<StarTransaction>,
+}
+
+/// Describes a Telegram Star transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StarTransaction {
+    /// Unique identifier of the transaction. Coincides with the identifier of
+    /// the original transaction for refund transactions. Coincides with
+    /// `SuccessfulPayment::telegram_payment_charge_id` for successful
+    /// incoming payments from users.
+    pub id: String,
+    /// Number of Telegram Stars transferred by the transaction
+    pub amount: i64,
+    /// Point in time when the transaction was created
+    #[serde(with = "unix_date_formatting")]
+    pub date: DateTime<Utc>,
+    /// Source of an incoming transaction (e.g., a user purchasing goods or
+    /// services, Fragment refunding a failed withdrawal). Only for incoming
+    /// transactions
+    pub source: Option<TransactionPartner>,
+    /// Receiver of an outgoing transaction (e.g., a user for a purchase
+    /// refund, Fragment for a withdrawal). Only for outgoing transactions
+    pub receiver: Option<TransactionPartner>,
+}
+
+/// This object describes the source of a transaction, or its recipient for
+/// outgoing transactions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum TransactionPartner {
+    /// Describes a withdrawal transaction with Fragment.
+    #[serde(rename = "fragment")]
+    Fragment {
+        /// State of the transaction if the transaction is outgoing
+        withdrawal_state: Option<RevenueWithdrawalState>,
+    },
+    /// Describes a transaction with a user.
+    #[serde(rename = "user")]
+    User {
+        /// Information about the user
+        user: User,
+    },
+    /// Describes a withdrawal transaction to the Telegram Ads platform.
+    #[serde(rename = "telegram_ads")]
+    TelegramAds,
+    /// Describes a transaction with an unknown source or recipient.
+    #[serde(rename = "other")]
+    Other,
+}
+
+/// This object describes the state of a revenue withdrawal operation.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum RevenueWithdrawalState {
+    /// The withdrawal is in progress.
+    #[serde(rename = "pending")]
+    Pending,
+    /// The withdrawal succeeded.
+    #[serde(rename = "succeeded")]
+    Succeeded {
+        /// Point in time when the withdrawal was completed
+        #[serde(with = "unix_date_formatting")]
+        date: DateTime<Utc>,
+        /// An HTTPS URL that can be used to see transaction details
+        url: String,
+    },
+    /// The withdrawal failed and the transaction was refunded.
+    #[serde(rename = "failed")]
+    Failed,
+}
@@ -5,7 +5,13 @@ use super::{
     message_contents::*,
     message_entity::*,
     utils::unix_date_formatting,
+    BusinessConnection,
+    BusinessMessagesDeleted,
     CallbackQuery,
+    ChatBackground,
+    ChatBoostAdded,
+    ChatBoostRemoved,
+    ChatBoostUpdated,
     ChatJoinRequest,
     ChatLocation,
     ChatMemberUpdated,
@@ -16,6 +22,10 @@ use super::{
     InlineKeyboardMarkup,
     InlineQuery,
     Invoice,
+    LinkPreviewOptions,
+    MessageReactionCountUpdated,
+    MessageReactionUpdated,
+    PaidMediaInfo,
     PassportData,
     PreCheckoutQuery,
     ShippingQuery,
@@ -33,10 +43,14 @@ pub struct RawMessage {
     pub message_thread_id: Option<i64>,
     pub from: Option<super::User>,
     pub sender_chat: Option<RawChat>,
+    pub sender_boost_count: Option<i64>,
+    pub sender_business_bot: Option<User>,
     #[serde(with = "unix_date_formatting")]
     pub date: DateTime<Utc>,
     pub chat: RawChat,
+    pub business_connection_id: Option<String>,
 
+    pub forward_origin: Option<super::MessageOrigin>,
     pub forward_from: Option<super::User>,
     pub forward_from_chat: Option<RawChat>,
     pub forward_from_message_id: Option<i64>,
@@ -51,6 +65,7 @@ pub struct RawMessage {
     pub is_automatic_forward: bool,
 
     pub reply_to_message: Option<Box<RawMessage>>,
+    pub reply_to_story: Option<Story>,
     pub via_bot: Option<User>,
 
     #[serde(default)]
@@ -61,18 +76,22 @@ pub struct RawMessage {
     pub has_protected_content: bool,
     #[serde(default)]
     pub has_media_spoiler: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub show_caption_above_media: bool,
 
     pub media_group_id: Option<String>,
     pub author_signature: Option<String>,
 
     pub text: Option<String>,
     pub entities: Option<Vec<MessageEntity>>,
+    pub link_preview_options: Option<LinkPreviewOptions>,
     pub caption_entities: Option<Vec<MessageEntity>>,
     pub audio: Option<Audio>,
     pub document: Option<Document>,
     pub animation: Option<Animation>,
     pub game: Option<Game>,
     pub photo: Option<Vec<PhotoSize>>,
+    pub paid_media: Option<PaidMediaInfo>,
     pub sticker: Option<Sticker>,
     pub video: Option<Video>,
     pub story: Option<Story>,
@@ -108,7 +127,15 @@ pub struct RawMessage {
     pub successful_payment: Option<SuccessfulPayment>,
 
     pub user_shared: Option<UserShared>,
+    pub users_shared: Option<UsersShared>,
     pub chat_shared: Option<ChatShared>,
+    pub chat_background_set: Option<ChatBackground>,
+    pub boost_added: Option<ChatBoostAdded>,
+
+    pub giveaway: Option<Giveaway>,
+    pub giveaway_created: Option<GiveawayCreated>,
+    pub giveaway_winners: Option<GiveawayWinners>,
+    pub giveaway_completed: Option<GiveawayCompleted>,
 
     pub connected_website: Option<String>,
     pub write_access_allowed: Option<WriteAccessAllowed>,
@@ -322,4 +349,33 @@ pub struct RawUpdate {
     /// can_invite_users administrator right in the chat to receive these
     /// updates.
     pub chat_join_request: Option<ChatJoinRequest>,
+    /// A reaction to a message was changed by a user. The bot must be an
+    /// administrator in the chat and must explicitly specify
+    /// “`message_reaction`” in the list of `allowed_updates` to receive these
+    /// updates.
+    pub message_reaction: Option<MessageReactionUpdated>,
+    /// Reactions to a message with anonymous reactions were changed. The bot
+    /// must be an administrator in the chat and must explicitly specify
+    /// “`message_reaction_count`” in the list of `allowed_updates` to
+    /// receive these updates.
+    pub message_reaction_count: Option<MessageReactionCountUpdated>,
+    /// A chat boost was added or changed. The bot must be an administrator in
+    /// the chat to receive these updates.
+    pub chat_boost: Option<ChatBoostUpdated>,
+    /// A boost was removed from a chat. The bot must be an administrator in
+    /// the chat to receive these updates.
+    pub removed_chat_boost: Option<ChatBoostRemoved>,
+    /// The bot was connected to or disconnected from a business account, or a
+    /// user edited an existing connection with the bot.
+    pub business_connection: Option<BusinessConnection>,
+    /// New message from a connected business account.
+    pub business_message: Option<RawMessage>,
+    /// New version of a message from a connected business account.
+    pub edited_business_message: Option<RawMessage>,
+    /// Messages were deleted from a connected business account.
+    pub deleted_business_messages: Option<BusinessMessagesDeleted>,
+    /// The fields of the update that are not known, for example because the
+    /// update is of a type that was added to the bot api later on
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
 }
@@ -29,6 +29,7 @@ pub struct Sticker {
     #[serde(default)]
     pub is_video: bool,
     /// Sticker thumbnail in the .WEBP or .JPG format
+    #[serde(alias = "thumb")]
     pub thumbnail: Option<PhotoSize>,
     /// Emoji associated with the sticker
     pub emoji: Option<String>,
@@ -64,9 +65,45 @@ pub struct StickerSet {
     /// List of all set stickers
     pub stickers: Vec<Sticker>,
     /// Optional. Sticker set thumbnail in the .WEBP or .TGS format
+    #[serde(alias = "thumb")]
     pub thumbnail: Option<PhotoSize>,
 }
 
+impl StickerSet {
+    /// Returns an iterator over the stickers in the set
+    pub fn iter(&self) -> std::slice::Iter<'_, Sticker> {
+        self.stickers.iter()
+    }
+
+    /// The amount of stickers in the set
+    pub fn len(&self) -> usize {
+        self.stickers.len()
+    }
+
+    /// Returns true if the set contains no stickers
+    pub fn is_empty(&self) -> bool {
+        self.stickers.is_empty()
+    }
+}
+
+impl IntoIterator for StickerSet {
+    type Item = Sticker;
+    type IntoIter = std::vec::IntoIter<Sticker>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stickers.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a StickerSet {
+    type Item = &'a Sticker;
+    type IntoIter = std::slice::Iter<'a, Sticker>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.stickers.iter()
+    }
+}
+
 /// This object describes a sticker to be added to a sticker set.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[build_struct]
@@ -77,6 +114,8 @@ pub struct InputSticker {
     /// multipart/form-data. Animated and video stickers can't be uploaded via
     /// HTTP URL.
     pub sticker: InputFile,
+    /// Format of the added sticker
+    pub format: StickerFormat,
     /// List of 1-20 emoji associated with the sticker.
     pub emoji_list: Vec<String>,
     /// position where the mask should be placed on faces. For “mask” stickers
@@ -121,8 +160,8 @@ pub enum MaskPoint {
     Chin,
 }
 
+/// The type of a sticker
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(untagged)]
 pub enum StickerType {
     #[serde(rename = "regular")]
     Regular,
@@ -130,10 +169,12 @@ pub enum StickerType {
     Mask,
     #[serde(rename = "custom_emoji")]
     CustomEmoji,
+    /// A sticker type not (yet) known to this library
+    #[serde(other)]
+    Unknown,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
-#[serde(untagged)]
 pub enum StickerFormat {
     #[serde(rename = "static")]
     Static,
@@ -142,3 +183,5 @@ pub enum StickerFormat {
     #[serde(rename = "video")]
     Video,
 }
+
+crate::utils::macros::impl_thumb_accessor!(PhotoSize, Sticker, StickerSet);
@@ -1,16 +1,23 @@
 use super::{
     raw::RawUpdate,
+    BusinessConnection,
+    BusinessMessagesDeleted,
     CallbackQuery,
+    ChatBoostRemoved,
+    ChatBoostUpdated,
     ChatJoinRequest,
     ChatMemberUpdated,
     ChosenInlineResult,
     InlineQuery,
     Message,
+    MessageReactionCountUpdated,
+    MessageReactionUpdated,
     Poll,
     PollAnswer,
     PreCheckoutQuery,
     ShippingQuery,
 };
+use crate::api::types::UpdateType;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// This object represents an incoming update
@@ -74,8 +81,65 @@ pub enum UpdateContent {
     /// can_invite_users administrator right in the chat to receive these
     /// updates.
     ChatJoinRequest(ChatJoinRequest),
-    /// An unknown update content
-    Unknown,
+    /// A reaction to a message was changed by a user. The bot must be an
+    /// administrator in the chat and must explicitly specify
+    /// “`message_reaction`” in the list of `allowed_updates` to receive these
+    /// updates.
+    MessageReaction(MessageReactionUpdated),
+    /// Reactions to a message with anonymous reactions were changed. The bot
+    /// must be an administrator in the chat and must explicitly specify
+    /// “`message_reaction_count`” in the list of `allowed_updates` to
+    /// receive these updates.
+    MessageReactionCount(MessageReactionCountUpdated),
+    /// A chat boost was added or changed. The bot must be an administrator in
+    /// the chat to receive these updates.
+    ChatBoost(ChatBoostUpdated),
+    /// A boost was removed from a chat. The bot must be an administrator in
+    /// the chat to receive these updates.
+    RemovedChatBoost(ChatBoostRemoved),
+    /// The bot was connected to or disconnected from a business account, or a
+    /// user edited an existing connection with the bot
+    BusinessConnection(BusinessConnection),
+    /// New message from a connected business account
+    BusinessMessage(Message),
+    /// New version of a message from a connected business account
+    EditedBusinessMessage(Message),
+    /// Messages were deleted from a connected business account
+    DeletedBusinessMessages(BusinessMessagesDeleted),
+    /// An update content that is not (yet) supported, holding its raw json so
+    /// it can still be handled
+    Unknown(serde_json::Value),
+}
+
+impl UpdateContent {
+    /// Gets the type of the update, or `None` for an unknown update
+    pub fn update_type(&self) -> Option<UpdateType> {
+        Some(match self {
+            Self::Message(_) => UpdateType::Message,
+            Self::EditedMessage(_) => UpdateType::EditedMessage,
+            Self::ChannelPost(_) => UpdateType::ChannelPost,
+            Self::EditedChannelPost(_) => UpdateType::EditedChannelPost,
+            Self::InlineQuery(_) => UpdateType::InlineQuery,
+            Self::ChosenInlineResult(_) => UpdateType::ChosenInlineResult,
+            Self::CallbackQuery(_) => UpdateType::CallbackQuery,
+            Self::ShippingQuery(_) => UpdateType::ShippingQuery,
+            Self::PreCheckoutQuery(_) => UpdateType::PreCheckoutQuery,
+            Self::Poll(_) => UpdateType::Poll,
+            Self::PollAnswer(_) => UpdateType::PollAnswer,
+            Self::MyChatMember(_) => UpdateType::MyChatMember,
+            Self::ChatMember(_) => UpdateType::ChatMember,
+            Self::ChatJoinRequest(_) => UpdateType::ChatJoinRequest,
+            Self::MessageReaction(_) => UpdateType::MessageReaction,
+            Self::MessageReactionCount(_) => UpdateType::MessageReactionCount,
+            Self::ChatBoost(_) => UpdateType::ChatBoost,
+            Self::RemovedChatBoost(_) => UpdateType::RemovedChatBoost,
+            Self::BusinessConnection(_) => UpdateType::BusinessConnection,
+            Self::BusinessMessage(_) => UpdateType::BusinessMessage,
+            Self::EditedBusinessMessage(_) => UpdateType::EditedBusinessMessage,
+            Self::DeletedBusinessMessages(_) => UpdateType::DeletedBusinessMessages,
+            Self::Unknown(_) => return None,
+        })
+    }
 }
 
 impl From<RawUpdate> for Update {
@@ -108,8 +172,16 @@ impl From<RawUpdate> for Update {
         set_content!(raw.my_chat_member, MyChatMember);
         set_content!(raw.chat_member, ChatMember);
         set_content!(raw.chat_join_request, ChatJoinRequest);
+        set_content!(raw.message_reaction, MessageReaction);
+        set_content!(raw.message_reaction_count, MessageReactionCount);
+        set_content!(raw.chat_boost, ChatBoost);
+        set_content!(raw.removed_chat_boost, RemovedChatBoost);
+        set_content!(raw.business_connection, BusinessConnection);
+        set_content!(raw.business_message, BusinessMessage);
+        set_content!(raw.edited_business_message, EditedBusinessMessage);
+        set_content!(raw.deleted_business_messages, DeletedBusinessMessages);
 
-        make_update(UpdateContent::Unknown)
+        make_update(UpdateContent::Unknown(serde_json::Value::Object(raw.other)))
     }
 }
 
@@ -131,6 +203,15 @@ impl From<Update> for RawUpdate {
             my_chat_member: None,
             chat_member: None,
             chat_join_request: None,
+            message_reaction: None,
+            message_reaction_count: None,
+            chat_boost: None,
+            removed_chat_boost: None,
+            business_connection: None,
+            business_message: None,
+            edited_business_message: None,
+            deleted_business_messages: None,
+            other: serde_json::Map::new(),
         };
 
         match update.content {
@@ -190,7 +271,44 @@ impl From<Update> for RawUpdate {
                 ret.chat_join_request = Some(c);
                 ret
             },
-            UpdateContent::Unknown => ret,
+            UpdateContent::MessageReaction(c) => {
+                ret.message_reaction = Some(c);
+                ret
+            },
+            UpdateContent::MessageReactionCount(c) => {
+                ret.message_reaction_count = Some(c);
+                ret
+            },
+            UpdateContent::ChatBoost(c) => {
+                ret.chat_boost = Some(c);
+                ret
+            },
+            UpdateContent::RemovedChatBoost(c) => {
+                ret.removed_chat_boost = Some(c);
+                ret
+            },
+            UpdateContent::BusinessConnection(c) => {
+                ret.business_connection = Some(c);
+                ret
+            },
+            UpdateContent::BusinessMessage(c) => {
+                ret.business_message = Some(c.into());
+                ret
+            },
+            UpdateContent::EditedBusinessMessage(c) => {
+                ret.edited_business_message = Some(c.into());
+                ret
+            },
+            UpdateContent::DeletedBusinessMessages(c) => {
+                ret.deleted_business_messages = Some(c);
+                ret
+            },
+            UpdateContent::Unknown(c) => {
+                if let serde_json::Value::Object(other) = c {
+                    ret.other = other;
+                }
+                ret
+            },
         }
     }
 }
@@ -43,3 +43,49 @@ pub struct UserProfilePhotos {
     /// Requested profile pictures (in up to 4 sizes each)
     pub photos: Vec<Vec<PhotoSize>>,
 }
+
+impl UserProfilePhotos {
+    /// Returns an iterator over the profile pictures, each in up to 4 sizes
+    pub fn iter(&self) -> std::slice::Iter<'_, Vec<PhotoSize>> {
+        self.photos.iter()
+    }
+
+    /// The amount of profile pictures that were retrieved, this can be less
+    /// than the `total_count`
+    pub fn len(&self) -> usize {
+        self.photos.len()
+    }
+
+    /// Returns true if no profile pictures were retrieved
+    pub fn is_empty(&self) -> bool {
+        self.photos.is_empty()
+    }
+
+    /// Gets all sizes of the newest profile picture that was retrieved
+    pub fn latest(&self) -> Option<&Vec<PhotoSize>> {
+        self.photos.first()
+    }
+
+    /// Gets the largest size of the newest profile picture that was retrieved
+    pub fn latest_largest(&self) -> Option<&PhotoSize> {
+        self.latest()?.iter().max_by_key(|p| p.width * p.height)
+    }
+}
+
+impl IntoIterator for UserProfilePhotos {
+    type Item = Vec<PhotoSize>;
+    type IntoIter = std::vec::IntoIter<Vec<PhotoSize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.photos.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a UserProfilePhotos {
+    type Item = &'a Vec<PhotoSize>;
+    type IntoIter = std::slice::Iter<'a, Vec<PhotoSize>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.photos.iter()
+    }
+}
@@ -12,17 +12,40 @@ pub struct TextBlock {
 impl TextBlock {
     /// Gets the part of the text described by the `TextBlock`
     pub fn get_text(&self, text: &str) -> String {
-        let mut res: Vec<u16> = Vec::with_capacity(self.length);
+        self.get_str(text).to_owned()
+    }
 
-        for (i, point) in text.encode_utf16().enumerate() {
-            if (self.offset + self.length) <= i {
-                break;
-            }
-            if self.offset <= i {
-                res.push(point);
-            }
-        }
+    /// Gets the part of the text described by the `TextBlock` as a slice of
+    /// that text, converting the UTF-16 offsets used by telegram to byte
+    /// offsets
+    pub fn get_str<'a>(&self, text: &'a str) -> &'a str {
+        let start = utf16_to_byte_index(text, self.offset);
+        let end = utf16_to_byte_index(text, self.offset + self.length);
+        &text[start..end.max(start)]
+    }
+
+    /// Gets the part of the text preceding the part described by the
+    /// `TextBlock`
+    pub fn get_str_before<'a>(&self, text: &'a str) -> &'a str {
+        &text[..utf16_to_byte_index(text, self.offset)]
+    }
 
-        String::from_utf16_lossy(res.as_slice())
+    /// Gets the part of the text following the part described by the
+    /// `TextBlock`
+    pub fn get_str_after<'a>(&self, text: &'a str) -> &'a str {
+        &text[utf16_to_byte_index(text, self.offset + self.length)..]
+    }
+}
+
+/// Gets the byte index in the text of the char starting at or right after the
+/// given amount of UTF-16 code units
+fn utf16_to_byte_index(text: &str, units: usize) -> usize {
+    let mut position = 0;
+    for (index, c) in text.char_indices() {
+        if position >= units {
+            return index;
+        }
+        position += c.len_utf16();
     }
+    text.len()
 }
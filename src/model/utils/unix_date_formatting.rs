@@ -35,8 +35,7 @@ pub mod optional {
     where
         D: Deserializer<'de>,
     {
-        Ok(Utc
-            .timestamp_opt(i64::deserialize(deserializer)?, 0)
-            .single())
+        Ok(Option::<i64>::deserialize(deserializer)?
+            .and_then(|timestamp| Utc.timestamp_opt(timestamp, 0).single()))
     }
 }
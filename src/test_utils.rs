@@ -0,0 +1,398 @@
+//! Builders for creating realistic [`Update`]s, [`Message`]s and
+//! [`CallbackQuery`]s in the tests of your bot, enabled by the `testing`
+//! feature.
+//!
+//! The builders fill in every field telegram always sends with sensible
+//! defaults, so only the parts relevant to a test have to be set. Message,
+//! update and callback query ids are increased for every object that is built,
+//! unless they are set explicitly.
+//!
+//! The builders are a stable part of the api of telexide: new defaults may be
+//! filled in when telegram adds fields, but existing methods keep building the
+//! same objects.
+//!
+//! ```rust
+//! use telexide::{
+//!     model::{Chat, UpdateContent},
+//!     test_utils::{MessageBuilder, UpdateBuilder},
+//! };
+//!
+//! let message = MessageBuilder::text("/start now")
+//!     .from_user(1)
+//!     .in_private_chat(1)
+//!     .build();
+//! let update = UpdateBuilder::message(message).build();
+//!
+//! if let UpdateContent::Message(message) = update.content {
+//!     assert!(matches!(message.chat, Chat::Private(_)));
+//! }
+//! ```
+//!
+//! [`Update`]: ../model/struct.Update.html
+//! [`Message`]: ../model/struct.Message.html
+//! [`CallbackQuery`]: ../model/struct.CallbackQuery.html
+
+use crate::{
+    api::types::TopicId,
+    model::{CallbackQuery, Message, MessageContent, Update, UpdateContent, User},
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+static NEXT_MESSAGE_ID: AtomicI64 = AtomicI64::new(1);
+static NEXT_UPDATE_ID: AtomicI64 = AtomicI64::new(1);
+static NEXT_CALLBACK_QUERY_ID: AtomicI64 = AtomicI64::new(1);
+
+/// The id of the user sending messages and callback queries by default
+const DEFAULT_USER_ID: i64 = 1;
+
+/// Creates a user with the given id, who is not a bot
+pub fn user(id: i64) -> User {
+    User {
+        id,
+        is_bot: false,
+        first_name: format!("user {id}"),
+        last_name: None,
+        username: Some(format!("user{id}")),
+        language_code: Some("en".to_owned()),
+        is_premium: None,
+        added_to_attachment_menu: None,
+        can_join_groups: None,
+        can_read_all_group_messages: None,
+        supports_inline_queries: None,
+    }
+}
+
+/// A builder for a [`Message`], which is sent by user 1 in their private chat
+/// with the bot by default.
+///
+/// [`Message`]: ../model/struct.Message.html
+#[derive(Debug, Clone)]
+pub struct MessageBuilder {
+    message_id: Option<i64>,
+    from: Option<User>,
+    chat: Option<Value>,
+    message_thread_id: Option<i64>,
+    text: Option<String>,
+    content: Option<MessageContent>,
+    reply_to_message: Option<Message>,
+    business_connection_id: Option<String>,
+}
+
+impl MessageBuilder {
+    /// Creates a builder for a text message. Like telegram does, a bot
+    /// command entity is added when the text starts with a `/`.
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn text(text: impl ToString) -> Self {
+        Self {
+            text: Some(text.to_string()),
+            ..Self::empty()
+        }
+    }
+
+    /// Creates a builder for a message with the given content
+    #[must_use]
+    pub fn content(content: MessageContent) -> Self {
+        Self {
+            content: Some(content),
+            ..Self::empty()
+        }
+    }
+
+    fn empty() -> Self {
+        Self {
+            message_id: None,
+            from: None,
+            chat: None,
+            message_thread_id: None,
+            text: None,
+            content: None,
+            reply_to_message: None,
+            business_connection_id: None,
+        }
+    }
+
+    /// Sets the id of the message
+    #[must_use]
+    pub fn message_id(mut self, message_id: i64) -> Self {
+        self.message_id = Some(message_id);
+        self
+    }
+
+    /// Sets the sender of the message to the user with the given id
+    #[must_use]
+    pub fn from_user(self, user_id: i64) -> Self {
+        self.from(user(user_id))
+    }
+
+    /// Sets the sender of the message
+    #[must_use]
+    pub fn from(mut self, user: User) -> Self {
+        self.from = Some(user);
+        self
+    }
+
+    /// Sends the message in the private chat with the given id, which is the
+    /// id of the other user
+    #[must_use]
+    pub fn in_private_chat(mut self, chat_id: i64) -> Self {
+        self.chat = Some(json!({
+            "id": chat_id,
+            "type": "private",
+            "first_name": format!("user {chat_id}")
+        }));
+        self
+    }
+
+    /// Sends the message in the group with the given id
+    #[must_use]
+    pub fn in_group(mut self, chat_id: i64) -> Self {
+        self.chat = Some(json!({"id": chat_id, "type": "group", "title": "group"}));
+        self
+    }
+
+    /// Sends the message in the supergroup with the given id, which is not a
+    /// forum
+    #[must_use]
+    pub fn in_supergroup(mut self, chat_id: i64) -> Self {
+        self.chat = Some(json!({"id": chat_id, "type": "supergroup", "title": "supergroup"}));
+        self
+    }
+
+    /// Sends the message in a topic of the forum supergroup with the given id
+    #[must_use]
+    pub fn in_forum_topic(mut self, chat_id: i64, topic: TopicId) -> Self {
+        self.chat = Some(json!({
+            "id": chat_id,
+            "type": "supergroup",
+            "title": "forum",
+            "is_forum": true
+        }));
+        self.message_thread_id = match topic {
+            TopicId::General => None,
+            TopicId::Topic(id) => Some(id),
+        };
+        self
+    }
+
+    /// Sends the message as a post in the channel with the given id, which
+    /// has no sender
+    #[must_use]
+    pub fn in_channel(mut self, chat_id: i64) -> Self {
+        self.chat = Some(json!({"id": chat_id, "type": "channel", "title": "channel"}));
+        self
+    }
+
+    /// Makes the message a reply to the given message
+    #[must_use]
+    pub fn reply_to(mut self, message: Message) -> Self {
+        self.reply_to_message = Some(message);
+        self
+    }
+
+    /// Makes the message received through the given business connection
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn business_connection(mut self, connection_id: impl ToString) -> Self {
+        self.business_connection_id = Some(connection_id.to_string());
+        self
+    }
+
+    /// Builds the message
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message the builder describes can not be created, which
+    /// should never happen.
+    pub fn build(self) -> Message {
+        let is_channel = self.chat.as_ref().is_some_and(|c| c["type"] == "channel");
+        let from = match self.from {
+            Some(from) => Some(from),
+            None if is_channel => None,
+            None => Some(user(DEFAULT_USER_ID)),
+        };
+        let chat = self.chat.unwrap_or_else(|| {
+            let id = from.as_ref().map_or(DEFAULT_USER_ID, |u| u.id);
+            json!({"id": id, "type": "private", "first_name": format!("user {id}")})
+        });
+
+        let mut raw = json!({
+            "message_id": self
+                .message_id
+                .unwrap_or_else(|| NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed)),
+            "date": Utc::now().timestamp(),
+            "chat": chat,
+            "from": from,
+            "message_thread_id": self.message_thread_id,
+            "is_topic_message": self.message_thread_id.is_some(),
+            "business_connection_id": self.business_connection_id,
+            "reply_to_message": self.reply_to_message,
+        });
+
+        if let Some(text) = &self.text {
+            raw["text"] = json!(text);
+            if text.starts_with('/') {
+                let command = text.split(char::is_whitespace).next().unwrap_or_default();
+                raw["entities"] = json!([{
+                    "type": "bot_command",
+                    "offset": 0,
+                    "length": command.encode_utf16().count()
+                }]);
+            }
+        }
+
+        let mut message: Message =
+            serde_json::from_value(raw).expect("the message builder creates invalid messages");
+        if let Some(content) = self.content {
+            message.content = content;
+        }
+        message
+    }
+}
+
+/// A builder for an [`Update`]
+///
+/// [`Update`]: ../model/struct.Update.html
+#[derive(Debug, Clone)]
+pub struct UpdateBuilder {
+    update_id: Option<i64>,
+    content: UpdateContent,
+}
+
+impl UpdateBuilder {
+    /// Creates a builder for an update with the given content
+    #[must_use]
+    pub fn new(content: UpdateContent) -> Self {
+        Self {
+            update_id: None,
+            content,
+        }
+    }
+
+    /// Creates a builder for an update with a new message
+    #[must_use]
+    pub fn message(message: Message) -> Self {
+        Self::new(UpdateContent::Message(message))
+    }
+
+    /// Creates a builder for an update with an edited message
+    #[must_use]
+    pub fn edited_message(message: Message) -> Self {
+        Self::new(UpdateContent::EditedMessage(message))
+    }
+
+    /// Creates a builder for an update with a callback query
+    #[must_use]
+    pub fn callback_query(query: CallbackQuery) -> Self {
+        Self::new(UpdateContent::CallbackQuery(query))
+    }
+
+    /// Sets the id of the update
+    #[must_use]
+    pub fn update_id(mut self, update_id: i64) -> Self {
+        self.update_id = Some(update_id);
+        self
+    }
+
+    /// Builds the update
+    pub fn build(self) -> Update {
+        Update {
+            update_id: self
+                .update_id
+                .unwrap_or_else(|| NEXT_UPDATE_ID.fetch_add(1, Ordering::Relaxed)),
+            content: self.content,
+        }
+    }
+}
+
+/// A builder for a [`CallbackQuery`], which is sent by user 1 by default
+///
+/// [`CallbackQuery`]: ../model/struct.CallbackQuery.html
+#[derive(Debug, Clone)]
+pub struct CallbackQueryBuilder {
+    id: Option<String>,
+    from: Option<User>,
+    message: Option<Message>,
+    data: Option<String>,
+    game_short_name: Option<String>,
+}
+
+impl CallbackQueryBuilder {
+    /// Creates a builder for a callback query of a button with the given data
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn data(data: impl ToString) -> Self {
+        Self {
+            id: None,
+            from: None,
+            message: None,
+            data: Some(data.to_string()),
+            game_short_name: None,
+        }
+    }
+
+    /// Creates a builder for a callback query of a button launching the game
+    /// with the given short name
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn game(game_short_name: impl ToString) -> Self {
+        Self {
+            data: None,
+            game_short_name: Some(game_short_name.to_string()),
+            ..Self::data("")
+        }
+    }
+
+    /// Sets the id of the query
+    #[must_use]
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn id(mut self, id: impl ToString) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    /// Sets the sender of the query to the user with the given id
+    #[must_use]
+    pub fn from_user(self, user_id: i64) -> Self {
+        self.from(user(user_id))
+    }
+
+    /// Sets the sender of the query
+    #[must_use]
+    pub fn from(mut self, user: User) -> Self {
+        self.from = Some(user);
+        self
+    }
+
+    /// Sets the message with the button that originated the query
+    #[must_use]
+    pub fn message(mut self, message: Message) -> Self {
+        self.message = Some(message);
+        self
+    }
+
+    /// Builds the callback query
+    pub fn build(self) -> CallbackQuery {
+        let chat_instance = self
+            .message
+            .as_ref()
+            .map_or(0, |m| m.chat.get_id())
+            .to_string();
+
+        CallbackQuery {
+            id: self.id.unwrap_or_else(|| {
+                NEXT_CALLBACK_QUERY_ID
+                    .fetch_add(1, Ordering::Relaxed)
+                    .to_string()
+            }),
+            from: self.from.unwrap_or_else(|| user(DEFAULT_USER_ID)),
+            message: self.message.map(Into::into),
+            inline_message_id: None,
+            chat_instance,
+            data: self.data,
+            game_short_name: self.game_short_name,
+        }
+    }
+}
@@ -1,90 +1,310 @@
 use super::result::{Result, TelegramError};
+use futures::{
+    stream::{self, BoxStream},
+    StreamExt,
+    TryStreamExt,
+};
+use hyper::body::{Body, Bytes};
 use serde_json::{Map, Value};
 use std::{
     fs::File,
-    io::{Read, Write},
-    path::Path,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
 };
+use tokio::io::AsyncReadExt;
+
+/// The size of the chunks files on disk are streamed in when uploading them
+const CHUNK_SIZE: usize = 64 * 1024;
 
+/// A file to be uploaded as part of a multipart form data request
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FormDataFile {
     pub bytes: Vec<u8>,
     pub name: String,
     pub file_name: Option<String>,
     pub media_type: Option<String>,
+    /// The file on disk the contents are streamed from when uploading, in
+    /// which case `bytes` is left empty
+    pub path: Option<PathBuf>,
 }
 
 impl FormDataFile {
     pub fn new(bytes: &[u8], media_type: &str, file_name: &str) -> Self {
         Self {
             bytes: bytes.to_vec(),
-            name: file_name
-                .splitn(2, '.')
-                .collect::<Vec<&str>>()
-                .first()
-                .unwrap_or(&"new_file")
-                .to_owned()
-                .to_owned(),
+            name: attach_name(file_name),
             media_type: Some(media_type.to_owned()),
             file_name: Some(file_name.to_owned()),
+            path: None,
         }
     }
 
     pub fn new_from_file(file: &mut File, file_name: &str) -> Result<Self> {
+        Self::new_from_reader(file, file_name)
+    }
+
+    /// Creates a file from everything the reader returns, with the media type
+    /// based on the extension of the file name
+    pub fn new_from_reader<R: Read>(mut reader: R, file_name: &str) -> Result<Self> {
         let mut bytes = Vec::new();
-        file.read_to_end(&mut bytes)?;
+        reader.read_to_end(&mut bytes)?;
+
+        Self::new_from_bytes(bytes, file_name)
+    }
 
+    /// Creates a file from its contents, with the media type based on the
+    /// extension of its name
+    pub fn new_from_bytes(bytes: Vec<u8>, file_name: &str) -> Result<Self> {
         Ok(Self {
             bytes,
-            name: file_name
-                .splitn(2, '.')
-                .collect::<Vec<&str>>()
-                .first()
-                .unwrap_or(&"new_file")
-                .to_owned()
-                .to_owned(),
+            name: attach_name(file_name),
+            file_name: Some(file_name.to_owned()),
+            media_type: Some(get_media_type(file_name)?.to_owned()),
+            path: None,
+        })
+    }
+
+    /// Creates a file whose contents are streamed from disk when it is
+    /// uploaded, instead of being read into memory, with the media type based
+    /// on the extension of its name
+    pub fn new_from_path<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let file_name = path.file_name().and_then(|n| n.to_str()).ok_or_else(|| {
+            TelegramError::InvalidArgument("file doesn't have a valid file name".to_owned())
+        })?;
+
+        // fail early on files that can't be uploaded
+        if !path.metadata()?.is_file() {
+            return Err(
+                TelegramError::InvalidArgument(format!("{} isn't a file", path.display())).into(),
+            );
+        }
+
+        Ok(Self {
+            bytes: Vec::new(),
+            name: attach_name(file_name),
             file_name: Some(file_name.to_owned()),
             media_type: Some(get_media_type(file_name)?.to_owned()),
+            path: Some(path.to_owned()),
+        })
+    }
+
+    /// Gets the size of the contents of the file in bytes
+    pub fn size(&self) -> Result<u64> {
+        match &self.path {
+            Some(path) => Ok(path.metadata()?.len()),
+            None => Ok(self.bytes.len() as u64),
+        }
+    }
+}
+
+/// Gets the name a file is attached under in the form data, which is the part
+/// of its file name before the first dot with the characters that aren't
+/// allowed in an `attach://` reference replaced by underscores
+fn attach_name(file_name: &str) -> String {
+    let stem = file_name.split('.').next().unwrap_or_default();
+    if stem.is_empty() {
+        return "new_file".to_owned();
+    }
+
+    stem.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
         })
+        .collect()
+}
+
+/// Escapes the quotes and line breaks in a file name, so it can be put in the
+/// `Content-Disposition` header of a form data part
+fn escape_file_name(file_name: &str) -> String {
+    file_name
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Prepares the form data fields of a request for uploading the files with it.
+///
+/// A field only referencing a file attached under its own name, like the
+/// `photo` field of a `photo.png`, is left out so the file itself is sent as
+/// the field. Files attached under the name of any other field are renamed to a
+/// name not used by a field or another file, and the references to them updated
+/// accordingly.
+pub(crate) fn prepare_attachments(data: &mut Value, files: &mut [FormDataFile]) {
+    let Some(map) = data.as_object_mut() else {
+        return;
+    };
+    let mut names: Vec<String> = files.iter().map(|f| f.name.clone()).collect();
+
+    for file in files.iter_mut() {
+        let reference = format!("attach://{}", file.name);
+        match map.get(&file.name) {
+            None => continue,
+            Some(Value::String(s)) if *s == reference => {
+                map.remove(&file.name);
+                continue;
+            },
+            Some(_) => (),
+        }
+
+        let mut name = format!("{}_file", file.name);
+        while map.contains_key(&name) || names.contains(&name) {
+            name.push_str("_file");
+        }
+        replace_references(map.values_mut(), &reference, &format!("attach://{name}"));
+        names.push(name.clone());
+        file.name = name;
+    }
+}
+
+fn replace_references<'a>(
+    values: impl Iterator<Item = &'a mut Value>,
+    reference: &str,
+    replacement: &str,
+) {
+    for value in values {
+        match value {
+            Value::String(s) if s == reference => replacement.clone_into(s),
+            Value::Array(a) => replace_references(a.iter_mut(), reference, replacement),
+            Value::Object(o) => replace_references(o.values_mut(), reference, replacement),
+            _ => (),
+        }
     }
 }
 
 pub static BOUNDARY: &str = "----------telexide-form-data-boundary";
 
+/// A part of a multipart form data body
+#[derive(Debug, Clone)]
+enum BodyPart {
+    Bytes(Bytes),
+    File(PathBuf),
+}
+
+/// The body of a multipart form data request, with the files on disk being
+/// streamed from there when the body is sent so they are never entirely kept
+/// in memory
+#[derive(Debug, Clone)]
+pub(crate) struct MultipartBody {
+    parts: Vec<BodyPart>,
+    /// The length of the body, unless the size of a file on disk is unknown
+    length: Option<u64>,
+}
+
+impl MultipartBody {
+    pub(crate) fn new(files: &[FormDataFile]) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut data = Vec::new();
+        let mut length = Some(0);
+
+        for file in files {
+            write_part_header(&mut data, file)?;
+
+            if let Some(path) = &file.path {
+                length = length
+                    .zip(path.metadata().ok())
+                    .map(|(l, m)| l + data.len() as u64 + m.len());
+                parts.push(BodyPart::Bytes(std::mem::take(&mut data).into()));
+                parts.push(BodyPart::File(path.clone()));
+            } else {
+                data.extend_from_slice(&file.bytes);
+            }
+
+            write!(&mut data, "\r\n")?;
+        }
+
+        write!(&mut data, "--{BOUNDARY}--\r\n")?;
+        length = length.map(|l| l + data.len() as u64);
+        parts.push(BodyPart::Bytes(data.into()));
+
+        Ok(Self {
+            parts,
+            length,
+        })
+    }
+
+    /// Gets the length of the body in bytes, if the sizes of all files on disk
+    /// are known
+    pub(crate) fn len(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Creates the request body, which can be done again for every retry of
+    /// the request
+    pub(crate) fn body(&self) -> Body {
+        let chunks = stream::iter(self.parts.clone()).flat_map(|part| -> BoxStream<_> {
+            match part {
+                BodyPart::Bytes(bytes) => stream::once(async { Ok(bytes) }).boxed(),
+                BodyPart::File(path) => stream_file(path),
+            }
+        });
+
+        Body::wrap_stream(chunks)
+    }
+}
+
+/// Streams the contents of the file in chunks of at most [`CHUNK_SIZE`] bytes
+fn stream_file(path: PathBuf) -> BoxStream<'static, io::Result<Bytes>> {
+    stream::once(tokio::fs::File::open(path))
+        .map_ok(|file| {
+            stream::try_unfold(file, |mut file| async move {
+                let mut chunk = vec![0; CHUNK_SIZE];
+                let read = file.read(&mut chunk).await?;
+                if read == 0 {
+                    return Ok(None);
+                }
+
+                chunk.truncate(read);
+                Ok(Some((Bytes::from(chunk), file)))
+            })
+        })
+        .try_flatten()
+        .boxed()
+}
+
+fn write_part_header(data: &mut Vec<u8>, file: &FormDataFile) -> Result<()> {
+    write!(data, "--{BOUNDARY}\r\n")?;
+
+    if let Some(file_name) = &file.file_name {
+        write!(
+            data,
+            "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
+            file.name,
+            escape_file_name(file_name)
+        )?;
+    } else {
+        write!(
+            data,
+            "Content-Disposition: form-data; name=\"{}\"\r\n",
+            file.name
+        )?;
+    }
+
+    if let Some(media_type) = &file.media_type {
+        write!(data, "Content-Type: {media_type}\r\n")?;
+    }
+
+    write!(data, "\r\n")?;
+    Ok(())
+}
+
 pub fn encode_multipart_form_data(files: &[FormDataFile]) -> Result<Vec<u8>> {
     let mut data = Vec::new();
 
     for file in files {
-        write!(&mut data, "--{BOUNDARY}\r\n")?;
-
-        if file.file_name.is_some() {
-            write!(
-                &mut data,
-                "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n",
-                file.name,
-                file.file_name.as_ref().unwrap()
-            )?;
-        } else {
-            write!(
-                &mut data,
-                "Content-Disposition: form-data; name=\"{}\"\r\n",
-                file.name
-            )?;
-        }
+        write_part_header(&mut data, file)?;
 
-        if file.media_type.is_some() {
-            write!(
-                &mut data,
-                "Content-Type: {}\r\n",
-                file.media_type.as_ref().unwrap()
-            )?;
+        if let Some(path) = &file.path {
+            File::open(path)?.read_to_end(&mut data)?;
+        } else {
+            data.extend_from_slice(&file.bytes);
         }
 
         write!(&mut data, "\r\n")?;
-
-        file.bytes.as_slice().read_to_end(&mut data)?;
-
-        write!(&mut data, "\r\n")?;
     }
 
     write!(&mut data, "--{BOUNDARY}--\r\n")?;
@@ -149,6 +369,7 @@ impl AsFormData for Value {
                 name: key,
                 file_name: None,
                 media_type: None,
+                path: None,
                 bytes: serde_json::to_string(&value)?
                     .trim_matches('"')
                     .as_bytes()
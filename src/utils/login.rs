@@ -0,0 +1,116 @@
+//! Verification of the authorization data telegram sends along when a user
+//! logs in using a [login url] button or the [Telegram Login Widget].
+//!
+//! [login url]: ../../model/struct.LoginUrl.html
+//! [Telegram Login Widget]: https://core.telegram.org/widgets/login
+
+use super::result::{Result, TelegramError};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, hash::BuildHasher};
+
+/// The maximum age of login data accepted by [`verify_login_data`]
+pub const DEFAULT_MAX_AGE: Duration = Duration::days(1);
+
+/// The authorization data of a user that logged in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoginData {
+    /// The user's unique identifier
+    pub id: i64,
+    /// The user's first name
+    pub first_name: String,
+    /// The user's last name
+    pub last_name: Option<String>,
+    /// The user's username
+    pub username: Option<String>,
+    /// The url of the user's profile picture
+    pub photo_url: Option<String>,
+    /// When the user logged in
+    pub auth_date: DateTime<Utc>,
+}
+
+/// Verifies the authorization data telegram added to the query string of a
+/// login url, as described in [Checking authorization], and parses it into
+/// [`LoginData`].
+///
+/// The fields must include the `hash` field, and the data is rejected if it is
+/// older than [`DEFAULT_MAX_AGE`].
+///
+/// [Checking authorization]: https://core.telegram.org/widgets/login#checking-authorization
+pub fn verify_login_data<S: BuildHasher>(
+    fields: &HashMap<String, String, S>,
+    bot_token: &str,
+) -> Result<LoginData> {
+    verify_login_data_with_max_age(fields, bot_token, DEFAULT_MAX_AGE)
+}
+
+/// Verifies the authorization data like [`verify_login_data`] does, but
+/// rejects data older than the given maximum age instead.
+pub fn verify_login_data_with_max_age<S: BuildHasher>(
+    fields: &HashMap<String, String, S>,
+    bot_token: &str,
+    max_age: Duration,
+) -> Result<LoginData> {
+    let hash = fields
+        .get("hash")
+        .and_then(|h| decode_hex(h))
+        .ok_or_else(|| invalid("hash", "missing or not a valid hex string"))?;
+
+    let mut data_check: Vec<String> = fields
+        .iter()
+        .filter(|(k, _)| k.as_str() != "hash")
+        .map(|(k, v)| format!("{k}={v}"))
+        .collect();
+    data_check.sort_unstable();
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&Sha256::digest(bot_token.as_bytes()))
+        .map_err(|e| TelegramError::Unknown(e.to_string()))?;
+    mac.update(data_check.join("\n").as_bytes());
+    mac.verify_slice(&hash)
+        .map_err(|_| invalid("hash", "does not match the login data"))?;
+
+    let auth_date = fields
+        .get("auth_date")
+        .and_then(|d| d.parse().ok())
+        .and_then(|d| DateTime::from_timestamp(d, 0))
+        .ok_or_else(|| invalid("auth_date", "missing or not a valid unix timestamp"))?;
+    if Utc::now() - auth_date > max_age {
+        return Err(invalid("auth_date", "the login data has expired"));
+    }
+
+    Ok(LoginData {
+        id: fields
+            .get("id")
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(|| invalid("id", "missing or not a valid user id"))?,
+        first_name: fields
+            .get("first_name")
+            .cloned()
+            .ok_or_else(|| invalid("first_name", "missing"))?,
+        last_name: fields.get("last_name").cloned(),
+        username: fields.get("username").cloned(),
+        photo_url: fields.get("photo_url").cloned(),
+        auth_date,
+    })
+}
+
+fn invalid(field: &str, reason: &str) -> super::result::Error {
+    TelegramError::InvalidArgument(format!("login data {field}: {reason}")).into()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => Some((hex_value(*high)? << 4) | hex_value(*low)?),
+            _ => None,
+        })
+        .collect()
+}
+
+fn hex_value(c: u8) -> Option<u8> {
+    char::from(c)
+        .to_digit(16)
+        .and_then(|d| u8::try_from(d).ok())
+}
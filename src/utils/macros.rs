@@ -1,19 +1,52 @@
 /// This macro creates the [`Framework`] object using the given bot name and
 /// registers all the given commands on it. Call it as in
-/// `create_framework!("bot_name", command1, command2, ...)`
+/// `create_framework!("bot_name", command1, command2, ...)`, or as in
+/// `create_framework!("bot_name", prefix = "!", command1, command2, ...)` to
+/// use a different command prefix than `/`.
 ///
 /// [`Framework`]: framework/struct.Framework.html
 #[macro_export]
 macro_rules! create_framework {
-    ($bot_name:expr, $( $c:expr ),*) => {
+    ($bot_name:expr, prefix = $prefix:expr, $( $c:expr ),*) => {
         {
             telexide::paste_expr! {
                 let mut fr = telexide::framework::Framework::new($bot_name);
+                fr.set_prefix($prefix);
+                $(
+                     fr.add_command(&(&[<$c _COMMAND>]));
+                )*
+                ::std::sync::Arc::new(fr)
+            }
+        }
+    };
+    ($bot_name:expr, $( $c:expr ),*) => {
+        {
+            telexide::paste_expr! {
+                let fr = telexide::framework::Framework::new($bot_name);
                 $(
                      fr.add_command(&(&[<$c _COMMAND>]));
                 )*
                 ::std::sync::Arc::new(fr)
             }
         }
-    }
+    };
+}
+
+/// Implements the deprecated `thumb` accessor on structs whose `thumb` field
+/// was renamed to `thumbnail` by telegram, so code still using the old name
+/// gets a deprecation warning instead of silently missing the thumbnail.
+macro_rules! impl_thumb_accessor {
+    ($thumb:ty, $( $name:ident ),+) => {
+        $(
+            impl $name {
+                /// Gets the thumbnail, which was previously called `thumb`
+                #[deprecated(note = "telegram renamed `thumb` to `thumbnail`")]
+                pub fn thumb(&self) -> Option<&$thumb> {
+                    self.thumbnail.as_ref()
+                }
+            }
+        )+
+    };
 }
+
+pub(crate) use impl_thumb_accessor;
@@ -1,5 +1,15 @@
+//! The utils module provides helpers for working with telegram data outside
+//! of the api, such as verifying the data received from a [login url] button.
+//!
+//! [login url]: ../model/struct.LoginUrl.html
+
 mod form_data;
-pub mod macros;
-pub mod result;
+pub mod login;
+pub(crate) mod macros;
+pub(crate) mod result;
+#[cfg(feature = "tracing")]
+pub(crate) mod spans;
 
-pub(crate) use form_data::{encode_multipart_form_data, AsFormData, FormDataFile, BOUNDARY};
+#[doc(hidden)]
+pub use form_data::FormDataFile;
+pub(crate) use form_data::{prepare_attachments, AsFormData, MultipartBody, BOUNDARY};
@@ -17,6 +17,11 @@ pub enum Error {
     JSON(serde_json::Error),
     /// An error happened in a command
     Command(CommandError),
+    /// A handler panicked, with the message it panicked with
+    Panic(String),
+    /// A listener made with `#[prepare_listener]` returned an error, with the
+    /// message of the error
+    Listener(String),
 }
 
 /// An error enum returned by errors generated within the library itself
@@ -29,8 +34,41 @@ pub enum TelegramError {
     InvalidEndpoint,
     InvalidCommandType,
     WebhookError,
+    /// The request to the given endpoint did not finish in time
+    Timeout {
+        endpoint: String,
+    },
+    /// Telegram's flood control was exceeded for the given endpoint, the
+    /// request can be repeated after the given amount of seconds
+    RateLimited {
+        endpoint: String,
+        retry_after: u64,
+    },
+    /// A usage limit of the [`APIClient`] was exceeded for the given endpoint,
+    /// so the request was not sent to telegram
+    ///
+    /// [`APIClient`]: ../api/struct.APIClient.html
+    LocalRateLimit {
+        endpoint: String,
+        calls: usize,
+    },
     InvalidArgument(String),
+    /// The telegram api returned an error, with its description, for a request
+    /// of which the endpoint isn't known, or a file could not be downloaded
     APIResponseError(String),
+    /// The telegram api refused the request to the given endpoint, with the
+    /// description of the error it returned
+    EndpointError {
+        endpoint: String,
+        description: String,
+    },
+    /// A response of the telegram api could not be deserialized, for example
+    /// because it uses a newer version of the bot api, with the raw json that
+    /// was received
+    Deserialization {
+        source: serde_json::Error,
+        raw_json: String,
+    },
     Unknown(String),
 }
 
@@ -49,15 +87,74 @@ impl TelegramError {
                 "The telegram server returned a 500 status code".to_owned()
             },
             TelegramError::WebhookError => "An error occurred in the webhook handling".to_owned(),
+            TelegramError::Timeout {
+                ref endpoint,
+            } => format!("The request to {endpoint} timed out"),
+            TelegramError::RateLimited {
+                ref endpoint,
+                retry_after,
+            } => format!(
+                "Rate limited by the telegram api on {endpoint}, retry after {retry_after} seconds"
+            ),
+            TelegramError::LocalRateLimit {
+                ref endpoint,
+                calls,
+            } => format!("Usage limit of {endpoint} exceeded with {calls} calls in its window"),
             TelegramError::InvalidEndpoint => "The requested endpoint does not exist".to_owned(),
             TelegramError::InvalidCommandType => {
                 "This action cannot be done on this command type".to_owned()
             },
             TelegramError::InvalidArgument(ref e) => format!("Invalid argument provided: {e}"),
             TelegramError::APIResponseError(ref e) => {
-                format!("the telegram api returned an error: {e}")
+                format!("The telegram api returned an error: {e}")
+            },
+            TelegramError::EndpointError {
+                ref endpoint,
+                ref description,
+            } => format!("The telegram api returned an error for {endpoint}: {description}"),
+            TelegramError::Deserialization {
+                ref source, ..
+            } => format!("The response of the telegram api could not be deserialized: {source}"),
+            TelegramError::Unknown(ref e) => format!("An unknown error occurred: {e}"),
+        }
+    }
+
+    /// A short message describing the error that is safe to send to the users
+    /// of the bot, as it contains no details like tokens, file paths or
+    /// responses of the telegram api.
+    pub fn user_facing_message(&self) -> String {
+        match *self {
+            TelegramError::NoToken | TelegramError::InvalidToken => {
+                "The bot is not set up correctly".to_owned()
+            },
+            TelegramError::MissingPermission => {
+                "The bot is not allowed to do that in this chat".to_owned()
+            },
+            TelegramError::NotFound => "That could not be found".to_owned(),
+            TelegramError::ServerError
+            | TelegramError::Timeout {
+                ..
+            } => "Telegram could not be reached, please try again later".to_owned(),
+            TelegramError::RateLimited {
+                retry_after, ..
+            } => format!("Too many requests, please try again in {retry_after} seconds"),
+            TelegramError::LocalRateLimit {
+                ..
+            } => "That was done too often, please try again later".to_owned(),
+            TelegramError::InvalidArgument(_) => "Invalid input was provided".to_owned(),
+            TelegramError::APIResponseError(_)
+            | TelegramError::EndpointError {
+                ..
+            } => "Telegram refused the request".to_owned(),
+            TelegramError::InvalidEndpoint
+            | TelegramError::InvalidCommandType
+            | TelegramError::WebhookError
+            | TelegramError::Deserialization {
+                ..
+            }
+            | TelegramError::Unknown(_) => {
+                "Something went wrong, please try again later".to_owned()
             },
-            TelegramError::Unknown(ref e) => format!("unknown error occurred: {e}"),
         }
     }
 }
@@ -68,15 +165,41 @@ impl std::fmt::Display for TelegramError {
     }
 }
 
+impl Error {
+    /// A short message describing the error that is safe to send to the users
+    /// of the bot, see [`TelegramError::user_facing_message`].
+    ///
+    /// [`TelegramError::user_facing_message`]: enum.TelegramError.html#method.user_facing_message
+    pub fn user_facing_message(&self) -> String {
+        match self {
+            Error::Telegram(e) => e.user_facing_message(),
+            Error::Hyper(_) | Error::HTTP(_) => {
+                "Telegram could not be reached, please try again later".to_owned()
+            },
+            Error::IO(_) | Error::JSON(_) => {
+                "Something went wrong, please try again later".to_owned()
+            },
+            Error::Command(_) | Error::Panic(_) => {
+                "The command failed, please try again later".to_owned()
+            },
+            Error::Listener(_) => {
+                "Your request could not be handled, please try again later".to_owned()
+            },
+        }
+    }
+}
+
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::Telegram(e) => std::fmt::Display::fmt(&e, f),
-            Error::Hyper(e) => std::fmt::Display::fmt(&e, f),
-            Error::IO(e) => std::fmt::Display::fmt(&e, f),
-            Error::HTTP(e) => std::fmt::Display::fmt(&e, f),
-            Error::JSON(e) => std::fmt::Display::fmt(&e, f),
-            Error::Command(e) => std::fmt::Display::fmt(&e.0, f),
+            Error::Hyper(e) => write!(f, "An error occurred in the http client: {e}"),
+            Error::IO(e) => write!(f, "An io error occurred: {e}"),
+            Error::HTTP(e) => write!(f, "An invalid http request was built: {e}"),
+            Error::JSON(e) => write!(f, "Invalid json was received or sent: {e}"),
+            Error::Command(e) => write!(f, "The command returned an error: {}", e.0),
+            Error::Panic(reason) => write!(f, "The handler panicked: {reason}"),
+            Error::Listener(e) => write!(f, "The listener returned an error: {e}"),
         }
     }
 }
@@ -98,11 +221,22 @@ impl std::fmt::Debug for Error {
             Error::HTTP(e) => std::fmt::Debug::fmt(&e, f),
             Error::JSON(e) => std::fmt::Debug::fmt(&e, f),
             Error::Command(e) => std::fmt::Debug::fmt(&e, f),
+            Error::Panic(reason) => f.debug_tuple("Panic").field(reason).finish(),
+            Error::Listener(e) => f.debug_tuple("Listener").field(e).finish(),
         }
     }
 }
 
-impl std::error::Error for TelegramError {}
+impl std::error::Error for TelegramError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            TelegramError::Deserialization {
+                source, ..
+            } => Some(source),
+            _ => None,
+        }
+    }
+}
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
@@ -112,7 +246,7 @@ impl std::error::Error for Error {
             Error::IO(e) => e,
             Error::HTTP(e) => e,
             Error::JSON(e) => e,
-            Error::Command(_) => return None,
+            Error::Command(_) | Error::Panic(_) | Error::Listener(_) => return None,
         })
     }
 }
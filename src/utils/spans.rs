@@ -0,0 +1,65 @@
+//! The spans emitted when the `tracing` feature is enabled
+
+use crate::{
+    api::APIEndpoint,
+    model::{MaybeInaccessibleMessage, Update, UpdateContent},
+};
+use std::{future::Future, time::Instant};
+use tracing::{field, Instrument, Span};
+
+/// Runs the request to the endpoint in an `api_request` span, recording how
+/// many milliseconds it took as its `latency_ms` field
+pub(crate) async fn instrument_request<F, T>(endpoint: &APIEndpoint, request: F) -> T
+where
+    F: Future<Output = T>,
+{
+    let span = tracing::debug_span!(
+        "api_request",
+        endpoint = endpoint.as_str(),
+        latency_ms = field::Empty
+    );
+    let start = Instant::now();
+    let res = request.instrument(span.clone()).await;
+    span.record(
+        "latency_ms",
+        u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX),
+    );
+    res
+}
+
+/// Creates the `dispatch` span, in which the handlers for the update are
+/// spawned
+pub(crate) fn dispatch_span(update: &Update) -> Span {
+    let span = tracing::debug_span!(
+        "dispatch",
+        update_id = update.update_id,
+        chat_id = field::Empty
+    );
+    if let Some(chat_id) = chat_id(&update.content) {
+        span.record("chat_id", chat_id);
+    }
+    span
+}
+
+/// Creates the `handler` span for a handler spawned within the current span
+pub(crate) fn handler_span() -> Span {
+    tracing::debug_span!("handler")
+}
+
+fn chat_id(content: &UpdateContent) -> Option<i64> {
+    match content {
+        UpdateContent::Message(m)
+        | UpdateContent::EditedMessage(m)
+        | UpdateContent::ChannelPost(m)
+        | UpdateContent::EditedChannelPost(m)
+        | UpdateContent::BusinessMessage(m)
+        | UpdateContent::EditedBusinessMessage(m) => Some(m.chat.get_id()),
+        UpdateContent::CallbackQuery(q) => q
+            .message
+            .as_ref()
+            .map(MaybeInaccessibleMessage::get_chat_id),
+        UpdateContent::MyChatMember(c) | UpdateContent::ChatMember(c) => Some(c.chat.get_id()),
+        UpdateContent::ChatJoinRequest(r) => Some(r.chat.get_id()),
+        _ => None,
+    }
+}
@@ -8,20 +8,81 @@ mod utils;
 #[allow(unused_extern_crates)]
 extern crate proc_macro;
 
-use crate::structs::{BuildableStruct, CommandFunc, ListenerFunc};
+use crate::structs::{BuildableStruct, CommandFunc, CommandParam, ListenerFunc};
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::parse_macro_input;
-use utils::{add_suffix, PunctuatedNamedArgs};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Ident};
+use utils::{add_suffix, NamedArgs, PunctuatedNamedArgs};
+
+/// The maximum length of a command name allowed by telegram
+const MAX_NAME_LENGTH: usize = 32;
+/// The minimum length of a command description allowed by telegram
+const MIN_DESCRIPTION_LENGTH: usize = 3;
+/// The maximum length of a command description allowed by telegram
+const MAX_DESCRIPTION_LENGTH: usize = 256;
 
 /// A function attribute macro for making event listeners easier.
 ///
 /// This macro transforms an async function into a function returning a pinned
 /// box containing a future, which is used internally by telexide to store the
 /// function.
+///
+/// When the function returns a `Result` with `()` as its success type, like
+/// `telexide::Result<()>` or `Result<(), E>` with an error that implements
+/// `Display`, the future still outputs `()` and an error is passed to the
+/// error handler of the client, which logs it by default. Other return types
+/// make the future output that type instead, as is used by the inline answer
+/// handlers of the framework.
+///
+/// ```rust,ignore
+/// #[prepare_listener]
+/// async fn greet(ctx: Context, update: Update) -> telexide::Result<()> {
+///     if let UpdateContent::Message(message) = update.content {
+///         ctx.reply(&message, "hello!").await?;
+///     }
+///     Ok(())
+/// }
+/// ```
+///
+/// # Filtering updates
+///
+/// With `updates = "message, callback_query"` the listener is only called
+/// for updates of those kinds, using the names of the update types of
+/// telegram. The macro then also creates a static named after the function
+/// with `_LISTENER` appended, which has to be added to the client using
+/// `ClientBuilder::add_listener` so the client doesn't call the listener for
+/// other updates at all.
+///
+/// Instead of the update, such a listener can take its content directly,
+/// which has to be the same type for all of the given update kinds.
+///
+/// ```rust,ignore
+/// #[prepare_listener(updates = "callback_query")]
+/// async fn on_button(ctx: Context, query: CallbackQuery) {
+///     ...
+/// }
+///
+/// ClientBuilder::new().add_listener(&on_button_LISTENER);
+/// ```
 #[proc_macro_attribute]
-pub fn prepare_listener(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    let listener = parse_macro_input!(item as ListenerFunc);
+pub fn prepare_listener(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut listener = parse_macro_input!(item as ListenerFunc);
+    let args: PunctuatedNamedArgs = parse_macro_input!(attr as PunctuatedNamedArgs);
+
+    for arg in args.0 {
+        let res = match arg.name.as_str() {
+            "updates" => listener.set_updates(&arg.value, arg.span),
+            name => Err(syn::Error::new(
+                arg.name_span,
+                format!("Unknown listener option {name}, expected updates"),
+            )),
+        };
+        if let Err(e) = res {
+            return e.to_compile_error().into();
+        }
+    }
+
     (quote! {
         #listener
     })
@@ -48,35 +109,290 @@ pub fn prepare_listener(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// command as to be displayed in telegram, 3-256 characters             |
 /// | Name        | name = "the command name"        | The name to be used
 /// within telegram, 1-32 characters                                        |
+/// | Priority    | priority = 10                    | The priority of the
+/// command when more than 100 commands are registered, defaults to 0        |
+/// | Aliases     | aliases = "foo, bar"             | Other names the command
+/// can be invoked with, which are not registered with telegram             |
+/// | Topics      | topics = "123, 456"              | The message thread ids of
+/// the forum topics the command can only be used in, see the notes         |
+/// | Localized description | description_localized(lang = "de", text = "...") |
+/// The description to display to users with the given language, can be repeated |
+/// | Subcommands | subcommands = "config_get, config_set" | The functions of
+/// the commands to invoke when their name follows this command, see the notes |
+/// | Checks      | checks = "dm_only, is_owner"     | The checks that have to
+/// allow a message to invoke the command, see the notes                      |
+/// | Cooldown    | cooldown = 10                    | The seconds a user has
+/// to wait before using the command again                                     |
+/// | Cooldown scope | cooldown_scope = "chat"       | Whether the cooldown is
+/// per `user` or per `chat`, defaults to `user`                               |
+/// | Usage       | usage = "<user> [reason]"        | The arguments of the
+/// command as shown by the help command, see the notes                        |
 ///
 /// # Notes
 ///
-/// - The description argument is required, because telegram requires it for a
-///   command to be displayed there.
-/// - The name argument defaults to the name of the command if not provided
+/// - A description is required, because telegram requires it for a command
+///   to be displayed there. When the description argument isn't given, the
+///   `///` doc comments of the function are used instead, joined into a
+///   single line and cut off at 256 characters.
+/// - The name argument defaults to the name of the command if not provided,
+///   and is turned into lowercase like the aliases. Names and aliases which
+///   telegram doesn't allow, with other characters than the letters a-z,
+///   digits and underscores, are refused at compile time, as are descriptions
+///   that are too short or too long and unknown options.
+/// - The priority is only used when auto prioritization is enabled on the
+///   framework, in which case the commands with the highest priority are
+///   registered with telegram
+/// - The names and aliases of the commands registered on a framework have to
+///   be unique, adding a command of which the name or an alias is taken
+///   panics.
+/// - The "General" forum topic has thread id 1, while `topics =
+///   "except_general"` allows the command in every forum topic except the
+///   "General" topic. Uses of the command outside of its topics are ignored.
+/// - The language of a localized description is a two-letter ISO 639-1 code,
+///   users with another language see the default description.
+/// - Subcommands are made with this macro as well and referenced by their
+///   function name. When the first word following the command is the name or
+///   an alias of a subcommand, like in `/config get`, the subcommand is called
+///   with the text following that word, otherwise the command itself is
+///   called. Subcommands don't have to be added to the framework.
+/// - A check is an async function taking a `Context` and `&Message` and
+///   returning a `telexide::Result<bool>`, the command is only called when
+///   all its checks return `Ok(true)`. The built-in checks `dm_only`,
+///   `group_only` and `chat_admin_only` from `telexide::framework::checks`
+///   can be used by their name. See `Framework::set_refusal_message` for
+///   telling users they can't use the command.
+/// - Uses of a command on cooldown are ignored, see
+///   `Framework::set_cooldown_message` for telling users when to try again.
+/// - The usage defaults to the typed arguments of the command, with required
+///   arguments in angle brackets and optional ones in square brackets. It is
+///   shown by the help command added using `Framework::add_help_command`.
+///
+/// # Arguments
+///
+/// Besides the context and message, a command can take arguments which are
+/// parsed from the text following the command using [`FromStr`], split like
+/// `Context::args` does.
+///
+/// - An `Option<T>` argument is `None` when there is no more text, it can
+///   only be followed by other optional arguments.
+/// - A `Vec<T>` as the last argument collects all remaining arguments.
+/// - A `String` or `Option<String>` as the last argument takes the rest of the
+///   text as is.
+///
+/// When an argument is missing or can't be parsed, or when more arguments are
+/// given than the command takes, the command isn't called and returns a
+/// `CommandError` describing the problem instead.
+///
+/// ~~~rust,ignore
+/// #[command(description = "bans a user")]
+/// async fn ban(
+///     ctx: Context,
+///     message: Message,
+///     user: i64,
+///     reason: Option<String>,
+/// ) -> CommandResult {
+///     ...
+/// }
+/// ~~~
+///
+/// [`FromStr`]: https://doc.rust-lang.org/std/str/trait.FromStr.html
 #[proc_macro_attribute]
 pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let command_fun = parse_macro_input!(item as CommandFunc);
     let args: PunctuatedNamedArgs = parse_macro_input!(attr as PunctuatedNamedArgs);
 
     let mut telegram_command_name = command_fun.name.to_string();
+    let mut name_span = command_fun.name.span();
     let mut description = String::new();
+    let mut description_span = command_fun.name.span();
+    let mut priority: i64 = 0;
+    let mut aliases: Vec<String> = Vec::new();
+    let mut topics = quote!(Everywhere);
+    let mut localized: Vec<(String, String)> = Vec::new();
+    let mut subcommands: Vec<Ident> = Vec::new();
+    let mut checks: Vec<TokenStream2> = Vec::new();
+    let mut cooldown: Option<u64> = None;
+    let mut cooldown_scope = quote!(User);
+    let mut usage: Option<String> = None;
 
-    for arg in args.0 {
+    let mut aliases_span = name_span;
+
+    let parsed = args.0.into_iter().try_for_each(|arg| -> syn::Result<()> {
         match arg.name.as_str() {
-            "name" => telegram_command_name = arg.value.clone(),
-            "description" => description = arg.value.clone(),
-            _ => (),
+            "name" => {
+                telegram_command_name = arg.value.clone();
+                name_span = arg.span;
+            },
+            "description" => {
+                description = arg.value.clone();
+                description_span = arg.span;
+            },
+            "aliases" => {
+                aliases = split_list(&arg.value).map(str::to_lowercase).collect();
+                aliases_span = arg.span;
+            },
+            "topics" if arg.value.trim() == "except_general" => topics = quote!(ExceptGeneral),
+            "topics" => {
+                let ids = split_list(&arg.value)
+                    .map(|t| {
+                        t.parse::<i64>().map_err(|_| {
+                            syn::Error::new(
+                                arg.span,
+                                format!(
+                                    "The topics of the {telegram_command_name} command must be message thread ids, got {t}"
+                                ),
+                            )
+                        })
+                    })
+                    .collect::<syn::Result<Vec<_>>>()?;
+                topics = quote!(Topics(::std::borrow::Cow::Borrowed(&[#(#ids),*])));
+            },
+            "description_localized" => {
+                let (lang, text) = parse_localized_description(&telegram_command_name, arg)?;
+                if localized.iter().any(|(l, _)| *l == lang.0) {
+                    return Err(syn::Error::new(
+                        lang.1,
+                        format!(
+                            "The {telegram_command_name} command has multiple {} descriptions",
+                            lang.0
+                        ),
+                    ));
+                }
+                localized.push((lang.0, text));
+            },
+            "subcommands" => {
+                subcommands = split_list(&arg.value)
+                    .map(|s| {
+                        let fun = syn::parse_str::<Ident>(s).map_err(|_| {
+                            syn::Error::new(
+                                arg.span,
+                                format!(
+                                    "The subcommands of the {telegram_command_name} command must be function names, got {s}"
+                                ),
+                            )
+                        })?;
+                        Ok(add_suffix(&fun, "COMMAND"))
+                    })
+                    .collect::<syn::Result<_>>()?;
+            },
+            "checks" => {
+                checks = split_list(&arg.value)
+                    .map(|c| match c {
+                        "dm_only" | "group_only" | "chat_admin_only" => {
+                            let check = format_ident!("{}", c);
+                            Ok(quote!(telexide::framework::checks::#check))
+                        },
+                        _ => {
+                            let check = syn::parse_str::<Ident>(c).map_err(|_| {
+                                syn::Error::new(
+                                    arg.span,
+                                    format!(
+                                        "The checks of the {telegram_command_name} command must be function names, got {c}"
+                                    ),
+                                )
+                            })?;
+                            Ok(quote!(#check))
+                        },
+                    })
+                    .collect::<syn::Result<_>>()?;
+            },
+            "cooldown" => {
+                cooldown = Some(arg.value.parse().map_err(|_| {
+                    syn::Error::new(
+                        arg.span,
+                        format!(
+                            "The cooldown of the {telegram_command_name} command must be a whole number of seconds, got {}",
+                            arg.value
+                        ),
+                    )
+                })?);
+            },
+            "cooldown_scope" => {
+                cooldown_scope = match arg.value.as_str() {
+                    "user" => quote!(User),
+                    "chat" => quote!(Chat),
+                    scope => {
+                        return Err(syn::Error::new(
+                            arg.span,
+                            format!(
+                                "The cooldown scope of the {telegram_command_name} command must be user or chat, got {scope}"
+                            ),
+                        ))
+                    },
+                }
+            },
+            "usage" => usage = Some(arg.value.clone()),
+            "priority" => {
+                priority = arg.value.parse().map_err(|_| {
+                    syn::Error::new(
+                        arg.span,
+                        format!(
+                            "The priority of the {telegram_command_name} command must be an integer, got {}",
+                            arg.value
+                        ),
+                    )
+                })?;
+            },
+            name => {
+                return Err(syn::Error::new(
+                    arg.name_span,
+                    format!("Unknown command option {name}, expected one of {COMMAND_OPTIONS}"),
+                ))
+            },
         }
+        Ok(())
+    });
+    if let Err(e) = parsed {
+        return e.to_compile_error().into();
     }
 
-    if description.len() < 3 {
-        panic!(
-            "No description longer than 3 characters has been provided for the {} command, while descriptions are required by telegram",
-            telegram_command_name
-        )
+    telegram_command_name = telegram_command_name.to_lowercase();
+    if let Err(e) = validate_name(
+        &format!("The name of the {telegram_command_name} command"),
+        &telegram_command_name,
+        name_span,
+    ) {
+        return e.to_compile_error().into();
+    }
+    for alias in &aliases {
+        let subject = format!("The alias {alias} of the {telegram_command_name} command");
+        if let Err(e) = validate_name(&subject, alias, aliases_span) {
+            return e.to_compile_error().into();
+        }
+    }
+
+    if description.is_empty() {
+        if let Some(doc) = command_fun.doc_comment() {
+            description = doc.chars().take(MAX_DESCRIPTION_LENGTH).collect();
+            description = description.trim_end().to_owned();
+        }
     }
 
+    if let Err(e) = validate_description(&telegram_command_name, &description, description_span) {
+        return e.to_compile_error().into();
+    }
+
+    let cooldown = match cooldown {
+        Some(seconds) => quote! {
+            ::std::option::Option::Some(telexide::framework::types::Cooldown {
+                duration: ::std::time::Duration::from_secs(#seconds),
+                scope: telexide::framework::types::CooldownScope::#cooldown_scope,
+            })
+        },
+        None => quote!(::std::option::Option::None),
+    };
+
+    let usage = usage.or_else(|| {
+        let params: Vec<String> = command_fun.params.iter().map(CommandParam::usage).collect();
+        (!params.is_empty()).then(|| params.join(" "))
+    });
+    let usage = match usage {
+        Some(usage) => quote!(::std::option::Option::Some(::std::borrow::Cow::Borrowed(#usage))),
+        None => quote!(::std::option::Option::None),
+    };
+
+    let (languages, localized_descriptions): (Vec<_>, Vec<_>) = localized.into_iter().unzip();
+
     let fun_name = command_fun.name.clone();
     let command_name = add_suffix(&fun_name, "COMMAND");
     let options_name = add_suffix(&fun_name, "COMMAND_OPTIONS");
@@ -87,17 +403,37 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     let command_struct_path = quote!(telexide::framework::types::TelegramCommand);
     let options_struct_path = quote!(telexide::framework::types::CommandOptions);
     let default_command_type_path = quote!(telexide::framework::types::CommandTypes::Default);
+    let topic_scope_path = quote!(telexide::framework::types::TopicScope);
+    let check_outcome_path = quote!(telexide::framework::types::CheckOutcome);
+    let check_func_path = quote!(telexide::framework::types::CommandCheckFunc);
+    let cow = quote!(::std::borrow::Cow::Borrowed);
 
     (quote! {
         #(#options_cooked)*
         pub static #options_name: #options_struct_path = #options_struct_path {
-            name: #telegram_command_name,
-            description: #description,
+            name: #cow(#telegram_command_name),
+            description: #cow(#description),
+            usage: #usage,
+            priority: #priority,
+            aliases: #cow(&[#(#cow(#aliases)),*]),
+            topics: #topic_scope_path::#topics,
+            localized_descriptions: #cow(&[#((#cow(#languages), #cow(#localized_descriptions))),*]),
+            subcommands: #cow(&[#(#cow(&#subcommands)),*]),
+            checks: #cow(&[#({
+                fn check<'a>(
+                    ctx: telexide::client::Context,
+                    message: &'a telexide::model::Message,
+                ) -> #check_outcome_path<'a> {
+                    ::std::boxed::Box::pin(#checks(ctx, message))
+                }
+                check as #check_func_path
+            }),*]),
+            cooldown: #cooldown,
         };
 
         #(#command_cooked)*
         pub static #command_name: #command_struct_path = #command_struct_path {
-            options: &#options_name,
+            options: #cow(&#options_name),
             command: #default_command_type_path(#fun_name),
         };
 
@@ -106,6 +442,111 @@ pub fn command(attr: TokenStream, item: TokenStream) -> TokenStream {
     .into()
 }
 
+/// The options the command macro takes
+const COMMAND_OPTIONS: &str = "name, description, description_localized, aliases, topics, subcommands, checks, cooldown, cooldown_scope, usage and priority";
+
+/// Splits a comma separated list, leaving out empty items
+fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    list.split(',').map(str::trim).filter(|i| !i.is_empty())
+}
+
+/// Parses a `description_localized(lang = "..", text = "..")` argument into
+/// its language, with the span of it, and its text
+fn parse_localized_description(
+    command: &str,
+    arg: NamedArgs,
+) -> syn::Result<((String, Span), String)> {
+    let mut lang = (String::new(), arg.name_span);
+    let mut text = (String::new(), arg.name_span);
+    for nested in arg.nested {
+        match nested.name.as_str() {
+            "lang" => lang = (nested.value, nested.span),
+            "text" => text = (nested.value, nested.span),
+            name => {
+                return Err(syn::Error::new(
+                    nested.name_span,
+                    format!("Unknown localized description option {name}, expected lang or text"),
+                ))
+            },
+        }
+    }
+
+    if lang.0.len() != 2 || !lang.0.chars().all(|c| c.is_ascii_lowercase()) {
+        return Err(syn::Error::new(
+            lang.1,
+            format!(
+                "The language of a localized description of the {command} command must be a two-letter language code, got {:?}",
+                lang.0
+            ),
+        ));
+    }
+
+    let length = text.0.chars().count();
+    if !(MIN_DESCRIPTION_LENGTH..=MAX_DESCRIPTION_LENGTH).contains(&length) {
+        return Err(syn::Error::new(
+            text.1,
+            format!(
+                "The {} description of the {command} command must be {MIN_DESCRIPTION_LENGTH}-{MAX_DESCRIPTION_LENGTH} characters, got {length}",
+                lang.0
+            ),
+        ));
+    }
+
+    Ok((lang, text.0))
+}
+
+/// Checks whether the name is allowed by telegram, being 1-32 characters of
+/// the letters a-z, digits and underscores. The subject names what the name
+/// is of in the error.
+fn validate_name(subject: &str, name: &str, span: Span) -> syn::Result<()> {
+    let length = name.chars().count();
+    if length == 0 || length > MAX_NAME_LENGTH {
+        return Err(syn::Error::new(
+            span,
+            format!("{subject} must be 1-{MAX_NAME_LENGTH} characters, got {length}"),
+        ));
+    }
+
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_lowercase() || c.is_ascii_digit() || *c == '_'))
+    {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "{subject} can only contain the letters a-z, digits and underscores, got {c:?}"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks whether the description is allowed by telegram, being 3-256
+/// characters
+fn validate_description(name: &str, description: &str, span: Span) -> syn::Result<()> {
+    if description.is_empty() {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "No description has been provided for the {name} command, either as the description argument or as doc comments, while descriptions are required by telegram"
+            ),
+        ));
+    }
+
+    let length = description.chars().count();
+    if !(MIN_DESCRIPTION_LENGTH..=MAX_DESCRIPTION_LENGTH).contains(&length) {
+        return Err(syn::Error::new(
+            span,
+            format!(
+                "The description of the {name} command must be {MIN_DESCRIPTION_LENGTH}-{MAX_DESCRIPTION_LENGTH} characters, got {length}"
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
 #[proc_macro_attribute]
 pub fn build_struct(_: TokenStream, item: TokenStream) -> TokenStream {
     let build_struct = parse_macro_input!(item as BuildableStruct);
@@ -1,13 +1,22 @@
-use proc_macro2::TokenStream as TokenStream2;
-use quote::{quote, ToTokens};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
 use syn::{
     braced,
     parse::{Parse, ParseStream, Result},
+    spanned::Spanned,
     Attribute,
     Block,
+    Error,
+    Expr,
+    ExprLit,
     FnArg,
+    GenericArgument,
     Ident,
     ItemStruct,
+    Lit,
+    Meta,
+    Pat,
+    PathArguments,
     ReturnType,
     Stmt,
     Token,
@@ -15,7 +24,34 @@ use syn::{
     Visibility,
 };
 
-use super::utils::{BuildImplBlock, ParenthesisedItems};
+use super::utils::{add_suffix, BuildImplBlock, ParenthesisedItems};
+
+/// The names of the update kinds listeners can filter on, with the variant
+/// of `UpdateContent` and `UpdateType` they belong to
+const UPDATE_KINDS: [(&str, &str); 22] = [
+    ("message", "Message"),
+    ("edited_message", "EditedMessage"),
+    ("channel_post", "ChannelPost"),
+    ("edited_channel_post", "EditedChannelPost"),
+    ("inline_query", "InlineQuery"),
+    ("chosen_inline_result", "ChosenInlineResult"),
+    ("callback_query", "CallbackQuery"),
+    ("shipping_query", "ShippingQuery"),
+    ("pre_checkout_query", "PreCheckoutQuery"),
+    ("poll", "Poll"),
+    ("poll_answer", "PollAnswer"),
+    ("my_chat_member", "MyChatMember"),
+    ("chat_member", "ChatMember"),
+    ("chat_join_request", "ChatJoinRequest"),
+    ("message_reaction", "MessageReaction"),
+    ("message_reaction_count", "MessageReactionCount"),
+    ("chat_boost", "ChatBoost"),
+    ("removed_chat_boost", "RemovedChatBoost"),
+    ("business_connection", "BusinessConnection"),
+    ("business_message", "BusinessMessage"),
+    ("edited_business_message", "EditedBusinessMessage"),
+    ("deleted_business_messages", "DeletedBusinessMessages"),
+];
 
 #[derive(Debug)]
 pub struct ListenerFunc {
@@ -26,7 +62,48 @@ pub struct ListenerFunc {
     pub visibility: Visibility,
     pub name: Ident,
     pub args: Vec<FnArg>,
+    /// The output of the future, `()` when no return type is given
+    pub ret: Option<Type>,
     pub body: Vec<Stmt>,
+    /// The variants of the update kinds the listener is called for, it is
+    /// called for every update when empty
+    pub updates: Vec<Ident>,
+}
+
+impl ListenerFunc {
+    /// Sets the update kinds the listener is called for from a list like
+    /// `"message, callback_query"`
+    pub fn set_updates(&mut self, value: &str, span: Span) -> Result<()> {
+        for kind in value.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+            let Some((_, variant)) = UPDATE_KINDS.iter().find(|(name, _)| *name == kind) else {
+                return Err(Error::new(
+                    span,
+                    format!(
+                        "{kind:?} is not an update kind, expected one of {}",
+                        UPDATE_KINDS.map(|(name, _)| name).join(", ")
+                    ),
+                ));
+            };
+            self.updates.push(Ident::new(variant, span));
+        }
+
+        if self.updates.is_empty() {
+            return Err(Error::new(span, "no update kinds were given"));
+        }
+        if self.args.len() != 2 {
+            return Err(Error::new_spanned(
+                &self.name,
+                "listeners filtering updates take a Context and the update or its content",
+            ));
+        }
+        if let Some(ret) = self.ret.as_ref().filter(|r| !is_unit_result(r)) {
+            return Err(Error::new_spanned(
+                ret,
+                "listeners filtering updates can only return nothing or a Result<(), E>",
+            ));
+        }
+        Ok(())
+    }
 }
 
 impl Parse for ListenerFunc {
@@ -45,9 +122,9 @@ impl Parse for ListenerFunc {
 
         let ParenthesisedItems(args) = input.parse::<ParenthesisedItems<FnArg>>()?;
 
-        match input.parse::<ReturnType>()? {
-            ReturnType::Type(_, _) => return Err(input.error("expected a default return value")),
-            ReturnType::Default => (),
+        let ret = match input.parse::<ReturnType>()? {
+            ReturnType::Type(_, t) => Some(*t),
+            ReturnType::Default => None,
         };
 
         let body_content;
@@ -62,7 +139,9 @@ impl Parse for ListenerFunc {
             visibility,
             name,
             args,
+            ret,
             body,
+            updates: Vec::new(),
         })
     }
 }
@@ -75,17 +154,98 @@ impl ToTokens for ListenerFunc {
             visibility,
             name,
             args,
+            ret,
             body,
+            updates,
         } = self;
 
+        let reports_errors = !args.is_empty() && ret.as_ref().is_some_and(is_unit_result);
+        if updates.is_empty() && !reports_errors {
+            let output = ret
+                .as_ref()
+                .map_or_else(|| quote!(()), ToTokens::to_token_stream);
+            stream.extend(quote! {
+                #(#cooked)*
+                #visibility fn #name (#(#args),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = #output> + ::std::marker::Send )>> {
+                    ::std::boxed::Box::pin(async move {
+                        #(#body)*
+                })
+                }
+            });
+            return;
+        }
+
+        let idents: Vec<Ident> = (0..args.len())
+            .map(|i| format_ident!("__telexide_arg_{}", i))
+            .collect();
+        let mut types: Vec<TokenStream2> = args
+            .iter()
+            .map(|arg| match arg {
+                FnArg::Typed(arg) => arg.ty.to_token_stream(),
+                FnArg::Receiver(arg) => arg.to_token_stream(),
+            })
+            .collect();
+        let ret_type = ret.as_ref().map(|r| quote!(-> #r));
+
+        let extract = if updates.is_empty() {
+            quote!()
+        } else {
+            let update = &idents[1];
+            let takes_update = matches!(&args[1], FnArg::Typed(arg) if is_update(&arg.ty));
+            types[1] = quote!(telexide::model::Update);
+
+            if takes_update {
+                quote! {
+                    if !matches!(#update.content, #(telexide::model::UpdateContent::#updates(_))|*) {
+                        return ::std::boxed::Box::pin(async {});
+                    }
+                }
+            } else {
+                quote! {
+                    let #update = match #update.content {
+                        #(telexide::model::UpdateContent::#updates(content))|* => content,
+                        _ => return ::std::boxed::Box::pin(async {}),
+                    };
+                }
+            }
+        };
+
+        let call = if reports_errors {
+            let ctx = &idents[0];
+            quote! {
+                let __telexide_ctx = ::std::clone::Clone::clone(&#ctx);
+                ::std::boxed::Box::pin(async move {
+                    if let ::std::result::Result::Err(e) = __telexide_listener_body(#(#idents),*).await {
+                        __telexide_ctx.report_error(telexide::Error::Listener(::std::string::ToString::to_string(&e)));
+                    }
+                })
+            }
+        } else {
+            quote!(::std::boxed::Box::pin(__telexide_listener_body(#(#idents),*)))
+        };
+
         stream.extend(quote! {
             #(#cooked)*
-            #visibility fn #name (#(#args),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = ()> + ::std::marker::Send )>> {
-                ::std::boxed::Box::pin(async move {
+            #visibility fn #name (#(#idents: #types),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = ()> + ::std::marker::Send )>> {
+                async fn __telexide_listener_body (#(#args),*) #ret_type {
                     #(#body)*
-            })
+                }
+
+                #extract
+                #call
             }
         });
+
+        if !updates.is_empty() {
+            let listener_name = add_suffix(name, "LISTENER");
+            stream.extend(quote! {
+                #(#cooked)*
+                #visibility static #listener_name: telexide::client::EventListener = telexide::client::EventListener {
+                    update_types: &[#(telexide::api::types::UpdateType::#updates),*],
+                    handler: #name,
+                };
+            });
+        }
     }
 }
 
@@ -99,9 +259,165 @@ pub struct CommandFunc {
     pub name: Ident,
     pub ret: Type,
     pub args: Vec<FnArg>,
+    /// The arguments after the context and message, which are parsed from
+    /// the text following the command
+    pub params: Vec<CommandParam>,
     pub body: Vec<Stmt>,
 }
 
+/// An argument of a command parsed from the text following the command
+#[derive(Debug)]
+pub struct CommandParam {
+    pub name: String,
+    pub kind: CommandParamKind,
+}
+
+#[derive(Debug)]
+pub enum CommandParamKind {
+    /// A single argument which has to be provided
+    Required(Type),
+    /// A single argument which may be left out, as `Option<T>`
+    Optional(Type),
+    /// All remaining arguments, as `Vec<T>` in the last position
+    All(Type),
+    /// The rest of the text, as `String` in the last position
+    RequiredRest,
+    /// The rest of the text if there is any, as `Option<String>` in the last
+    /// position
+    OptionalRest,
+}
+
+impl CommandParam {
+    fn new(index: usize, arg: &FnArg, is_last: bool) -> Result<Self> {
+        let FnArg::Typed(arg) = arg else {
+            return Err(Error::new_spanned(arg, "commands can't take self"));
+        };
+        let name = match &*arg.pat {
+            Pat::Ident(pat) => pat.ident.to_string().trim_start_matches('_').to_owned(),
+            _ => format!("argument {}", index + 1),
+        };
+
+        let kind = match outer_type(&arg.ty) {
+            Some(("Option", Some(inner))) if is_last && is_string(inner) => {
+                CommandParamKind::OptionalRest
+            },
+            Some(("Option", Some(inner))) => CommandParamKind::Optional(inner.clone()),
+            Some(("Vec", Some(_))) if !is_last => {
+                return Err(Error::new_spanned(
+                    &arg.ty,
+                    "only the last argument of a command can collect the remaining arguments",
+                ))
+            },
+            Some(("Vec", Some(inner))) => CommandParamKind::All(inner.clone()),
+            _ if is_last && is_string(&arg.ty) => CommandParamKind::RequiredRest,
+            _ => CommandParamKind::Required((*arg.ty).clone()),
+        };
+
+        Ok(Self {
+            name,
+            kind,
+        })
+    }
+
+    /// How the argument is shown in the usage of the command, like `<user>`
+    /// or `[reason...]`
+    pub fn usage(&self) -> String {
+        let name = &self.name;
+        match self.kind {
+            CommandParamKind::Required(_) => format!("<{name}>"),
+            CommandParamKind::Optional(_) => format!("[{name}]"),
+            CommandParamKind::RequiredRest => format!("<{name}...>"),
+            CommandParamKind::All(_) | CommandParamKind::OptionalRest => format!("[{name}...]"),
+        }
+    }
+}
+
+/// Gets the name of the outer type and its first type argument, like
+/// `("Option", Some(T))` for `Option<T>`
+fn outer_type(ty: &Type) -> Option<(&'static str, Option<&Type>)> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|a| match a {
+            GenericArgument::Type(t) => Some(t),
+            _ => None,
+        }),
+        _ => None,
+    };
+
+    ["Option", "Vec", "String"]
+        .into_iter()
+        .find(|name| segment.ident == name)
+        .map(|name| (name, inner))
+}
+
+/// Whether the type is a `Result` with `()` as its success type, like
+/// `Result<(), E>` or `telexide::Result<()>`
+fn is_unit_result(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    let Some(segment) = path.path.segments.last() else {
+        return false;
+    };
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return false;
+    };
+
+    segment.ident == "Result"
+        && matches!(
+            args.args.first(),
+            Some(GenericArgument::Type(Type::Tuple(t))) if t.elems.is_empty()
+        )
+}
+
+/// Whether the type is the `Update` itself rather than its content
+fn is_update(ty: &Type) -> bool {
+    let Type::Path(path) = ty else {
+        return false;
+    };
+    path.path
+        .segments
+        .last()
+        .is_some_and(|s| s.ident == "Update" && s.arguments.is_empty())
+}
+
+fn is_string(ty: &Type) -> bool {
+    matches!(outer_type(ty), Some(("String", None)))
+}
+
+impl CommandFunc {
+    /// Gets the text of the `///` doc comments of the function, with the lines
+    /// trimmed and joined by spaces, or `None` when it has none
+    pub fn doc_comment(&self) -> Option<String> {
+        let lines: Vec<String> = self
+            .attributes
+            .iter()
+            .filter_map(|a| match &a.meta {
+                Meta::NameValue(meta) if meta.path.is_ident("doc") => match &meta.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(doc),
+                        ..
+                    }) => Some(doc.value()),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .flat_map(|doc| {
+                doc.lines()
+                    .map(str::trim)
+                    .filter(|l| !l.is_empty())
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        (!lines.is_empty()).then(|| lines.join(" "))
+    }
+}
+
 impl Parse for CommandFunc {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let attributes = input.call(Attribute::parse_outer)?;
@@ -129,6 +445,24 @@ impl Parse for CommandFunc {
 
         let args = args.into_iter().collect::<Vec<FnArg>>();
 
+        let mut params: Vec<CommandParam> = Vec::new();
+        for (index, arg) in args.iter().enumerate().skip(2) {
+            let param = CommandParam::new(index - 2, arg, index == args.len() - 1)?;
+            if matches!(
+                param.kind,
+                CommandParamKind::Required(_) | CommandParamKind::RequiredRest
+            ) && params
+                .iter()
+                .any(|p| matches!(p.kind, CommandParamKind::Optional(_)))
+            {
+                return Err(Error::new_spanned(
+                    arg,
+                    "required arguments can't follow an optional argument",
+                ));
+            }
+            params.push(param);
+        }
+
         Ok(Self {
             attributes,
             cooked,
@@ -136,6 +470,7 @@ impl Parse for CommandFunc {
             name,
             ret,
             args,
+            params,
             body,
         })
     }
@@ -150,15 +485,54 @@ impl ToTokens for CommandFunc {
             ret,
             name,
             args,
+            params,
             body,
         } = self;
 
+        let command = if params.is_empty() {
+            quote!(__telexide_command_body)
+        } else {
+            let idents: Vec<Ident> = (0..params.len())
+                .map(|i| format_ident!("__telexide_arg_{}", i))
+                .collect();
+            let parsers = params.iter().map(
+                |CommandParam {
+                     name,
+                     kind,
+                 }| match kind {
+                    CommandParamKind::Required(ty) => {
+                        quote_spanned!(ty.span()=> required::<#ty>(#name)?)
+                    },
+                    CommandParamKind::Optional(ty) => {
+                        quote_spanned!(ty.span()=> optional::<#ty>(#name)?)
+                    },
+                    CommandParamKind::All(ty) => quote_spanned!(ty.span()=> all::<#ty>(#name)?),
+                    CommandParamKind::RequiredRest => quote!(required_rest(#name)?),
+                    CommandParamKind::OptionalRest => quote!(optional_rest()),
+                },
+            );
+
+            quote! {
+                |ctx: telexide::client::Context, message: telexide::model::Message| async move {
+                    let mut __telexide_args = telexide::framework::handlers::CommandArgs::new(ctx.rest());
+                    #(let #idents = __telexide_args.#parsers;)*
+                    __telexide_args.finish()?;
+
+                    __telexide_command_body(ctx, message, #(#idents),*).await
+                }
+            }
+        };
+
         stream.extend(quote! {
             #(#cooked)*
-            #visibility fn #name (#(#args),*) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = #ret> + ::std::marker::Send )>> {
-                ::std::boxed::Box::pin(async move {
+            #visibility fn #name (ctx: telexide::client::Context, message: telexide::model::Message) -> ::std::pin::Pin<::std::boxed::Box<(dyn ::std::future::Future<Output = telexide::framework::CommandResult> + ::std::marker::Send )>> {
+                async fn __telexide_command_body (#(#args),*) -> #ret {
                     #(#body)*
-            })
+                }
+
+                ::std::boxed::Box::pin(
+                    telexide::framework::handlers::respond(ctx, message, #command)
+                )
             }
         });
     }
@@ -4,7 +4,7 @@ use syn::{
     parenthesized,
     parse::{Parse, ParseStream, Result},
     punctuated::Punctuated,
-    token::Comma,
+    token::{Comma, Paren},
     Field,
     GenericArgument,
     Path,
@@ -33,22 +33,44 @@ impl Parse for PunctuatedNamedArgs {
     }
 }
 
+/// An argument in the form of `name = "value"`, or `name(a = "b", ...)` in
+/// which case the value is empty and the inner arguments are in `nested`
 pub struct NamedArgs {
     pub name: String,
+    pub name_span: Span,
     pub value: String,
+    /// The span of the value, or of the name when it has nested arguments
+    pub span: Span,
+    pub nested: Vec<NamedArgs>,
 }
 
 impl Parse for NamedArgs {
     fn parse(input: ParseStream<'_>) -> Result<Self> {
-        let name = input.parse::<Ident>()?.to_string();
+        let ident = input.parse::<Ident>()?;
+        let name = ident.to_string();
+        if input.peek(Paren) {
+            let ParenthesisedItems(nested) = input.parse::<ParenthesisedItems<NamedArgs>>()?;
+            return Ok(Self {
+                name,
+                name_span: ident.span(),
+                value: String::new(),
+                span: ident.span(),
+                nested: nested.into_iter().collect(),
+            });
+        }
+
         input.parse::<Token![=]>()?;
-        let mut value = input.parse::<Literal>()?.to_string();
+        let literal = input.parse::<Literal>()?;
+        let mut value = literal.to_string();
         value = value.trim_start_matches('\"').to_owned();
         value = value.trim_end_matches('\"').to_owned();
 
         Ok(Self {
             name,
+            name_span: ident.span(),
             value,
+            span: literal.span(),
+            nested: Vec::new(),
         })
     }
 }
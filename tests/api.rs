@@ -0,0 +1,1992 @@
+use async_trait::async_trait;
+use parking_lot::Mutex;
+use serde_json::{json, Value};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Duration,
+};
+use telexide::{
+    api::{
+        types::{
+            AnswerInlineQuery,
+            CloseForumTopic,
+            CopyMessage,
+            CopyMessages,
+            CreateNewStickerSet,
+            DeleteMessages,
+            EditMessageMedia,
+            EditMessageText,
+            ForwardMessages,
+            GeneralTopicEncoding,
+            GetBusinessConnection,
+            GetStarTransactions,
+            GetUserChatBoosts,
+            InlineQueryResultsButton,
+            InputFile,
+            InputMedia,
+            InputMediaDocument,
+            InputMediaVideo,
+            InputPaidMedia,
+            InputPaidMediaPhoto,
+            InputPaidMediaVideo,
+            RefundStarPayment,
+            ReplaceStickerInSet,
+            ReplyParameters,
+            SendAudio,
+            SendChatAction,
+            SendDice,
+            SendGame,
+            SendMediaGroup,
+            SendMessage,
+            SendPaidMedia,
+            SendPhoto,
+            SendPoll,
+            SendSticker,
+            SendVideo,
+            TopicId,
+            UnpinAllGeneralForumTopicMessages,
+        },
+        APIClient,
+        APIEndpoint,
+        FormDataFile,
+        JsonFileMediaStore,
+        MediaCache,
+        Response,
+        UsageLimit,
+        UsageTracker,
+        API,
+    },
+    client::ClientBuilder,
+    model::{
+        ChatAction,
+        File,
+        InputSticker,
+        IntegerOrString,
+        LinkPreviewOptions,
+        Message,
+        ParseMode,
+        RevenueWithdrawalState,
+        StickerFormat,
+        TransactionPartner,
+    },
+    Error,
+    Result,
+    TelegramError,
+};
+
+/// An api that answers every request with the configured result and records
+/// the requests it received
+struct MockAPI {
+    result: Value,
+    requests: Mutex<Vec<(String, Option<Value>)>>,
+    uploads: Mutex<Vec<FormDataFile>>,
+}
+
+impl MockAPI {
+    fn new(result: Value) -> Self {
+        Self {
+            result,
+            requests: Mutex::new(Vec::new()),
+            uploads: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn respond(&self, endpoint: &APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.requests.lock().push((endpoint.to_string(), data));
+        Ok(Response {
+            ok: true,
+            description: None,
+            result: Some(self.result.clone()),
+            error_code: None,
+            parameters: None,
+        })
+    }
+}
+
+#[async_trait]
+impl API for MockAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        if let APIEndpoint::GetForumTopicIconStickers = endpoint {
+            self.requests.lock().push((endpoint.to_string(), data));
+            return Ok(Response {
+                ok: true,
+                description: None,
+                result: Some(forum_icons()),
+                error_code: None,
+                parameters: None,
+            });
+        }
+        self.respond(&endpoint, data)
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.respond(&endpoint, data)
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.uploads.lock().extend(files.unwrap_or_default());
+        self.respond(&endpoint, data)
+    }
+}
+
+fn forum_icons() -> Value {
+    let icon = |emoji: &str, id: &str| {
+        json!({
+            "file_id": format!("file-{id}"),
+            "file_unique_id": format!("unique-{id}"),
+            "type": "custom_emoji",
+            "width": 512,
+            "height": 512,
+            "emoji": emoji,
+            "custom_emoji_id": id
+        })
+    };
+
+    json!([
+        icon("📰", "5434144690511290129"),
+        icon("💡", "5312536423851630001")
+    ])
+}
+
+#[tokio::test]
+async fn unpin_all_general_forum_topic_messages() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+
+    assert!(
+        api.unpin_all_general_forum_topic_messages(UnpinAllGeneralForumTopicMessages::new(
+            IntegerOrString::Integer(-100)
+        ))
+        .await?
+    );
+
+    assert_eq!(
+        *api.requests.lock(),
+        vec![(
+            "unpinAllGeneralForumTopicMessages".to_owned(),
+            Some(json!({"chat_id": -100}))
+        )]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn delete_messages_sends_message_ids() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+
+    assert!(
+        api.delete_messages(DeleteMessages::new(
+            IntegerOrString::Integer(-100),
+            vec![3, 4]
+        ))
+        .await?
+    );
+
+    assert_eq!(
+        *api.requests.lock(),
+        vec![(
+            "deleteMessages".to_owned(),
+            Some(json!({"chat_id": -100, "message_ids": [3, 4]}))
+        )]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn delete_messages_rejects_invalid_amounts() {
+    let api = MockAPI::new(json!(true));
+
+    for message_ids in [Vec::new(), (0..101).collect()] {
+        let data = DeleteMessages::new(IntegerOrString::Integer(-100), message_ids);
+        assert!(matches!(
+            api.delete_messages(data).await,
+            Err(Error::Telegram(TelegramError::InvalidArgument(_)))
+        ));
+    }
+
+    assert!(api.requests.lock().is_empty());
+}
+
+#[tokio::test]
+async fn forward_and_copy_messages() -> Result<()> {
+    let api = MockAPI::new(json!([{"message_id": 20}, {"message_id": 21}]));
+    let chat_id = IntegerOrString::Integer(-100);
+    let from_chat_id = IntegerOrString::Integer(538733);
+
+    let forwarded = api
+        .forward_messages(ForwardMessages::new(
+            chat_id.clone(),
+            from_chat_id.clone(),
+            vec![3, 4],
+        ))
+        .await?;
+    let mut copy = CopyMessages::new(chat_id, from_chat_id, vec![3, 4]);
+    copy.set_remove_caption(true);
+    let copied = api.copy_messages(copy).await?;
+
+    assert_eq!(forwarded.len(), 2);
+    assert_eq!(copied[1].message_id, 21);
+    assert_eq!(
+        *api.requests.lock(),
+        vec![
+            (
+                "forwardMessages".to_owned(),
+                Some(json!({"chat_id": -100, "from_chat_id": 538733, "message_ids": [3, 4]}))
+            ),
+            (
+                "copyMessages".to_owned(),
+                Some(json!({
+                    "chat_id": -100,
+                    "from_chat_id": 538733,
+                    "message_ids": [3, 4],
+                    "remove_caption": true
+                }))
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn forward_and_copy_messages_reject_invalid_amounts() {
+    let api = MockAPI::new(json!([]));
+    let chat_id = IntegerOrString::Integer(-100);
+
+    for message_ids in [Vec::new(), (0..101).collect::<Vec<i64>>()] {
+        let forward = ForwardMessages::new(chat_id.clone(), chat_id.clone(), message_ids.clone());
+        assert!(matches!(
+            api.forward_messages(forward).await,
+            Err(Error::Telegram(TelegramError::InvalidArgument(_)))
+        ));
+
+        let copy = CopyMessages::new(chat_id.clone(), chat_id.clone(), message_ids);
+        assert!(matches!(
+            api.copy_messages(copy).await,
+            Err(Error::Telegram(TelegramError::InvalidArgument(_)))
+        ));
+    }
+
+    assert!(api.requests.lock().is_empty());
+}
+
+fn replace_sticker(sticker: InputFile) -> ReplaceStickerInSet {
+    ReplaceStickerInSet::new(
+        538733,
+        "animals_by_test_bot",
+        "old-sticker",
+        InputSticker::new(sticker, StickerFormat::Static, vec!["🦀".to_owned()]),
+    )
+}
+
+#[tokio::test]
+async fn replace_sticker_in_set_uploads_files() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+    let file = InputFile::from_bytes(b"RIFF".to_vec(), "crab.webp")?;
+
+    assert!(api.replace_sticker_in_set(replace_sticker(file)).await?);
+
+    let requests = api.requests.lock();
+    assert_eq!(requests[0].0, "replaceStickerInSet");
+    assert_eq!(
+        requests[0].1,
+        Some(json!({
+            "user_id": 538733,
+            "name": "animals_by_test_bot",
+            "old_sticker": "old-sticker",
+            "sticker": {"sticker": "attach://crab", "format": "static", "emoji_list": ["🦀"]}
+        }))
+    );
+    let uploads = api.uploads.lock();
+    assert_eq!(uploads.len(), 1);
+    assert_eq!(uploads[0].name, "crab");
+    Ok(())
+}
+
+#[tokio::test]
+async fn replace_sticker_in_set_sends_file_ids() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+    let file = InputFile::String("new-sticker".to_owned());
+
+    assert!(api.replace_sticker_in_set(replace_sticker(file)).await?);
+
+    let requests = api.requests.lock();
+    assert_eq!(
+        requests[0].1.as_ref().map(|d| &d["sticker"]["sticker"]),
+        Some(&json!("new-sticker"))
+    );
+    assert!(api.uploads.lock().is_empty());
+    Ok(())
+}
+
+#[tokio::test]
+async fn create_new_sticker_set_uses_sticker_formats() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+    let stickers = vec![
+        InputSticker::new(
+            InputFile::String("animated-sticker".to_owned()),
+            StickerFormat::Animated,
+            vec!["🦀".to_owned()],
+        ),
+        InputSticker::new(
+            InputFile::from_bytes(b"RIFF".to_vec(), "crab.webp")?,
+            StickerFormat::Static,
+            vec!["🦀".to_owned()],
+        ),
+    ];
+
+    let data = CreateNewStickerSet::new(538733, "animals_by_test_bot", "Animals", stickers);
+    assert!(api.create_new_sticker_set(data).await?);
+
+    let requests = api.requests.lock();
+    let data = requests[0].1.clone().unwrap_or_default();
+    assert!(data.get("sticker_format").is_none());
+    assert_eq!(data["stickers"][0]["format"], "animated");
+    assert_eq!(data["stickers"][1]["format"], "static");
+    assert_eq!(api.uploads.lock().len(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn edit_forum_topic_icon_uses_matching_icon() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+
+    assert!(
+        api.edit_forum_topic_icon(IntegerOrString::Integer(-100), 7, "💡")
+            .await?
+    );
+
+    let requests = api.requests.lock();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[1].0, "editForumTopic");
+    assert_eq!(
+        requests[1].1,
+        Some(json!({
+            "chat_id": -100,
+            "message_thread_id": 7,
+            "icon_custom_emoji_id": "5312536423851630001"
+        }))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn edit_forum_topic_icon_lists_available_emoji() {
+    let api = MockAPI::new(json!(true));
+
+    match api
+        .edit_forum_topic_icon(IntegerOrString::Integer(-100), 7, "🦀")
+        .await
+    {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.contains("📰") && e.contains("💡"), "{e}");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+
+    assert_eq!(api.requests.lock().len(), 1);
+}
+
+fn sent_message() -> Value {
+    json!({
+        "message_id": 1,
+        "date": 1585772722,
+        "chat": {
+            "id": 538733,
+            "type": "private",
+            "first_name": "test"
+        },
+        "text": "https://example.com"
+    })
+}
+
+#[tokio::test]
+async fn send_message_keeps_disable_web_page_preview() -> Result<()> {
+    let api = MockAPI::new(sent_message());
+
+    let mut data = SendMessage::new(IntegerOrString::Integer(538733), "https://example.com");
+    data.set_disable_web_page_preview(true);
+    api.send_message(data).await?;
+
+    assert_eq!(
+        api.requests.lock()[0].1,
+        Some(json!({
+            "chat_id": 538733,
+            "text": "https://example.com",
+            "disable_web_page_preview": true
+        }))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn link_preview_options_supersede_disable_web_page_preview() -> Result<()> {
+    let api = MockAPI::new(sent_message());
+
+    let mut data = SendMessage::new(IntegerOrString::Integer(538733), "https://example.com");
+    data.set_disable_web_page_preview(true)
+        .set_link_preview_options(LinkPreviewOptions {
+            show_above_text: Some(true),
+            ..LinkPreviewOptions::default()
+        });
+    api.send_message(data).await?;
+
+    let mut edit = EditMessageText::new("edited");
+    edit.set_inline_message_id("inline")
+        .set_disable_web_page_preview(false)
+        .set_link_preview_options(LinkPreviewOptions {
+            is_disabled: Some(true),
+            ..LinkPreviewOptions::default()
+        });
+    api.edit_message_text(edit).await?;
+
+    let requests = api.requests.lock();
+    assert_eq!(
+        requests[0].1,
+        Some(json!({
+            "chat_id": 538733,
+            "text": "https://example.com",
+            "link_preview_options": {
+                "show_above_text": true
+            }
+        }))
+    );
+    assert_eq!(
+        requests[1].1,
+        Some(json!({
+            "inline_message_id": "inline",
+            "text": "edited",
+            "link_preview_options": {
+                "is_disabled": true
+            }
+        }))
+    );
+    Ok(())
+}
+
+#[test]
+fn link_preview_options_builder_serialization() -> Result<()> {
+    use telexide::api::types::LinkPreviewOptions;
+
+    let mut data = SendMessage::new(IntegerOrString::Integer(538733), "https://example.com");
+    data.set_link_preview_options(
+        LinkPreviewOptions::new()
+            .url("https://example.org")
+            .prefer_small_media(true)
+            .show_above_text(true),
+    );
+
+    assert_eq!(
+        serde_json::to_value(data)?,
+        json!({
+            "chat_id": 538733,
+            "text": "https://example.com",
+            "link_preview_options": {
+                "url": "https://example.org",
+                "prefer_small_media": true,
+                "show_above_text": true
+            }
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn reply_parameters_serialization() -> Result<()> {
+    let message: Message = serde_json::from_value(sent_message())?;
+
+    let mut legacy = SendDice::new(IntegerOrString::Integer(538733));
+    legacy
+        .set_reply_to_message_id(1)
+        .set_allow_sending_without_reply(true);
+    assert_eq!(
+        serde_json::to_value(legacy)?,
+        json!({
+            "chat_id": 538733,
+            "reply_to_message_id": 1,
+            "allow_sending_without_reply": true
+        })
+    );
+
+    let mut params = ReplyParameters::to_message(&message);
+    params.set_quote("example");
+    let mut data = SendMessage::new(IntegerOrString::Integer(-100), "quoting");
+    data.set_reply_parameters(params);
+    assert_eq!(
+        serde_json::to_value(data)?,
+        json!({
+            "chat_id": -100,
+            "text": "quoting",
+            "reply_parameters": {
+                "message_id": 1,
+                "chat_id": 538733,
+                "quote": "example"
+            }
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn reply_in_forum_topic() -> Result<()> {
+    let mut topic_message = sent_message();
+    topic_message["message_thread_id"] = json!(7);
+    topic_message["is_topic_message"] = json!(true);
+    let topic_message: Message = serde_json::from_value(topic_message)?;
+
+    assert_eq!(
+        serde_json::to_value(SendMessage::reply_to(&topic_message, "hi"))?,
+        json!({
+            "chat_id": 538733,
+            "message_thread_id": 7,
+            "text": "hi",
+            "reply_parameters": {
+                "message_id": 1
+            }
+        })
+    );
+
+    let message: Message = serde_json::from_value(sent_message())?;
+    assert_eq!(
+        serde_json::to_value(SendMessage::reply_to(&message, "hi"))?,
+        json!({
+            "chat_id": 538733,
+            "text": "hi",
+            "reply_parameters": {
+                "message_id": 1
+            }
+        })
+    );
+
+    assert_eq!(
+        SendMessage::new_in_thread(IntegerOrString::Integer(-100), 7, "hi").message_thread_id,
+        Some(7)
+    );
+    Ok(())
+}
+
+#[test]
+fn protect_content_serialization() -> Result<()> {
+    let message: Message = serde_json::from_value(sent_message())?;
+    let chat_id = IntegerOrString::Integer(-100);
+
+    let mut game = SendGame::new(chat_id.clone(), "game");
+    assert!(serde_json::to_value(&game)?
+        .get("protect_content")
+        .is_none());
+    game.set_protect_content(true);
+    assert_eq!(serde_json::to_value(&game)?["protect_content"], json!(true));
+
+    let mut sticker = SendSticker::new(chat_id.clone(), InputFile::String("sticker".to_owned()));
+    assert!(serde_json::to_value(&sticker)?
+        .get("protect_content")
+        .is_none());
+    sticker.set_protect_content(true);
+    assert_eq!(
+        serde_json::to_value(&sticker)?["protect_content"],
+        json!(true)
+    );
+
+    let mut copy = CopyMessage::from_message(chat_id.clone(), &message);
+    assert!(serde_json::to_value(&copy)?
+        .get("protect_content")
+        .is_none());
+    copy.set_protect_content(false);
+    assert_eq!(
+        serde_json::to_value(&copy)?["protect_content"],
+        json!(false)
+    );
+
+    let mut data = SendMessage::new(chat_id, "protected");
+    assert!(serde_json::to_value(&data)?
+        .get("protect_content")
+        .is_none());
+    data.set_protect_content(true);
+    assert_eq!(serde_json::to_value(&data)?["protect_content"], json!(true));
+    Ok(())
+}
+
+#[test]
+fn poll_close_date_serialization() -> Result<()> {
+    let options = vec!["yes".to_owned(), "no".to_owned()];
+    let mut data = SendPoll::new(IntegerOrString::Integer(-100), "lunch?", options);
+    data.set_open_period(60);
+
+    let json = serde_json::to_value(&data)?;
+    assert!(
+        json.get("close_date").is_none(),
+        "close_date was sent in {json}"
+    );
+    assert_eq!(json["open_period"], json!(60));
+    assert_eq!(serde_json::from_value::<SendPoll>(json)?, data);
+
+    data.open_period = None;
+    data.set_close_date(chrono::DateTime::from_timestamp(1585772722, 0).unwrap_or_default());
+    let json = serde_json::to_value(&data)?;
+    assert_eq!(json["close_date"], json!(1585772722));
+    assert!(json.get("open_period").is_none());
+    assert_eq!(serde_json::from_value::<SendPoll>(json)?, data);
+    Ok(())
+}
+
+#[test]
+fn caption_above_media_serialization() -> Result<()> {
+    let mut data = SendPhoto::new(
+        IntegerOrString::Integer(538733),
+        InputFile::String("photo".to_owned()),
+    );
+    data.set_caption("above")
+        .set_show_caption_above_media(true)
+        .set_message_effect_id("5104841245755180586");
+
+    assert_eq!(
+        serde_json::to_value(data)?,
+        json!({
+            "chat_id": 538733,
+            "photo": "photo",
+            "caption": "above",
+            "show_caption_above_media": true,
+            "message_effect_id": "5104841245755180586"
+        })
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_user_chat_boosts() -> Result<()> {
+    let boost = |boost_id: &str, expiration_date: i64| {
+        json!({
+            "boost_id": boost_id,
+            "add_date": 1585772722,
+            "expiration_date": expiration_date,
+            "source": {
+                "source": "gift_code",
+                "user": {"id": 456, "is_bot": false, "first_name": "x"}
+            }
+        })
+    };
+    let api = MockAPI::new(json!({"boosts": [boost("old", 1588364722)]}));
+
+    let boosts = api
+        .get_user_chat_boosts(GetUserChatBoosts::new(IntegerOrString::Integer(-100), 456))
+        .await?;
+    assert_eq!(boosts.boosts.len(), 1);
+    assert!(!boosts.is_boosting());
+    assert_eq!(
+        api.requests.lock()[0],
+        (
+            "getUserChatBoosts".to_owned(),
+            Some(json!({"chat_id": -100, "user_id": 456}))
+        )
+    );
+
+    let api = MockAPI::new(json!({
+        "boosts": [boost("old", 1588364722), boost("new", 4102444800)]
+    }));
+    assert!(api
+        .get_user_chat_boosts(GetUserChatBoosts::new(IntegerOrString::Integer(-100), 456))
+        .await?
+        .is_boosting());
+    Ok(())
+}
+
+#[tokio::test]
+async fn refund_star_payment() -> Result<()> {
+    let api = MockAPI::new(json!(true));
+
+    assert!(
+        api.refund_star_payment(RefundStarPayment::new(456, "charge"))
+            .await?
+    );
+    assert_eq!(
+        api.requests.lock()[0],
+        (
+            "refundStarPayment".to_owned(),
+            Some(json!({"user_id": 456, "telegram_payment_charge_id": "charge"}))
+        )
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_star_transactions() -> Result<()> {
+    let api = MockAPI::new(json!({
+        "transactions": [
+            {
+                "id": "charge",
+                "amount": 50,
+                "date": 1585772722,
+                "source": {
+                    "type": "user",
+                    "user": {"id": 456, "is_bot": false, "first_name": "x"}
+                }
+            },
+            {
+                "id": "withdrawal",
+                "amount": 40,
+                "date": 1585772723,
+                "receiver": {
+                    "type": "fragment",
+                    "withdrawal_state": {
+                        "type": "succeeded",
+                        "date": 1585772724,
+                        "url": "https://fragment.com"
+                    }
+                }
+            }
+        ]
+    }));
+
+    let mut data = GetStarTransactions::new();
+    data.set_limit(2);
+    let transactions = api.get_star_transactions(data).await?.transactions;
+    assert_eq!(
+        api.requests.lock()[0],
+        ("getStarTransactions".to_owned(), Some(json!({"limit": 2})))
+    );
+
+    assert_eq!(transactions.len(), 2);
+    assert!(matches!(
+        &transactions[0].source,
+        Some(TransactionPartner::User { user }) if user.id == 456
+    ));
+    assert!(transactions[0].receiver.is_none());
+    assert_eq!(transactions[1].amount, 40);
+    assert!(matches!(
+        &transactions[1].receiver,
+        Some(TransactionPartner::Fragment {
+            withdrawal_state: Some(RevenueWithdrawalState::Succeeded { url, .. })
+        }) if url == "https://fragment.com"
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn get_business_connection() -> Result<()> {
+    let api = MockAPI::new(json!({
+        "id": "connection",
+        "user": {"id": 456, "is_bot": false, "first_name": "x"},
+        "user_chat_id": 456,
+        "date": 1585772722,
+        "can_reply": false,
+        "is_enabled": true
+    }));
+
+    let connection = api
+        .get_business_connection(GetBusinessConnection::new("connection"))
+        .await?;
+    assert_eq!(connection.user_chat_id, 456);
+    assert!(!connection.can_reply && connection.is_enabled);
+    assert_eq!(
+        api.requests.lock()[0],
+        (
+            "getBusinessConnection".to_owned(),
+            Some(json!({"business_connection_id": "connection"}))
+        )
+    );
+    Ok(())
+}
+
+/// An api replaying recorded http responses of the bot api, using only the
+/// public surface of `Response`
+struct CassetteAPI {
+    cassette: Vec<(&'static str, u16, &'static str)>,
+}
+
+impl CassetteAPI {
+    fn replay(&self, endpoint: &APIEndpoint) -> Result<Response> {
+        let (_, status, body) = self
+            .cassette
+            .iter()
+            .find(|(e, ..)| *e == endpoint.as_str())
+            .ok_or_else(|| TelegramError::Unknown(format!("{endpoint} was not recorded")))?;
+        Response::new(*status, body.as_bytes())
+    }
+}
+
+#[async_trait]
+impl API for CassetteAPI {
+    async fn get(&self, endpoint: APIEndpoint, _data: Option<Value>) -> Result<Response> {
+        self.replay(&endpoint)
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, _data: Option<Value>) -> Result<Response> {
+        self.replay(&endpoint)
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        _data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.replay(&endpoint)
+    }
+}
+
+#[tokio::test]
+async fn custom_api_using_public_response() -> Result<()> {
+    let api = CassetteAPI {
+        cassette: vec![
+            (
+                "getMe",
+                200,
+                r#"{"ok":true,"result":{"id":1234,"is_bot":true,"first_name":"test"}}"#,
+            ),
+            (
+                "sendMessage",
+                400,
+                r#"{"ok":false,"error_code":400,"description":"Bad Request: chat not found"}"#,
+            ),
+            (
+                "logOut",
+                429,
+                r#"{"ok":false,"error_code":429,"parameters":{"retry_after":3}}"#,
+            ),
+            ("close", 502, "<html>Bad Gateway</html>"),
+        ],
+    };
+
+    let me = api.replay(&APIEndpoint::GetMe)?;
+    assert!(me.ok());
+    assert_eq!(me.result_raw().map(|r| r["id"].clone()), Some(json!(1234)));
+    assert_eq!(api.get_me().await?.id, 1234);
+
+    let not_found = api.replay(&APIEndpoint::SendMessage)?;
+    assert!(!not_found.ok());
+    assert_eq!(not_found.error_code(), Some(400));
+    assert_eq!(not_found.description(), Some("Bad Request: chat not found"));
+    assert!(matches!(
+        api.send_message(SendMessage::new(IntegerOrString::Integer(1), "hi"))
+            .await,
+        Err(Error::Telegram(TelegramError::EndpointError {
+            endpoint,
+            description,
+        })) if endpoint == "sendMessage" && description.contains("chat not found")
+    ));
+
+    let limited = api.replay(&APIEndpoint::LogOut)?;
+    assert_eq!(limited.parameters().and_then(|p| p.retry_after), Some(3));
+    assert!(matches!(
+        api.log_out().await,
+        Err(Error::Telegram(TelegramError::RateLimited {
+            retry_after: 3,
+            ..
+        }))
+    ));
+
+    let bad_gateway = api.replay(&APIEndpoint::Close)?;
+    assert_eq!(bad_gateway.error_code(), Some(502));
+    assert_eq!(bad_gateway.description(), Some("<html>Bad Gateway</html>"));
+
+    assert!(matches!(
+        Response::new(200, b"not json"),
+        Err(Error::JSON(_))
+    ));
+    Ok(())
+}
+
+/// Starts a server serving a single file as the bot api would, returning the
+/// url to reach it on
+async fn file_server() -> String {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+        StatusCode,
+    };
+
+    let make_svc = make_service_fn(|_| async {
+        Ok::<_, hyper::Error>(service_fn(|req: hyper::Request<Body>| async move {
+            let res = if req.uri().path() == "/file/bottest/documents/file_1.txt" {
+                hyper::Response::new(Body::from("file contents"))
+            } else if req.uri().path() == "/file/bottest/documents/stalled.txt" {
+                // sends the start of the file, but never the rest
+                let start = futures::stream::iter([Ok::<_, hyper::Error>("file")]);
+                hyper::Response::new(Body::wrap_stream(futures::StreamExt::chain(
+                    start,
+                    futures::stream::pending(),
+                )))
+            } else {
+                let mut res = hyper::Response::new(Body::empty());
+                *res.status_mut() = StatusCode::NOT_FOUND;
+                res
+            };
+            Ok::<_, hyper::Error>(res)
+        }))
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+fn file(file_path: Option<&str>) -> File {
+    File {
+        file_id: "file".to_owned(),
+        file_unique_id: "unique".to_owned(),
+        file_size: Some(13),
+        file_path: file_path.map(ToOwned::to_owned),
+    }
+}
+
+#[tokio::test]
+async fn download_file() -> Result<()> {
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(file_server().await);
+
+    let contents = client
+        .download_file(&file(Some("documents/file_1.txt")))
+        .await?;
+    assert_eq!(contents, b"file contents");
+
+    let dest = std::env::temp_dir().join("telexide_download_file_test.txt");
+    client
+        .download_file_to(&file(Some("documents/file_1.txt")), &dest)
+        .await?;
+    assert_eq!(std::fs::read(&dest)?, b"file contents");
+    std::fs::remove_file(&dest)?;
+
+    assert!(matches!(
+        client
+            .download_file(&file(Some("documents/missing.txt")))
+            .await,
+        Err(Error::Telegram(TelegramError::APIResponseError(_)))
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn stalled_download_times_out_without_leaving_a_file() -> Result<()> {
+    let mut client = APIClient::new_default("test");
+    client
+        .set_api_url(file_server().await)
+        .set_timeout(Duration::from_millis(200));
+
+    assert!(matches!(
+        client
+            .download_file(&file(Some("documents/stalled.txt")))
+            .await,
+        Err(Error::Telegram(TelegramError::Timeout { .. }))
+    ));
+
+    let dest = std::env::temp_dir().join("telexide_stalled_download_test.txt");
+    assert!(matches!(
+        client
+            .download_file_to(&file(Some("documents/stalled.txt")), &dest)
+            .await,
+        Err(Error::Telegram(TelegramError::Timeout { .. }))
+    ));
+    assert!(!dest.exists());
+    assert!(!dest.with_extension("txt.part").exists());
+    Ok(())
+}
+
+#[tokio::test]
+async fn download_file_without_path() {
+    let client = APIClient::new_default("test");
+
+    match client.download_file(&file(None)).await {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.contains("get_file"), "{e}");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+}
+
+/// Starts a server that rate limits the first request it receives and
+/// answers every following request successfully, returning the url to reach
+/// it on
+async fn rate_limited_server() -> String {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+        StatusCode,
+    };
+    use std::sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    };
+
+    let limited = Arc::new(AtomicBool::new(false));
+    let make_svc = make_service_fn(move |_| {
+        let limited = limited.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |_req: hyper::Request<Body>| {
+                let first = !limited.swap(true, Ordering::SeqCst);
+                async move {
+                    let res = if first {
+                        let mut res = hyper::Response::new(Body::from(
+                            json!({
+                                "ok": false,
+                                "error_code": 429,
+                                "description": "Too Many Requests: retry after 1",
+                                "parameters": {"retry_after": 1}
+                            })
+                            .to_string(),
+                        ));
+                        *res.status_mut() = StatusCode::TOO_MANY_REQUESTS;
+                        res
+                    } else {
+                        let body = json!({"ok": true, "result": true});
+                        hyper::Response::new(Body::from(body.to_string()))
+                    };
+                    Ok::<_, hyper::Error>(res)
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+#[tokio::test]
+async fn rate_limited_request_is_retried() -> Result<()> {
+    let mut client = APIClient::new_default("test");
+    client
+        .set_api_url(rate_limited_server().await)
+        .set_max_retries(1);
+
+    assert!(client.log_out().await?);
+    Ok(())
+}
+
+#[tokio::test]
+async fn rate_limited_request_errors_without_retries() {
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(rate_limited_server().await);
+
+    assert!(matches!(
+        client.log_out().await,
+        Err(Error::Telegram(TelegramError::RateLimited {
+            endpoint,
+            retry_after: 1,
+        })) if endpoint == "logOut"
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn usage_window_slides() -> Result<()> {
+    let tracker = UsageTracker::new();
+
+    for _ in 0..12 {
+        tracker.record(&APIEndpoint::SetMyCommands)?;
+    }
+    tracker.record(&APIEndpoint::SendMessage)?;
+    assert_eq!(tracker.usage(&APIEndpoint::SetMyCommands), 12);
+    assert_eq!(tracker.usage(&APIEndpoint::SendMessage), 0);
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    tracker.record(&APIEndpoint::SetMyCommands)?;
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(tracker.usage(&APIEndpoint::SetMyCommands), 1);
+    assert_eq!(tracker.snapshot().get("setMyCommands").copied(), Some(1));
+
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(tracker.usage(&APIEndpoint::SetMyCommands), 0);
+    assert!(tracker.snapshot().is_empty());
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn enforced_usage_limit_refuses_calls() -> Result<()> {
+    let mut tracker = UsageTracker::new();
+    tracker.set_enforce(true).set_limit(
+        &APIEndpoint::SetChatTitle,
+        UsageLimit::new(2, Duration::from_secs(10)),
+    );
+
+    tracker.record(&APIEndpoint::SetChatTitle)?;
+    tokio::time::advance(Duration::from_secs(5)).await;
+    tracker.record(&APIEndpoint::SetChatTitle)?;
+    assert!(matches!(
+        tracker.record(&APIEndpoint::SetChatTitle),
+        Err(Error::Telegram(TelegramError::LocalRateLimit {
+            calls: 2,
+            ..
+        }))
+    ));
+
+    tokio::time::advance(Duration::from_secs(5)).await;
+    tracker.record(&APIEndpoint::SetChatTitle)?;
+    assert_eq!(tracker.usage(&APIEndpoint::SetChatTitle), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn enforced_usage_limit_is_applied_before_sending() {
+    let mut tracker = UsageTracker::new();
+    tracker
+        .set_enforce(true)
+        .set_limit(&APIEndpoint::LogOut, UsageLimit::per_minute(0));
+
+    let mut client = APIClient::new_default("test");
+    client
+        .set_api_url("http://127.0.0.1:9")
+        .set_usage_tracker(tracker);
+
+    assert!(matches!(
+        client.log_out().await,
+        Err(Error::Telegram(TelegramError::LocalRateLimit {
+            calls: 0,
+            ..
+        }))
+    ));
+    assert_eq!(client.usage().usage(&APIEndpoint::LogOut), 0);
+}
+
+async fn recording_server(result: Value) -> (String, std::sync::Arc<Mutex<Vec<Value>>>) {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+    use std::sync::Arc;
+
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let recorded = bodies.clone();
+    let make_svc = make_service_fn(move |_| {
+        let bodies = bodies.clone();
+        let result = result.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req: hyper::Request<Body>| {
+                let bodies = bodies.clone();
+                let result = result.clone();
+                async move {
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    bodies
+                        .lock()
+                        .push(serde_json::from_slice(&body).unwrap_or_else(|_| {
+                            Value::String(String::from_utf8_lossy(&body).into_owned())
+                        }));
+
+                    let res = json!({"ok": true, "result": result});
+                    Ok::<_, hyper::Error>(hyper::Response::new(Body::from(res.to_string())))
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    (url, recorded)
+}
+
+#[tokio::test]
+async fn default_parse_mode_is_applied() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let client = ClientBuilder::new()
+        .set_token("test")
+        .set_api_url(url)
+        .set_default_parse_mode(ParseMode::HTML)
+        .build();
+
+    let chat_id = IntegerOrString::Integer(538733);
+    client
+        .api_client
+        .send_message(SendMessage::new(chat_id.clone(), "<b>hi</b>"))
+        .await?;
+
+    let mut explicit = SendMessage::new(chat_id.clone(), "*hi*");
+    explicit.set_parse_mode(ParseMode::MarkdownV2);
+    client.api_client.send_message(explicit).await?;
+
+    let mut with_entities = SendMessage::new(chat_id, "hi");
+    with_entities.set_enitites(Vec::new());
+    client.api_client.send_message(with_entities).await?;
+
+    let bodies = bodies.lock();
+    assert_eq!(bodies[0]["parse_mode"], "HTML");
+    assert_eq!(bodies[1]["parse_mode"], "MarkdownV2");
+    assert!(bodies[2].get("parse_mode").is_none());
+    Ok(())
+}
+
+#[test]
+fn topic_id_conversions() -> serde_json::Result<()> {
+    assert_eq!(TopicId::from(1), TopicId::General);
+    assert_eq!(TopicId::from(7), TopicId::Topic(7));
+    assert_eq!(serde_json::to_value(TopicId::General)?, json!(1));
+    assert_eq!(
+        serde_json::from_value::<TopicId>(json!(7))?,
+        TopicId::Topic(7)
+    );
+    Ok(())
+}
+
+#[test]
+fn endpoints_round_trip() -> Result<()> {
+    let mut names = std::collections::HashSet::new();
+    let other = APIEndpoint::Other("sendSomethingNew".to_owned());
+
+    for endpoint in APIEndpoint::ALL.into_iter().chain([other]) {
+        let name = endpoint.to_string();
+        assert!(names.insert(name.clone()), "{name} is listed twice");
+        assert_eq!(serde_json::to_value(&endpoint)?, json!(name));
+
+        let deserialized: APIEndpoint = serde_json::from_value(json!(name))?;
+        assert_eq!(deserialized.as_str(), name);
+        assert_eq!(
+            std::mem::discriminant(&deserialized),
+            std::mem::discriminant(&endpoint),
+            "{name}"
+        );
+    }
+
+    for endpoint in APIEndpoint::ALL {
+        let parsed: APIEndpoint = endpoint.as_str().parse()?;
+        assert_eq!(
+            std::mem::discriminant(&parsed),
+            std::mem::discriminant(&endpoint)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn endpoint_names_are_parsed() -> Result<()> {
+    assert!(matches!("getMe".parse()?, APIEndpoint::GetMe));
+    assert!(matches!("setWebhook".parse()?, APIEndpoint::SetWebhook));
+    assert!(matches!("SENDMESSAGE".parse()?, APIEndpoint::SendMessage));
+
+    match "setMyNam".parse::<APIEndpoint>() {
+        Err(TelegramError::InvalidArgument(e)) => assert_eq!(
+            e,
+            "setMyNam is not a known telegram api method, did you mean setMyName, getMyName?"
+        ),
+        _ => panic!("expected an invalid argument error"),
+    }
+    match "somethingElse".parse::<APIEndpoint>() {
+        Err(TelegramError::InvalidArgument(e)) => {
+            assert_eq!(e, "somethingElse is not a known telegram api method");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+    Ok(())
+}
+
+#[test]
+fn general_topic_encoding_per_endpoint() {
+    let table = [
+        (APIEndpoint::SendMessage, GeneralTopicEncoding::Omit),
+        (APIEndpoint::SendChatAction, GeneralTopicEncoding::Omit),
+        (APIEndpoint::CopyMessage, GeneralTopicEncoding::Omit),
+        (APIEndpoint::EditForumTopic, GeneralTopicEncoding::Literal),
+        (APIEndpoint::CloseForumTopic, GeneralTopicEncoding::Literal),
+        (APIEndpoint::ReopenForumTopic, GeneralTopicEncoding::Literal),
+        (APIEndpoint::DeleteForumTopic, GeneralTopicEncoding::Literal),
+        (
+            APIEndpoint::UnpinAllForumTopicMessages,
+            GeneralTopicEncoding::Literal,
+        ),
+        (
+            APIEndpoint::UnpinAllGeneralForumTopicMessages,
+            GeneralTopicEncoding::Omit,
+        ),
+        (
+            APIEndpoint::Other("sendSomethingNew".to_owned()),
+            GeneralTopicEncoding::Omit,
+        ),
+    ];
+
+    for (endpoint, encoding) in table {
+        assert_eq!(endpoint.general_topic_encoding(), encoding, "{endpoint}");
+    }
+}
+
+#[tokio::test]
+async fn general_topic_is_encoded_per_endpoint() -> Result<()> {
+    let (url, bodies) = recording_server(json!(true)).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let chat_id = IntegerOrString::Integer(-100);
+    client
+        .send_chat_action(SendChatAction::new_in_thread(
+            chat_id.clone(),
+            TopicId::General,
+            ChatAction::Typing,
+        ))
+        .await?;
+    client
+        .send_chat_action(SendChatAction::new_in_thread(
+            chat_id.clone(),
+            7,
+            ChatAction::Typing,
+        ))
+        .await?;
+    client
+        .close_forum_topic(CloseForumTopic::new(chat_id, TopicId::General))
+        .await?;
+
+    let bodies = bodies.lock();
+    assert!(bodies[0].get("message_thread_id").is_none());
+    assert_eq!(bodies[1]["message_thread_id"], 7);
+    assert_eq!(bodies[2]["message_thread_id"], 1);
+    Ok(())
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_payload_accessors() {
+    let mut audio = SendAudio::new(
+        IntegerOrString::Integer(538733),
+        InputFile::String("audio".to_owned()),
+    );
+    assert!(audio.thumb().is_none());
+    audio.set_thumbnail(InputFile::String("thumbnail".to_owned()));
+    assert_eq!(audio.thumb(), audio.thumbnail.as_ref());
+
+    let mut answer = AnswerInlineQuery::new("query", Vec::new());
+    assert_eq!(answer.switch_pm_text(), None);
+    let mut button = InlineQueryResultsButton::new("connect");
+    button.set_start_parameter("login");
+    answer.set_button(button);
+    assert_eq!(answer.switch_pm_text(), Some("connect"));
+    assert_eq!(answer.switch_pm_parameter(), Some("login"));
+}
+
+fn paid_media(uploaded: &[&FormDataFile]) -> SendPaidMedia {
+    let mut video = InputPaidMediaVideo::new(uploaded[1].clone().into());
+    video
+        .set_thumbnail(uploaded[2].clone().into())
+        .set_duration(12);
+
+    let mut data = SendPaidMedia::new(
+        IntegerOrString::Integer(-100),
+        25,
+        vec![
+            InputPaidMedia::Photo(InputPaidMediaPhoto::new(uploaded[0].clone().into())),
+            InputPaidMedia::Video(video),
+            InputPaidMedia::Photo(InputPaidMediaPhoto::new("existing-photo".into())),
+            InputPaidMedia::Photo(InputPaidMediaPhoto::new(uploaded[0].clone().into())),
+        ],
+    );
+    data.set_caption("exclusive").set_payload("order-1");
+    data
+}
+
+#[tokio::test]
+async fn paid_media_files_are_attached() -> Result<()> {
+    let photo = FormDataFile::new(b"photo", "image/png", "photo.png");
+    let video = FormDataFile::new(b"video", "video/mp4", "clip.mp4");
+    let thumb = FormDataFile::new(b"thumb", "image/jpeg", "thumb.jpg");
+
+    let api = MockAPI::new(sent_message());
+    api.send_paid_media(paid_media(&[&photo, &video, &thumb]))
+        .await?;
+
+    assert_eq!(
+        api.requests.lock()[0],
+        (
+            "sendPaidMedia".to_owned(),
+            Some(json!({
+                "chat_id": -100,
+                "star_count": 25,
+                "media": [
+                    {"type": "photo", "media": "attach://photo"},
+                    {
+                        "type": "video",
+                        "media": "attach://clip",
+                        "thumbnail": "attach://thumb",
+                        "duration": 12
+                    },
+                    {"type": "photo", "media": "existing-photo"},
+                    {"type": "photo", "media": "attach://photo"}
+                ],
+                "payload": "order-1",
+                "caption": "exclusive"
+            }))
+        )
+    );
+    assert_eq!(*api.uploads.lock(), vec![photo, video, thumb]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn uploaded_thumbnails_are_attached() -> Result<()> {
+    let video = FormDataFile::new(b"video", "video/mp4", "clip.mp4");
+    let thumb = FormDataFile::new(b"thumb", "image/jpeg", "thumb.jpg");
+
+    let api = MockAPI::new(sent_message());
+    let mut data = SendVideo::new(IntegerOrString::Integer(-100), video.clone().into());
+    data.set_thumbnail(thumb.clone().into());
+    api.send_video(data).await?;
+
+    let mut document = InputMediaDocument::new("existing-document".into());
+    document.set_thumbnail(thumb.clone().into());
+    let mut edit = EditMessageMedia::new(InputMedia::Document(document.clone()));
+    edit.set_chat_id(-100).set_message_id(1);
+    api.edit_message_media(edit).await?;
+
+    assert_eq!(
+        *api.requests.lock(),
+        vec![
+            (
+                "sendVideo".to_owned(),
+                Some(
+                    json!({"chat_id": -100, "video": "attach://clip", "thumbnail": "attach://thumb"})
+                )
+            ),
+            (
+                "editMessageMedia".to_owned(),
+                Some(json!({
+                    "chat_id": -100,
+                    "message_id": 1,
+                    "media": {
+                        "type": "document",
+                        "media": "existing-document",
+                        "thumbnail": "attach://thumb"
+                    }
+                }))
+            ),
+        ]
+    );
+    assert_eq!(
+        *api.uploads.lock(),
+        vec![video.clone(), thumb.clone(), thumb.clone()]
+    );
+
+    let api = MockAPI::new(json!([sent_message()]));
+    let mut album_video = InputMediaVideo::new(video.clone().into());
+    album_video.set_thumbnail(thumb.clone().into());
+    api.send_media_group(SendMediaGroup::new(
+        IntegerOrString::Integer(-100),
+        vec![
+            InputMedia::Video(album_video),
+            InputMedia::Document(document),
+        ],
+    ))
+    .await?;
+
+    assert_eq!(
+        api.requests.lock()[0].1,
+        Some(json!({
+            "chat_id": -100,
+            "media": [
+                {"type": "video", "media": "attach://clip", "thumbnail": "attach://thumb"},
+                {"type": "document", "media": "existing-document", "thumbnail": "attach://thumb"}
+            ]
+        }))
+    );
+    assert_eq!(*api.uploads.lock(), vec![video, thumb]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn conflicting_paid_media_files_are_renamed() -> Result<()> {
+    let photo = FormDataFile::new(b"photo", "image/png", "cover.png");
+    let video = FormDataFile::new(b"video", "video/mp4", "cover.mp4");
+    let thumb = FormDataFile::new(b"thumb", "image/jpeg", "cover.jpg");
+
+    let api = MockAPI::new(sent_message());
+    api.send_paid_media(paid_media(&[&photo, &video, &thumb]))
+        .await?;
+
+    let requests = api.requests.lock();
+    let media = &requests[0].1.as_ref().expect("the request has data")["media"];
+    assert_eq!(
+        *media,
+        json!([
+            {"type": "photo", "media": "attach://cover"},
+            {
+                "type": "video",
+                "media": "attach://cover_1",
+                "thumbnail": "attach://cover_2",
+                "duration": 12
+            },
+            {"type": "photo", "media": "existing-photo"},
+            {"type": "photo", "media": "attach://cover"}
+        ])
+    );
+
+    let names: Vec<_> = api.uploads.lock().iter().map(|f| f.name.clone()).collect();
+    assert_eq!(names, vec!["cover", "cover_1", "cover_2"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn files_are_not_renamed_to_the_name_of_another_file() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let mut data = SendAudio::new(
+        IntegerOrString::Integer(-100),
+        InputFile::from_bytes(b"mp3-bytes".to_vec(), "caption.mp3")?,
+    );
+    data.set_caption("the caption")
+        .set_thumbnail(InputFile::from_bytes(
+            b"jpeg-bytes".to_vec(),
+            "caption_file.jpg",
+        )?);
+    client.send_audio(data).await?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    for part in [
+        "name=\"audio\"\r\n\r\nattach://caption_file_file\r\n",
+        "name=\"caption_file_file\"; filename=\"caption.mp3\"",
+        "name=\"thumbnail\"\r\n\r\nattach://caption_file\r\n",
+        "name=\"caption_file\"; filename=\"caption_file.jpg\"",
+    ] {
+        assert!(body.contains(part), "{part:?} is missing from {body:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn paid_media_is_sent_as_multipart_form_data() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let photo = FormDataFile::new(b"photo-bytes", "image/png", "photo.png");
+    let video = FormDataFile::new(b"video-bytes", "video/mp4", "clip.mp4");
+    let thumb = FormDataFile::new(b"thumb-bytes", "image/jpeg", "thumb.jpg");
+    client
+        .send_paid_media(paid_media(&[&photo, &video, &thumb]))
+        .await?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    for part in [
+        "name=\"photo\"; filename=\"photo.png\"\r\nContent-Type: image/png\r\n\r\nphoto-bytes",
+        "name=\"clip\"; filename=\"clip.mp4\"\r\nContent-Type: video/mp4\r\n\r\nvideo-bytes",
+        "name=\"thumb\"; filename=\"thumb.jpg\"\r\nContent-Type: image/jpeg\r\n\r\nthumb-bytes",
+        "name=\"star_count\"\r\n\r\n25\r\n",
+        "\"media\":\"attach://clip\"",
+    ] {
+        assert!(body.contains(part), "{part:?} is missing from {body:?}");
+    }
+    assert_eq!(body.matches("filename=\"photo.png\"").count(), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn photo_bytes_are_uploaded_as_the_photo_field() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let photo = InputFile::from_bytes(b"png-bytes".to_vec(), "photo.png")?;
+    client
+        .send_photo(SendPhoto::new(IntegerOrString::Integer(-100), photo))
+        .await?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    assert!(
+        body.contains(
+            "name=\"photo\"; filename=\"photo.png\"\r\nContent-Type: image/png\r\n\r\npng-bytes"
+        ),
+        "the photo is missing from {body:?}"
+    );
+    assert_eq!(body.matches("name=\"photo\"").count(), 1);
+    assert!(!body.contains("attach://"));
+    Ok(())
+}
+
+#[tokio::test]
+async fn read_files_are_renamed_when_their_name_is_taken() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let reader = std::io::Cursor::new(b"jpeg-bytes".to_vec());
+    let photo = InputFile::from_reader(reader, "caption.jpg")?;
+    let mut data = SendPhoto::new(IntegerOrString::Integer(-100), photo);
+    data.set_caption("the caption");
+    client.send_photo(data).await?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    for part in [
+        "name=\"caption\"\r\n\r\nthe caption\r\n",
+        "name=\"photo\"\r\n\r\nattach://caption_file\r\n",
+        "name=\"caption_file\"; filename=\"caption.jpg\"\r\n\
+         Content-Type: image/jpeg\r\n\r\njpeg-bytes",
+    ] {
+        assert!(body.contains(part), "{part:?} is missing from {body:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn file_names_are_escaped_in_form_data() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let photo = InputFile::from_bytes(b"png-bytes".to_vec(), "my \"best\" cat.png")?;
+    client
+        .send_photo(SendPhoto::new(IntegerOrString::Integer(-100), photo))
+        .await?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    for part in [
+        "name=\"photo\"\r\n\r\nattach://my__best__cat\r\n",
+        "name=\"my__best__cat\"; filename=\"my %22best%22 cat.png\"\r\n",
+    ] {
+        assert!(body.contains(part), "{part:?} is missing from {body:?}");
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn large_files_are_streamed_from_disk() -> Result<()> {
+    let (url, bodies) = recording_server(sent_message()).await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url);
+
+    let dir = std::env::temp_dir().join(format!("telexide-upload-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("photo.png");
+    // larger than a single chunk and not a multiple of its size
+    let contents: String = (0..200_000).map(|i| format!("{i:x},")).collect();
+    std::fs::write(&path, &contents)?;
+
+    let photo = InputFile::from_path(&path)?;
+    let mut data = SendPhoto::new(IntegerOrString::Integer(-100), photo);
+    data.set_caption("big");
+    let sent = client.send_photo(data).await;
+    std::fs::remove_dir_all(&dir)?;
+    sent?;
+
+    let body = bodies.lock()[0].as_str().unwrap_or_default().to_owned();
+    let boundary = body.split("\r\n").next().unwrap_or_default().to_owned();
+    assert!(boundary.starts_with("--"), "{boundary:?} isn't a boundary");
+    assert!(body.ends_with(&format!("\r\n{boundary}--\r\n")));
+
+    let file_part = format!(
+        "{boundary}\r\nContent-Disposition: form-data; name=\"photo\"; \
+         filename=\"photo.png\"\r\nContent-Type: image/png\r\n\r\n{contents}\r\n{boundary}"
+    );
+    assert!(
+        body.contains(&file_part),
+        "the photo isn't framed correctly"
+    );
+    assert!(body.contains(&format!("name=\"caption\"\r\n\r\nbig\r\n{boundary}")));
+    assert_eq!(body.matches(&boundary).count(), 4);
+    Ok(())
+}
+
+#[tokio::test]
+async fn streamed_files_are_sent_with_their_length() -> Result<()> {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+    use std::sync::Arc;
+
+    let lengths = Arc::new(Mutex::new(Vec::new()));
+    let recorded = lengths.clone();
+    let make_svc = make_service_fn(move |_| {
+        let lengths = lengths.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req: hyper::Request<Body>| {
+                let lengths = lengths.clone();
+                async move {
+                    let header = req
+                        .headers()
+                        .get("content-length")
+                        .and_then(|l| l.to_str().ok()?.parse::<usize>().ok());
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    lengths.lock().push((header, body.len()));
+
+                    let res = json!({"ok": true, "result": sent_message()});
+                    Ok::<_, hyper::Error>(hyper::Response::new(Body::from(res.to_string())))
+                }
+            }))
+        }
+    });
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(format!("http://{}", server.local_addr()));
+    tokio::spawn(server);
+
+    let dir = std::env::temp_dir().join(format!("telexide-length-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join("photo.png");
+    std::fs::write(&path, vec![7; 100_000])?;
+
+    let photo = InputFile::from_path(&path)?;
+    let sent = client
+        .send_photo(SendPhoto::new(IntegerOrString::Integer(-100), photo))
+        .await;
+    std::fs::remove_dir_all(&dir)?;
+    sent?;
+
+    let lengths = recorded.lock().clone();
+    assert_eq!(lengths.len(), 1);
+    assert_eq!(lengths[0].0, Some(lengths[0].1));
+    assert!(lengths[0].1 > 100_000);
+    Ok(())
+}
+
+/// A server that answers every request after the given delay, keeping track
+/// of the largest amount of requests it was handling at the same time
+async fn slow_server(delay: Duration) -> (String, std::sync::Arc<AtomicUsize>) {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+    use std::sync::Arc;
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let max_in_flight = Arc::new(AtomicUsize::new(0));
+    let recorded = max_in_flight.clone();
+    let make_svc = make_service_fn(move |_| {
+        let in_flight = in_flight.clone();
+        let max_in_flight = max_in_flight.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |_req: hyper::Request<Body>| {
+                let in_flight = in_flight.clone();
+                let max_in_flight = max_in_flight.clone();
+                async move {
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(delay).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                    let res = json!({"ok": true, "result": true});
+                    Ok::<_, hyper::Error>(hyper::Response::new(Body::from(res.to_string())))
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    (url, recorded)
+}
+
+async fn max_overlap(client: APIClient, requests: usize) -> Result<usize> {
+    let (url, max_in_flight) = slow_server(Duration::from_millis(50)).await;
+    let mut client = client;
+    client.set_api_url(url);
+
+    let results = futures::future::join_all((0..requests).map(|_| client.log_out())).await;
+    for res in results {
+        assert!(res?);
+    }
+    Ok(max_in_flight.load(Ordering::SeqCst))
+}
+
+#[tokio::test]
+async fn concurrent_requests_are_limited() -> Result<()> {
+    let mut client = APIClient::new_default("test");
+    client.set_max_concurrent_requests(2);
+    assert_eq!(max_overlap(client, 6).await?, 2);
+
+    let client = APIClient::new_default("test");
+    assert!(max_overlap(client, 6).await? > 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn pooled_hyper_client_sends_requests() -> Result<()> {
+    let mut pool = hyper::Client::builder();
+    pool.pool_max_idle_per_host(1)
+        .pool_idle_timeout(Duration::from_secs(5));
+    let client = APIClient::new(Some(APIClient::build_hyper_client(&pool)), "test");
+
+    assert_eq!(max_overlap(client, 3).await?, 3);
+    Ok(())
+}
+
+/// A server that answers every request with true and counts the connections
+/// that were made to it
+async fn connection_counting_server() -> (String, std::sync::Arc<AtomicUsize>) {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+    use std::sync::Arc;
+
+    let connections = Arc::new(AtomicUsize::new(0));
+    let recorded = connections.clone();
+    let make_svc = make_service_fn(move |_| {
+        connections.fetch_add(1, Ordering::SeqCst);
+        async move {
+            Ok::<_, hyper::Error>(service_fn(|_req: hyper::Request<Body>| async {
+                let res = json!({"ok": true, "result": true});
+                Ok::<_, hyper::Error>(hyper::Response::new(Body::from(res.to_string())))
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    (url, recorded)
+}
+
+#[tokio::test]
+async fn custom_http_client_is_used() -> Result<()> {
+    let mut no_idle_connections = hyper::Client::builder();
+    no_idle_connections.pool_max_idle_per_host(0);
+    let custom = APIClient::build_hyper_client(&no_idle_connections);
+
+    let (url, connections) = connection_counting_server().await;
+    let mut client = APIClient::with_client("test", custom.clone());
+    client.set_api_url(&url);
+    assert!(client.log_out().await?);
+    assert!(client.log_out().await?);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+    let (url, connections) = connection_counting_server().await;
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_api_url(&url)
+        .set_http_client(custom)
+        .build();
+    assert!(c.api_client.log_out().await?);
+    assert!(c.api_client.log_out().await?);
+    assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+    let (url, connections) = connection_counting_server().await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(&url);
+    assert!(client.log_out().await?);
+    assert!(client.log_out().await?);
+    assert_eq!(connections.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+/// A server that accepts uploaded photos and file ids except for "stale-id",
+/// answering with a photo message with the file id "photo-id"
+async fn media_server() -> (String, std::sync::Arc<Mutex<Vec<Value>>>) {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+    use std::sync::Arc;
+
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let recorded = bodies.clone();
+    let make_svc = make_service_fn(move |_| {
+        let bodies = bodies.clone();
+        async move {
+            Ok::<_, hyper::Error>(service_fn(move |req: hyper::Request<Body>| {
+                let bodies = bodies.clone();
+                async move {
+                    let body = hyper::body::to_bytes(req.into_body()).await?;
+                    let body = serde_json::from_slice(&body).unwrap_or_else(|_| {
+                        Value::String(String::from_utf8_lossy(&body).into_owned())
+                    });
+                    let res = if body["photo"] == "stale-id" {
+                        json!({
+                            "ok": false,
+                            "error_code": 400,
+                            "description": "Bad Request: wrong file identifier/HTTP URL specified"
+                        })
+                    } else {
+                        let mut message = sent_message();
+                        let size = |file_id: &str, width: i64| {
+                            json!({
+                                "file_id": file_id,
+                                "file_unique_id": file_id,
+                                "width": width,
+                                "height": width
+                            })
+                        };
+                        message["photo"] = json!([size("thumb-id", 90), size("photo-id", 800)]);
+                        json!({"ok": true, "result": message})
+                    };
+                    bodies.lock().push(body);
+
+                    let mut response = hyper::Response::new(Body::from(res.to_string()));
+                    if res["ok"] == false {
+                        *response.status_mut() = hyper::StatusCode::BAD_REQUEST;
+                    }
+                    Ok::<_, hyper::Error>(response)
+                }
+            }))
+        }
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    (url, recorded)
+}
+
+fn send_photo(photo: &FormDataFile) -> SendPhoto {
+    SendPhoto::new(IntegerOrString::Integer(538733), photo.clone().into())
+}
+
+#[tokio::test]
+async fn media_cache_reuses_file_ids() -> Result<()> {
+    let (url, bodies) = media_server().await;
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(url).set_media_cache(MediaCache::new());
+
+    let photo = FormDataFile::new(b"photo-bytes", "image/png", "photo.png");
+    client.send_photo(send_photo(&photo)).await?;
+    let cache = client.media_cache().expect("the media cache should be set");
+    assert_eq!(cache.get(&photo).await.as_deref(), Some("photo-id"));
+
+    let renamed = FormDataFile::new(b"photo-bytes", "image/png", "welcome.png");
+    client.send_photo(send_photo(&renamed)).await?;
+    client
+        .send_photo(send_photo(&FormDataFile::new(
+            b"other-bytes",
+            "image/png",
+            "other.png",
+        )))
+        .await?;
+
+    let bodies = bodies.lock();
+    assert!(bodies[0]
+        .as_str()
+        .is_some_and(|b| b.contains("photo-bytes")));
+    assert_eq!(bodies[1], json!({"chat_id": 538733, "photo": "photo-id"}));
+    assert!(bodies[2]
+        .as_str()
+        .is_some_and(|b| b.contains("other-bytes")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn media_cache_uploads_again_after_stale_file_id() -> Result<()> {
+    let (url, bodies) = media_server().await;
+    let path = std::env::temp_dir().join(format!("telexide-media-{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let photo = FormDataFile::new(b"photo-bytes", "image/png", "photo.png");
+    MediaCache::with_store(JsonFileMediaStore::open(&path)?)
+        .insert(&photo, "stale-id")
+        .await;
+
+    let mut client = APIClient::new_default("test");
+    client
+        .set_api_url(url)
+        .set_media_cache(MediaCache::with_store(JsonFileMediaStore::open(&path)?));
+    let message = client.send_photo(send_photo(&photo)).await?;
+    assert_eq!(message.message_id, 1);
+
+    {
+        let bodies = bodies.lock();
+        assert_eq!(bodies.len(), 2);
+        assert_eq!(bodies[0]["photo"], "stale-id");
+        assert!(bodies[1]
+            .as_str()
+            .is_some_and(|b| b.contains("photo-bytes")));
+    }
+
+    let reopened = MediaCache::with_store(JsonFileMediaStore::open(&path)?);
+    assert_eq!(reopened.get(&photo).await.as_deref(), Some("photo-id"));
+    std::fs::remove_file(&path)?;
+    Ok(())
+}
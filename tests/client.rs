@@ -1,9 +1,62 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use async_trait::async_trait;
+use futures::StreamExt;
+use parking_lot::{Mutex, RwLock};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 use telexide::{
-    client::{ClientBuilder, Context},
-    model::{Update, UpdateContent},
+    api::{
+        types::{
+            AnswerCallbackQuery,
+            GetUpdates,
+            InputFile,
+            InputMedia,
+            InputMediaPhoto,
+            SendMessage,
+            TopicId,
+            UpdateType,
+        },
+        APIEndpoint,
+        FormDataFile,
+        Response,
+        API,
+    },
+    client::{
+        ChatMigrated,
+        Client,
+        ClientBuilder,
+        Context,
+        EventHandlerFunc,
+        SmartReplyPolicy,
+        UpdatesMode,
+        UpdatesStream,
+        WebhookOptions,
+    },
+    framework::{CallbackRouter, CommandResult, Framework, NamedBytes, MAX_PHOTO_SIZE},
+    macros::{command, create_framework, prepare_listener},
+    model::{
+        CallbackQuery,
+        InlineKeyboardButton,
+        InlineKeyboardMarkup,
+        IntegerOrString,
+        Message,
+        MessageContent,
+        ReplyMarkup,
+        Update,
+        UpdateContent,
+    },
+    test_utils::{CallbackQueryBuilder, MessageBuilder, UpdateBuilder},
+    Error,
     Result,
+    TelegramError,
 };
+use typemap_rev::{TypeMap, TypeMapKey};
 
 #[tokio::test]
 async fn update_handler_gets_called() -> Result<()> {
@@ -18,7 +71,7 @@ async fn update_handler_gets_called() -> Result<()> {
 
     c.fire_handlers(Update {
         update_id: 10,
-        content: UpdateContent::Unknown,
+        content: UpdateContent::Unknown(serde_json::Value::Null),
     });
 
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -46,7 +99,7 @@ async fn test_using_func() -> Result<()> {
 
     c.fire_handlers(Update {
         update_id: 10,
-        content: UpdateContent::Unknown,
+        content: UpdateContent::Unknown(serde_json::Value::Null),
     });
 
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -54,3 +107,1499 @@ async fn test_using_func() -> Result<()> {
     assert_eq!(FUNC_B.load(Ordering::Relaxed), 10);
     Ok(())
 }
+
+/// An api that returns a single batch of updates and fails all requests for
+/// updates after it, which stops the client
+struct UpdatesAPI {
+    batch: Mutex<Option<Value>>,
+}
+
+#[async_trait]
+impl API for UpdatesAPI {
+    async fn get(&self, endpoint: APIEndpoint, _data: Option<Value>) -> Result<Response> {
+        if let APIEndpoint::GetMe = endpoint {
+            return Ok(Response {
+                ok: true,
+                description: None,
+                result: Some(json!({
+                    "id": 1234,
+                    "is_bot": true,
+                    "first_name": "test",
+                    "username": "test_bot"
+                })),
+                error_code: None,
+                parameters: None,
+            });
+        }
+
+        let batch = self.batch.lock().take();
+        Ok(Response {
+            ok: batch.is_some(),
+            description: Some("no more updates".to_owned()),
+            result: batch,
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn unknown_update_kind_is_dispatched() -> Result<()> {
+    static RECEIVED: Mutex<Vec<(i64, Option<Value>)>> = Mutex::new(Vec::new());
+    static RAW_RECEIVED: Mutex<Vec<Value>> = Mutex::new(Vec::new());
+
+    let batch = json!([
+        {"update_id": 1},
+        {"update_id": 2, "made_up_update": {"id": 5, "text": "new"}},
+        {"update_id": 3}
+    ]);
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(Some(batch)),
+        })))
+        .build();
+    c.subscribe_handler_func(|_c, u| {
+        Box::pin(async move {
+            let raw = match u.content {
+                UpdateContent::Unknown(Value::Object(raw)) if !raw.is_empty() => {
+                    Some(Value::Object(raw))
+                },
+                _ => None,
+            };
+            RECEIVED.lock().push((u.update_id, raw));
+        })
+    });
+    c.subscribe_raw_handler(|_c, u| {
+        Box::pin(async move {
+            if let Some(v) = u.other.get("made_up_update") {
+                RAW_RECEIVED.lock().push(v.clone());
+            }
+        })
+    });
+
+    assert!(c.start().await.is_err());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut received = RECEIVED.lock().clone();
+    received.sort_unstable_by_key(|(id, _)| *id);
+    assert_eq!(
+        received,
+        vec![
+            (1, None),
+            (2, Some(json!({"made_up_update": {"id": 5, "text": "new"}}))),
+            (3, None)
+        ]
+    );
+    assert_eq!(*RAW_RECEIVED.lock(), vec![json!({"id": 5, "text": "new"})]);
+    Ok(())
+}
+
+/// The sender the ids of the dispatched updates are sent through
+struct Dispatched;
+
+impl TypeMapKey for Dispatched {
+    type Value = tokio::sync::mpsc::UnboundedSender<i64>;
+}
+
+/// Subscribes a handler sending the ids of the updates it receives, returning
+/// the receiver for them
+fn record_dispatched(c: &mut Client) -> tokio::sync::mpsc::UnboundedReceiver<i64> {
+    let (sender, receiver) = tokio::sync::mpsc::unbounded_channel();
+    c.data.write().insert::<Dispatched>(sender);
+    c.subscribe_handler_func(|c, u| {
+        Box::pin(async move {
+            if let Some(sender) = c.data.read().get::<Dispatched>() {
+                let _ = sender.send(u.update_id);
+            }
+        })
+    });
+    receiver
+}
+
+/// Receives the given amount of dispatched update ids, sorted
+async fn dispatched(
+    receiver: &mut tokio::sync::mpsc::UnboundedReceiver<i64>,
+    amount: usize,
+) -> Vec<i64> {
+    let mut ids = Vec::new();
+    while ids.len() < amount {
+        let id = tokio::time::timeout(Duration::from_secs(5), receiver.recv())
+            .await
+            .expect("the updates weren't dispatched")
+            .expect("the client was dropped");
+        ids.push(id);
+    }
+    ids.sort_unstable();
+    ids
+}
+
+#[tokio::test]
+async fn unparseable_update_is_skipped() -> Result<()> {
+    let batch = json!([
+        {"update_id": 1},
+        {"update_id": 2, "message": {"message_id": "not a number"}},
+        {"update_id": 3}
+    ]);
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(Some(batch)),
+        })))
+        .build();
+    let mut receiver = record_dispatched(&mut c);
+
+    assert!(c.start().await.is_err());
+    assert_eq!(dispatched(&mut receiver, 2).await, vec![1, 3]);
+    Ok(())
+}
+
+/// An api answering the requests for updates with the given batches, and
+/// with an error once they run out, recording the requested offsets
+#[derive(Default)]
+struct BatchesAPI {
+    batches: Mutex<VecDeque<Value>>,
+    offsets: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait]
+impl API for BatchesAPI {
+    async fn get(&self, _endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.offsets
+            .lock()
+            .push(data.map_or(Value::Null, |d| d["offset"].clone()));
+
+        let batch = self.batches.lock().pop_front();
+        Ok(Response {
+            ok: batch.is_some(),
+            description: Some("no more updates".to_owned()),
+            result: batch,
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+/// Polls the stream over the batches until it fails to retrieve more
+/// updates, returning the ids of the updates and whether an update couldn't
+/// be parsed for every item, together with the requested offsets
+async fn poll_batches(
+    batches: Vec<Value>,
+    skip_unparseable: bool,
+) -> (Vec<Option<i64>>, Vec<Value>) {
+    let api = BatchesAPI {
+        batches: Mutex::new(batches.into()),
+        offsets: Arc::default(),
+    };
+    let offsets = api.offsets.clone();
+
+    let mut stream = UpdatesStream::new(Arc::new(Box::new(api)));
+    stream.set_skip_unparseable_updates(skip_unparseable);
+
+    let mut items = Vec::new();
+    while let Some(item) = stream.next().await {
+        match item {
+            Ok(update) => items.push(Some(update.update_id)),
+            Err(Error::JSON(_)) => items.push(None),
+            Err(_) => break,
+        }
+    }
+
+    let offsets = offsets.lock().clone();
+    (items, offsets)
+}
+
+#[tokio::test]
+async fn stream_moves_past_skipped_updates() {
+    let batches = vec![
+        json!([{"update_id": 1}, {"update_id": 2, "message": {"message_id": "not a number"}}]),
+        json!([{"update_id": 3}]),
+    ];
+
+    assert_eq!(
+        poll_batches(batches, true).await,
+        (vec![Some(1), Some(3)], vec![json!(1), json!(3), json!(4)])
+    );
+}
+
+#[tokio::test]
+async fn stream_retrieves_unparseable_update_again_when_not_skipped() {
+    let bad = json!({"update_id": 2, "message": {"message_id": "not a number"}});
+    let batches = vec![
+        json!([{"update_id": 1}, bad, {"update_id": 3}]),
+        json!([bad]),
+    ];
+
+    assert_eq!(
+        poll_batches(batches, false).await,
+        (
+            vec![Some(1), None, None],
+            vec![json!(1), json!(2), json!(2)]
+        )
+    );
+}
+
+#[tokio::test]
+async fn get_updates_leaves_out_unparseable_updates() -> Result<()> {
+    let api = UpdatesAPI {
+        batch: Mutex::new(Some(json!([
+            {"update_id": 1, "message": {"message_id": "not a number"}},
+            {"update_id": 2}
+        ]))),
+    };
+
+    let updates = api.get_updates(GetUpdates::new()).await?;
+    assert_eq!(
+        updates.iter().map(|u| u.update_id).collect::<Vec<_>>(),
+        vec![2]
+    );
+    Ok(())
+}
+
+/// An api that takes 100ms to handle a request, recording the requests it
+/// completed
+#[derive(Default)]
+struct DelayedAPI {
+    completed: Arc<Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl API for DelayedAPI {
+    async fn get(&self, endpoint: APIEndpoint, _data: Option<Value>) -> Result<Response> {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        self.completed.lock().push(endpoint.to_string());
+        Ok(Response {
+            ok: true,
+            description: None,
+            result: Some(json!(true)),
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn api_with_timeout_aborts_call() -> Result<()> {
+    let api = DelayedAPI::default();
+    let completed = api.completed.clone();
+    let ctx = Context::new(
+        Arc::new(Box::new(api)),
+        Arc::new(RwLock::new(TypeMap::custom())),
+    );
+
+    let res = ctx
+        .api_with_timeout(Duration::from_millis(20))
+        .send_message(SendMessage::new(IntegerOrString::Integer(1), "hi"))
+        .await;
+    assert!(matches!(
+        res,
+        Err(Error::Telegram(TelegramError::Timeout { endpoint })) if endpoint == "sendMessage"
+    ));
+
+    let dropped = ctx
+        .api
+        .send_message(SendMessage::new(IntegerOrString::Integer(1), "hi"));
+    tokio::select! {
+        _ = dropped => panic!("the call should not complete before the sleep"),
+        () = tokio::time::sleep(Duration::from_millis(20)) => {},
+    }
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert!(completed.lock().is_empty());
+
+    assert!(
+        ctx.api_with_timeout(Duration::from_millis(500))
+            .log_out()
+            .await?
+    );
+    assert_eq!(*completed.lock(), vec!["logOut".to_owned()]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn unparseable_update_errors_when_not_skipped() -> Result<()> {
+    let batch = json!([
+        {"update_id": 1},
+        {"update_id": 2, "message": {"message_id": "not a number"}}
+    ]);
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(Some(batch)),
+        })))
+        .set_strict_update_parsing(true)
+        .build();
+    let mut receiver = record_dispatched(&mut c);
+
+    assert!(matches!(c.start().await, Err(Error::JSON(_))));
+    assert_eq!(dispatched(&mut receiver, 1).await, vec![1]);
+    Ok(())
+}
+
+/// An [`UpdatesAPI`] that counts the requests made to `getMe`
+struct GetMeCountingAPI {
+    api: UpdatesAPI,
+    get_me_calls: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl API for GetMeCountingAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        if let APIEndpoint::GetMe = endpoint {
+            self.get_me_calls.fetch_add(1, Ordering::SeqCst);
+        }
+        self.api.get(endpoint, data).await
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn bot_user_is_cached_before_handlers_run() -> Result<()> {
+    static USERNAMES: Mutex<Vec<Option<String>>> = Mutex::new(Vec::new());
+
+    let get_me_calls = Arc::new(AtomicUsize::new(0));
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(GetMeCountingAPI {
+            api: UpdatesAPI {
+                batch: Mutex::new(Some(json!([{"update_id": 1}, {"update_id": 2}]))),
+            },
+            get_me_calls: get_me_calls.clone(),
+        })))
+        .build();
+    c.subscribe_handler_func(|c, _u| {
+        Box::pin(async move {
+            let user = c
+                .bot_user()
+                .await
+                .expect("the bot user should be available");
+            USERNAMES.lock().push(user.username);
+        })
+    });
+
+    assert!(c.start().await.is_err());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    assert_eq!(*USERNAMES.lock(), vec![Some("test_bot".to_owned()); 2]);
+    assert_eq!(get_me_calls.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+#[tokio::test]
+async fn bot_user_is_retrieved_when_not_cached() -> Result<()> {
+    let get_me_calls = Arc::new(AtomicUsize::new(0));
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(GetMeCountingAPI {
+            api: UpdatesAPI {
+                batch: Mutex::new(None),
+            },
+            get_me_calls: get_me_calls.clone(),
+        })))
+        .build();
+    let ctx = Context::new(c.api_client.clone(), c.data.clone());
+
+    assert_eq!(ctx.bot_user().await?.id, 1234);
+    assert_eq!(ctx.bot_user().await?.id, 1234);
+    assert_eq!(get_me_calls.load(Ordering::SeqCst), 1);
+    Ok(())
+}
+
+type Requests = Vec<(String, Option<Value>)>;
+
+/// An api that answers every request with true and records the requests and
+/// uploaded files it received
+#[derive(Default, Clone)]
+struct RecordingAPI {
+    requests: Arc<Mutex<Requests>>,
+    uploads: Arc<Mutex<Vec<FormDataFile>>>,
+    received: Arc<tokio::sync::Notify>,
+}
+
+impl RecordingAPI {
+    /// Waits until the api received the given amount of requests in total
+    async fn wait_for_requests(&self, amount: usize) {
+        let received = async {
+            while self.requests.lock().len() < amount {
+                self.received.notified().await;
+            }
+        };
+        tokio::time::timeout(Duration::from_secs(5), received)
+            .await
+            .expect("the requests weren't sent");
+    }
+}
+
+#[async_trait]
+impl API for RecordingAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.requests.lock().push((endpoint.to_string(), data));
+        self.received.notify_one();
+        Ok(Response {
+            ok: true,
+            description: None,
+            result: Some(json!(true)),
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.uploads.lock().extend(files.unwrap_or_default());
+        self.get(endpoint, data).await
+    }
+}
+
+async fn fire_callback_query(handler: EventHandlerFunc) -> Result<Requests> {
+    let api = RecordingAPI::default();
+
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_auto_answer_callback_queries(true)
+        .build();
+    c.subscribe_handler_func(handler);
+
+    let query = CallbackQueryBuilder::data("vote:1")
+        .id("query")
+        .from_user(538733)
+        .build();
+    c.fire_handlers(UpdateBuilder::callback_query(query).build());
+    api.wait_for_requests(1).await;
+
+    let requests = api.requests.lock().clone();
+    Ok(requests)
+}
+
+#[tokio::test]
+async fn answered_callback_query_is_not_answered_again() -> Result<()> {
+    let requests = fire_callback_query(|c, _u| {
+        Box::pin(async move {
+            let mut data = AnswerCallbackQuery::new("query");
+            data.set_text("done");
+            c.api.answer_callback_query(data).await.unwrap();
+        })
+    })
+    .await?;
+
+    assert_eq!(
+        requests,
+        vec![(
+            "answerCallbackQuery".to_owned(),
+            Some(json!({"callback_query_id": "query", "text": "done"}))
+        )]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn unanswered_callback_query_is_answered() -> Result<()> {
+    let requests = fire_callback_query(|_c, _u| Box::pin(async {})).await?;
+
+    assert_eq!(
+        requests,
+        vec![(
+            "answerCallbackQuery".to_owned(),
+            Some(json!({"callback_query_id": "query"}))
+        )]
+    );
+    Ok(())
+}
+
+#[prepare_listener]
+async fn vote(c: Context, q: CallbackQuery, choice: String) {
+    c.answer_callback(q.id, format!("voted {choice}"))
+        .await
+        .expect("answering failed");
+}
+
+#[prepare_listener]
+async fn vote_up(c: Context, q: CallbackQuery, rest: String) {
+    c.answer_callback(q.id, format!("upvoted {rest}"))
+        .await
+        .expect("answering failed");
+}
+
+#[prepare_listener]
+async fn refuse_vote(_c: Context, _q: CallbackQuery, _rest: String) -> Result<()> {
+    Err(TelegramError::InvalidArgument("voting is closed".to_owned()).into())
+}
+
+/// Routes callback queries through a `CallbackRouter` set on the client, or
+/// through the callback query handlers of the framework
+async fn route_callback_queries(use_router: bool, auto_answer: Option<bool>) -> Requests {
+    let api = RecordingAPI::default();
+    let mut builder = ClientBuilder::new();
+    builder.set_api_client(Arc::new(Box::new(api.clone())));
+    if let Some(auto_answer) = auto_answer {
+        builder.set_auto_answer_callback_queries(auto_answer);
+    }
+
+    if use_router {
+        let mut router = CallbackRouter::new();
+        router
+            .on("vote:", vote)
+            .on("vote:up:", vote_up)
+            .on("vote:", vote_up)
+            .on("closed:", refuse_vote);
+        builder.set_callback_router(Arc::new(router));
+    } else {
+        let mut fr = Framework::new("test_bot");
+        fr.add_callback_query_handler("vote:", vote)
+            .add_callback_query_handler("vote:up:", vote_up)
+            .add_callback_query_handler("vote:", vote_up)
+            .add_callback_query_handler("closed:", refuse_vote);
+        builder.set_framework(Arc::new(fr));
+    }
+
+    let c = builder.build();
+    let queries = [
+        ("1", "vote:yes"),
+        ("2", "vote:up:7"),
+        ("3", "poll:yes"),
+        ("4", "closed:yes"),
+    ];
+    for (id, data) in queries {
+        let query = CallbackQueryBuilder::data(data).id(id).build();
+        c.fire_handlers(UpdateBuilder::callback_query(query).build());
+    }
+
+    // the failing query and the one without a route are only answered
+    // automatically
+    let auto_answered = auto_answer.unwrap_or(true);
+    api.wait_for_requests(if auto_answered { 4 } else { 2 })
+        .await;
+    let mut requests = api.requests.lock().clone();
+    requests.sort_by_key(|(_, data)| data.as_ref().map(|d| d["callback_query_id"].to_string()));
+    requests
+}
+
+#[tokio::test]
+async fn client_callback_router_takes_precedence_over_framework() {
+    let api = RecordingAPI::default();
+    let mut router = CallbackRouter::new();
+    router.on("vote:", vote);
+    let mut fr = Framework::new("test_bot");
+    fr.add_callback_query_handler("vote:", vote_up)
+        .add_callback_query_handler("poll:", vote_up);
+
+    let mut builder = ClientBuilder::new();
+    builder
+        .set_api_client(Arc::new(Box::new(api.clone())))
+        .set_callback_router(Arc::new(router))
+        .set_framework(Arc::new(fr));
+    let c = builder.build();
+    for (id, data) in [("1", "vote:yes"), ("2", "poll:yes")] {
+        let query = CallbackQueryBuilder::data(data).id(id).build();
+        c.fire_handlers(UpdateBuilder::callback_query(query).build());
+    }
+
+    api.wait_for_requests(2).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let mut requests = api.requests.lock().clone();
+    requests.sort_by_key(|(_, data)| data.as_ref().map(|d| d["callback_query_id"].to_string()));
+    assert_eq!(
+        requests,
+        vec![
+            (
+                "answerCallbackQuery".to_owned(),
+                Some(json!({"callback_query_id": "1", "text": "voted yes"}))
+            ),
+            (
+                "answerCallbackQuery".to_owned(),
+                Some(json!({"callback_query_id": "2", "text": "upvoted yes"}))
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn client_with_framework_answers_callback_queries() {
+    let api = RecordingAPI::default();
+    let mut fr = Framework::new("test_bot");
+    fr.add_callback_query_handler("vote:", vote);
+
+    let mut c = Client::with_framework(Arc::new(fr), "test");
+    c.api_client = Arc::new(Box::new(api.clone()));
+    let query = CallbackQueryBuilder::data("poll:yes").id("1").build();
+    c.fire_handlers(UpdateBuilder::callback_query(query).build());
+
+    api.wait_for_requests(1).await;
+    assert_eq!(
+        *api.requests.lock(),
+        vec![(
+            "answerCallbackQuery".to_owned(),
+            Some(json!({"callback_query_id": "1"}))
+        )]
+    );
+}
+
+#[tokio::test]
+async fn callback_queries_are_routed_by_prefix() -> Result<()> {
+    let answer = |id: &str, text: Option<&str>| {
+        let mut data = json!({"callback_query_id": id});
+        if let Some(text) = text {
+            data["text"] = json!(text);
+        }
+        ("answerCallbackQuery".to_owned(), Some(data))
+    };
+
+    for use_router in [false, true] {
+        assert_eq!(
+            route_callback_queries(use_router, Some(false)).await,
+            vec![
+                answer("1", Some("voted yes")),
+                answer("2", Some("upvoted 7"))
+            ]
+        );
+        // callback queries are answered automatically by default once routed
+        assert_eq!(
+            route_callback_queries(use_router, None).await,
+            vec![
+                answer("1", Some("voted yes")),
+                answer("2", Some("upvoted 7")),
+                answer("3", None),
+                answer("4", Some("error"))
+            ]
+        );
+    }
+    Ok(())
+}
+
+#[tokio::test]
+async fn failed_callback_query_is_answered_with_error() -> Result<()> {
+    let requests = fire_callback_query(|_c, _u| {
+        Box::pin(async {
+            panic!("handler failed");
+        })
+    })
+    .await?;
+
+    assert_eq!(
+        requests,
+        vec![(
+            "answerCallbackQuery".to_owned(),
+            Some(json!({"callback_query_id": "query", "text": "error"}))
+        )]
+    );
+    Ok(())
+}
+
+#[prepare_listener]
+async fn failing_listener(_c: Context, _u: Update) -> Result<()> {
+    Err(TelegramError::InvalidArgument("no votes yet".to_owned()).into())
+}
+
+#[tokio::test]
+async fn failed_listener_callback_query_is_answered_with_error() -> Result<()> {
+    let requests = fire_callback_query(failing_listener).await?;
+
+    assert_eq!(
+        requests,
+        vec![(
+            "answerCallbackQuery".to_owned(),
+            Some(json!({"callback_query_id": "query", "text": "error"}))
+        )]
+    );
+    Ok(())
+}
+
+#[command(description = "testing")]
+async fn ping(_c: Context, _m: Message) -> CommandResult {
+    Ok(())
+}
+
+#[tokio::test]
+async fn startup_info_when_polling() -> Result<()> {
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(None),
+        })))
+        .set_framework(create_framework!("test_bot", ping))
+        .set_polling_timeout(5)
+        .set_allowed_updates(vec![UpdateType::Message, UpdateType::CallbackQuery])
+        .build();
+    c.subscribe_handler_func(|_c, _u| Box::pin(async {}));
+    c.subscribe_raw_handler(|_c, _u| Box::pin(async {}));
+
+    let info = c.startup_info().await?;
+    assert_eq!(info.bot_id, 1234);
+    assert_eq!(info.bot_username.as_deref(), Some("test_bot"));
+    assert_eq!(
+        info.mode,
+        UpdatesMode::Polling {
+            timeout: Some(5)
+        }
+    );
+    assert_eq!(
+        info.allowed_updates,
+        vec![UpdateType::Message, UpdateType::CallbackQuery]
+    );
+    assert_eq!(info.commands, 1);
+    assert_eq!(info.listeners, 2);
+    assert_eq!(
+        info.to_string(),
+        "bot @test_bot (1234), mode: polling with a timeout of 5s, allowed updates: [Message, \
+         CallbackQuery], commands: 1, listeners: 2"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn startup_info_when_using_webhook() -> Result<()> {
+    let mut opts = WebhookOptions::new();
+    opts.set_url("https://example.com/bot")?;
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(None),
+        })))
+        .set_webhook(&opts)
+        .build();
+
+    let info = c.startup_info().await?;
+    assert_eq!(info.bot_id, 1234);
+    assert_eq!(
+        info.mode,
+        UpdatesMode::Webhook {
+            url: Some("https://example.com/bot".to_owned())
+        }
+    );
+    assert!(info.allowed_updates.is_empty());
+    assert_eq!(info.commands, 0);
+    assert_eq!(info.listeners, 0);
+    Ok(())
+}
+
+#[tokio::test]
+async fn concurrent_handlers_are_limited() -> Result<()> {
+    static RUNNING: AtomicUsize = AtomicUsize::new(0);
+    static MAX_RUNNING: AtomicUsize = AtomicUsize::new(0);
+    static FINISHED: AtomicUsize = AtomicUsize::new(0);
+
+    let mut c = ClientBuilder::new()
+        .set_token("test")
+        .set_max_concurrent_handlers(2)
+        .build();
+    c.subscribe_handler_func(|_c, _u| {
+        Box::pin(async move {
+            let running = RUNNING.fetch_add(1, Ordering::SeqCst) + 1;
+            MAX_RUNNING.fetch_max(running, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            RUNNING.fetch_sub(1, Ordering::SeqCst);
+            FINISHED.fetch_add(1, Ordering::SeqCst);
+        })
+    });
+
+    for update_id in 0..6 {
+        c.fire_handlers(Update {
+            update_id,
+            content: UpdateContent::Unknown(serde_json::Value::Null),
+        });
+    }
+
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    assert_eq!(FINISHED.load(Ordering::SeqCst), 6);
+    assert_eq!(MAX_RUNNING.load(Ordering::SeqCst), 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn business_updates_are_dispatched() -> Result<()> {
+    static RECEIVED: Mutex<Vec<(i64, String)>> = Mutex::new(Vec::new());
+
+    let chat = json!({"id": 456, "type": "private", "first_name": "x"});
+    let message = json!({
+        "message_id": 7,
+        "date": 1585772722,
+        "chat": chat,
+        "business_connection_id": "connection",
+        "text": "hi"
+    });
+    let batch = json!([
+        {"update_id": 1, "business_connection": {
+            "id": "connection",
+            "user": {"id": 456, "is_bot": false, "first_name": "x"},
+            "user_chat_id": 456,
+            "date": 1585772722,
+            "can_reply": true,
+            "is_enabled": true
+        }},
+        {"update_id": 2, "business_message": message},
+        {"update_id": 3, "edited_business_message": message},
+        {"update_id": 4, "deleted_business_messages": {
+            "business_connection_id": "connection",
+            "chat": chat,
+            "message_ids": [7]
+        }}
+    ]);
+    let handler: EventHandlerFunc = |_c, u| {
+        Box::pin(async move {
+            let received = match u.content {
+                UpdateContent::BusinessConnection(c) if c.can_reply => c.id,
+                UpdateContent::BusinessMessage(m) | UpdateContent::EditedBusinessMessage(m) => {
+                    SendMessage::reply_to(&m, "hello")
+                        .business_connection_id
+                        .unwrap_or_default()
+                },
+                UpdateContent::DeletedBusinessMessages(d) if d.message_ids == [7] => {
+                    d.business_connection_id
+                },
+                _ => "unexpected".to_owned(),
+            };
+            RECEIVED.lock().push((u.update_id, received));
+        })
+    };
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(UpdatesAPI {
+            batch: Mutex::new(Some(batch)),
+        })))
+        .add_handler_func(handler)
+        .build();
+
+    assert!(c.start().await.is_err());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut received = RECEIVED.lock().clone();
+    received.sort_unstable();
+    assert_eq!(
+        received,
+        (1..=4)
+            .map(|id| (id, "connection".to_owned()))
+            .collect::<Vec<_>>()
+    );
+    Ok(())
+}
+
+/// An api that sends albums and messages, recording the requests it received
+/// and failing to send messages if configured to
+#[derive(Default)]
+struct AlbumAPI {
+    fail_messages: bool,
+    requests: Arc<Mutex<Requests>>,
+}
+
+#[async_trait]
+impl API for AlbumAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.requests.lock().push((endpoint.to_string(), data));
+        let message = |message_id: i64| {
+            json!({
+                "message_id": message_id,
+                "date": 1585772722,
+                "chat": {"id": 538733, "type": "private", "first_name": "test"}
+            })
+        };
+        let (ok, result) = match endpoint {
+            APIEndpoint::SendMediaGroup => (true, json!([message(1), message(2)])),
+            APIEndpoint::SendMessage => (!self.fail_messages, message(3)),
+            _ => (true, json!(true)),
+        };
+        Ok(Response {
+            ok,
+            description: Some("Bad Request: message is too long".to_owned()),
+            result: Some(result),
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+async fn send_album(api: AlbumAPI) -> Result<(Vec<Message>, Message)> {
+    let ctx = Context::new(
+        Arc::new(Box::new(api)),
+        Arc::new(RwLock::new(TypeMap::custom())),
+    );
+    let photo =
+        |id: &str| InputMedia::Photo(InputMediaPhoto::new(InputFile::String(id.to_owned())));
+    let markup = ReplyMarkup::InlineKeyboardMarkup(InlineKeyboardMarkup {
+        inline_keyboard: vec![vec![InlineKeyboardButton::new("like", false)]],
+    });
+
+    ctx.send_album_with_keyboard(
+        IntegerOrString::Integer(538733),
+        vec![photo("a"), photo("b")],
+        "what do you think?",
+        markup,
+        true,
+    )
+    .await
+}
+
+#[tokio::test]
+async fn album_with_keyboard_replies_to_album() -> Result<()> {
+    let api = AlbumAPI::default();
+    let requests = api.requests.clone();
+
+    let (album, message) = send_album(api).await?;
+    assert_eq!(album.len(), 2);
+    assert_eq!(message.message_id, 3);
+
+    let requests = requests.lock();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].0, "sendMediaGroup");
+    assert_eq!(
+        requests[1].1.as_ref().map(|d| &d["reply_parameters"]),
+        Some(&json!({"message_id": 1}))
+    );
+    assert_eq!(
+        requests[1]
+            .1
+            .as_ref()
+            .map(|d| &d["reply_markup"]["inline_keyboard"][0][0]["text"]),
+        Some(&json!("like"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn reply_and_send_build_messages() -> Result<()> {
+    let api = AlbumAPI::default();
+    let requests = api.requests.clone();
+    let ctx = Context::new(
+        Arc::new(Box::new(api)),
+        Arc::new(RwLock::new(TypeMap::custom())),
+    );
+    let message = MessageBuilder::text("hello")
+        .message_id(12)
+        .in_private_chat(538733)
+        .business_connection("connection")
+        .build();
+
+    assert_eq!(ctx.reply(&message, "hi!").await?.message_id, 3);
+    assert_eq!(ctx.send(-40, "hello everyone").await?.message_id, 3);
+
+    assert_eq!(
+        *requests.lock(),
+        vec![
+            (
+                "sendMessage".to_owned(),
+                Some(json!({
+                    "chat_id": 538733,
+                    "text": "hi!",
+                    "reply_parameters": {"message_id": 12},
+                    "business_connection_id": "connection"
+                }))
+            ),
+            (
+                "sendMessage".to_owned(),
+                Some(json!({"chat_id": -40, "text": "hello everyone"}))
+            ),
+        ]
+    );
+    Ok(())
+}
+
+fn hello() -> MessageBuilder {
+    MessageBuilder::text("hello").message_id(12)
+}
+
+async fn smart_reply_to(message: &Message) -> Result<Option<Value>> {
+    let api = AlbumAPI::default();
+    let requests = api.requests.clone();
+    let ctx = Context::new(
+        Arc::new(Box::new(api)),
+        Arc::new(RwLock::new(TypeMap::custom())),
+    );
+
+    ctx.smart_reply(message, "hi!").await?;
+    let data = requests.lock().pop().and_then(|(_, data)| data);
+    Ok(data)
+}
+
+#[tokio::test]
+async fn smart_reply_follows_the_chat_type() -> Result<()> {
+    let reply = json!({"message_id": 12, "allow_sending_without_reply": true});
+
+    assert_eq!(
+        smart_reply_to(&hello().in_private_chat(538733).build()).await?,
+        Some(json!({"chat_id": 538733, "text": "hi!"}))
+    );
+    assert_eq!(
+        smart_reply_to(&hello().in_group(-40).build()).await?,
+        Some(json!({"chat_id": -40, "text": "hi!", "reply_parameters": reply}))
+    );
+    assert_eq!(
+        smart_reply_to(&hello().in_forum_topic(-100, TopicId::Topic(7)).build()).await?,
+        Some(json!({
+            "chat_id": -100,
+            "text": "hi!",
+            "message_thread_id": 7,
+            "reply_parameters": reply
+        }))
+    );
+    assert_eq!(
+        smart_reply_to(&hello().in_forum_topic(-100, TopicId::General).build()).await?,
+        Some(json!({"chat_id": -100, "text": "hi!", "reply_parameters": reply}))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn smart_reply_policy_is_set_on_the_builder() -> Result<()> {
+    let api = AlbumAPI::default();
+    let requests = api.requests.clone();
+    let mut c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api)))
+        .set_smart_reply_policy(SmartReplyPolicy {
+            reply_in_groups: false,
+            reply_in_private_chats: true,
+            allow_sending_without_reply: false,
+        })
+        .build();
+    c.subscribe_handler_func(|c, u| {
+        Box::pin(async move {
+            if let UpdateContent::Message(m) = u.content {
+                c.smart_reply(&m, "hi!")
+                    .await
+                    .expect("the reply should be sent");
+            }
+        })
+    });
+
+    for message in [hello().in_private_chat(538733), hello().in_group(-40)] {
+        c.fire_handlers(UpdateBuilder::message(message.build()).build());
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    let requests: Vec<_> = requests.lock().drain(..).filter_map(|(_, d)| d).collect();
+    assert_eq!(
+        requests,
+        vec![
+            json!({"chat_id": 538733, "text": "hi!", "reply_parameters": {"message_id": 12}}),
+            json!({"chat_id": -40, "text": "hi!"}),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn album_with_keyboard_rolls_back_album() {
+    let api = AlbumAPI {
+        fail_messages: true,
+        ..AlbumAPI::default()
+    };
+    let requests = api.requests.clone();
+
+    assert!(matches!(
+        send_album(api).await,
+        Err(Error::Telegram(TelegramError::EndpointError { .. }))
+    ));
+
+    let requests = requests.lock();
+    let deleted: Vec<_> = requests
+        .iter()
+        .filter(|(e, _)| e == "deleteMessage")
+        .map(|(_, d)| d.clone())
+        .collect();
+    assert_eq!(
+        deleted,
+        vec![
+            Some(json!({"chat_id": 538733, "message_id": 1})),
+            Some(json!({"chat_id": 538733, "message_id": 2}))
+        ]
+    );
+}
+
+struct KeepAliveAPI {
+    pings: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl API for KeepAliveAPI {
+    async fn get(&self, endpoint: APIEndpoint, _data: Option<Value>) -> Result<Response> {
+        if let APIEndpoint::GetMe = endpoint {
+            self.pings.fetch_add(1, Ordering::SeqCst);
+            return Ok(Response {
+                ok: true,
+                description: None,
+                result: Some(json!({"id": 1234, "is_bot": true, "first_name": "test"})),
+                error_code: None,
+                parameters: None,
+            });
+        }
+
+        // a long poll that stops the client after 330 seconds
+        tokio::time::sleep(Duration::from_secs(330)).await;
+        Ok(Response {
+            ok: false,
+            description: Some("stopped polling".to_owned()),
+            result: None,
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn keep_alive_runs_while_client_is_running() {
+    let pings = Arc::new(AtomicUsize::new(0));
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(KeepAliveAPI {
+            pings: pings.clone(),
+        })))
+        .set_keep_alive_interval(Duration::from_secs(60))
+        .build();
+
+    let client = tokio::spawn(async move { c.start().await });
+
+    // only the startup summary has asked for the bot's identity yet
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    assert_eq!(pings.load(Ordering::SeqCst), 1);
+
+    tokio::time::sleep(Duration::from_secs(100)).await;
+    assert_eq!(pings.load(Ordering::SeqCst), 3);
+
+    assert!(client.await.expect("client panicked").is_err());
+    let after_stop = pings.load(Ordering::SeqCst);
+    assert_eq!(after_stop, 6);
+
+    tokio::time::sleep(Duration::from_secs(600)).await;
+    assert_eq!(pings.load(Ordering::SeqCst), after_stop);
+}
+
+#[tokio::test(start_paused = true)]
+async fn keep_alive_is_disabled_by_default() {
+    let pings = Arc::new(AtomicUsize::new(0));
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(KeepAliveAPI {
+            pings: pings.clone(),
+        })))
+        .build();
+
+    assert!(c.start().await.is_err());
+    assert_eq!(pings.load(Ordering::SeqCst), 1);
+}
+
+async fn requests_on_start(builder: &mut ClientBuilder) -> Requests {
+    let api = RecordingAPI::default();
+    let requests = api.requests.clone();
+    let c = builder.set_api_client(Arc::new(Box::new(api))).build();
+
+    let running = tokio::spawn(async move { c.start().await });
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    running.abort();
+
+    let requests = requests.lock().clone();
+    requests
+}
+
+fn sent_field(requests: &Requests, endpoint: &str, field: &str) -> Value {
+    requests
+        .iter()
+        .find(|(e, _)| e == endpoint)
+        .and_then(|(_, data)| data.as_ref()?.get(field).cloned())
+        .unwrap_or(Value::Null)
+}
+
+#[tokio::test]
+async fn allowed_updates_are_sent_to_telegram() -> Result<()> {
+    let allowed = vec![
+        UpdateType::Message,
+        UpdateType::ChatMember,
+        UpdateType::ChatBoost,
+    ];
+    let expected = json!(["message", "chat_member", "chat_boost"]);
+
+    let requests =
+        requests_on_start(ClientBuilder::new().set_allowed_updates(allowed.clone())).await;
+    assert_eq!(
+        sent_field(&requests, "getUpdates", "allowed_updates"),
+        expected
+    );
+
+    let mut opts = WebhookOptions::new();
+    opts.set_port(8026).set_url("https://example.com/hook")?;
+    let requests = requests_on_start(
+        ClientBuilder::new()
+            .set_allowed_updates(allowed)
+            .set_webhook(&opts),
+    )
+    .await;
+    assert_eq!(
+        sent_field(&requests, "setWebHook", "allowed_updates"),
+        expected
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn drop_pending_updates_is_sent_to_telegram() -> Result<()> {
+    let mut opts = WebhookOptions::new();
+    opts.set_port(8027).set_url("https://example.com/hook")?;
+    let requests = requests_on_start(
+        ClientBuilder::new()
+            .set_webhook(&opts)
+            .set_drop_pending_updates(true),
+    )
+    .await;
+    assert_eq!(
+        sent_field(&requests, "setWebHook", "drop_pending_updates"),
+        json!(true)
+    );
+
+    opts.set_port(8028);
+    let requests = requests_on_start(ClientBuilder::new().set_webhook(&opts)).await;
+    assert_eq!(
+        sent_field(&requests, "setWebHook", "drop_pending_updates"),
+        Value::Null
+    );
+
+    let requests = requests_on_start(ClientBuilder::new().set_drop_pending_updates(true)).await;
+    assert_eq!(sent_field(&requests, "getUpdates", "offset"), json!(-1));
+    assert_eq!(sent_field(&requests, "getUpdates", "limit"), json!(1));
+    assert_eq!(sent_field(&requests, "getUpdates", "timeout"), json!(0));
+    Ok(())
+}
+
+/// An api that has a single pending update and records the offsets of the
+/// other `getUpdates` requests, to which it answers with an error
+#[derive(Default)]
+struct PendingUpdateAPI {
+    offsets: Arc<Mutex<Vec<Value>>>,
+}
+
+#[async_trait]
+impl API for PendingUpdateAPI {
+    async fn get(&self, _endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let offset = data.map_or(Value::Null, |d| d["offset"].clone());
+        if offset == json!(-1) {
+            return Ok(Response {
+                ok: true,
+                description: None,
+                result: Some(json!([{"update_id": 41}])),
+                error_code: None,
+                parameters: None,
+            });
+        }
+
+        self.offsets.lock().push(offset);
+        Ok(Response {
+            ok: false,
+            description: Some("stopped polling".to_owned()),
+            result: None,
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn dropped_updates_are_skipped_by_the_stream() -> Result<()> {
+    let api = PendingUpdateAPI::default();
+    let offsets = api.offsets.clone();
+
+    let mut stream = UpdatesStream::new(Arc::new(Box::new(api)));
+    stream.drop_pending_updates().await?;
+    assert!(matches!(stream.next().await, Some(Err(_))));
+
+    assert_eq!(*offsets.lock(), vec![json!(42)]);
+    Ok(())
+}
+
+#[command(description = "sends a report")]
+async fn report(_c: Context, _m: Message) -> CommandResult<NamedBytes> {
+    let csv = [("ping", 1), ("pong", 2)]
+        .iter()
+        .fold("name,count\n".to_owned(), |csv, (name, count)| {
+            csv + &format!("{name},{count}\n")
+        });
+
+    Ok(NamedBytes::new("report.csv", csv.into_bytes()))
+}
+
+#[command(description = "sends a photo that is too large")]
+async fn huge_photo(_c: Context, _m: Message) -> CommandResult<NamedBytes> {
+    Ok(NamedBytes::photo("huge.png", vec![0; MAX_PHOTO_SIZE + 1]))
+}
+
+async fn run_command(command: &str) -> Result<(Requests, Vec<FormDataFile>)> {
+    let api = RecordingAPI::default();
+    let requests = api.requests.clone();
+    let uploads = api.uploads.clone();
+
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api)))
+        .set_framework(create_framework!("test_bot", report, huge_photo))
+        .build();
+    let message = MessageBuilder::text(command)
+        .message_id(12)
+        .in_forum_topic(-1001234, TopicId::Topic(3))
+        .build();
+    c.fire_handlers(UpdateBuilder::message(message).build());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let requests = requests.lock().clone();
+    let uploads = uploads.lock().clone();
+    Ok((requests, uploads))
+}
+
+#[tokio::test]
+async fn command_responds_with_file() -> Result<()> {
+    let (requests, uploads) = run_command("/report").await?;
+
+    assert_eq!(
+        requests,
+        vec![(
+            "sendDocument".to_owned(),
+            Some(json!({
+                "chat_id": -1001234,
+                "message_thread_id": 3,
+                "document": "attach://report"
+            }))
+        )]
+    );
+    assert_eq!(
+        uploads,
+        vec![FormDataFile::new(
+            b"name,count\nping,1\npong,2\n",
+            "text/csv",
+            "report.csv"
+        )]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn command_file_over_upload_limit_is_not_sent() -> Result<()> {
+    let (requests, uploads) = run_command("/huge_photo").await?;
+
+    assert!(requests.is_empty());
+    assert!(uploads.is_empty());
+    Ok(())
+}
+
+static MIGRATIONS: Mutex<Vec<ChatMigrated>> = Mutex::new(Vec::new());
+
+#[prepare_listener]
+async fn record_migration(_ctx: Context, migration: ChatMigrated) {
+    MIGRATIONS.lock().push(migration);
+}
+
+#[tokio::test]
+async fn chat_migrations_are_recorded_once() {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .add_chat_migrated_handler_func(record_migration)
+        .build();
+
+    let migrate_to = MessageBuilder::content(MessageContent::MigrateToChatID {
+        content: -100_123,
+    })
+    .in_group(-10)
+    .build();
+    let migrate_from = MessageBuilder::content(MessageContent::MigrateFromChatID {
+        content: -10,
+    })
+    .in_supergroup(-100_123)
+    .build();
+
+    assert_eq!(c.migrated_chat_id(-10), None);
+    c.fire_handlers(UpdateBuilder::message(migrate_to).build());
+    c.fire_handlers(UpdateBuilder::message(migrate_from).build());
+    c.fire_handlers(UpdateBuilder::message(hello().in_supergroup(-100_123).build()).build());
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    assert_eq!(
+        *MIGRATIONS.lock(),
+        vec![ChatMigrated {
+            old_id: -10,
+            new_id: -100_123,
+        }]
+    );
+    assert_eq!(c.migrated_chat_id(-10), Some(-100_123));
+    assert_eq!(c.clone().migrated_chat_id(-10), Some(-100_123));
+    assert_eq!(c.migrated_chat_id(-100_123), None);
+}
@@ -0,0 +1,116 @@
+#![cfg(feature = "config")]
+
+use std::time::Duration;
+use telexide::{
+    api::types::UpdateType,
+    client::{ClientBuilder, ClientConfig},
+    model::ParseMode,
+    Error,
+    Result,
+    TelegramError,
+};
+
+#[test]
+fn builder_from_toml_config() -> Result<()> {
+    let config = ClientConfig::from_toml_str(
+        r#"
+        token = "test"
+        api_url = "http://localhost:8081/"
+        parse_mode = "HTML"
+        allowed_updates = ["message", "callback_query"]
+
+        [webhook]
+        url = "https://example.com/bot"
+        port = 8443
+        secret_token = "secret"
+
+        [polling]
+        limit = 50
+        timeout = 30
+
+        [timeouts]
+        request = 10
+        "#,
+    )?;
+
+    let builder = ClientBuilder::from_config(config)?;
+    let state = builder.inspect();
+
+    assert_eq!(state.token, Some("test"));
+    assert_eq!(state.api_url, Some("http://localhost:8081/"));
+    assert_eq!(state.parse_mode, Some(&ParseMode::HTML));
+    assert_eq!(
+        state.allowed_updates,
+        &[UpdateType::Message, UpdateType::CallbackQuery]
+    );
+    assert_eq!(state.polling_limit, Some(50));
+    assert_eq!(state.polling_timeout, Some(30));
+    assert_eq!(state.request_timeout, Some(Duration::from_secs(10)));
+
+    let webhook = state.webhook.expect("webhook options should be set");
+    assert_eq!(
+        webhook.url.as_ref().map(ToString::to_string),
+        Some("https://example.com/bot".to_owned())
+    );
+    assert_eq!(webhook.port, 8443);
+    assert_eq!(webhook.secret_token.as_deref(), Some("secret"));
+    Ok(())
+}
+
+#[test]
+fn builder_from_env_config() -> Result<()> {
+    std::env::set_var("TELEXIDE_TOKEN", "env-token");
+    std::env::set_var("TELEXIDE_PARSE_MODE", "MarkdownV2");
+    std::env::set_var("TELEXIDE_ALLOWED_UPDATES", "message, inline_query");
+    std::env::set_var("TELEXIDE_POLLING_LIMIT", "10");
+    std::env::set_var("TELEXIDE_REQUEST_TIMEOUT", "15");
+
+    let builder = ClientBuilder::from_config(ClientConfig::from_env()?)?;
+    let state = builder.inspect();
+
+    assert_eq!(state.token, Some("env-token"));
+    assert_eq!(state.parse_mode, Some(&ParseMode::MarkdownV2));
+    assert_eq!(
+        state.allowed_updates,
+        &[UpdateType::Message, UpdateType::InlineQuery]
+    );
+    assert_eq!(state.polling_limit, Some(10));
+    assert_eq!(state.polling_timeout, None);
+    assert_eq!(state.request_timeout, Some(Duration::from_secs(15)));
+    assert!(state.webhook.is_none());
+
+    std::env::set_var("TELEXIDE_POLLING_LIMIT", "many");
+    let res = ClientConfig::from_env();
+    std::env::remove_var("TELEXIDE_POLLING_LIMIT");
+
+    match res {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.starts_with("polling.limit"), "{e}");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+    Ok(())
+}
+
+#[test]
+fn config_validation_names_field() {
+    let cases = [
+        ("api_url = \"x\"", "token"),
+        ("token = \"t\"\n[polling]\nlimit = 101", "polling.limit"),
+        (
+            "token = \"t\"\n[webhook]\nurl = \"not a url\"",
+            "webhook.url",
+        ),
+        ("token = \"t\"\n[webhook]\npath = \"bot\"", "webhook.path"),
+        ("token = \"t\"\n[timeouts]\nrequest = 0", "timeouts.request"),
+    ];
+
+    for (toml, field) in cases {
+        match ClientConfig::from_toml_str(toml) {
+            Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+                assert!(e.starts_with(field), "expected '{field}' in '{e}'");
+            },
+            _ => panic!("expected an invalid argument error for {field}"),
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use serde_json::json;
+use telexide::{
+    api::{APIEndpoint, Response},
+    framework::CommandError,
+    model::User,
+    Error,
+    Result,
+    TelegramError,
+};
+
+fn telegram_errors() -> Vec<TelegramError> {
+    vec![
+        TelegramError::NoToken,
+        TelegramError::InvalidToken,
+        TelegramError::MissingPermission,
+        TelegramError::NotFound,
+        TelegramError::ServerError,
+        TelegramError::InvalidEndpoint,
+        TelegramError::InvalidCommandType,
+        TelegramError::WebhookError,
+        TelegramError::Timeout {
+            endpoint: "getUpdates".to_owned(),
+        },
+        TelegramError::RateLimited {
+            endpoint: "sendMessage".to_owned(),
+            retry_after: 30,
+        },
+        TelegramError::LocalRateLimit {
+            endpoint: "setMyCommands".to_owned(),
+            calls: 10,
+        },
+        TelegramError::InvalidArgument("chat_id is missing".to_owned()),
+        TelegramError::APIResponseError("Bad Request: chat not found".to_owned()),
+        TelegramError::EndpointError {
+            endpoint: "sendMessage".to_owned(),
+            description: "Bad Request: chat not found".to_owned(),
+        },
+        TelegramError::Unknown("response had no result".to_owned()),
+    ]
+}
+
+#[test]
+fn telegram_error_display() {
+    let displayed: Vec<String> = telegram_errors().iter().map(ToString::to_string).collect();
+
+    assert_eq!(
+        displayed,
+        vec![
+            "No token provided to login to telegram",
+            "Invalid token provided for logging in to telegram",
+            "Missing permission to execute action in chat",
+            "The requested resource doesn't exist",
+            "The telegram server returned a 500 status code",
+            "The requested endpoint does not exist",
+            "This action cannot be done on this command type",
+            "An error occurred in the webhook handling",
+            "The request to getUpdates timed out",
+            "Rate limited by the telegram api on sendMessage, retry after 30 seconds",
+            "Usage limit of setMyCommands exceeded with 10 calls in its window",
+            "Invalid argument provided: chat_id is missing",
+            "The telegram api returned an error: Bad Request: chat not found",
+            "The telegram api returned an error for sendMessage: Bad Request: chat not found",
+            "An unknown error occurred: response had no result",
+        ]
+    );
+}
+
+#[test]
+fn error_display() {
+    let json = serde_json::from_str::<u8>("true").unwrap_err();
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "config.toml");
+
+    assert_eq!(
+        Error::from(TelegramError::NotFound).to_string(),
+        "The requested resource doesn't exist"
+    );
+    assert_eq!(
+        Error::from(json).to_string(),
+        "Invalid json was received or sent: invalid type: boolean `true`, expected u8 at line 1 \
+         column 4"
+    );
+    assert_eq!(
+        Error::from(io).to_string(),
+        "An io error occurred: config.toml"
+    );
+    assert_eq!(
+        Error::from(CommandError("no arguments given".to_owned())).to_string(),
+        "The command returned an error: no arguments given"
+    );
+}
+
+#[test]
+fn user_facing_messages() {
+    let messages: Vec<String> = telegram_errors()
+        .iter()
+        .map(TelegramError::user_facing_message)
+        .collect();
+
+    assert_eq!(
+        messages,
+        vec![
+            "The bot is not set up correctly",
+            "The bot is not set up correctly",
+            "The bot is not allowed to do that in this chat",
+            "That could not be found",
+            "Telegram could not be reached, please try again later",
+            "Something went wrong, please try again later",
+            "Something went wrong, please try again later",
+            "Something went wrong, please try again later",
+            "Telegram could not be reached, please try again later",
+            "Too many requests, please try again in 30 seconds",
+            "That was done too often, please try again later",
+            "Invalid input was provided",
+            "Telegram refused the request",
+            "Telegram refused the request",
+            "Something went wrong, please try again later",
+        ]
+    );
+
+    let io = std::io::Error::new(std::io::ErrorKind::NotFound, "/home/bot/config.toml");
+    assert_eq!(
+        Error::from(io).user_facing_message(),
+        "Something went wrong, please try again later"
+    );
+    assert_eq!(
+        Error::from(CommandError("token 123:abc is invalid".to_owned())).user_facing_message(),
+        "The command failed, please try again later"
+    );
+    assert_eq!(
+        Error::Listener("token 123:abc is invalid".to_owned()).user_facing_message(),
+        "Your request could not be handled, please try again later"
+    );
+}
+
+#[test]
+fn raw_json_is_kept_when_deserializing_fails() {
+    let response = Response {
+        ok: true,
+        description: None,
+        result: Some(json!({"id": "not a number", "is_bot": true, "first_name": "bot"})),
+        error_code: None,
+        parameters: None,
+    };
+
+    match Result::<User>::from(response) {
+        Err(Error::Telegram(TelegramError::Deserialization {
+            source,
+            raw_json,
+        })) => {
+            assert!(source.to_string().contains("invalid type"), "{source}");
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(&raw_json).unwrap(),
+                json!({"id": "not a number", "is_bot": true, "first_name": "bot"})
+            );
+        },
+        other => panic!("expected a deserialization error, got {other:?}"),
+    }
+}
+
+#[test]
+fn refused_requests_hold_their_endpoint() {
+    let response = Response {
+        ok: false,
+        description: Some("Bad Request: chat not found".to_owned()),
+        result: None,
+        error_code: Some(400),
+        parameters: None,
+    };
+
+    assert!(matches!(
+        response.clone().into_result::<User>(&APIEndpoint::GetChat),
+        Err(Error::Telegram(TelegramError::EndpointError {
+            endpoint,
+            description,
+        })) if endpoint == "getChat" && description == "Bad Request: chat not found"
+    ));
+    assert!(matches!(
+        Result::<User>::from(response),
+        Err(Error::Telegram(TelegramError::APIResponseError(description)))
+            if description == "Bad Request: chat not found"
+    ));
+}
@@ -0,0 +1,109 @@
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use telexide::{
+    model::{InlineKeyboardButton, LoginUrl},
+    utils::login::{verify_login_data, verify_login_data_with_max_age},
+    Error,
+    Result,
+    TelegramError,
+};
+
+const TOKEN: &str = "123456:dummy-token";
+
+/// Signs the fields the same way telegram does
+fn signed(mut fields: HashMap<String, String>, token: &str) -> HashMap<String, String> {
+    let mut data_check: Vec<String> = fields.iter().map(|(k, v)| format!("{k}={v}")).collect();
+    data_check.sort();
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(&Sha256::digest(token.as_bytes())).unwrap();
+    mac.update(data_check.join("\n").as_bytes());
+    let hash: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+
+    fields.insert("hash".to_owned(), hash);
+    fields
+}
+
+fn login_fields(auth_date: i64) -> HashMap<String, String> {
+    [
+        ("id", "538733".to_owned()),
+        ("first_name", "test".to_owned()),
+        ("username", "tester".to_owned()),
+        ("auth_date", auth_date.to_string()),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k.to_owned(), v))
+    .collect()
+}
+
+fn assert_invalid(res: Result<impl std::fmt::Debug>, field: &str) {
+    match res {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.contains(field), "expected '{field}' in '{e}'");
+        },
+        other => panic!("expected an invalid argument error, got {other:?}"),
+    }
+}
+
+#[test]
+fn valid_login_data() -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
+    let data = verify_login_data(&signed(login_fields(now), TOKEN), TOKEN)?;
+
+    assert_eq!(data.id, 538733);
+    assert_eq!(data.first_name, "test");
+    assert_eq!(data.username.as_deref(), Some("tester"));
+    assert_eq!(data.last_name, None);
+    assert_eq!(data.auth_date.timestamp(), now);
+    Ok(())
+}
+
+#[test]
+fn tampered_login_data() {
+    let now = chrono::Utc::now().timestamp();
+
+    let mut fields = signed(login_fields(now), TOKEN);
+    fields.insert("id".to_owned(), "1".to_owned());
+    assert_invalid(verify_login_data(&fields, TOKEN), "hash");
+
+    let fields = signed(login_fields(now), "654321:other-token");
+    assert_invalid(verify_login_data(&fields, TOKEN), "hash");
+
+    let mut fields = login_fields(now);
+    fields.insert("hash".to_owned(), "not hex".to_owned());
+    assert_invalid(verify_login_data(&fields, TOKEN), "hash");
+}
+
+#[test]
+fn expired_login_data() -> Result<()> {
+    let two_days_ago = chrono::Utc::now().timestamp() - 2 * 24 * 60 * 60;
+    let fields = signed(login_fields(two_days_ago), TOKEN);
+
+    assert_invalid(verify_login_data(&fields, TOKEN), "auth_date");
+    verify_login_data_with_max_age(&fields, TOKEN, chrono::Duration::days(3))?;
+    Ok(())
+}
+
+#[test]
+fn login_button_serialization() -> serde_json::Result<()> {
+    let mut url = LoginUrl::new("https://example.com/login");
+    url.request_write_access = true;
+
+    assert_eq!(
+        serde_json::to_value(InlineKeyboardButton::login("Log in", url))?,
+        serde_json::json!({
+            "text": "Log in",
+            "login_url": {
+                "url": "https://example.com/login",
+                "request_write_access": true
+            },
+            "pay": false
+        })
+    );
+    Ok(())
+}
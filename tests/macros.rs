@@ -1,19 +1,61 @@
-use std::sync::atomic::{AtomicUsize, Ordering};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 use telexide::{
+    api::{
+        types::{
+            InlineQueryResult,
+            InlineQueryResultCachedSticker,
+            SetMyCommands,
+            TopicId,
+            UpdateType,
+        },
+        APIClient,
+        APIEndpoint,
+        FormDataFile,
+        Response,
+        API,
+    },
     client::{ClientBuilder, Context},
-    framework::CommandResult,
+    framework::{
+        handlers::CommandArgs,
+        types::{CommandOptions, TelegramCommand},
+        CommandError,
+        CommandResult,
+        Dialogue,
+        DialogueKey,
+        DialogueState,
+        DialogueStorage,
+        Framework,
+        HookDecision,
+        InlineAnswer,
+        MemoryDialogueStorage,
+        Middleware,
+        TopicScope,
+    },
     macros::{command, create_framework, prepare_listener},
     model::{
-        Chat,
+        BotCommandScope,
+        CallbackQuery,
+        ChosenInlineResult,
+        InlineQuery,
+        IntegerOrString,
         Message,
         MessageContent,
         MessageEntity,
-        PrivateChat,
+        ParseMode,
         TextBlock,
         Update,
         UpdateContent,
     },
+    test_utils::{CallbackQueryBuilder, MessageBuilder, UpdateBuilder},
+    Error,
     Result,
+    TelegramError,
 };
 
 static MACRO_B: AtomicUsize = AtomicUsize::new(0);
@@ -31,7 +73,7 @@ async fn test_using_macro_to_prepare() -> Result<()> {
 
     c.fire_handlers(Update {
         update_id: 10,
-        content: UpdateContent::Unknown,
+        content: UpdateContent::Unknown(serde_json::Value::Null),
     });
 
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
@@ -56,90 +98,1972 @@ async fn test_using_command() -> Result<()> {
         .set_framework(create_framework!("test_bot", testing_command))
         .build();
 
-    c.fire_handlers(Update {
-        update_id: 10,
-        content: UpdateContent::Message(Message {
-            message_id: 30,
-            message_thread_id: None,
-            from: None,
-            date: chrono::offset::Utc::now(),
-            chat: Chat::Private(PrivateChat {
-                id: 40,
-                active_usernames: Vec::new(),
-                username: None,
-                first_name: None,
-                bio: None,
-                last_name: None,
-                photo: None,
-                has_private_forwards: false,
-                has_restricted_voice_and_video_messages: None,
-                message_auto_delete_time: None,
-                emoji_status_custom_emoji_id: None,
-                emoji_status_expiration_date: None,
-            }),
-            sender_chat: None,
-            forward_data: None,
-            reply_to_message: None,
-            via_bot: None,
-            edit_date: None,
-            author_signature: None,
-            connected_website: None,
-            passport_data: None,
-            reply_markup: None,
-            is_topic_message: false,
-            has_protected_content: false,
-            content: MessageContent::Unknown,
-        }),
-    });
+    let message = MessageBuilder::content(MessageContent::Unknown)
+        .message_id(30)
+        .in_private_chat(40)
+        .build();
+    c.fire_handlers(UpdateBuilder::message(message).build());
 
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
 
     assert_eq!(COMMAND_B.load(Ordering::Relaxed), 0);
 
-    c.fire_handlers(Update {
-        update_id: 10,
-        content: UpdateContent::Message(Message {
-            message_id: 30,
-            message_thread_id: None,
-            from: None,
-            date: chrono::offset::Utc::now(),
-            chat: Chat::Private(PrivateChat {
-                id: 40,
-                active_usernames: Vec::new(),
-                username: None,
-                first_name: None,
-                bio: None,
-                last_name: None,
-                photo: None,
-                has_private_forwards: false,
-                has_restricted_voice_and_video_messages: None,
-                message_auto_delete_time: None,
-                emoji_status_custom_emoji_id: None,
-                emoji_status_expiration_date: None,
-            }),
-            sender_chat: None,
-            forward_data: None,
-            reply_to_message: None,
-            via_bot: None,
-            edit_date: None,
-            author_signature: None,
-            connected_website: None,
-            passport_data: None,
-            reply_markup: None,
-            is_topic_message: false,
-            has_protected_content: false,
-            content: MessageContent::Text {
-                content: "/testing_command".to_owned(),
-                entities: vec![MessageEntity::BotCommand(TextBlock {
-                    offset: 0,
-                    length: 16,
-                })],
-            },
+    let message = MessageBuilder::text("/testing_command")
+        .message_id(30)
+        .in_private_chat(40)
+        .build();
+    assert_eq!(
+        message.content,
+        MessageContent::Text {
+            content: "/testing_command".to_owned(),
+            entities: vec![MessageEntity::BotCommand(TextBlock {
+                offset: 0,
+                length: 16,
+            })],
+        }
+    );
+    c.fire_handlers(UpdateBuilder::message(message).build());
+
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    assert_eq!(COMMAND_B.load(Ordering::Relaxed), 30);
+    Ok(())
+}
+
+#[command(description = "always registered", priority = 10)]
+async fn important(_c: Context, _m: Message) -> CommandResult {
+    Ok(())
+}
+
+fn framework_with_filler(filler: usize) -> Framework {
+    let fr = Framework::new("test_bot");
+    for i in 0..filler {
+        fr.add_command(&TelegramCommand::new(
+            CommandOptions::new(format!("filler{i}"), "filler command"),
+            important_COMMAND.command.clone(),
+        ));
+    }
+    fr.add_command(&important_COMMAND);
+    fr
+}
+
+#[test]
+fn too_many_commands_are_rejected() {
+    let fr = framework_with_filler(101);
+
+    match fr.get_bot_commands() {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.contains("filler100, important"), "{e}");
+            assert!(!e.contains("filler99"), "{e}");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+}
+
+#[tokio::test]
+async fn set_my_commands_rejects_too_many_commands() {
+    let fr = framework_with_filler(100);
+    let data = SetMyCommands::from(&fr.get_commands());
+
+    match APIClient::new_default("test").set_my_commands(data).await {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => {
+            assert!(e.contains("important"), "{e}");
+        },
+        _ => panic!("expected an invalid argument error"),
+    }
+}
+
+#[test]
+fn commands_are_prioritized() -> Result<()> {
+    let mut fr = framework_with_filler(101);
+    fr.set_auto_prioritize(true);
+
+    let commands = fr.get_bot_commands()?;
+    assert_eq!(commands.len(), 100);
+    assert_eq!(commands[0].command, "important");
+    assert_eq!(commands[99].command, "filler98");
+
+    assert_eq!(framework_with_filler(99).get_bot_commands()?.len(), 100);
+    Ok(())
+}
+
+static ECHOED: parking_lot::Mutex<Vec<(String, Vec<String>)>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "echoes its arguments")]
+async fn echo(c: Context, _m: Message) -> CommandResult {
+    ECHOED.lock().push((c.rest().to_owned(), c.args()));
+    Ok(())
+}
+
+fn text_update(update_id: i64, text: &str) -> Update {
+    let message = MessageBuilder::text(text)
+        .message_id(update_id)
+        .in_private_chat(40)
+        .build();
+    UpdateBuilder::message(message).update_id(update_id).build()
+}
+
+#[tokio::test]
+async fn command_arguments_are_parsed() -> Result<()> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", echo))
+        .build();
+
+    c.fire_handlers(text_update(
+        1,
+        "/echo@test_bot  say \"hello  world\" \"\" now ",
+    ));
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    c.fire_handlers(text_update(2, "/echo"));
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    assert_eq!(
+        *ECHOED.lock(),
+        vec![
+            (
+                "say \"hello  world\" \"\" now".to_owned(),
+                vec![
+                    "say".to_owned(),
+                    "hello  world".to_owned(),
+                    String::new(),
+                    "now".to_owned()
+                ]
+            ),
+            (String::new(), Vec::new())
+        ]
+    );
+    Ok(())
+}
+
+static SHOUTED: parking_lot::Mutex<Vec<(i64, String)>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "shouts the text")]
+async fn shout(c: Context, m: Message) -> CommandResult {
+    SHOUTED.lock().push((m.message_id, c.rest().to_uppercase()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn commands_are_found_by_their_entity() -> Result<()> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", shout))
+        .build();
+
+    let chat = json!({"id": 40, "type": "private", "first_name": "x"});
+    let messages = [
+        json!({
+            "text": "👋🏽 hey /shout@Test_Bot 🎉 loud",
+            "entities": [{"type": "bot_command", "offset": 9, "length": 15}]
+        }),
+        json!({
+            "photo": [{"file_id": "a", "file_unique_id": "b", "width": 1, "height": 1}],
+            "caption": "🐈\u{200d}⬛ \u{200b}/shout@TEST_BOT meow",
+            "caption_entities": [{"type": "bot_command", "offset": 6, "length": 15}]
         }),
+        json!({
+            "text": "🚀 /shout@other_bot no",
+            "entities": [{"type": "bot_command", "offset": 3, "length": 16}]
+        }),
+    ];
+    for (id, mut message) in (1..).zip(messages) {
+        message["message_id"] = json!(id);
+        message["date"] = json!(1585772722);
+        message["chat"] = chat.clone();
+        let message: Message = serde_json::from_value(message)?;
+        c.fire_handlers(UpdateBuilder::message(message).build());
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut shouted = SHOUTED.lock().clone();
+    shouted.sort_unstable();
+    assert_eq!(
+        shouted,
+        vec![(1, "🎉 LOUD".to_owned()), (2, "MEOW".to_owned())]
+    );
+    Ok(())
+}
+
+static SIGNED: parking_lot::Mutex<Vec<(i64, bool)>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "signs the message")]
+async fn sign(c: Context, m: Message) -> CommandResult {
+    SIGNED.lock().push((m.message_id, c.is_edited()));
+    Ok(())
+}
+
+#[tokio::test]
+async fn captions_and_edits_invoke_commands_when_enabled() -> Result<()> {
+    let photo = |id: i64| -> Result<Update> {
+        let message: Message = serde_json::from_value(json!({
+            "message_id": id,
+            "date": 1585772722,
+            "chat": {"id": 40, "type": "private", "first_name": "x"},
+            "photo": [{"file_id": "a", "file_unique_id": "b", "width": 1, "height": 1}],
+            "caption": "/sign here",
+            "caption_entities": [{"type": "bot_command", "offset": 0, "length": 5}]
+        }))?;
+        Ok(UpdateBuilder::message(message).update_id(id).build())
+    };
+    let text = |id: i64| {
+        MessageBuilder::text("/sign again")
+            .message_id(id)
+            .in_private_chat(40)
+    };
+
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", sign))
+        .build();
+    c.fire_handlers(photo(1)?);
+    c.fire_handlers(UpdateBuilder::edited_message(text(2).build()).build());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut fr = Framework::new("test_bot");
+    fr.set_match_captions(false)
+        .set_match_edited_messages(true)
+        .add_command(&sign_COMMAND);
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+    c.fire_handlers(photo(3)?);
+    c.fire_handlers(UpdateBuilder::edited_message(text(4).build()).build());
+    c.fire_handlers(UpdateBuilder::message(text(5).build()).build());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut signed = SIGNED.lock().clone();
+    signed.sort_unstable();
+    assert_eq!(signed, vec![(1, false), (4, true), (5, false)]);
+    Ok(())
+}
+
+static HELPED: parking_lot::Mutex<Vec<(i64, String)>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "shows the help", aliases = "start, info")]
+async fn help(c: Context, m: Message) -> CommandResult {
+    HELPED.lock().push((m.message_id, c.rest().to_owned()));
+    Ok(())
+}
+
+#[command(description = "waves at the user", aliases = "hi")]
+async fn wave(_c: Context, _m: Message) -> CommandResult {
+    Ok(())
+}
+
+#[tokio::test]
+async fn aliases_dispatch_to_their_command() -> Result<()> {
+    let fr = create_framework!("test_bot", hello, help);
+    let commands: Vec<String> = fr
+        .get_bot_commands()?
+        .into_iter()
+        .map(|c| c.command)
+        .collect();
+    assert_eq!(commands, vec!["hello", "help"]);
+
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(fr)
+        .build();
+    for (id, text) in [
+        "/start@test_bot show me",
+        "/info",
+        "/start@other_bot",
+        "/starting",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        c.fire_handlers(text_update(id as i64 + 1, text));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut helped = HELPED.lock().clone();
+    helped.sort_unstable();
+    assert_eq!(helped, vec![(1, "show me".to_owned()), (2, String::new())]);
+    Ok(())
+}
+
+#[test]
+fn alias_collisions_are_refused() {
+    let fr = Framework::new("test_bot");
+    fr.add_command(&hello_COMMAND);
+
+    match fr.try_add_command(&wave_COMMAND) {
+        Err(Error::Telegram(TelegramError::InvalidArgument(e))) => assert_eq!(
+            e,
+            "the alias hi of the wave command is already used by the hello command"
+        ),
+        _ => panic!("expected an invalid argument error"),
+    }
+    assert!(fr.try_add_command(&help_COMMAND).is_ok());
+    assert_eq!(fr.get_commands().len(), 2);
+}
+
+#[test]
+#[should_panic(expected = "the name hello of the hello command is already used")]
+fn duplicate_commands_panic() {
+    create_framework!("test_bot", hello, hello);
+}
+
+static BANNED: parking_lot::Mutex<Vec<(i64, Option<String>)>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "bans a user")]
+async fn ban_user(_c: Context, _m: Message, user: i64, reason: Option<String>) -> CommandResult {
+    BANNED.lock().push((user, reason));
+    Ok(())
+}
+
+static SUMS: parking_lot::Mutex<Vec<i64>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "adds up numbers")]
+async fn sum(_c: Context, _m: Message, numbers: Vec<i64>) -> CommandResult {
+    SUMS.lock().push(numbers.iter().sum());
+    Ok(())
+}
+
+#[tokio::test]
+async fn typed_command_arguments_are_parsed() -> Result<()> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", ban_user, sum))
+        .build();
+
+    for (id, text) in [
+        "/ban_user 5 spamming links",
+        "/ban_user 6",
+        "/ban_user",
+        "/ban_user six",
+        "/sum 1 2 \"3\"",
+        "/sum",
+        "/sum 1 two",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        c.fire_handlers(text_update(id as i64 + 1, text));
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+    }
+
+    assert_eq!(
+        *BANNED.lock(),
+        vec![(5, Some("spamming links".to_owned())), (6, None)]
+    );
+    assert_eq!(*SUMS.lock(), vec![6, 0]);
+    Ok(())
+}
+
+#[test]
+fn command_argument_errors() {
+    let error = |result: CommandResult<()>| result.unwrap_err().0;
+
+    let mut args = CommandArgs::new("five");
+    assert_eq!(
+        error(args.required::<i64>("user").map(|_| ())),
+        "invalid user argument \"five\": invalid digit found in string"
+    );
+    assert_eq!(
+        error(args.required::<i64>("user").map(|_| ())),
+        "missing the user argument"
+    );
+    assert_eq!(
+        error(CommandArgs::new("  ").required_rest("reason").map(|_| ())),
+        "missing the reason argument"
+    );
+
+    let mut args = CommandArgs::new("1 \"a b\"  c ");
+    assert_eq!(args.optional::<u8>("count").ok(), Some(Some(1)));
+    assert_eq!(args.next().as_deref(), Some("a b"));
+    assert_eq!(error(args.clone().finish()), "unexpected arguments: c");
+    assert_eq!(args.optional_rest().as_deref(), Some("c"));
+    assert!(args.finish().is_ok());
+}
+
+static GREETED: parking_lot::Mutex<Vec<i64>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "greets the user", aliases = "hi, Hey")]
+async fn hello(_c: Context, m: Message) -> CommandResult {
+    GREETED.lock().push(m.message_id);
+    Ok(())
+}
+
+async fn greeted(c: &telexide::client::Client, updates: Vec<Update>) -> Vec<i64> {
+    GREETED.lock().clear();
+    for update in updates {
+        c.fire_handlers(update);
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut greeted = GREETED.lock().clone();
+    greeted.sort_unstable();
+    greeted
+}
+
+// the tests using the hello command share the GREETED list, so they run as
+// one test
+#[tokio::test]
+async fn command_aliases_and_prefixes() -> Result<()> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", hello))
+        .build();
+    let updates = vec![
+        text_update(1, "/hello"),
+        text_update(2, "/hi@test_bot"),
+        text_update(3, "/Hey"),
+        text_update(4, "/hey"),
+        text_update(5, "/hi@other_bot"),
+        text_update(6, "!hi"),
+    ];
+    // aliases are lowercased like the names of commands
+    assert_eq!(greeted(&c, updates).await, vec![1, 2, 4]);
+
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", prefix = "!", hello))
+        .build();
+    let updates = vec![
+        text_update(1, "!hello"),
+        text_update(2, "!hi@test_bot with arguments"),
+        text_update(3, "say !hi"),
+        text_update(4, "/hello"),
+    ];
+    assert_eq!(greeted(&c, updates).await, vec![1, 2]);
+
+    let mut fr = Framework::new("test_bot");
+    fr.set_case_insensitive(true).add_command(&hello_COMMAND);
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+    let updates = vec![
+        text_update(1, "/HELLO"),
+        text_update(2, "/hey"),
+        text_update(3, "/hell"),
+    ];
+    assert_eq!(greeted(&c, updates).await, vec![1, 2]);
+    Ok(())
+}
+
+static CLOSED: parking_lot::Mutex<Vec<i64>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "closes the topic", topics = "5, 7")]
+async fn close(_c: Context, m: Message) -> CommandResult {
+    CLOSED.lock().push(m.message_id);
+    Ok(())
+}
+
+fn forum_command_update(update_id: i64, topic: TopicId) -> Update {
+    let message = MessageBuilder::text("/close")
+        .message_id(update_id)
+        .in_forum_topic(-40, topic)
+        .build();
+    UpdateBuilder::message(message).update_id(update_id).build()
+}
+
+async fn closed(fr: Framework) -> Vec<i64> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    CLOSED.lock().clear();
+    for update in [
+        forum_command_update(1, TopicId::General),
+        forum_command_update(5, TopicId::Topic(5)),
+        forum_command_update(6, TopicId::Topic(6)),
+        text_update(8, "/close"),
+    ] {
+        c.fire_handlers(update);
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut closed = CLOSED.lock().clone();
+    closed.sort_unstable();
+    closed
+}
+
+// the tests using the close command share the CLOSED list, so they run as
+// one test
+#[tokio::test]
+async fn commands_are_restricted_to_topics() -> Result<()> {
+    let fr = Framework::new("test_bot");
+    fr.add_command(&close_COMMAND);
+    assert_eq!(closed(fr).await, vec![5]);
+
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&close_COMMAND);
+    fr.restrict_command_to_topics("close", vec![1, 6]);
+    assert_eq!(closed(fr).await, vec![1, 6]);
+
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&close_COMMAND);
+    fr.set_command_topic_scope("close", TopicScope::ExceptGeneral);
+    assert_eq!(closed(fr).await, vec![5, 6]);
+
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&close_COMMAND);
+    fr.set_command_topic_scope("close", TopicScope::Everywhere);
+    assert_eq!(closed(fr).await, vec![1, 5, 6, 8]);
+    Ok(())
+}
+
+#[command(
+    description = "shows the weather",
+    description_localized(lang = "de", text = "zeigt das Wetter"),
+    description_localized(lang = "nl", text = "toont het weer")
+)]
+async fn weather(_c: Context, _m: Message) -> CommandResult {
+    Ok(())
+}
+
+#[command(
+    description = "bans a user",
+    description_localized(lang = "de", text = "sperrt")
+)]
+async fn ban(_c: Context, _m: Message) -> CommandResult {
+    Ok(())
+}
+
+#[derive(Default)]
+struct CommandsAPI {
+    requests: std::sync::Arc<parking_lot::Mutex<Vec<(String, Value)>>>,
+    received: std::sync::Arc<tokio::sync::Notify>,
+}
+
+impl CommandsAPI {
+    /// Waits until the api received the given amount of requests in total
+    async fn wait_for_requests(&self, amount: usize) {
+        let received = async {
+            while self.requests.lock().len() < amount {
+                self.received.notified().await;
+            }
+        };
+        tokio::time::timeout(tokio::time::Duration::from_secs(5), received)
+            .await
+            .expect("the requests weren't sent");
+    }
+}
+
+#[async_trait]
+impl API for CommandsAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        if matches!(endpoint, APIEndpoint::GetUpdates) {
+            // a running client waits for updates forever
+            return futures::future::pending().await;
+        }
+
+        self.requests
+            .lock()
+            .push((endpoint.to_string(), data.unwrap_or_default()));
+        self.received.notify_one();
+        Ok(Response {
+            ok: true,
+            description: None,
+            result: Some(json!(true)),
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn commands_are_synced_per_scope_and_language() -> Result<()> {
+    let admins = BotCommandScope::ChatAdministrators {
+        chat_id: IntegerOrString::Integer(-40),
+    };
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&weather_COMMAND);
+    fr.add_command(&ban_COMMAND);
+    fr.set_command_scopes("ban", vec![admins.clone()]);
+
+    assert_eq!(
+        fr.get_bot_command_scopes(),
+        vec![BotCommandScope::Default, admins.clone()]
+    );
+    assert_eq!(fr.get_bot_command_languages(), vec!["de", "nl"]);
+
+    let german = fr.get_scoped_bot_commands(&admins, Some("de"))?;
+    assert_eq!(german[0].description, "zeigt das Wetter");
+    assert_eq!(german[1].description, "sperrt");
+    let dutch = fr.get_scoped_bot_commands(&admins, Some("nl"))?;
+    assert_eq!(dutch[1].description, "bans a user");
+
+    let api = CommandsAPI::default();
+    fr.sync_commands(&api, &fr.get_bot_command_scopes()).await?;
+
+    let requests = api.requests.lock().clone();
+    assert_eq!(requests.len(), 6);
+    assert!(requests.iter().all(|(e, _)| e == "setMyCommands"));
+    assert_eq!(
+        requests[1].1,
+        json!({
+            "commands": [{"command": "weather", "description": "zeigt das Wetter"}],
+            "language_code": "de",
+            "scope": {"type": "default"}
+        })
+    );
+    assert_eq!(
+        requests[3].1,
+        json!({
+            "commands": [
+                {"command": "weather", "description": "shows the weather"},
+                {"command": "ban", "description": "bans a user"}
+            ],
+            "scope": {"type": "chat_administrators", "chat_id": -40}
+        })
+    );
+
+    let api = CommandsAPI::default();
+    Framework::new("test_bot")
+        .sync_commands(&api, &[BotCommandScope::AllGroupChats])
+        .await?;
+    assert_eq!(
+        *api.requests.lock(),
+        vec![(
+            "deleteMyCommands".to_owned(),
+            json!({"scope": {"type": "all_group_chats"}})
+        )]
+    );
+    Ok(())
+}
+
+/// A framework with a command made at runtime from the given greeting, as if
+/// it was read from a configuration file
+fn greeting_framework(greeting: &str, greeted: Arc<parking_lot::Mutex<Vec<String>>>) -> Framework {
+    let mut options = CommandOptions::new(String::from("greet"), format!("says {greeting}"));
+    options.aliases = vec!["salut".into()].into();
+    options.localized_descriptions = vec![("fr".into(), format!("dit {greeting}").into())].into();
+
+    let greeting = greeting.to_owned();
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&weather_COMMAND);
+    fr.add_command(&TelegramCommand::new(
+        options,
+        move |_c: Context, _m: Message| {
+            greeted.lock().push(greeting.clone());
+            async { Ok(()) }
+        },
+    ));
+    fr.set_command_scopes("greet", vec![BotCommandScope::AllGroupChats]);
+    fr
+}
+
+#[tokio::test]
+async fn commands_are_added_and_removed_at_runtime() -> Result<()> {
+    let greeted = Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(Arc::new(greeting_framework("bonjour", greeted.clone())))
+        .build();
+    c.fire_handlers(text_update(1, "/salut"));
+    c.fire_handlers(text_update(2, "/greet"));
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    assert_eq!(*greeted.lock(), vec!["bonjour", "bonjour"]);
+
+    let fr = greeting_framework("bonjour", greeted);
+    assert_eq!(fr.get_bot_command_languages(), vec!["de", "fr", "nl"]);
+    assert!(fr.remove_command("salut").is_none());
+    let removed = fr
+        .remove_command("greet")
+        .map(|c| c.options.name.to_string());
+    assert_eq!(removed.as_deref(), Some("greet"));
+    assert!(fr.remove_command("greet").is_none());
+    let commands = fr.get_commands();
+    let names: Vec<&str> = commands.iter().map(|c| &*c.options.name).collect();
+    assert_eq!(names, vec!["weather"]);
+
+    // the lists only the removed command was in are cleared on the next sync
+    assert_eq!(
+        fr.get_bot_command_scopes(),
+        vec![BotCommandScope::Default, BotCommandScope::AllGroupChats]
+    );
+    assert_eq!(fr.get_bot_command_languages(), vec!["de", "fr", "nl"]);
+
+    let api = CommandsAPI::default();
+    fr.sync_all_commands(&api).await?;
+    let requests = api.requests.lock().clone();
+    assert_eq!(requests.len(), 8);
+    assert_eq!(
+        requests
+            .iter()
+            .filter(|(_, d)| d["language_code"] == "fr")
+            .count(),
+        2
+    );
+    for (endpoint, data) in &requests {
+        assert_eq!(endpoint, "setMyCommands");
+        assert!(!data.to_string().contains("greet"), "{data}");
+    }
+
+    assert_eq!(fr.get_bot_command_scopes(), vec![BotCommandScope::Default]);
+    assert_eq!(fr.get_bot_command_languages(), vec!["de", "nl"]);
+    Ok(())
+}
+
+#[tokio::test]
+async fn commands_changed_while_running_are_synced() -> Result<()> {
+    let api = CommandsAPI::default();
+    let requests = api.requests.clone();
+    let received = api.received.clone();
+    let fr = Arc::new(greeting_framework("hallo", Arc::default()));
+    let c = ClientBuilder::new()
+        .set_api_client(Arc::new(Box::new(api)))
+        .set_framework(fr.clone())
+        .build();
+    let running = tokio::spawn(async move { c.start().await });
+    let api = CommandsAPI {
+        requests,
+        received,
+    };
+
+    let synced_commands = |from: usize| -> Vec<(Option<String>, String)> {
+        api.requests.lock()[from..]
+            .iter()
+            .filter(|(endpoint, _)| endpoint == "setMyCommands" || endpoint == "deleteMyCommands")
+            .map(|(endpoint, data)| {
+                let scope = data["scope"]["type"].as_str().map(str::to_owned);
+                (scope, format!("{endpoint} {}", data["commands"]))
+            })
+            .collect()
+    };
+
+    // the commands are synced in every scope and language when starting,
+    // after which the client gets its own user
+    api.wait_for_requests(9).await;
+    assert_eq!(synced_commands(0).len(), 8);
+    let sent = 9;
+
+    let greet = fr.remove_command("greet").expect("greet is registered");
+    api.wait_for_requests(sent + 8).await;
+    let synced = synced_commands(sent);
+    assert_eq!(synced.len(), 8);
+    assert!(
+        synced.iter().all(|(_, c)| !c.contains("greet")),
+        "{synced:?}"
+    );
+    assert_eq!(fr.get_bot_command_scopes(), vec![BotCommandScope::Default]);
+
+    // the scopes of a command are removed with it
+    fr.add_command(&greet);
+    api.wait_for_requests(sent + 12).await;
+    let synced = synced_commands(sent + 8);
+    assert_eq!(synced.len(), 4);
+    assert!(synced
+        .iter()
+        .all(|(scope, c)| scope.as_deref() == Some("default") && c.contains("greet")));
+
+    running.abort();
+    Ok(())
+}
+
+static CONFIGURED: parking_lot::Mutex<Vec<(i64, &str, String)>> =
+    parking_lot::Mutex::new(Vec::new());
+
+#[command(
+    description = "manages the settings",
+    subcommands = "config_get, config_set"
+)]
+async fn config(c: Context, m: Message) -> CommandResult {
+    CONFIGURED
+        .lock()
+        .push((m.message_id, "config", c.rest().to_owned()));
+    Ok(())
+}
+
+#[command(name = "get", description = "shows a setting", aliases = "show")]
+async fn config_get(_c: Context, m: Message, setting: String) -> CommandResult {
+    CONFIGURED.lock().push((m.message_id, "get", setting));
+    Ok(())
+}
+
+#[command(name = "set", description = "changes a setting")]
+async fn config_set(_c: Context, m: Message, setting: String, value: String) -> CommandResult {
+    CONFIGURED
+        .lock()
+        .push((m.message_id, "set", format!("{setting}={value}")));
+    Ok(())
+}
+
+#[tokio::test]
+async fn subcommands_are_dispatched() -> Result<()> {
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(create_framework!("test_bot", config))
+        .build();
+    for (id, text) in [
+        "/config",
+        "/config get colour",
+        "/config@test_bot show colour",
+        "/config_set colour red",
+        "/config reset colour",
+        "/get colour",
+        "/config_reset",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        c.fire_handlers(text_update(id as i64 + 1, text));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut configured = CONFIGURED.lock().clone();
+    configured.sort_unstable();
+    assert_eq!(
+        configured,
+        vec![
+            (1, "config", String::new()),
+            (2, "get", "colour".to_owned()),
+            (3, "get", "colour".to_owned()),
+            (4, "set", "colour=red".to_owned()),
+            (5, "config", "reset colour".to_owned()),
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn subcommands_are_listed() -> Result<()> {
+    let fr = create_framework!("test_bot", config, wave);
+
+    let commands: Vec<String> = fr
+        .get_bot_commands()?
+        .into_iter()
+        .map(|c| c.command)
+        .collect();
+    assert_eq!(commands, vec!["config", "config_get", "config_set", "wave"]);
+
+    assert_eq!(
+        fr.get_help_text(None),
+        "/config - manages the settings\n/config get - shows a setting\n/config set - changes a \
+         setting\n/wave - waves at the user"
+    );
+    Ok(())
+}
+
+#[command(description = "always fails")]
+async fn broken(_c: Context, _m: Message) -> CommandResult {
+    Err("it broke".into())
+}
+
+/// Middleware recording the hooks called, which aborts the commands of the
+/// messages with the given id
+struct Recorder {
+    name: &'static str,
+    abort_message: i64,
+    calls: std::sync::Arc<parking_lot::Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Middleware for Recorder {
+    async fn before(&self, _ctx: Context, message: &Message, command: &str) -> HookDecision {
+        self.calls.lock().push(format!(
+            "{} before {command} {}",
+            self.name, message.message_id
+        ));
+        if message.message_id == self.abort_message {
+            HookDecision::Abort(CommandError("banned".to_owned()))
+        } else {
+            HookDecision::Continue
+        }
+    }
+
+    async fn after(
+        &self,
+        _ctx: Context,
+        message: &Message,
+        command: &str,
+        result: &CommandResult,
+        _elapsed: std::time::Duration,
+    ) {
+        let result = match result {
+            Ok(()) => "ok".to_owned(),
+            Err(e) => e.0.clone(),
+        };
+        self.calls.lock().push(format!(
+            "{} after {command} {} {result}",
+            self.name, message.message_id
+        ));
+    }
+}
+
+#[tokio::test]
+async fn middleware_runs_around_commands() -> Result<()> {
+    let calls = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+    let mut fr = Framework::new("test_bot");
+    fr.add_middleware(Recorder {
+        name: "first",
+        abort_message: 2,
+        calls: calls.clone(),
+    })
+    .add_middleware(Recorder {
+        name: "second",
+        abort_message: 0,
+        calls: calls.clone(),
     });
+    fr.add_command(&wave_COMMAND);
+    fr.add_command(&broken_COMMAND);
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
 
+    for (id, text) in [(1, "/wave"), (2, "/wave"), (3, "/broken")] {
+        c.fire_handlers(text_update(id, text));
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    assert_eq!(
+        *calls.lock(),
+        vec![
+            "first before wave 1",
+            "second before wave 1",
+            "first after wave 1 ok",
+            "second after wave 1 ok",
+            "first before wave 2",
+            "first after wave 2 banned",
+            "second after wave 2 banned",
+            "first before broken 3",
+            "second before broken 3",
+            "first after broken 3 it broke",
+            "second after broken 3 it broke",
+        ]
+    );
+    Ok(())
+}
+
+static COMMAND_ERRORS: parking_lot::Mutex<Vec<(i64, String, String)>> =
+    parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "always panics")]
+async fn panicking(_c: Context, _m: Message) -> CommandResult {
+    panic!("oh no")
+}
+
+#[prepare_listener]
+async fn record_command_error(_c: Context, m: Message, e: CommandError) {
+    let text = match m.content {
+        MessageContent::Text {
+            content, ..
+        } => content,
+        _ => String::new(),
+    };
+    COMMAND_ERRORS.lock().push((m.message_id, text, e.0));
+}
+
+#[tokio::test]
+async fn command_errors_reach_the_error_handler() -> Result<()> {
+    let mut fr = Framework::new("test_bot");
+    fr.set_error_handler(record_command_error);
+    fr.add_command(&broken_COMMAND);
+    fr.add_command(&panicking_COMMAND);
+    fr.add_command(&wave_COMMAND);
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    for (id, text) in [(1, "/broken now"), (2, "/wave"), (3, "/panicking")] {
+        c.fire_handlers(text_update(id, text));
+    }
     tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
 
-    assert_eq!(COMMAND_B.load(Ordering::Relaxed), 30);
+    let mut errors = COMMAND_ERRORS.lock().clone();
+    errors.sort_unstable();
+    assert_eq!(
+        errors,
+        vec![
+            (1, "/broken now".to_owned(), "it broke".to_owned()),
+            (
+                3,
+                "/panicking".to_owned(),
+                "the command panicked: oh no".to_owned()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_error_handler_gets_handler_errors() -> Result<()> {
+    let errors = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+
+    let fr = Framework::new("test_bot");
+    fr.add_command(&broken_COMMAND);
+    fr.add_command(&wave_COMMAND);
+    let recorded = errors.clone();
+    let mut c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .set_error_handler(move |ctx, err| {
+            let update_id = ctx.update().map(|u| u.update_id);
+            recorded.lock().push((update_id, err.to_string()));
+        })
+        .build();
+    c.subscribe_handler_func(|_c, u| {
+        Box::pin(async move {
+            if u.update_id == 3 {
+                panic!("handler broke");
+            }
+        })
+    });
+
+    for (id, text) in [(1, "/broken now"), (2, "/wave"), (3, "hello")] {
+        c.fire_handlers(text_update(id, text));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut errors = errors.lock().clone();
+    errors.sort_unstable();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                Some(1),
+                "The command returned an error: it broke".to_owned()
+            ),
+            (Some(3), "The handler panicked: handler broke".to_owned()),
+        ]
+    );
+    Ok(())
+}
+
+#[prepare_listener]
+async fn failing_listener(_ctx: Context, update: Update) -> telexide::Result<()> {
+    if update.update_id == 1 {
+        return Err(telexide::TelegramError::NotFound.into());
+    }
+    Ok(())
+}
+
+#[prepare_listener]
+async fn failing_display_listener(_c: Context, update: Update) -> std::result::Result<(), String> {
+    match update.update_id {
+        2 => Err("no luck".to_owned()),
+        _ => Ok(()),
+    }
+}
+
+#[tokio::test]
+async fn listener_errors_reach_the_error_handler() -> Result<()> {
+    let errors = std::sync::Arc::new(parking_lot::Mutex::new(Vec::new()));
+
+    let recorded = errors.clone();
+    let mut c = ClientBuilder::new()
+        .set_token("test")
+        .set_error_handler(move |ctx, err| {
+            let update_id = ctx.update().map(|u| u.update_id);
+            recorded.lock().push((update_id, err.to_string()));
+        })
+        .build();
+    c.subscribe_handler_func(failing_listener);
+    c.subscribe_handler_func(failing_display_listener);
+
+    for id in 1..=3 {
+        c.fire_handlers(text_update(id, "hello"));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut errors = errors.lock().clone();
+    errors.sort_unstable();
+    assert_eq!(
+        errors,
+        vec![
+            (
+                Some(1),
+                "The listener returned an error: The requested resource doesn't exist".to_owned()
+            ),
+            (
+                Some(2),
+                "The listener returned an error: no luck".to_owned()
+            ),
+        ]
+    );
+    Ok(())
+}
+
+static INLINE_QUERIES: parking_lot::Mutex<Vec<(&str, String)>> =
+    parking_lot::Mutex::new(Vec::new());
+
+#[prepare_listener]
+async fn answer_any_query(c: Context, q: InlineQuery) {
+    INLINE_QUERIES.lock().push(("any", q.query));
+    let sticker = InlineQueryResultCachedSticker::new("1", "sticker-id");
+    c.answer_inline(q.id, vec![InlineQueryResult::CachedSticker(sticker)])
+        .await
+        .expect("answering the query failed");
+}
+
+#[prepare_listener]
+async fn answer_gif_query(_c: Context, q: InlineQuery) {
+    INLINE_QUERIES.lock().push(("gif", q.query));
+}
+
+fn inline_query_update(update_id: i64, query: &str, offset: &str) -> Update {
+    let query: InlineQuery = serde_json::from_value(json!({
+        "id": format!("query{update_id}"),
+        "from": {"id": 40, "is_bot": false, "first_name": "x"},
+        "query": query,
+        "offset": offset
+    }))
+    .expect("invalid inline query");
+    UpdateBuilder::new(UpdateContent::InlineQuery(query))
+        .update_id(update_id)
+        .build()
+}
+
+#[tokio::test]
+async fn inline_queries_are_routed_to_their_handlers() -> Result<()> {
+    let api = CommandsAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.add_inline_query_handler(answer_any_query)
+        .add_prefixed_inline_query_handler("gif", answer_gif_query);
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    for (id, query) in [(1, "gif cats"), (2, "gifs"), (3, "/gif")] {
+        c.fire_handlers(inline_query_update(id, query, ""));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut queries = INLINE_QUERIES.lock().clone();
+    queries.sort_unstable();
+    assert_eq!(
+        queries,
+        vec![
+            ("any", "/gif".to_owned()),
+            ("any", "gif cats".to_owned()),
+            ("any", "gifs".to_owned()),
+            ("gif", "gif cats".to_owned()),
+        ]
+    );
+
+    let mut requests = requests.lock().clone();
+    requests.sort_by_key(|(_, data)| data["inline_query_id"].to_string());
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+        requests[0],
+        (
+            "answerInlineQuery".to_owned(),
+            json!({
+                "inline_query_id": "query1",
+                "results": [{"type": "sticker", "id": "1", "sticker_file_id": "sticker-id"}]
+            })
+        )
+    );
+    Ok(())
+}
+
+fn stickers(ids: std::ops::Range<usize>) -> Vec<InlineQueryResult> {
+    ids.map(|id| {
+        let sticker = InlineQueryResultCachedSticker::new(id, "sticker-id");
+        InlineQueryResult::CachedSticker(sticker)
+    })
+    .collect()
+}
+
+#[prepare_listener]
+async fn echo_query(_c: Context, q: InlineQuery) -> CommandResult<InlineAnswer> {
+    if q.query == "fail" {
+        return Err("no results".into());
+    }
+    Ok(stickers(0..120).into())
+}
+
+#[prepare_listener]
+async fn page_query(_c: Context, q: InlineQuery) -> CommandResult<InlineAnswer> {
+    let mut answer = InlineAnswer::new(stickers(0..60));
+    answer.set_next_offset(format!("{}page", q.offset));
+    Ok(answer)
+}
+
+static CHOSEN: parking_lot::Mutex<Vec<String>> = parking_lot::Mutex::new(Vec::new());
+
+#[prepare_listener]
+async fn record_chosen(_c: Context, r: ChosenInlineResult) {
+    CHOSEN.lock().push(r.result_id);
+}
+
+#[tokio::test]
+async fn inline_answers_are_paginated() -> Result<()> {
+    let api = CommandsAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.add_inline_answer_handler(echo_query)
+        .add_prefixed_inline_answer_handler("page", page_query)
+        .add_chosen_inline_result_handler(record_chosen)
+        .set_inline_cache_time(10)
+        .set_inline_personal(true);
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    let queries = [
+        (1, "hello", ""),
+        (2, "hello", "50"),
+        (3, "hello", "100"),
+        (4, "page two", ""),
+        (5, "fail", ""),
+    ];
+    for (id, query, offset) in queries {
+        c.fire_handlers(inline_query_update(id, query, offset));
+    }
+    let chosen: ChosenInlineResult = serde_json::from_value(json!({
+        "result_id": "7",
+        "from": {"id": 40, "is_bot": false, "first_name": "x"},
+        "query": "hello"
+    }))?;
+    c.fire_handlers(UpdateBuilder::new(UpdateContent::ChosenInlineResult(chosen)).build());
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut answers: Vec<(Value, Value, usize, Value)> = requests
+        .lock()
+        .iter()
+        .map(|(_, data)| {
+            assert_eq!(data["cache_time"], json!(10));
+            assert_eq!(data["is_personal"], json!(true));
+            let results = data["results"].as_array().expect("no results");
+            (
+                data["inline_query_id"].clone(),
+                results[0]["id"].clone(),
+                results.len(),
+                data["next_offset"].clone(),
+            )
+        })
+        .collect();
+    answers.sort_by_key(|(id, ..)| id.to_string());
+    assert_eq!(
+        answers,
+        vec![
+            (json!("query1"), json!("0"), 50, json!("50")),
+            (json!("query2"), json!("50"), 50, json!("100")),
+            (json!("query3"), json!("100"), 20, Value::Null),
+            (json!("query4"), json!("0"), 50, json!("page")),
+        ]
+    );
+    assert_eq!(*CHOSEN.lock(), vec!["7".to_owned()]);
+    Ok(())
+}
+
+static UNKNOWN: parking_lot::Mutex<Vec<(i64, String, String)>> =
+    parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "handles unknown commands")]
+async fn unknown(c: Context, m: Message) -> CommandResult {
+    UNKNOWN.lock().push((
+        m.message_id,
+        c.command_name().unwrap_or_default().to_owned(),
+        c.rest().to_owned(),
+    ));
+    Ok(())
+}
+
+#[tokio::test]
+async fn unknown_commands_reach_the_fallback() -> Result<()> {
+    let mut fr = Framework::new("test_bot");
+    fr.set_unknown_command_handler(&unknown_COMMAND);
+    fr.add_command(&wave_COMMAND);
+    fr.add_command(&ban_COMMAND);
+    let commands: Vec<String> = fr
+        .get_bot_commands()?
+        .into_iter()
+        .map(|c| c.command)
+        .collect();
+    assert_eq!(commands, vec!["wave", "ban"]);
+
+    let c = ClientBuilder::new()
+        .set_token("test")
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+    for (id, text) in [
+        "/wave",
+        "/hi@test_bot",
+        "/ban 5",
+        "/doesnotexist now",
+        "/wav@test_bot",
+        "/doesnotexist@other_bot",
+        "say /doesnotexist",
+        "hello",
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        c.fire_handlers(text_update(id as i64 + 1, text));
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut unknown = UNKNOWN.lock().clone();
+    unknown.sort_unstable();
+    assert_eq!(
+        unknown,
+        vec![
+            (4, "doesnotexist".to_owned(), "now".to_owned()),
+            (5, "wav".to_owned(), String::new()),
+        ]
+    );
+    Ok(())
+}
+
+static CHECKED: parking_lot::Mutex<Vec<(i64, &str)>> = parking_lot::Mutex::new(Vec::new());
+
+async fn is_owner(_c: Context, m: &Message) -> Result<bool> {
+    Ok(m.from.as_ref().is_some_and(|u| u.id == 1))
+}
+
+#[command(description = "only in private", checks = "dm_only")]
+async fn secret(_c: Context, m: Message) -> CommandResult {
+    CHECKED.lock().push((m.message_id, "secret"));
+    Ok(())
+}
+
+#[command(
+    description = "only for admins",
+    checks = "group_only, chat_admin_only"
+)]
+async fn purge(_c: Context, m: Message) -> CommandResult {
+    CHECKED.lock().push((m.message_id, "purge"));
+    Ok(())
+}
+
+#[command(description = "only for the owner", checks = "is_owner")]
+async fn shutdown(_c: Context, m: Message) -> CommandResult {
+    CHECKED.lock().push((m.message_id, "shutdown"));
+    Ok(())
+}
+
+/// An api answering getChatMember with user 1 being an administrator, which
+/// records the requests it received
+#[derive(Default)]
+struct ChecksAPI {
+    requests: std::sync::Arc<parking_lot::Mutex<Vec<(String, Value)>>>,
+}
+
+#[async_trait]
+impl API for ChecksAPI {
+    async fn get(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        let data = data.unwrap_or_default();
+        let result = match endpoint {
+            APIEndpoint::GetChatMember => json!({
+                "status": if data["user_id"] == 1 { "administrator" } else { "member" },
+                "user": {"id": data["user_id"], "is_bot": false, "first_name": "x"},
+                "can_be_edited": false,
+                "is_anonymous": false,
+                "can_manage_chat": true,
+                "can_delete_messages": true,
+                "can_manage_video_chats": true,
+                "can_restrict_members": true,
+                "can_promote_members": false,
+                "can_change_info": true,
+                "can_invite_users": true,
+                "can_post_stories": false,
+                "can_edit_stories": false,
+                "can_delete_stories": false
+            }),
+            APIEndpoint::SendMessage => json!({
+                "message_id": 100,
+                "date": 1585772722,
+                "chat": {"id": data["chat_id"], "type": "private", "first_name": "x"},
+                "text": data["text"]
+            }),
+            _ => json!(true),
+        };
+        self.requests.lock().push((endpoint.to_string(), data));
+        Ok(Response {
+            ok: true,
+            description: None,
+            result: Some(result),
+            error_code: None,
+            parameters: None,
+        })
+    }
+
+    async fn post(&self, endpoint: APIEndpoint, data: Option<Value>) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+
+    async fn post_file(
+        &self,
+        endpoint: APIEndpoint,
+        data: Option<Value>,
+        _files: Option<Vec<FormDataFile>>,
+    ) -> Result<Response> {
+        self.get(endpoint, data).await
+    }
+}
+
+#[tokio::test]
+async fn commands_are_guarded_by_their_checks() -> Result<()> {
+    let api = ChecksAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.set_refusal_message("not allowed");
+    for command in [&secret_COMMAND, &purge_COMMAND, &shutdown_COMMAND] {
+        fr.add_command(command);
+    }
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    let messages = [
+        MessageBuilder::text("/secret")
+            .in_private_chat(1)
+            .from_user(1),
+        MessageBuilder::text("/secret").in_group(-10).from_user(1),
+        MessageBuilder::text("/purge").in_group(-10).from_user(1),
+        MessageBuilder::text("/purge").in_group(-10).from_user(2),
+        MessageBuilder::text("/purge").in_group(-10).from_user(1),
+        MessageBuilder::text("/purge")
+            .in_private_chat(1)
+            .from_user(1),
+        MessageBuilder::text("/shutdown")
+            .in_private_chat(2)
+            .from_user(2),
+        MessageBuilder::text("/shutdown")
+            .in_private_chat(1)
+            .from_user(1),
+    ];
+    for (id, message) in messages.into_iter().enumerate() {
+        let message = message.message_id(id as i64 + 1).build();
+        c.fire_handlers(UpdateBuilder::message(message).build());
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    assert_eq!(
+        *CHECKED.lock(),
+        vec![(1, "secret"), (3, "purge"), (5, "purge"), (8, "shutdown")]
+    );
+
+    let requests = requests.lock().clone();
+    let member_lookups: Vec<&Value> = requests
+        .iter()
+        .filter(|(e, _)| e == "getChatMember")
+        .map(|(_, data)| &data["user_id"])
+        .collect();
+    assert_eq!(member_lookups, vec![&json!(1), &json!(2)]);
+
+    let refused: Vec<&Value> = requests
+        .iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .map(|(_, data)| &data["reply_parameters"]["message_id"])
+        .collect();
+    assert_eq!(refused, vec![&json!(2), &json!(4), &json!(6), &json!(7)]);
+    assert!(requests
+        .iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .all(|(_, data)| data["text"] == "not allowed"));
+    Ok(())
+}
+
+/// Sends the messages to a client of which the framework has a help command,
+/// returning the replies to them by message id
+async fn help_replies(
+    parse_mode: ParseMode,
+    commands: &[&TelegramCommand],
+    messages: Vec<MessageBuilder>,
+) -> Vec<(i64, String)> {
+    let api = ChecksAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    for command in commands {
+        fr.add_command(command);
+    }
+    fr.add_help_command(parse_mode.clone());
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    for (id, message) in messages.into_iter().enumerate() {
+        let message = message.message_id(id as i64 + 1).build();
+        c.fire_handlers(UpdateBuilder::message(message).build());
+        tokio::time::sleep(tokio::time::Duration::from_millis(20)).await;
+    }
+
+    let requests = requests.lock().clone();
+    requests
+        .into_iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .map(|(_, data)| {
+            assert_eq!(data["parse_mode"], json!(parse_mode));
+            let id = data["reply_parameters"]["message_id"].as_i64().unwrap();
+            (id, data["text"].as_str().unwrap().to_owned())
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn help_lists_the_commands_the_user_can_use() {
+    let replies = help_replies(
+        ParseMode::MarkdownV2,
+        &[
+            &config_COMMAND,
+            &ban_user_COMMAND,
+            &wave_COMMAND,
+            &secret_COMMAND,
+            &purge_COMMAND,
+        ],
+        vec![
+            MessageBuilder::text("/help")
+                .in_private_chat(2)
+                .from_user(2),
+            MessageBuilder::text("/help").in_group(-10).from_user(1),
+            MessageBuilder::text("/help config")
+                .in_private_chat(2)
+                .from_user(2),
+            MessageBuilder::text("/help /config_show")
+                .in_private_chat(2)
+                .from_user(2),
+            MessageBuilder::text("/help purge")
+                .in_private_chat(2)
+                .from_user(2),
+        ],
+    )
+    .await;
+
+    let config = "/config \\- manages the settings";
+    let subcommands = "/config get <setting\\.\\.\\.\\> \\- shows a setting\n/config set \
+                       <setting\\> <value\\.\\.\\.\\> \\- changes a setting";
+    let others = "/ban\\_user <user\\> \\[reason\\.\\.\\.\\] \\- bans a user\n/wave \\- waves at \
+                  the user";
+    let help = "/help \\[command\\] \\- lists the commands";
+    assert_eq!(
+        replies,
+        vec![
+            (
+                1,
+                format!("{config}\n{subcommands}\n{others}\n/secret \\- only in private\n{help}")
+            ),
+            (
+                2,
+                format!("{config}\n{subcommands}\n{others}\n/purge \\- only for admins\n{help}")
+            ),
+            (3, format!("{config}\nSubcommands:\n{subcommands}")),
+            (
+                4,
+                "/config get <setting\\.\\.\\.\\> \\- shows a setting\nAliases: /config show"
+                    .to_owned()
+            ),
+            (5, "There is no /purge command".to_owned()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn help_is_escaped_for_html() {
+    let replies = help_replies(
+        ParseMode::HTML,
+        &[&ban_user_COMMAND, &wave_COMMAND],
+        vec![
+            MessageBuilder::text("/help ban_user")
+                .in_private_chat(2)
+                .from_user(2),
+            MessageBuilder::text("/help hi")
+                .in_private_chat(2)
+                .from_user(2),
+            MessageBuilder::text("/help <b>")
+                .in_private_chat(2)
+                .from_user(2),
+        ],
+    )
+    .await;
+
+    assert_eq!(
+        replies,
+        vec![
+            (
+                1,
+                "/ban_user &lt;user&gt; [reason...] - bans a user".to_owned()
+            ),
+            (2, "/wave - waves at the user\nAliases: /hi".to_owned()),
+            (3, "There is no /&lt;b&gt; command".to_owned()),
+        ]
+    );
+}
+
+static COOLED: parking_lot::Mutex<Vec<i64>> = parking_lot::Mutex::new(Vec::new());
+
+#[command(description = "takes a while", cooldown = 10)]
+async fn heavy(_c: Context, m: Message) -> CommandResult {
+    COOLED.lock().push(m.message_id);
+    Ok(())
+}
+
+#[command(description = "rolls a die", cooldown = 5, cooldown_scope = "chat")]
+async fn roll(_c: Context, m: Message) -> CommandResult {
+    COOLED.lock().push(m.message_id);
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+async fn commands_are_limited_by_their_cooldown() -> Result<()> {
+    let api = ChecksAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.set_cooldown_message("try again in {seconds}s");
+    fr.add_command(&heavy_COMMAND);
+    fr.add_command(&roll_COMMAND);
+    let fr = std::sync::Arc::new(fr);
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(fr.clone())
+        .build();
+
+    let send = |id: i64, message: MessageBuilder| {
+        c.fire_handlers(UpdateBuilder::message(message.message_id(id).build()).build());
+    };
+    let wait = || tokio::time::sleep(tokio::time::Duration::from_millis(20));
+
+    send(
+        1,
+        MessageBuilder::text("/heavy")
+            .in_private_chat(1)
+            .from_user(1),
+    );
+    wait().await;
+    send(
+        2,
+        MessageBuilder::text("/heavy")
+            .in_private_chat(1)
+            .from_user(1),
+    );
+    send(
+        3,
+        MessageBuilder::text("/heavy")
+            .in_private_chat(2)
+            .from_user(2),
+    );
+    wait().await;
+    tokio::time::advance(tokio::time::Duration::from_secs(10)).await;
+    send(4, MessageBuilder::text("/heavy").in_group(-20).from_user(1));
+    wait().await;
+
+    send(5, MessageBuilder::text("/roll").in_group(-20).from_user(1));
+    wait().await;
+    send(6, MessageBuilder::text("/roll").in_group(-20).from_user(2));
+    send(7, MessageBuilder::text("/roll").in_group(-30).from_user(2));
+    wait().await;
+
+    let mut cooled = COOLED.lock().clone();
+    cooled.sort_unstable();
+    assert_eq!(cooled, vec![1, 3, 4, 5, 7]);
+
+    let mut replies: Vec<(Value, Value)> = requests
+        .lock()
+        .iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .map(|(_, data)| {
+            (
+                data["reply_parameters"]["message_id"].clone(),
+                data["text"].clone(),
+            )
+        })
+        .collect();
+    replies.sort_by_key(|(id, _)| id.as_i64());
+    assert_eq!(
+        replies,
+        vec![
+            (json!(2), json!("try again in 10s")),
+            (json!(6), json!("try again in 5s"))
+        ]
+    );
+
+    // the cooldowns that ran out are removed once a minute
+    assert_eq!(fr.cooldown_count(), 4);
+    tokio::time::advance(tokio::time::Duration::from_secs(60)).await;
+    send(
+        8,
+        MessageBuilder::text("/heavy")
+            .in_private_chat(3)
+            .from_user(3),
+    );
+    wait().await;
+    assert_eq!(fr.cooldown_count(), 1);
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+enum Setup {
+    AskName,
+    AskAge { name: String },
+}
+
+impl DialogueState for Setup {
+    const NAME: &'static str = "setup";
+}
+
+#[command(description = "set up your profile")]
+async fn setup(c: Context, m: Message) -> CommandResult {
+    c.dialogue::<Setup>(&m).update(Setup::AskName).await?;
+    c.reply(&m, "what is your name?").await?;
+    Ok(())
+}
+
+#[prepare_listener]
+async fn ask_name(c: Context, m: Message, d: Dialogue<Setup>, _s: Setup) -> CommandResult {
+    let name = m.get_text().ok_or("the name is not text")?;
+    d.update(Setup::AskAge {
+        name,
+    })
+    .await?;
+    c.reply(&m, "how old are you?").await?;
+    Ok(())
+}
+
+#[prepare_listener]
+async fn ask_age(c: Context, m: Message, d: Dialogue<Setup>, s: Setup) -> CommandResult {
+    let Setup::AskAge {
+        name,
+    } = s
+    else {
+        return Err("not asking for an age".into());
+    };
+
+    match m.get_text().and_then(|t| t.parse::<u8>().ok()) {
+        Some(age) => {
+            d.exit().await?;
+            c.reply(&m, format!("{name} is {age}")).await?;
+        },
+        None => {
+            c.reply(&m, "that is not an age").await?;
+        },
+    }
+    Ok(())
+}
+
+type StoredStates = std::sync::Arc<parking_lot::Mutex<Vec<(DialogueKey, Option<Value>)>>>;
+
+/// Records the states stored by the dialogues
+#[derive(Default)]
+struct RecordingStorage {
+    inner: MemoryDialogueStorage,
+    stored: StoredStates,
+}
+
+#[async_trait]
+impl DialogueStorage for RecordingStorage {
+    async fn get(&self, name: &str, key: DialogueKey) -> Result<Option<Value>> {
+        self.inner.get(name, key).await
+    }
+
+    async fn set(&self, name: &str, key: DialogueKey, state: Value) -> Result<()> {
+        self.stored.lock().push((key, Some(state.clone())));
+        self.inner.set(name, key, state).await
+    }
+
+    async fn remove(&self, name: &str, key: DialogueKey) -> Result<()> {
+        self.stored.lock().push((key, None));
+        self.inner.remove(name, key).await
+    }
+}
+
+#[tokio::test]
+async fn dialogues_walk_through_their_states() -> Result<()> {
+    let api = ChecksAPI::default();
+    let requests = api.requests.clone();
+    let storage = RecordingStorage::default();
+    let stored = storage.stored.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.add_command(&setup_COMMAND);
+    fr.add_dialogue_handler(|s| matches!(s, Setup::AskName), ask_name)
+        .add_dialogue_handler(|s| matches!(s, Setup::AskAge { .. }), ask_age);
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .set_dialogue_storage(storage)
+        .build();
+
+    let messages = [
+        (1, 1, "hello"),
+        (2, 1, "/setup"),
+        (3, 1, "Alice"),
+        (4, 2, "Bob"),
+        (5, 1, "old"),
+        (6, 1, "30"),
+        (7, 1, "31"),
+    ];
+    for (id, user, text) in messages {
+        let message = MessageBuilder::text(text)
+            .in_group(-10)
+            .from_user(user)
+            .message_id(id)
+            .build();
+        c.fire_handlers(UpdateBuilder::message(message).build());
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    let replies: Vec<(Value, Value)> = requests
+        .lock()
+        .iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .map(|(_, data)| {
+            (
+                data["reply_parameters"]["message_id"].clone(),
+                data["text"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        replies,
+        vec![
+            (json!(2), json!("what is your name?")),
+            (json!(3), json!("how old are you?")),
+            (json!(5), json!("that is not an age")),
+            (json!(6), json!("Alice is 30")),
+        ]
+    );
+
+    let key = DialogueKey {
+        chat_id: -10,
+        user_id: Some(1),
+    };
+    assert_eq!(
+        *stored.lock(),
+        vec![
+            (key, Some(json!("AskName"))),
+            (key, Some(json!({"AskAge": {"name": "Alice"}}))),
+            (key, None),
+        ]
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn dialogues_and_cooldowns_continue_after_chat_migration() -> Result<()> {
+    let api = ChecksAPI::default();
+    let requests = api.requests.clone();
+    let mut fr = Framework::new("test_bot");
+    fr.set_cooldown_message("try again in {seconds}s");
+    fr.add_command(&setup_COMMAND);
+    fr.add_command(&roll_COMMAND);
+    fr.add_dialogue_handler(|s| matches!(s, Setup::AskName), ask_name)
+        .add_dialogue_handler(|s| matches!(s, Setup::AskAge { .. }), ask_age);
+    let c = ClientBuilder::new()
+        .set_api_client(std::sync::Arc::new(Box::new(api)))
+        .set_framework(std::sync::Arc::new(fr))
+        .build();
+
+    let migrate = MessageBuilder::content(MessageContent::MigrateToChatID {
+        content: -100_040,
+    })
+    .in_group(-40);
+    let messages = [
+        MessageBuilder::text("/setup").in_group(-40).from_user(1),
+        MessageBuilder::text("/roll").in_group(-40).from_user(1),
+        migrate,
+        MessageBuilder::text("Alice")
+            .in_supergroup(-100_040)
+            .from_user(1),
+        MessageBuilder::text("/roll")
+            .in_supergroup(-100_040)
+            .from_user(2),
+    ];
+    for (id, message) in (1..).zip(messages) {
+        c.fire_handlers(UpdateBuilder::message(message.message_id(id).build()).build());
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+    }
+
+    let replies: Vec<(Value, Value)> = requests
+        .lock()
+        .iter()
+        .filter(|(e, _)| e == "sendMessage")
+        .map(|(_, data)| {
+            (
+                data["reply_parameters"]["message_id"].clone(),
+                data["text"].clone(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        replies,
+        vec![
+            (json!(1), json!("what is your name?")),
+            (json!(4), json!("how old are you?")),
+            (json!(5), json!("try again in 5s")),
+        ]
+    );
+    Ok(())
+}
+
+static FILTERED: parking_lot::Mutex<Vec<(&str, i64)>> = parking_lot::Mutex::new(Vec::new());
+
+#[prepare_listener(updates = "message, edited_message")]
+async fn on_message(_c: Context, m: Message) {
+    FILTERED.lock().push(("message", m.message_id));
+}
+
+#[prepare_listener(updates = "callback_query")]
+async fn on_button(_c: Context, q: CallbackQuery) -> std::result::Result<(), String> {
+    let data = q.data.ok_or("no data")?;
+    FILTERED
+        .lock()
+        .push(("callback_query", data.parse().map_err(|_| data)?));
+    Ok(())
+}
+
+#[prepare_listener(updates = "inline_query")]
+async fn on_inline_update(_c: Context, u: Update) {
+    FILTERED.lock().push(("inline_query", u.update_id));
+}
+
+#[tokio::test]
+async fn listeners_are_filtered_by_update_type() -> Result<()> {
+    assert_eq!(
+        on_message_LISTENER.update_types,
+        &[UpdateType::Message, UpdateType::EditedMessage]
+    );
+
+    let mut c = ClientBuilder::new()
+        .set_token("test")
+        .add_listener(&on_message_LISTENER)
+        .add_listener(&on_button_LISTENER)
+        .build();
+    c.subscribe_listener(&on_inline_update_LISTENER);
+    // the listener itself ignores other updates when added without its filter
+    c.subscribe_handler_func(on_button);
+
+    let edited = MessageBuilder::text("edited").message_id(2).build();
+    let button = CallbackQueryBuilder::data("3").build();
+    for update in [
+        text_update(1, "hello"),
+        UpdateBuilder::edited_message(edited).update_id(2).build(),
+        UpdateBuilder::callback_query(button).update_id(3).build(),
+        inline_query_update(4, "query", ""),
+    ] {
+        c.fire_handlers(update);
+    }
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let mut filtered = FILTERED.lock().clone();
+    filtered.sort_unstable_by_key(|(_, id)| *id);
+    assert_eq!(
+        filtered,
+        vec![
+            ("message", 1),
+            ("message", 2),
+            ("callback_query", 3),
+            ("callback_query", 3),
+            ("inline_query", 4)
+        ]
+    );
     Ok(())
 }
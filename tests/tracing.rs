@@ -0,0 +1,135 @@
+use parking_lot::Mutex;
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+use telexide::{
+    api::{APIClient, API},
+    client::ClientBuilder,
+    test_utils::{MessageBuilder, UpdateBuilder},
+    Result,
+};
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    Subscriber,
+};
+use tracing_subscriber::{
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+    util::SubscriberInitExt,
+    Layer,
+};
+
+/// The name of a span, with its recorded fields
+type CapturedSpan = (String, HashMap<String, String>);
+
+/// The spans that were created, with their recorded fields
+#[derive(Clone, Default)]
+struct Spans(Arc<Mutex<Vec<CapturedSpan>>>);
+
+impl Spans {
+    fn find(&self, name: &str) -> Option<HashMap<String, String>> {
+        self.0
+            .lock()
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, fields)| fields.clone())
+    }
+}
+
+struct Fields<'a>(&'a mut HashMap<String, String>);
+
+impl Visit for Fields<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.0.insert(field.name().to_owned(), format!("{value:?}"));
+    }
+}
+
+struct SpanIndex(usize);
+
+impl<S> Layer<S> for Spans
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut fields = HashMap::new();
+        attrs.record(&mut Fields(&mut fields));
+
+        let mut spans = self.0.lock();
+        spans.push((attrs.metadata().name().to_owned(), fields));
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(SpanIndex(spans.len() - 1));
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let index = span.extensions().get::<SpanIndex>().map(|i| i.0);
+        if let Some(index) = index {
+            values.record(&mut Fields(&mut self.0.lock()[index].1));
+        }
+    }
+}
+
+/// Starts a server answering every request successfully, returning the url
+/// to reach it on
+async fn ok_server() -> String {
+    use hyper::{
+        service::{make_service_fn, service_fn},
+        Body,
+        Server,
+    };
+
+    let make_svc = make_service_fn(|_| async {
+        Ok::<_, hyper::Error>(service_fn(|_req: hyper::Request<Body>| async {
+            let body = json!({"ok": true, "result": true});
+            Ok::<_, hyper::Error>(hyper::Response::new(Body::from(body.to_string())))
+        }))
+    });
+
+    let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_svc);
+    let url = format!("http://{}", server.local_addr());
+    tokio::spawn(server);
+    url
+}
+
+#[tokio::test]
+async fn api_requests_are_traced() -> Result<()> {
+    let spans = Spans::default();
+    let _guard = tracing_subscriber::registry()
+        .with(spans.clone())
+        .set_default();
+
+    let mut client = APIClient::new_default("test");
+    client.set_api_url(ok_server().await);
+    assert!(client.log_out().await?);
+
+    let fields = spans.find("api_request").expect("no api_request span");
+    assert_eq!(fields["endpoint"], "\"logOut\"");
+    assert!(fields.contains_key("latency_ms"), "{fields:?}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn handler_dispatch_is_traced() -> Result<()> {
+    let spans = Spans::default();
+    let _guard = tracing_subscriber::registry()
+        .with(spans.clone())
+        .set_default();
+
+    let mut c = ClientBuilder::new().set_token("test").build();
+    c.subscribe_handler_func(|_c, _u| Box::pin(async {}));
+    c.fire_handlers(
+        UpdateBuilder::message(MessageBuilder::text("hi").in_group(-100).build())
+            .update_id(7)
+            .build(),
+    );
+    tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+
+    let fields = spans.find("dispatch").expect("no dispatch span");
+    assert_eq!(fields["update_id"], "7");
+    assert_eq!(fields["chat_id"], "-100");
+    assert!(spans.find("handler").is_some());
+    Ok(())
+}
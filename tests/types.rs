@@ -1,4 +1,46 @@
-use telexide::model::{Chat, Message, MessageContent, User};
+use serde_json::json;
+#[cfg(feature = "testing")]
+use telexide::{
+    api::types::TopicId,
+    test_utils::{CallbackQueryBuilder, MessageBuilder, UpdateBuilder},
+};
+use telexide::{
+    api::types::{
+        InlineQueryResult,
+        InlineQueryResultCachedPhoto,
+        InlineQueryResultCachedSticker,
+        SetMyCommands,
+        UpdateType,
+    },
+    model::{
+        BackgroundFill,
+        BackgroundType,
+        BotCommand,
+        BotCommandScope,
+        CallbackQuery,
+        Chat,
+        ChatBoostSource,
+        IntegerOrString,
+        KeyboardButton,
+        KeyboardButtonRequestUsers,
+        MaybeInaccessibleMessage,
+        Message,
+        MessageContent,
+        MessageOrigin,
+        PaidMedia,
+        ParseMode,
+        PollType,
+        ReactionType,
+        ReplyKeyboardMarkup,
+        StickerType,
+        TextBlock,
+        Update,
+        UpdateContent,
+        User,
+        UserProfilePhotos,
+        WriteAccessAllowed,
+    },
+};
 
 #[test]
 fn decode_user() -> serde_json::Result<()> {
@@ -53,3 +95,1277 @@ fn decode_message() -> serde_json::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn decode_message_link_preview_options() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 16373893,
+            "date": 1585772722,
+            "chat": {
+                "id": 538733,
+                "type": "private",
+                "first_name": "test"
+            },
+            "text": "https://example.com",
+            "link_preview_options": {
+                "url": "https://example.com",
+                "prefer_large_media": true
+            }
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+    let options = m.link_preview_options.expect("no link preview options");
+
+    assert_eq!(options.url, Some("https://example.com".to_owned()));
+    assert_eq!(options.prefer_large_media, Some(true));
+    assert_eq!(options.is_disabled, None);
+    Ok(())
+}
+
+#[test]
+fn reaction_type_round_trip() -> serde_json::Result<()> {
+    let reactions = vec![
+        ReactionType::Emoji {
+            emoji: "👍".to_owned(),
+        },
+        ReactionType::CustomEmoji {
+            custom_emoji_id: "5368324170671202286".to_owned(),
+        },
+    ];
+
+    let json = serde_json::to_value(&reactions)?;
+    assert_eq!(
+        json,
+        serde_json::json!([
+            {"type": "emoji", "emoji": "👍"},
+            {"type": "custom_emoji", "custom_emoji_id": "5368324170671202286"}
+        ])
+    );
+
+    let decoded: Vec<ReactionType> = serde_json::from_value(json)?;
+    assert_eq!(decoded, reactions);
+    Ok(())
+}
+
+#[test]
+fn bot_command_scope_round_trip() -> serde_json::Result<()> {
+    let scopes = vec![
+        BotCommandScope::Default,
+        BotCommandScope::AllPrivateChats,
+        BotCommandScope::AllGroupChats,
+        BotCommandScope::AllChatAdministrators,
+        BotCommandScope::Chat {
+            chat_id: IntegerOrString::Integer(-100),
+        },
+        BotCommandScope::ChatAdministrators {
+            chat_id: IntegerOrString::String("@channel".to_owned()),
+        },
+        BotCommandScope::ChatMember {
+            chat_id: IntegerOrString::Integer(-100),
+            user_id: 456,
+        },
+    ];
+
+    let json = serde_json::to_value(&scopes)?;
+    assert_eq!(
+        json,
+        json!([
+            {"type": "default"},
+            {"type": "all_private_chats"},
+            {"type": "all_group_chats"},
+            {"type": "all_chat_administrators"},
+            {"type": "chat", "chat_id": -100},
+            {"type": "chat_administrators", "chat_id": "@channel"},
+            {"type": "chat_member", "chat_id": -100, "user_id": 456}
+        ])
+    );
+
+    let decoded: Vec<BotCommandScope> = serde_json::from_value(json)?;
+    assert_eq!(decoded, scopes);
+
+    let mut data = SetMyCommands::new(vec![BotCommand {
+        command: "start".to_owned(),
+        description: "starts the bot".to_owned(),
+    }]);
+    data.set_scope(BotCommandScope::AllPrivateChats)
+        .set_language_code("nl");
+    assert_eq!(
+        serde_json::to_value(&data)?,
+        json!({
+            "commands": [{"command": "start", "description": "starts the bot"}],
+            "scope": {"type": "all_private_chats"},
+            "language_code": "nl"
+        })
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_write_access_allowed() -> serde_json::Result<()> {
+    let fixtures = [
+        (r#"{}"#, WriteAccessAllowed::default()),
+        (
+            r#"{"from_request": true}"#,
+            WriteAccessAllowed {
+                from_request: true,
+                ..WriteAccessAllowed::default()
+            },
+        ),
+        (
+            r#"{"web_app_name": "telexide_app"}"#,
+            WriteAccessAllowed {
+                web_app_name: Some("telexide_app".to_owned()),
+                ..WriteAccessAllowed::default()
+            },
+        ),
+        (
+            r#"{"from_attachment_menu": true}"#,
+            WriteAccessAllowed {
+                from_attachment_menu: true,
+                ..WriteAccessAllowed::default()
+            },
+        ),
+    ];
+
+    for (fixture, expected) in fixtures {
+        let t = format!(
+            r#"{{
+                "message_id": 16373894,
+                "date": 1585772722,
+                "chat": {{
+                    "id": 538733,
+                    "type": "private",
+                    "first_name": "test"
+                }},
+                "write_access_allowed": {fixture}
+            }}"#
+        );
+
+        let m: Message = serde_json::from_str(&t)?;
+        assert_eq!(
+            m.content,
+            MessageContent::WriteAccessAllowed {
+                content: expected
+            }
+        );
+
+        let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+        assert_eq!(round_trip, m);
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_message_reaction_update() -> serde_json::Result<()> {
+    let t = r#"{
+            "update_id": 10,
+            "message_reaction": {
+                "chat": {
+                    "id": -1001234,
+                    "type": "supergroup",
+                    "title": "test"
+                },
+                "message_id": 42,
+                "user": {
+                    "id": 456,
+                    "is_bot": false,
+                    "first_name": "x"
+                },
+                "date": 1585772722,
+                "old_reaction": [],
+                "new_reaction": [
+                    {"type": "emoji", "emoji": "👍"}
+                ]
+            }
+        }"#;
+
+    let u: Update = serde_json::from_str(t)?;
+
+    if let UpdateContent::MessageReaction(r) = u.content {
+        assert_eq!(r.message_id, 42);
+        assert_eq!(r.user.map(|u| u.id), Some(456));
+        assert!(r.old_reaction.is_empty());
+        assert_eq!(
+            r.new_reaction,
+            vec![ReactionType::Emoji {
+                emoji: "👍".to_owned()
+            }]
+        );
+    } else {
+        panic!("no message reaction")
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_message_reaction_count_update() -> serde_json::Result<()> {
+    let t = r#"{
+            "update_id": 11,
+            "message_reaction_count": {
+                "chat": {
+                    "id": -1001234,
+                    "type": "channel",
+                    "title": "test"
+                },
+                "message_id": 42,
+                "date": 1585772722,
+                "reactions": [
+                    {"type": {"type": "emoji", "emoji": "👍"}, "total_count": 3},
+                    {"type": {"type": "custom_emoji", "custom_emoji_id": "123"}, "total_count": 1}
+                ]
+            }
+        }"#;
+
+    let u: Update = serde_json::from_str(t)?;
+
+    if let UpdateContent::MessageReactionCount(r) = u.content {
+        assert_eq!(r.message_id, 42);
+        assert_eq!(r.reactions.len(), 2);
+        assert_eq!(r.reactions[0].total_count, 3);
+        assert_eq!(
+            r.reactions[1].kind,
+            ReactionType::CustomEmoji {
+                custom_emoji_id: "123".to_owned()
+            }
+        );
+    } else {
+        panic!("no message reaction count")
+    }
+
+    assert_eq!(
+        serde_json::to_value([
+            UpdateType::MessageReaction,
+            UpdateType::MessageReactionCount
+        ])?,
+        serde_json::json!(["message_reaction", "message_reaction_count"])
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_chat_boost_updates() -> serde_json::Result<()> {
+    let t = r#"{
+            "update_id": 12,
+            "chat_boost": {
+                "chat": {
+                    "id": -1001234,
+                    "type": "channel",
+                    "title": "test"
+                },
+                "boost": {
+                    "boost_id": "boost",
+                    "add_date": 1585772722,
+                    "expiration_date": 1588364722,
+                    "source": {
+                        "source": "premium",
+                        "user": {
+                            "id": 456,
+                            "is_bot": false,
+                            "first_name": "x"
+                        }
+                    }
+                }
+            }
+        }"#;
+
+    let u: Update = serde_json::from_str(t)?;
+
+    if let UpdateContent::ChatBoost(b) = u.content {
+        assert_eq!(b.boost.boost_id, "boost");
+        assert!(matches!(
+            b.boost.source,
+            ChatBoostSource::Premium {
+                user: User {
+                    id: 456,
+                    ..
+                }
+            }
+        ));
+    } else {
+        panic!("no chat boost")
+    }
+
+    let t = r#"{
+            "update_id": 13,
+            "removed_chat_boost": {
+                "chat": {
+                    "id": -1001234,
+                    "type": "channel",
+                    "title": "test"
+                },
+                "boost_id": "boost",
+                "remove_date": 1585772722,
+                "source": {
+                    "source": "giveaway",
+                    "giveaway_message_id": 5,
+                    "is_unclaimed": true
+                }
+            }
+        }"#;
+
+    let u: Update = serde_json::from_str(t)?;
+
+    if let UpdateContent::RemovedChatBoost(b) = u.content {
+        assert_eq!(b.boost_id, "boost");
+        assert_eq!(
+            b.source,
+            ChatBoostSource::Giveaway {
+                giveaway_message_id: 5,
+                user: None,
+                is_unclaimed: true,
+            }
+        );
+    } else {
+        panic!("no removed chat boost")
+    }
+
+    assert_eq!(
+        serde_json::to_value([UpdateType::ChatBoost, UpdateType::RemovedChatBoost])?,
+        serde_json::json!(["chat_boost", "removed_chat_boost"])
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_giveaway_messages() -> serde_json::Result<()> {
+    let channel = r#"{"id": -1001234, "type": "channel", "title": "giveaways"}"#;
+    let giveaway = format!(
+        r#"{{
+            "message_id": 20,
+            "date": 1585772722,
+            "chat": {channel},
+            "giveaway": {{
+                "chats": [{channel}],
+                "winners_selection_date": 1588364722,
+                "winner_count": 3,
+                "only_new_members": true,
+                "prize_description": "stickers",
+                "country_codes": ["NL", "BE"],
+                "premium_subscription_month_count": 6
+            }}
+        }}"#
+    );
+    let fixtures = [
+        giveaway.clone(),
+        format!(
+            r#"{{
+                "message_id": 19,
+                "date": 1585772722,
+                "chat": {channel},
+                "giveaway_created": {{}}
+            }}"#
+        ),
+        format!(
+            r#"{{
+                "message_id": 21,
+                "date": 1588364722,
+                "chat": {channel},
+                "giveaway_winners": {{
+                    "chat": {channel},
+                    "giveaway_message_id": 20,
+                    "winners_selection_date": 1588364722,
+                    "winner_count": 3,
+                    "winners": [{{"id": 456, "is_bot": false, "first_name": "x"}}],
+                    "unclaimed_prize_count": 2,
+                    "premium_subscription_month_count": 6
+                }}
+            }}"#
+        ),
+        format!(
+            r#"{{
+                "message_id": 22,
+                "date": 1588364722,
+                "chat": {channel},
+                "giveaway_completed": {{
+                    "winner_count": 3,
+                    "giveaway_message": {giveaway}
+                }}
+            }}"#
+        ),
+    ];
+
+    let messages = fixtures
+        .iter()
+        .map(|f| serde_json::from_str(f))
+        .collect::<serde_json::Result<Vec<Message>>>()?;
+
+    match &messages[0].content {
+        MessageContent::Giveaway {
+            content,
+        } => {
+            assert_eq!(content.chats.len(), 1);
+            assert_eq!(content.winner_count, 3);
+            assert!(content.only_new_members && !content.has_public_winners);
+            assert_eq!(content.country_codes, vec!["NL", "BE"]);
+        },
+        c => panic!("expected a giveaway, got {c:?}"),
+    }
+    assert!(matches!(
+        messages[1].content,
+        MessageContent::GiveawayCreated { .. }
+    ));
+    match &messages[2].content {
+        MessageContent::GiveawayWinners {
+            content,
+        } => {
+            assert_eq!(content.giveaway_message_id, 20);
+            assert_eq!(content.winners[0].id, 456);
+            assert_eq!(content.unclaimed_prize_count, Some(2));
+            assert!(!content.was_refunded);
+        },
+        c => panic!("expected giveaway winners, got {c:?}"),
+    }
+    match &messages[3].content {
+        MessageContent::GiveawayCompleted {
+            content,
+        } => {
+            assert_eq!(content.winner_count, 3);
+            assert_eq!(content.giveaway_message.as_deref(), Some(&messages[0]));
+        },
+        c => panic!("expected a completed giveaway, got {c:?}"),
+    }
+
+    for m in messages {
+        let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+        assert_eq!(round_trip, m);
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_story_messages() -> serde_json::Result<()> {
+    let channel = r#"{"id": -1001234, "type": "channel", "title": "stories"}"#;
+    let fixtures = [
+        format!(
+            r#"{{
+                "message_id": 30,
+                "date": 1585772722,
+                "chat": {{"id": 123, "type": "private", "first_name": "x"}},
+                "story": {{"chat": {channel}, "id": 5}}
+            }}"#
+        ),
+        format!(
+            r#"{{
+                "message_id": 31,
+                "date": 1585772722,
+                "chat": {{"id": 123, "type": "private", "first_name": "x"}},
+                "reply_to_story": {{"chat": {channel}, "id": 5}},
+                "text": "nice story"
+            }}"#
+        ),
+    ];
+
+    let messages = fixtures
+        .iter()
+        .map(|f| serde_json::from_str(f))
+        .collect::<serde_json::Result<Vec<Message>>>()?;
+
+    match &messages[0].content {
+        MessageContent::Story {
+            content,
+        } => {
+            assert_eq!(content.id, 5);
+            assert_eq!(content.chat.get_id(), -1001234);
+        },
+        c => panic!("expected a story, got {c:?}"),
+    }
+    assert!(messages[0].reply_to_story.is_none());
+    let story = messages[1]
+        .reply_to_story
+        .as_ref()
+        .expect("a replied to story");
+    assert_eq!((story.chat.get_id(), story.id), (-1001234, 5));
+
+    for m in messages {
+        let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+        assert_eq!(round_trip, m);
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_users_shared_message() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 32,
+            "date": 1585772722,
+            "chat": {"id": 123, "type": "private", "first_name": "x"},
+            "users_shared": {
+                "request_id": 4,
+                "users": [
+                    {"user_id": 456, "first_name": "y", "username": "why"},
+                    {
+                        "user_id": 789,
+                        "photo": [{
+                            "file_id": "a",
+                            "file_unique_id": "b",
+                            "width": 160,
+                            "height": 160
+                        }]
+                    }
+                ]
+            }
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+    match &m.content {
+        MessageContent::UsersShared {
+            content,
+        } => {
+            assert_eq!(content.request_id, 4);
+            assert_eq!(content.users.len(), 2);
+            assert_eq!(content.users[0].username.as_deref(), Some("why"));
+            assert!(content.users[0].photo.is_none());
+            assert_eq!(content.users[1].photo.as_ref().map(Vec::len), Some(1));
+        },
+        c => panic!("expected shared users, got {c:?}"),
+    }
+
+    let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+    assert_eq!(round_trip, m);
+    Ok(())
+}
+
+#[test]
+fn request_users_button_serialization() -> serde_json::Result<()> {
+    let mut request = KeyboardButtonRequestUsers::new(4);
+    request.set_max_quantity(3).set_request_name(true);
+    let mut button = KeyboardButton::new("pick friends");
+    button.set_request_users(request);
+
+    assert_eq!(
+        serde_json::to_value(&button)?,
+        serde_json::json!({
+            "text": "pick friends",
+            "request_users": {
+                "request_id": 4,
+                "max_quantity": 3,
+                "request_name": true
+            }
+        })
+    );
+
+    #[allow(deprecated)]
+    let old = telexide::model::KeyboardButtonRequestUser::new(4);
+    assert_eq!(old, KeyboardButtonRequestUsers::new(4));
+    Ok(())
+}
+
+#[test]
+fn reply_keyboard_with_request_buttons() -> serde_json::Result<()> {
+    let mut request = KeyboardButtonRequestUsers::new(7);
+    request.set_user_is_bot(false).set_max_quantity(2);
+
+    let mut keyboard = ReplyKeyboardMarkup::default();
+    keyboard
+        .add_button(KeyboardButton::new("hello"))
+        .add_button(KeyboardButton::users("invite", request))
+        .add_new_row()
+        .add_button(KeyboardButton::contact("share number"))
+        .add_button(KeyboardButton::poll("new quiz", PollType::Quiz));
+    keyboard.set_one_time_keyboard(true);
+
+    let value = serde_json::to_value(&keyboard)?;
+    assert_eq!(
+        value,
+        json!({
+            "keyboard": [
+                [
+                    {"text": "hello"},
+                    {
+                        "text": "invite",
+                        "request_users": {
+                            "request_id": 7,
+                            "user_is_bot": false,
+                            "max_quantity": 2
+                        }
+                    }
+                ],
+                [
+                    {"text": "share number", "request_contact": true},
+                    {"text": "new quiz", "request_poll": {"type": "quiz"}}
+                ]
+            ],
+            "one_time_keyboard": true
+        })
+    );
+
+    let round_trip: ReplyKeyboardMarkup = serde_json::from_value(value)?;
+    assert_eq!(round_trip, keyboard);
+    Ok(())
+}
+
+#[test]
+fn entities_are_sliced_by_utf16_offsets() -> serde_json::Result<()> {
+    let m: Message = serde_json::from_value(json!({
+        "message_id": 1,
+        "date": 1585772722,
+        "chat": {"id": 538733, "type": "private", "first_name": "test"},
+        "text": "👋🏽 hey @alice, see https://t.me/telexide 🎉 or /start@test_bot — ✨ @bob",
+        "entities": [
+            {"type": "mention", "offset": 9, "length": 6},
+            {"type": "text_link", "offset": 17, "length": 3, "url": "https://docs.rs/telexide"},
+            {"type": "url", "offset": 21, "length": 21},
+            {"type": "bot_command", "offset": 49, "length": 15},
+            {"type": "mention", "offset": 69, "length": 4}
+        ]
+    }))?;
+
+    assert_eq!(m.mentions(), vec!["@alice", "@bob"]);
+    assert_eq!(
+        m.urls(),
+        vec!["https://docs.rs/telexide", "https://t.me/telexide"]
+    );
+    assert_eq!(m.bot_commands(), vec!["/start@test_bot"]);
+    assert_eq!(m.entity_text(&m.entities()[1]), "see");
+
+    // blocks reaching past the end of the text are cut off
+    let block = |offset, length| TextBlock {
+        offset,
+        length,
+    };
+    assert_eq!(block(2, 10).get_str("✨ @bob"), "@bob");
+    assert_eq!(block(69, 10).get_str("✨ @bob"), "");
+    assert_eq!(block(2, 4).get_str_before("✨ @bob hi"), "✨ ");
+    assert_eq!(block(2, 4).get_str_after("✨ @bob hi"), " hi");
+    Ok(())
+}
+
+#[test]
+fn caption_entities_are_extracted() -> serde_json::Result<()> {
+    let m: Message = serde_json::from_value(json!({
+        "message_id": 2,
+        "date": 1585772722,
+        "chat": {"id": 538733, "type": "private", "first_name": "test"},
+        "photo": [{"file_id": "a", "file_unique_id": "b", "width": 1, "height": 1}],
+        "caption": "🐈 by @cat_fan",
+        "caption_entities": [{"type": "mention", "offset": 6, "length": 8}]
+    }))?;
+
+    assert_eq!(m.mentions(), vec!["@cat_fan"]);
+    assert!(m.urls().is_empty());
+    Ok(())
+}
+
+#[test]
+fn decode_sticker_message() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 16373895,
+            "date": 1585772722,
+            "chat": {
+                "id": 538733,
+                "type": "private",
+                "first_name": "test"
+            },
+            "sticker": {
+                "width": 512,
+                "height": 512,
+                "emoji": "😂",
+                "set_name": "test_stickers",
+                "is_animated": false,
+                "is_video": false,
+                "type": "regular",
+                "thumb": {
+                    "file_id": "thumb-file",
+                    "file_unique_id": "thumb1",
+                    "file_size": 5416,
+                    "width": 128,
+                    "height": 128
+                },
+                "file_id": "sticker-file",
+                "file_unique_id": "sticker1",
+                "file_size": 24532
+            }
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+
+    if let MessageContent::Sticker {
+        content,
+    } = m.content
+    {
+        assert_eq!(content.kind, StickerType::Regular);
+        assert!(!content.is_animated && !content.is_video);
+        #[allow(deprecated)]
+        let thumb = content.thumb().cloned();
+        assert_eq!(thumb, content.thumbnail);
+        assert_eq!(
+            content.thumbnail.map(|t| t.file_id),
+            Some("thumb-file".to_owned())
+        );
+    } else {
+        panic!("no sticker")
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_unknown_sticker_type() -> serde_json::Result<()> {
+    let kind: StickerType = serde_json::from_str(r#""holographic""#)?;
+    assert_eq!(kind, StickerType::Unknown);
+    Ok(())
+}
+
+#[test]
+fn user_profile_photos_helpers() -> serde_json::Result<()> {
+    let t = r#"{
+            "total_count": 5,
+            "photos": [
+                [
+                    {"file_id": "new-small", "file_unique_id": "a", "width": 160, "height": 160},
+                    {"file_id": "new-big", "file_unique_id": "b", "width": 640, "height": 640},
+                    {"file_id": "new-medium", "file_unique_id": "c", "width": 320, "height": 320}
+                ],
+                [
+                    {"file_id": "old-small", "file_unique_id": "d", "width": 160, "height": 160}
+                ]
+            ]
+        }"#;
+
+    let photos: UserProfilePhotos = serde_json::from_str(t)?;
+
+    assert_eq!(photos.len(), 2);
+    assert!(!photos.is_empty());
+    assert_eq!(photos.latest().map(Vec::len), Some(3));
+    assert_eq!(
+        photos.latest_largest().map(|p| p.file_id.as_str()),
+        Some("new-big")
+    );
+    assert_eq!(photos.iter().map(Vec::len).collect::<Vec<_>>(), vec![3, 1]);
+
+    let ids: Vec<String> = photos.into_iter().flatten().map(|p| p.file_id).collect();
+    assert_eq!(ids.len(), 4);
+    assert_eq!(ids[3], "old-small");
+
+    let empty = UserProfilePhotos {
+        total_count: 0,
+        photos: Vec::new(),
+    };
+    assert!(empty.is_empty());
+    assert!(empty.latest_largest().is_none());
+    Ok(())
+}
+
+#[test]
+fn decode_caption_above_media() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 1,
+            "date": 1585772722,
+            "chat": {
+                "id": 538733,
+                "type": "private",
+                "first_name": "test"
+            },
+            "photo": [
+                {"file_id": "photo", "file_unique_id": "a", "width": 160, "height": 160}
+            ],
+            "caption": "above",
+            "show_caption_above_media": true
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+
+    match &m.content {
+        MessageContent::Photo {
+            show_caption_above_media,
+            ..
+        } => assert!(show_caption_above_media),
+        _ => panic!("expected a photo message"),
+    }
+
+    let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+    assert_eq!(round_trip, m);
+
+    let below: Message = serde_json::from_str(&t.replace("true", "false"))?;
+    assert!(serde_json::to_value(&below)?
+        .get("show_caption_above_media")
+        .is_none());
+    Ok(())
+}
+
+#[test]
+fn decode_forward_origin_user() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 2314,
+            "from": {"id": 538733, "is_bot": false, "first_name": "test"},
+            "chat": {"id": 538733, "type": "private", "first_name": "test"},
+            "date": 1704067200,
+            "forward_origin": {
+                "type": "user",
+                "sender_user": {"id": 777000, "is_bot": false, "first_name": "Telegram"},
+                "date": 1704060000
+            },
+            "forward_from": {"id": 777000, "is_bot": false, "first_name": "Telegram"},
+            "forward_date": 1704060000,
+            "text": "forwarded"
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+    let data = m.forward_data.clone().expect("message should be forwarded");
+
+    assert_eq!(data.from.map(|u| u.id), Some(777000));
+    assert_eq!(data.date.timestamp(), 1704060000);
+    assert!(matches!(data.origin, Some(MessageOrigin::User { .. })));
+
+    let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+    assert_eq!(round_trip, m);
+    Ok(())
+}
+
+#[test]
+fn decode_forward_origin_channel() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 2315,
+            "chat": {"id": 538733, "type": "private", "first_name": "test"},
+            "date": 1704067200,
+            "forward_origin": {
+                "type": "channel",
+                "chat": {"id": -1001234567890, "title": "News", "username": "news", "type": "channel"},
+                "message_id": 42,
+                "author_signature": "editor",
+                "date": 1704050000
+            },
+            "text": "forwarded from a channel"
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+    let data = m.forward_data.expect("message should be forwarded");
+
+    assert_eq!(data.from_chat.map(|c| c.get_id()), Some(-1001234567890));
+    assert_eq!(data.from_message_id, Some(42));
+    assert_eq!(data.signature.as_deref(), Some("editor"));
+    assert_eq!(data.date.timestamp(), 1704050000);
+    match data.origin {
+        Some(MessageOrigin::Channel {
+            message_id, ..
+        }) => assert_eq!(message_id, 42),
+        _ => panic!("expected a channel origin"),
+    }
+    Ok(())
+}
+
+#[test]
+#[allow(deprecated)]
+fn deprecated_forward_accessors() -> serde_json::Result<()> {
+    let t = r#"{
+            "message_id": 2316,
+            "chat": {"id": 538733, "type": "private", "first_name": "test"},
+            "date": 1704067200,
+            "forward_origin": {
+                "type": "channel",
+                "chat": {"id": -1001234567890, "title": "News", "type": "channel"},
+                "message_id": 42,
+                "author_signature": "editor",
+                "date": 1704050000
+            },
+            "text": "forwarded from a channel"
+        }"#;
+
+    let m: Message = serde_json::from_str(t)?;
+    let data = m
+        .forward_data
+        .as_ref()
+        .expect("message should be forwarded");
+
+    assert_eq!(m.forward_from(), data.from.as_ref());
+    assert_eq!(m.forward_from_chat(), data.from_chat.as_ref());
+    assert_eq!(m.forward_from_message_id(), Some(42));
+    assert_eq!(m.forward_signature(), Some("editor"));
+    assert_eq!(m.forward_sender_name(), None);
+    assert_eq!(m.forward_date(), Some(data.date));
+    Ok(())
+}
+
+#[test]
+fn chat_background_round_trip() -> serde_json::Result<()> {
+    let document = json!({"file_id": "bg", "file_unique_id": "bg1"});
+    let backgrounds = [
+        json!({
+            "type": "fill",
+            "fill": {"type": "solid", "color": 16777215},
+            "dark_theme_dimming": 50
+        }),
+        json!({
+            "type": "wallpaper",
+            "document": document,
+            "dark_theme_dimming": 0,
+            "is_blurred": true
+        }),
+        json!({
+            "type": "pattern",
+            "document": document,
+            "fill": {
+                "type": "gradient",
+                "top_color": 255,
+                "bottom_color": 65280,
+                "rotation_angle": 45
+            },
+            "intensity": 60
+        }),
+        json!({
+            "type": "fill",
+            "fill": {"type": "freeform_gradient", "colors": [1, 2, 3, 4]},
+            "dark_theme_dimming": 10
+        }),
+        json!({"type": "chat_theme", "theme_name": "🌷"}),
+    ];
+
+    let mut decoded = Vec::new();
+    for background in backgrounds {
+        let t = json!({
+            "message_id": 33,
+            "date": 1585772722,
+            "chat": {"id": 123, "type": "private", "first_name": "x"},
+            "chat_background_set": {"type": background}
+        });
+
+        let m: Message = serde_json::from_value(t)?;
+        let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+        assert_eq!(round_trip, m);
+
+        match m.content {
+            MessageContent::ChatBackgroundSet {
+                content,
+            } => decoded.push(content.background_type),
+            c => panic!("expected a chat background, got {c:?}"),
+        }
+    }
+
+    assert!(matches!(
+        decoded[0],
+        BackgroundType::Fill {
+            fill: BackgroundFill::Solid {
+                color: 16777215
+            },
+            dark_theme_dimming: 50,
+        }
+    ));
+    assert!(matches!(
+        decoded[1],
+        BackgroundType::Wallpaper {
+            is_blurred: true,
+            is_moving: false,
+            ..
+        }
+    ));
+    assert!(matches!(
+        decoded[2],
+        BackgroundType::Pattern {
+            fill: BackgroundFill::Gradient {
+                rotation_angle: 45,
+                ..
+            },
+            intensity: 60,
+            is_inverted: false,
+            ..
+        }
+    ));
+    match &decoded[3] {
+        BackgroundType::Fill {
+            fill: BackgroundFill::FreeformGradient {
+                colors,
+            },
+            ..
+        } => assert_eq!(colors, &vec![1, 2, 3, 4]),
+        b => panic!("expected a freeform gradient, got {b:?}"),
+    }
+    assert_eq!(
+        decoded[4],
+        BackgroundType::ChatTheme {
+            theme_name: "🌷".to_owned(),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn decode_chat_shared_with_details() -> serde_json::Result<()> {
+    let t = json!({
+        "message_id": 34,
+        "date": 1585772722,
+        "chat": {"id": 123, "type": "private", "first_name": "x"},
+        "chat_shared": {
+            "request_id": 5,
+            "chat_id": -1001234,
+            "title": "shared",
+            "username": "shared_chat"
+        }
+    });
+
+    let m: Message = serde_json::from_value(t)?;
+    match &m.content {
+        MessageContent::ChatShared {
+            content,
+        } => {
+            assert_eq!(content.chat_id, -1001234);
+            assert_eq!(content.title.as_deref(), Some("shared"));
+            assert_eq!(content.username.as_deref(), Some("shared_chat"));
+            assert!(content.photo.is_none());
+        },
+        c => panic!("expected a shared chat, got {c:?}"),
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_boost_messages() -> serde_json::Result<()> {
+    let group = json!({"id": -1001234, "type": "supergroup", "title": "boosted"});
+    let booster = json!({"id": 456, "is_bot": false, "first_name": "y"});
+    let fixtures = [
+        json!({
+            "message_id": 35,
+            "date": 1585772722,
+            "chat": group,
+            "from": booster,
+            "boost_added": {"boost_count": 2}
+        }),
+        json!({
+            "message_id": 36,
+            "date": 1585772722,
+            "chat": group,
+            "from": booster,
+            "sender_boost_count": 2,
+            "sender_business_bot": {"id": 789, "is_bot": true, "first_name": "helper"},
+            "text": "hi"
+        }),
+    ];
+
+    let messages = fixtures
+        .into_iter()
+        .map(serde_json::from_value)
+        .collect::<serde_json::Result<Vec<Message>>>()?;
+
+    match &messages[0].content {
+        MessageContent::BoostAdded {
+            content,
+        } => assert_eq!(content.boost_count, 2),
+        c => panic!("expected an added boost, got {c:?}"),
+    }
+    assert_eq!(messages[0].sender_boost_count, None);
+    assert_eq!(messages[1].sender_boost_count, Some(2));
+    assert_eq!(
+        messages[1].sender_business_bot.as_ref().map(|b| b.id),
+        Some(789)
+    );
+
+    for m in messages {
+        let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+        assert_eq!(round_trip, m);
+    }
+    Ok(())
+}
+
+#[test]
+fn decode_callback_query_messages() -> serde_json::Result<()> {
+    let query = |message| {
+        json!({
+            "id": "query",
+            "from": {"id": 456, "is_bot": false, "first_name": "y"},
+            "chat_instance": "instance",
+            "data": "vote:1",
+            "message": message
+        })
+    };
+    let chat = json!({"id": -1001234, "type": "supergroup", "title": "votes"});
+
+    let accessible: CallbackQuery = serde_json::from_value(query(json!({
+        "message_id": 37,
+        "date": 1585772722,
+        "chat": chat,
+        "text": "vote here"
+    })))?;
+    let inaccessible: CallbackQuery = serde_json::from_value(query(json!({
+        "message_id": 38,
+        "date": 0,
+        "chat": chat
+    })))?;
+
+    let accessible = accessible.message.expect("the query has a message");
+    assert!(matches!(accessible, MaybeInaccessibleMessage::Message(_)));
+    assert_eq!(accessible.get_chat_id(), -1001234);
+    assert_eq!(accessible.get_message_id(), 37);
+    assert_eq!(
+        accessible
+            .accessible()
+            .and_then(Message::get_text)
+            .as_deref(),
+        Some("vote here")
+    );
+
+    let inaccessible = inaccessible.message.expect("the query has a message");
+    assert!(matches!(
+        inaccessible,
+        MaybeInaccessibleMessage::Inaccessible {
+            message_id: 38,
+            ..
+        }
+    ));
+    assert_eq!(inaccessible.get_chat_id(), -1001234);
+    assert_eq!(inaccessible.get_message_id(), 38);
+    assert!(inaccessible.accessible().is_none());
+
+    let value = serde_json::to_value(&inaccessible)?;
+    assert_eq!(value["date"], 0);
+    assert_eq!(
+        serde_json::from_value::<MaybeInaccessibleMessage>(value)?,
+        inaccessible
+    );
+
+    let round_trip = serde_json::from_value(serde_json::to_value(&accessible)?)?;
+    assert_eq!(accessible, round_trip);
+    Ok(())
+}
+
+#[test]
+fn decode_paid_media_message() -> serde_json::Result<()> {
+    let t = json!({
+        "message_id": 39,
+        "date": 1585772722,
+        "chat": {"id": -1001234, "type": "channel", "title": "paid"},
+        "caption": "exclusive",
+        "show_caption_above_media": true,
+        "paid_media": {
+            "star_count": 25,
+            "paid_media": [
+                {"type": "preview", "width": 1280, "height": 720, "duration": 12},
+                {
+                    "type": "photo",
+                    "photo": [{
+                        "file_id": "photo",
+                        "file_unique_id": "unique-photo",
+                        "width": 90,
+                        "height": 90
+                    }]
+                },
+                {
+                    "type": "video",
+                    "video": {
+                        "file_id": "video",
+                        "file_unique_id": "unique-video",
+                        "width": 1280,
+                        "height": 720,
+                        "duration": 12
+                    }
+                }
+            ]
+        }
+    });
+
+    let m: Message = serde_json::from_value(t)?;
+    assert_eq!(m.get_text().as_deref(), Some("exclusive"));
+    match &m.content {
+        MessageContent::PaidMedia {
+            content,
+            show_caption_above_media,
+            ..
+        } => {
+            assert!(show_caption_above_media);
+            assert_eq!(content.star_count, 25);
+            assert_eq!(
+                content.paid_media[0],
+                PaidMedia::Preview {
+                    width: Some(1280),
+                    height: Some(720),
+                    duration: Some(12),
+                }
+            );
+            assert!(matches!(
+                &content.paid_media[1],
+                PaidMedia::Photo { photo } if photo.len() == 1
+            ));
+            assert!(matches!(
+                &content.paid_media[2],
+                PaidMedia::Video { video } if video.file_id == "video"
+            ));
+        },
+        c => panic!("expected paid media, got {c:?}"),
+    }
+
+    let round_trip: Message = serde_json::from_value(serde_json::to_value(&m)?)?;
+    assert_eq!(round_trip, m);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "testing")]
+fn test_utils_build_valid_objects() -> serde_json::Result<()> {
+    let first = MessageBuilder::text("hi").build();
+    let reply = MessageBuilder::text("/close@test_bot now")
+        .from_user(5)
+        .in_forum_topic(-100, TopicId::Topic(7))
+        .reply_to(first.clone())
+        .build();
+    let post = MessageBuilder::text("news").in_channel(-200).build();
+
+    assert_ne!(first.message_id, reply.message_id);
+    assert_eq!(first.from.as_ref().map(|u| u.id), Some(1));
+    assert!(matches!(first.chat, Chat::Private(ref c) if c.id == 1));
+    assert_eq!(reply.topic_id(), Some(TopicId::Topic(7)));
+    assert_eq!(reply.get_text(), Some("/close@test_bot now".to_owned()));
+    assert!(post.from.is_none());
+
+    for message in [first, reply.clone(), post] {
+        let json = serde_json::to_value(&message)?;
+        assert_eq!(serde_json::from_value::<Message>(json)?, message);
+    }
+
+    let query = CallbackQueryBuilder::data("vote:1").message(reply).build();
+    assert_eq!(query.from.id, 1);
+    assert_eq!(query.message.map(|m| m.get_chat_id()), Some(-100));
+
+    let first = UpdateBuilder::message(MessageBuilder::text("hi").build()).build();
+    let second = UpdateBuilder::message(MessageBuilder::text("hi").build()).build();
+    assert!(second.update_id > first.update_id);
+    Ok(())
+}
+
+#[test]
+fn cached_inline_query_results_round_trip() -> serde_json::Result<()> {
+    let sticker =
+        InlineQueryResult::CachedSticker(InlineQueryResultCachedSticker::new("1", "sticker-id"));
+    let mut photo = InlineQueryResultCachedPhoto::new("2", "photo-id");
+    photo.set_caption("a cat");
+    let photo = InlineQueryResult::CachedPhoto(photo);
+
+    assert_eq!(
+        serde_json::to_value(&sticker)?,
+        json!({"type": "sticker", "id": "1", "sticker_file_id": "sticker-id"})
+    );
+    assert_eq!(
+        serde_json::to_value(&photo)?,
+        json!({"type": "photo", "id": "2", "photo_file_id": "photo-id", "caption": "a cat"})
+    );
+
+    for result in [sticker, photo] {
+        let decoded: InlineQueryResult = serde_json::from_value(serde_json::to_value(&result)?)?;
+        assert_eq!(decoded, result);
+    }
+
+    let url_photo: InlineQueryResult = serde_json::from_value(json!({
+        "type": "photo",
+        "id": "3",
+        "photo_url": "https://example.com/cat.jpg",
+        "thumbnail_url": "https://example.com/cat_thumb.jpg"
+    }))?;
+    assert!(matches!(url_photo, InlineQueryResult::Photo(p) if p.id == "3"));
+    let unknown = json!({"type": "nope", "id": "4"});
+    assert!(serde_json::from_value::<InlineQueryResult>(unknown).is_err());
+    Ok(())
+}
+
+#[test]
+fn parse_mode_escaping() {
+    let text = "*bold* [link](x) <b>1 & 2</b> a_b `c` 1.5!";
+    assert_eq!(
+        ParseMode::MarkdownV2.escape(text),
+        "\\*bold\\* \\[link\\]\\(x\\) <b\\>1 & 2</b\\> a\\_b \\`c\\` 1\\.5\\!"
+    );
+    assert_eq!(
+        ParseMode::Markdown.escape(text),
+        "\\*bold\\* \\[link](x) <b>1 & 2</b> a\\_b \\`c\\` 1.5!"
+    );
+    assert_eq!(
+        ParseMode::HTML.escape(text),
+        "*bold* [link](x) &lt;b&gt;1 &amp; 2&lt;/b&gt; a_b `c` 1.5!"
+    );
+    assert_eq!(ParseMode::MarkdownV2.escape("\\"), "\\\\");
+}
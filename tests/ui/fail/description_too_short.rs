@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(description = "hi")]
+async fn hi(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
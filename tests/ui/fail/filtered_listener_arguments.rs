@@ -0,0 +1,6 @@
+use telexide::prelude::*;
+
+#[prepare_listener(updates = "message")]
+async fn on_message(_ctx: Context) {}
+
+fn main() {}
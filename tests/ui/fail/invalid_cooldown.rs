@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(description = "says hi", cooldown = "soon")]
+async fn say_hi(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(name = "say-hi", description = "says hi")]
+async fn say_hi(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
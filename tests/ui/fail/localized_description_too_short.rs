@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(description = "says hi", description_localized(lang = "de", text = "üü"))]
+async fn say_hi(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
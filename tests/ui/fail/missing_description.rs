@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(name = "ping")]
+async fn ping(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
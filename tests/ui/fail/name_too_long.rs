@@ -0,0 +1,8 @@
+use telexide::prelude::*;
+
+#[command(description = "does too much")]
+async fn does_everything_that_could_be_wanted(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use telexide::prelude::*;
+
+#[command(description = "bans a user")]
+async fn ban(_ctx: Context, _message: Message, reason: Option<String>, user: i64) -> CommandResult {
+    println!("banning {user} for {reason:?}");
+    Ok(())
+}
+
+fn main() {}
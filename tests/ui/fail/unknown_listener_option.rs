@@ -0,0 +1,6 @@
+use telexide::prelude::*;
+
+#[prepare_listener(update = "message")]
+async fn on_update(_ctx: Context, _update: Update) {}
+
+fn main() {}
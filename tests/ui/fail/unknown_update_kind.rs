@@ -0,0 +1,6 @@
+use telexide::prelude::*;
+
+#[prepare_listener(updates = "message, callback")]
+async fn on_update(_ctx: Context, _update: Update) {}
+
+fn main() {}
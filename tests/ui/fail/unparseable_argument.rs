@@ -0,0 +1,10 @@
+use telexide::prelude::*;
+
+struct Duration;
+
+#[command(description = "mutes a user")]
+async fn mute(_ctx: Context, _message: Message, _duration: Duration) -> CommandResult {
+    Ok(())
+}
+
+fn main() {}
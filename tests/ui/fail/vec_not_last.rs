@@ -0,0 +1,9 @@
+use telexide::prelude::*;
+
+#[command(description = "tags users")]
+async fn tag(_ctx: Context, _message: Message, users: Vec<i64>, tag: String) -> CommandResult {
+    println!("tagging {users:?} with {tag}");
+    Ok(())
+}
+
+fn main() {}
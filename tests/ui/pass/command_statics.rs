@@ -0,0 +1,38 @@
+use telexide::prelude::*;
+
+async fn always(_ctx: Context, _message: &Message) -> telexide::Result<bool> {
+    Ok(true)
+}
+
+#[command(description = "gets a setting")]
+async fn config_get(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+#[command(
+    description = "manages the settings",
+    description_localized(lang = "nl", text = "beheert de instellingen"),
+    aliases = "settings, cfg",
+    usage = "[key]",
+    subcommands = "config_get",
+    checks = "always, dm_only",
+    topics = "1, 2",
+    cooldown = 5,
+    cooldown_scope = "chat",
+    priority = 3
+)]
+async fn config(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+static COMMANDS: [&telexide::framework::TelegramCommand; 2] = [&config_COMMAND, &config_get_COMMAND];
+
+fn main() {
+    let options = &config_COMMAND.options;
+    let name: &str = &options.name;
+    let aliases: Vec<&str> = options.aliases.iter().map(|a| &**a).collect();
+    println!("{name} {aliases:?} {}", options.subcommands[0].options.name);
+    println!("{}", COMMANDS.len());
+
+    let _ = create_framework!("bot", config);
+}
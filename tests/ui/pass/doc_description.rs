@@ -0,0 +1,25 @@
+use telexide::prelude::*;
+
+/// Replies with pong,
+/// to check whether the bot is online
+#[command]
+async fn ping(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+/// Does nothing at all, but has a doc comment which is far too long to be used
+/// as the description of a command in telegram as is, because telegram only
+/// allows command descriptions of up to 256 characters, so the macro has to cut
+/// it off somewhere instead of making the bot fail to register its commands.
+#[command]
+async fn noop(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(
+        ping_COMMAND.options.description,
+        "Replies with pong, to check whether the bot is online"
+    );
+    assert_eq!(noop_COMMAND.options.description.chars().count(), 256);
+}
@@ -0,0 +1,92 @@
+use telexide::{api::types::UpdateType, client::EventListener, model::*, prelude::*};
+
+#[prepare_listener(updates = "message, edited_message, channel_post, edited_channel_post")]
+async fn on_message(_ctx: Context, _message: Message) {}
+
+#[prepare_listener(updates = "business_message, edited_business_message")]
+async fn on_business_message(_ctx: Context, _message: Message) -> telexide::Result<()> {
+    Ok(())
+}
+
+#[prepare_listener(updates = "inline_query")]
+async fn on_inline_query(_ctx: Context, _query: InlineQuery) {}
+
+#[prepare_listener(updates = "chosen_inline_result")]
+async fn on_chosen_inline_result(_ctx: Context, _result: ChosenInlineResult) {}
+
+#[prepare_listener(updates = "callback_query")]
+async fn on_callback_query(_ctx: Context, _query: CallbackQuery) {}
+
+#[prepare_listener(updates = "shipping_query")]
+async fn on_shipping_query(_ctx: Context, _query: ShippingQuery) {}
+
+#[prepare_listener(updates = "pre_checkout_query")]
+async fn on_pre_checkout_query(_ctx: Context, _query: PreCheckoutQuery) {}
+
+#[prepare_listener(updates = "poll")]
+async fn on_poll(_ctx: Context, _poll: Poll) {}
+
+#[prepare_listener(updates = "poll_answer")]
+async fn on_poll_answer(_ctx: Context, _answer: PollAnswer) {}
+
+#[prepare_listener(updates = "my_chat_member, chat_member")]
+async fn on_chat_member(_ctx: Context, _member: ChatMemberUpdated) {}
+
+#[prepare_listener(updates = "chat_join_request")]
+async fn on_chat_join_request(_ctx: Context, _request: ChatJoinRequest) {}
+
+#[prepare_listener(updates = "message_reaction")]
+async fn on_message_reaction(_ctx: Context, _reaction: MessageReactionUpdated) {}
+
+#[prepare_listener(updates = "message_reaction_count")]
+async fn on_message_reaction_count(_ctx: Context, _count: MessageReactionCountUpdated) {}
+
+#[prepare_listener(updates = "chat_boost")]
+async fn on_chat_boost(_ctx: Context, _boost: ChatBoostUpdated) {}
+
+#[prepare_listener(updates = "removed_chat_boost")]
+async fn on_removed_chat_boost(_ctx: Context, _boost: ChatBoostRemoved) {}
+
+#[prepare_listener(updates = "business_connection")]
+async fn on_business_connection(_ctx: Context, _connection: BusinessConnection) {}
+
+#[prepare_listener(updates = "deleted_business_messages")]
+async fn on_deleted_business_messages(_ctx: Context, _deleted: BusinessMessagesDeleted) {}
+
+#[prepare_listener(updates = "message, callback_query")]
+async fn on_update(_ctx: Context, _update: Update) {}
+
+static LISTENERS: [&EventListener; 18] = [
+    &on_message_LISTENER,
+    &on_business_message_LISTENER,
+    &on_inline_query_LISTENER,
+    &on_chosen_inline_result_LISTENER,
+    &on_callback_query_LISTENER,
+    &on_shipping_query_LISTENER,
+    &on_pre_checkout_query_LISTENER,
+    &on_poll_LISTENER,
+    &on_poll_answer_LISTENER,
+    &on_chat_member_LISTENER,
+    &on_chat_join_request_LISTENER,
+    &on_message_reaction_LISTENER,
+    &on_message_reaction_count_LISTENER,
+    &on_chat_boost_LISTENER,
+    &on_removed_chat_boost_LISTENER,
+    &on_business_connection_LISTENER,
+    &on_deleted_business_messages_LISTENER,
+    &on_update_LISTENER,
+];
+
+fn main() {
+    let kinds = LISTENERS.iter().map(|l| l.update_types.len()).sum::<usize>();
+    assert_eq!(kinds, 24);
+    assert_eq!(on_update_LISTENER.update_types, &[
+        UpdateType::Message,
+        UpdateType::CallbackQuery
+    ]);
+
+    let mut builder = ClientBuilder::new();
+    for listener in LISTENERS {
+        builder.add_listener(listener);
+    }
+}
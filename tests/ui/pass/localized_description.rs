@@ -0,0 +1,15 @@
+use telexide::prelude::*;
+
+#[command(
+    description = "says hi",
+    aliases = "Hi, hello",
+    description_localized(lang = "de", text = "üüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüüü")
+)]
+async fn say_hi(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(say_hi_COMMAND.options.aliases[..], ["hi", "hello"]);
+    assert_eq!(say_hi_COMMAND.options.localized_descriptions[0].1.chars().count(), 200);
+}
@@ -0,0 +1,10 @@
+use telexide::prelude::*;
+
+#[command(name = "Ping_2", description = "replies with pong")]
+async fn ping(_ctx: Context, _message: Message) -> CommandResult {
+    Ok(())
+}
+
+fn main() {
+    assert_eq!(ping_COMMAND.options.name, "ping_2");
+}
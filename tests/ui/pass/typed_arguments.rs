@@ -0,0 +1,34 @@
+use telexide::prelude::*;
+
+#[command(description = "bans a user")]
+async fn ban(_ctx: Context, _message: Message, user: i64, reason: Option<String>) -> CommandResult {
+    println!("banning {user} for {reason:?}");
+    Ok(())
+}
+
+#[command(description = "adds up numbers")]
+async fn sum(_ctx: Context, _message: Message, numbers: Vec<f64>) -> CommandResult {
+    println!("{}", numbers.iter().sum::<f64>());
+    Ok(())
+}
+
+#[command(description = "renames a user")]
+async fn rename(_ctx: Context, _message: Message, user: i64, name: String) -> CommandResult {
+    println!("renaming {user} to {name}");
+    Ok(())
+}
+
+#[command(description = "rolls dice")]
+async fn roll(
+    _ctx: Context,
+    _message: Message,
+    dice: Option<u8>,
+    _sides: Option<u16>,
+) -> CommandResult {
+    println!("rolling {dice:?}");
+    Ok(())
+}
+
+fn main() {
+    let _ = create_framework!("bot", ban, sum, rename, roll);
+}
@@ -35,7 +35,7 @@ async fn webhook_gets_called() -> Result<()> {
         .header("accept", "application/json")
         .body(hyper::Body::from(serde_json::to_string(&Update {
             update_id: 10,
-            content: UpdateContent::Unknown,
+            content: UpdateContent::Unknown(serde_json::Value::Null),
         })?))?;
     client.request(req).await?;
 
@@ -43,3 +43,57 @@ async fn webhook_gets_called() -> Result<()> {
     assert_eq!(ATOMIC.load(Ordering::Relaxed), 10);
     Ok(())
 }
+
+#[tokio::test]
+async fn webhook_checks_secret_token() -> Result<()> {
+    let client = hyper::Client::new();
+
+    let mut webhook_opts = WebhookOptions::new();
+    webhook_opts.set_port(8016).set_secret_token("s3cret")?;
+
+    let mut update_receiver = Webhook::new(&webhook_opts).start();
+    tokio::time::sleep(tokio::time::Duration::from_millis(150)).await;
+
+    let mut statuses = Vec::new();
+    for (update_id, token) in [(1, Some("wrong")), (2, None), (3, Some("s3cret"))] {
+        let mut req = hyper::Request::post("http://localhost:8016/")
+            .header("content-type", "application/json");
+        if let Some(token) = token {
+            req = req.header("X-Telegram-Bot-Api-Secret-Token", token);
+        }
+
+        let req = req.body(hyper::Body::from(serde_json::to_string(&Update {
+            update_id,
+            content: UpdateContent::Unknown(serde_json::Value::Null),
+        })?))?;
+        statuses.push(client.request(req).await?.status());
+    }
+
+    assert_eq!(
+        statuses,
+        [
+            hyper::StatusCode::UNAUTHORIZED,
+            hyper::StatusCode::UNAUTHORIZED,
+            hyper::StatusCode::OK
+        ]
+    );
+    let update = update_receiver
+        .recv()
+        .await
+        .expect("an update was received")?;
+    assert_eq!(update.update_id, 3);
+    assert!(update_receiver.try_recv().is_err());
+    Ok(())
+}
+
+#[test]
+fn webhook_rejects_invalid_secret_token() {
+    let mut webhook_opts = WebhookOptions::new();
+    for token in ["", "with space", "ünicode", &"a".repeat(257)] {
+        assert!(
+            webhook_opts.set_secret_token(token).is_err(),
+            "{token:?} should be rejected"
+        );
+    }
+    assert!(webhook_opts.set_secret_token(&"a-Z_9".repeat(51)).is_ok());
+}